@@ -87,42 +87,43 @@ pub extern "C" fn compute_privacy_usage(
 ) -> ffi_support::ByteBuffer {
     let request_buffer = unsafe { ptr_to_buffer(request_ptr, request_length) };
 
-    let response = proto::ResponseComputePrivacyUsage {
-        value: match proto::RequestComputePrivacyUsage::decode(request_buffer) {
-            Ok(request) => {
-                let proto::RequestComputePrivacyUsage {
-                    analysis, release
-                } = request;
-
-
-                let run = || -> Result<proto::PrivacyUsage> {
-                    let proto::Analysis {
-                        privacy_definition, computation_graph
-                    } = analysis
-                        .ok_or_else(|| Error::from("analysis must be defined"))?;
-                    let release = parse_release(release
-                        .ok_or_else(|| Error::from("release must be defined"))?);
-
-                    let privacy_definition = privacy_definition
-                        .ok_or_else(|| Error::from("privacy_definition must be defined"))?;
-                    let computation_graph = computation_graph
-                        .ok_or_else(|| Error::from("computation_graph must be defined"))?.value;
-
-                    smartnoise_validator::compute_privacy_usage(privacy_definition, computation_graph, release)
-                };
-
-                match run() {
-                    Ok(x) =>
-                        Some(proto::response_compute_privacy_usage::Value::Data(x)),
-                    Err(err) =>
-                        Some(proto::response_compute_privacy_usage::Value::Error(serialize_error(err))),
-                }
+    let mut composition = Vec::new();
+    let value = match proto::RequestComputePrivacyUsage::decode(request_buffer) {
+        Ok(request) => {
+            let proto::RequestComputePrivacyUsage {
+                analysis, release
+            } = request;
+
+
+            let run = || -> Result<(proto::PrivacyUsage, Vec<proto::CompositionTheorem>)> {
+                let proto::Analysis {
+                    privacy_definition, computation_graph
+                } = analysis
+                    .ok_or_else(|| Error::from("analysis must be defined"))?;
+                let release = parse_release(release
+                    .ok_or_else(|| Error::from("release must be defined"))?);
+
+                let privacy_definition = privacy_definition
+                    .ok_or_else(|| Error::from("privacy_definition must be defined"))?;
+                let computation_graph = computation_graph
+                    .ok_or_else(|| Error::from("computation_graph must be defined"))?.value;
+
+                smartnoise_validator::compute_privacy_usage(privacy_definition, computation_graph, release)
+            };
+
+            match run() {
+                Ok((usage, theorems)) => {
+                    composition = theorems.into_iter().map(|theorem| theorem as i32).collect();
+                    Some(proto::response_compute_privacy_usage::Value::Data(usage))
+                },
+                Err(err) =>
+                    Some(proto::response_compute_privacy_usage::Value::Error(serialize_error(err))),
             }
-            Err(_) =>
-                Some(proto::response_compute_privacy_usage::Value::Error(serialize_error("unable to parse protobuf".into())))
         }
+        Err(_) =>
+            Some(proto::response_compute_privacy_usage::Value::Error(serialize_error("unable to parse protobuf".into())))
     };
-    buffer_to_ptr(response)
+    buffer_to_ptr(proto::ResponseComputePrivacyUsage { value, composition })
 }
 
 /// FFI wrapper for [generate_report](../fn.generate_report.html)
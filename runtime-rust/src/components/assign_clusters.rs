@@ -0,0 +1,40 @@
+use ndarray::{Array, Ix2};
+
+use smartnoise_validator::{Integer, proto};
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::to_nd;
+
+impl Evaluable for proto::AssignClusters {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let data = to_nd(take_argument(&mut arguments, "data")?.array()?.cast_float()?, 2)?
+            .into_dimensionality::<Ix2>()?;
+        let centroids = to_nd(take_argument(&mut arguments, "centroids")?.array()?.cast_float()?, 2)?
+            .into_dimensionality::<Ix2>()?;
+
+        if data.shape()[1] != centroids.shape()[1] {
+            return Err("centroids must have one column for each column of the data".into())
+        }
+        if centroids.shape()[0] == 0 {
+            return Err("centroids must contain at least one centroid".into())
+        }
+
+        let assignments = data.genrows().into_iter()
+            .map(|row| centroids.genrows().into_iter()
+                .map(|centroid| row.iter().zip(centroid.iter())
+                    .map(|(x, c)| (x - c).powi(2))
+                    .sum::<f64>())
+                .enumerate()
+                // strict comparison keeps the lowest index among ties
+                .fold((0, f64::INFINITY), |best, (index, distance)|
+                    if distance < best.1 { (index, distance) } else { best })
+                .0 as Integer)
+            .collect::<Vec<Integer>>();
+
+        Ok(ReleaseNode::new(Array::from(assignments).into_dyn().into()))
+    }
+}
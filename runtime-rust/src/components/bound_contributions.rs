@@ -0,0 +1,116 @@
+use std::collections::{HashMap, HashSet};
+
+use ndarray::Axis;
+
+use smartnoise_validator::base::{Array, IndexKey, ReleaseNode};
+use smartnoise_validator::errors::*;
+use smartnoise_validator::proto;
+use smartnoise_validator::utilities::array::slow_select;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::noise;
+
+impl Evaluable for proto::BoundContributions {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let data = take_argument(&mut arguments, "data")?.array()?;
+        let by = to_keys(take_argument(&mut arguments, "by")?.array()?)?;
+        let partition = match arguments.remove::<IndexKey>(&"partition".into()) {
+            Some(partition) => Some(to_keys(partition.array()?)?),
+            None => None
+        };
+
+        let max_partitions = partition.as_ref().map(|_| self.max_partitions as usize);
+        let indices = bound_contributions(
+            &by, partition.as_deref(),
+            self.max_contributions as usize, max_partitions,
+            enforce_constant_time)?;
+
+        Ok(ReleaseNode::new(match data {
+            Array::Float(data) => data.select(Axis(0), &indices).into(),
+            Array::Int(data) => data.select(Axis(0), &indices).into(),
+            Array::Bool(data) => data.select(Axis(0), &indices).into(),
+            Array::Str(data) => slow_select(&data, Axis(0), &indices).into(),
+        }))
+    }
+}
+
+/// Convert a column of keys into one key per row.
+fn to_keys(array: Array) -> Result<Vec<IndexKey>> {
+    Ok(match array {
+        Array::Int(array) => array.iter().cloned().map(IndexKey::from).collect(),
+        Array::Bool(array) => array.iter().cloned().map(IndexKey::from).collect(),
+        Array::Str(array) => array.iter().cloned().map(IndexKey::from).collect(),
+        Array::Float(_) => return Err("keys may not be float".into())
+    })
+}
+
+/// Indices of the rows kept when each individual's contributions are bounded.
+///
+/// Rows are visited in a uniformly random order, and a row is kept only if its individual has not yet
+/// reached `max_contributions` rows (in the row's partition, if partitioned),
+/// and the row's partition is one of the first `max_partitions` partitions visited for the individual.
+/// Kept indices are returned in their original order.
+///
+/// # Arguments
+/// * `by` - Identifier of the individual who contributed each row.
+/// * `partition` - Optional partition key of each row.
+/// * `max_contributions` - Maximum number of rows for each individual, or each individual in each partition.
+/// * `max_partitions` - Maximum number of partitions for each individual. Only used if partitioned.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::components::bound_contributions::bound_contributions;
+/// use smartnoise_validator::base::IndexKey;
+/// let by: Vec<IndexKey> = vec![1i64, 1, 1, 2].into_iter().map(IndexKey::from).collect();
+/// let indices = bound_contributions(&by, None, 2, None, false).unwrap();
+/// assert_eq!(indices.len(), 3);
+/// assert!(indices.contains(&3));
+/// ```
+pub fn bound_contributions(
+    by: &[IndexKey],
+    partition: Option<&[IndexKey]>,
+    max_contributions: usize,
+    max_partitions: Option<usize>,
+    enforce_constant_time: bool,
+) -> Result<Vec<usize>> {
+    if let Some(partition) = partition {
+        if partition.len() != by.len() {
+            return Err("partition and by must share the same number of records".into())
+        }
+    }
+
+    let order = noise::shuffle((0..by.len()).collect(), enforce_constant_time)?;
+
+    let mut contributions = HashMap::<(&IndexKey, Option<&IndexKey>), usize>::new();
+    let mut partitions = HashMap::<&IndexKey, HashSet<&IndexKey>>::new();
+
+    let mut indices = order.into_iter().filter(|idx| {
+        let key = partition.map(|partition| &partition[*idx]);
+
+        if let (Some(key), Some(max_partitions)) = (key, max_partitions) {
+            let visited = partitions.entry(&by[*idx]).or_insert_with(HashSet::new);
+            if !visited.contains(key) {
+                if visited.len() >= max_partitions {
+                    return false
+                }
+                visited.insert(key);
+            }
+        }
+
+        let count = contributions.entry((&by[*idx], key)).or_insert(0);
+        if *count >= max_contributions {
+            return false
+        }
+        *count += 1;
+        true
+    }).collect::<Vec<usize>>();
+
+    indices.sort_unstable();
+    Ok(indices)
+}
@@ -0,0 +1,245 @@
+use indexmap::indexmap;
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::{ReleaseNode, Value};
+use smartnoise_validator::components::chi_square_test::{get_common_scale, get_p_value, goodness_of_fit_statistic, independence_proportions, independence_statistic};
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::noise::{sample_discrete_gaussian, sample_discrete_laplace, sample_gaussian, sample_laplace, sample_uniform};
+
+impl Evaluable for proto::ChiSquareTest {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let counts = take_argument(&mut arguments, "data")?.array()?.cast_float()?
+            .iter().map(|v| *v as f64).collect::<Vec<f64>>();
+        let noise_scale = get_common_scale(take_argument(&mut arguments, "noise_scale")?.ref_array()?)?;
+
+        let (statistic, p_value) = match self.test.to_lowercase().as_str() {
+            "goodnessoffit" => {
+                let proportions = match take_argument(&mut arguments, "proportions") {
+                    Ok(proportions) => proportions.array()?.cast_float()?
+                        .iter().map(|v| *v as f64).collect::<Vec<f64>>(),
+                    Err(_) => vec![1. / counts.len() as f64; counts.len()]
+                };
+                if proportions.len() != counts.len() {
+                    return Err("proportions: must contain one proportion for every count".into())
+                }
+                chi_square_test(
+                    &counts, &proportions, |counts| goodness_of_fit_statistic(counts, &proportions),
+                    &self.noise, noise_scale, self.num_simulations, enforce_constant_time)?
+            }
+            "independence" => {
+                let shape = take_argument(&mut arguments, "categories")?.jagged()?.num_records();
+                if shape.len() != 2 {
+                    return Err("categories: an independence test must be over two columns".into())
+                }
+                let shape = (shape[0] as usize, shape[1] as usize);
+                if shape.0 * shape.1 != counts.len() {
+                    return Err("data: must contain one count for every cell of the two-way table".into())
+                }
+                chi_square_test(
+                    &counts, &independence_proportions(&counts, shape), |counts| independence_statistic(counts, shape),
+                    &self.noise, noise_scale, self.num_simulations, enforce_constant_time)?
+            }
+            _ => return Err(format!("test: unexpected invalid token {:?}", self.test).into())
+        };
+
+        Ok(ReleaseNode::new(Value::Dataframe(indexmap![
+            "statistic".into() => (statistic as Float).into(),
+            "p_value".into() => (p_value as Float).into()
+        ])))
+    }
+}
+
+/// Chi-square statistic of noisy counts, and its p-value under a null distribution that includes the noise.
+///
+/// Each simulation draws counts from a multinomial distribution with the null proportions
+/// and the number of records estimated from the noisy counts,
+/// adds noise of the same distribution and scale as the released counts,
+/// and computes the statistic of the simulated noisy counts.
+/// Gaboardi, Lim, Rogers and Vadhan (2016), Differentially Private Chi-Squared Hypothesis Testing
+///
+/// # Arguments
+/// * `counts` - Noisy counts.
+/// * `proportions` - Proportion of each count under the null hypothesis.
+/// * `statistic` - Function computing the test statistic of noisy counts.
+/// * `noise` - Distribution of the noise, one of `laplace`, `gaussian`, `discretelaplace` or `discretegaussian`.
+/// * `noise_scale` - Scale of the laplace or discrete laplace distribution, or sigma of the gaussian or discrete gaussian distribution.
+/// * `num_simulations` - Number of simulated datasets.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// The statistic and its p-value.
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::components::chi_square_test::chi_square_test;
+/// use smartnoise_validator::components::chi_square_test::goodness_of_fit_statistic;
+/// let counts = vec![260., 240., 500.];
+/// let proportions = vec![0.25, 0.25, 0.5];
+/// let (statistic, p_value) = chi_square_test(
+///     &counts, &proportions, |counts| goodness_of_fit_statistic(counts, &proportions),
+///     "laplace", 2., 100, false).unwrap();
+/// assert!(statistic > 0.);
+/// assert!(p_value > 0. && p_value <= 1.);
+/// ```
+pub fn chi_square_test(
+    counts: &[f64], proportions: &[f64], statistic: impl Fn(&[f64]) -> f64,
+    noise: &str, noise_scale: f64, num_simulations: u32,
+    enforce_constant_time: bool,
+) -> Result<(f64, f64)> {
+    let sample_noise = |enforce_constant_time| -> Result<f64> {
+        if noise_scale == 0. {
+            return Ok(0.)
+        }
+        match noise.to_lowercase().as_str() {
+            "laplace" => sample_laplace(0., noise_scale, enforce_constant_time),
+            "gaussian" => sample_gaussian(0., noise_scale, enforce_constant_time),
+            "discretelaplace" => sample_discrete_laplace(noise_scale).map(|noise| noise as f64),
+            "discretegaussian" => sample_discrete_gaussian(noise_scale).map(|noise| noise as f64),
+            _ => Err(format!("noise: unexpected invalid token {:?}", noise).into())
+        }
+    };
+
+    let observed = statistic(counts);
+    let num_records = counts.iter().sum::<f64>().max(0.).round() as i64;
+
+    let simulated = (0..num_simulations)
+        .map(|_| {
+            let simulated_counts = sample_multinomial(num_records, proportions, enforce_constant_time)?.into_iter()
+                .map(|count| Ok(count as f64 + sample_noise(enforce_constant_time)?))
+                .collect::<Result<Vec<f64>>>()?;
+            Ok(statistic(&simulated_counts))
+        })
+        .collect::<Result<Vec<f64>>>()?;
+
+    Ok((observed, get_p_value(observed, &simulated)))
+}
+
+/// Sample counts from the multinomial distribution, as a sequence of conditional binomials.
+fn sample_multinomial(n: i64, proportions: &[f64], enforce_constant_time: bool) -> Result<Vec<i64>> {
+    let mut remaining_records = n;
+    let mut remaining_mass = proportions.iter().sum::<f64>();
+    proportions.iter()
+        .map(|proportion| {
+            let prob = if remaining_mass > 0. { (proportion / remaining_mass).min(1.) } else { 0. };
+            let count = sample_binomial_approximate(remaining_records, prob, enforce_constant_time)?;
+            remaining_records -= count;
+            remaining_mass -= proportion;
+            Ok(count)
+        })
+        .collect()
+}
+
+/// Sample from the binomial distribution with one uniform or gaussian draw.
+///
+/// Binomials of small variance are sampled exactly by inverting the distribution function,
+/// and binomials of large variance are approximated by a rounded gaussian.
+/// The simulations are post-processing, so the approximation does not affect privacy.
+fn sample_binomial_approximate(n: i64, prob: f64, enforce_constant_time: bool) -> Result<i64> {
+    if n <= 0 || prob <= 0. {
+        return Ok(0)
+    }
+    if prob >= 1. {
+        return Ok(n)
+    }
+    // invert around the smaller probability, so that the probability of zero successes does not underflow
+    if prob > 0.5 {
+        return Ok(n - sample_binomial_approximate(n, 1. - prob, enforce_constant_time)?)
+    }
+
+    let n_float = n as f64;
+    let variance = n_float * prob * (1. - prob);
+    if variance > 25. {
+        let sample = sample_gaussian(n_float * prob, variance.sqrt(), enforce_constant_time)?;
+        return Ok(sample.round().max(0.).min(n_float) as i64)
+    }
+
+    let uniform = sample_uniform(0., 1., enforce_constant_time)?;
+    let ratio = prob / (1. - prob);
+    let mut mass = (n_float * (1. - prob).ln()).exp();
+    let mut cumulative = mass;
+    let mut successes = 0;
+    while cumulative < uniform && successes < n {
+        mass *= ratio * (n - successes) as f64 / (successes + 1) as f64;
+        cumulative += mass;
+        successes += 1;
+    }
+    Ok(successes)
+}
+
+#[cfg(test)]
+mod test_chi_square_test {
+    use ndarray::{arr1, Array};
+
+    use smartnoise_validator::base::{IndexKey, Jagged, Value};
+    use smartnoise_validator::bindings::Analysis;
+
+    use crate::utilities::fixtures::{approximate_usage, release_analysis, unprotected_analysis};
+
+    fn get_p_value(mut analysis: Analysis, dependent: bool) -> f64 {
+        // two binary columns, where the second either copies or is independent of the first
+        let data = Array::from_shape_fn((1000, 2), |(i, j)| if j == 0 || dependent {
+            (i % 2) as i64
+        } else {
+            ((i / 2) % 2) as i64
+        });
+
+        let data = analysis.literal().value(data.into_dyn().into()).value_public(true).build();
+        let categories = analysis.literal()
+            .value(Value::Jagged(Jagged::Int(vec![vec![0, 1], vec![0, 1]]))).value_public(true).build();
+        let null_value = analysis.literal().value(arr1(&[-1, -1]).into_dyn().into()).value_public(true).build();
+        let clamped = analysis.clamp(data).categories(categories).null_value(null_value).build();
+
+        let test = analysis.dp_chi_square_test(clamped, vec![approximate_usage(1., 0.)])
+            .test("Independence".to_string()).build();
+
+        let release = release_analysis(&analysis).unwrap();
+
+        release[&test].value.clone().dataframe().unwrap()
+            .remove(&IndexKey::from("p_value")).unwrap()
+            .array().unwrap().first_float().unwrap()
+    }
+
+    #[test]
+    fn test_dp_independence() {
+        assert!(get_p_value(unprotected_analysis(), true) < 0.01);
+        // the cells are exactly balanced, so only the noise departs from independence
+        assert!(get_p_value(unprotected_analysis(), false) > 0.01);
+    }
+
+    #[test]
+    fn test_dp_independence_default_privacy_definition() {
+        assert!(get_p_value(Analysis::new(), true) < 0.01);
+        let p_value = get_p_value(Analysis::new(), false);
+        assert!(p_value > 0. && p_value <= 1.);
+    }
+
+    #[test]
+    fn test_dp_goodness_of_fit() {
+        // exactly uniform over three categories
+        let data = Array::from_shape_fn((999, 1), |(i, _)| (i % 3) as i64);
+
+        let mut analysis = unprotected_analysis();
+
+        let data = analysis.literal().value(data.into_dyn().into()).value_public(true).build();
+        let categories = analysis.literal()
+            .value(Value::Jagged(Jagged::Int(vec![vec![0, 1, 2]]))).value_public(true).build();
+        let null_value = analysis.literal().value(arr1(&[-1]).into_dyn().into()).value_public(true).build();
+        let clamped = analysis.clamp(data).categories(categories).null_value(null_value).build();
+
+        let test = analysis.dp_chi_square_test(clamped, vec![approximate_usage(1., 0.)]).build();
+
+        let release = release_analysis(&analysis).unwrap();
+
+        let p_value = release[&test].value.clone().dataframe().unwrap()
+            .remove(&IndexKey::from("p_value")).unwrap()
+            .array().unwrap().first_float().unwrap();
+        assert!(p_value > 0.01, "p-value: {}", p_value);
+    }
+}
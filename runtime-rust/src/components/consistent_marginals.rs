@@ -0,0 +1,34 @@
+use indexmap::map::IndexMap;
+use ndarray::Array;
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::{IndexKey, ReleaseNode, Value};
+use smartnoise_validator::components::consistent_marginals::consistent_marginals;
+use smartnoise_validator::components::contingency_table::get_table_key;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::contingency_table::take_marginals;
+use crate::components::Evaluable;
+use crate::NodeArguments;
+
+impl Evaluable for proto::ConsistentMarginals {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let tables = take_argument(&mut arguments, "data")?.array()?.cast_float()?
+            .iter().map(|v| *v as f64).collect::<Vec<f64>>();
+        let shape = take_argument(&mut arguments, "categories")?.jagged()?.num_records().into_iter()
+            .map(|num_categories| num_categories as usize)
+            .collect::<Vec<usize>>();
+        let marginals = take_marginals(&mut arguments)?;
+
+        let all_columns = (0..shape.len()).collect::<Vec<usize>>();
+        let tables = std::iter::once(&all_columns).chain(marginals.iter())
+            .zip(consistent_marginals(&tables, &shape, &marginals)?.into_iter())
+            .map(|(columns, table)| (
+                get_table_key(columns),
+                Array::from(table.into_iter().map(|v| v as Float).collect::<Vec<Float>>()).into_dyn().into()))
+            .collect::<IndexMap<IndexKey, Value>>();
+
+        Ok(ReleaseNode::new(Value::Dataframe(tables)))
+    }
+}
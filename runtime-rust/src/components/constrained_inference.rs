@@ -0,0 +1,23 @@
+use ndarray::Array;
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::components::constrained_inference::constrained_inference;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+
+impl Evaluable for proto::ConstrainedInference {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let tree = take_argument(&mut arguments, "data")?.array()?.cast_float()?
+            .iter().map(|v| *v as f64).collect::<Vec<f64>>();
+
+        let leaves = constrained_inference(&tree, self.branching, self.num_leaves as usize)?.into_iter()
+            .map(|v| v as Float)
+            .collect::<Vec<Float>>();
+
+        Ok(ReleaseNode::new(Array::from(leaves).into_dyn().into()))
+    }
+}
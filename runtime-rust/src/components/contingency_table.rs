@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use ndarray::{Array, ArrayD};
+
+use smartnoise_validator::{Integer, proto};
+use smartnoise_validator::base::{Array as BaseArray, IndexKey, Jagged, ReleaseNode};
+use smartnoise_validator::components::contingency_table::{get_marginal_indices, get_table_size};
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+
+impl Evaluable for proto::ContingencyTable {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let data = match take_argument(&mut arguments, "data")?.array()? {
+            BaseArray::Bool(data) => to_index_keys(data),
+            BaseArray::Int(data) => to_index_keys(data),
+            BaseArray::Str(data) => to_index_keys(data),
+            BaseArray::Float(_) => return Err("data: contingency tables over floats are not supported".into())
+        };
+        let categories = take_argument(&mut arguments, "categories")?.jagged()?.to_index_keys()?;
+        let marginals = take_marginals(&mut arguments)?;
+
+        Ok(ReleaseNode::new(Array::from(contingency_table(&data, &categories, &marginals)?).into_dyn().into()))
+    }
+}
+
+/// Take the columns of each marginal from the arguments, defaulting to no marginals.
+pub fn take_marginals(arguments: &mut NodeArguments) -> Result<Vec<Vec<usize>>> {
+    Ok(match take_argument(arguments, "marginals") {
+        Ok(marginals) => match marginals.jagged()? {
+            Jagged::Int(marginals) => marginals.into_iter()
+                .map(|marginal| marginal.into_iter().map(|column| column as usize).collect())
+                .collect(),
+            _ => return Err("marginals: must be integer column indices".into())
+        },
+        Err(_) => Vec::new()
+    })
+}
+
+/// Values of each column of the data, as index keys.
+pub fn to_index_keys<T: Clone + Into<IndexKey>>(data: ArrayD<T>) -> Vec<Vec<IndexKey>> {
+    data.gencolumns().into_iter()
+        .map(|column| column.iter().cloned().map(Into::into).collect())
+        .collect()
+}
+
+/// Counts of the full table over all columns, followed by the counts of each marginal.
+///
+/// Records with a value outside of the categories of its column are not counted.
+/// Cells of each table are ordered by the categories of its columns, with the last column varying fastest.
+///
+/// # Arguments
+/// * `data` - Values of each column.
+/// * `categories` - Ordered categories of each column.
+/// * `marginals` - Columns of each marginal, in increasing order.
+///
+/// # Example
+/// ```
+/// use smartnoise_validator::base::IndexKey;
+/// use smartnoise_runtime::components::contingency_table::contingency_table;
+/// let keys = |values: Vec<i64>| values.into_iter().map(IndexKey::from).collect::<Vec<IndexKey>>();
+/// let data = vec![keys(vec![0, 0, 1, 2]), keys(vec![1, 0, 1, 1])];
+/// let categories = vec![keys(vec![0, 1]), keys(vec![0, 1])];
+/// let counts = contingency_table(&data, &categories, &[vec![1]]).unwrap();
+/// assert_eq!(counts, vec![1, 1, 0, 1, 1, 2]);
+/// ```
+pub fn contingency_table(
+    data: &[Vec<IndexKey>], categories: &[Vec<IndexKey>], marginals: &[Vec<usize>],
+) -> Result<Vec<Integer>> {
+    if data.len() != categories.len() {
+        return Err("categories must be defined for every column of the data".into())
+    }
+    let shape = categories.iter().map(Vec::len).collect::<Vec<usize>>();
+    let num_cells = get_table_size(&shape, &(0..shape.len()).collect::<Vec<usize>>())?;
+
+    let lookups = categories.iter()
+        .map(|column| column.iter().enumerate()
+            .map(|(index, category)| (category, index))
+            .collect::<HashMap<&IndexKey, usize>>())
+        .collect::<Vec<_>>();
+
+    let num_records = data.first().map(Vec::len).unwrap_or(0);
+    let mut table = vec![0 as Integer; num_cells];
+    (0..num_records).for_each(|row| {
+        let cell = data.iter().zip(lookups.iter()).zip(shape.iter())
+            .try_fold(0, |cell, ((column, lookup), size)| lookup.get(&column[row])
+                .map(|index| cell * size + index));
+        if let Some(cell) = cell {
+            table[cell] += 1;
+        }
+    });
+
+    let mut counts = table.clone();
+    for marginal in marginals {
+        let mut marginal_table = vec![0 as Integer; get_table_size(&shape, marginal)?];
+        get_marginal_indices(&shape, marginal)?.into_iter().zip(table.iter())
+            .for_each(|(index, count)| marginal_table[index] += count);
+        counts.extend(marginal_table);
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod test_contingency_table {
+    use ndarray::{arr1, Array};
+
+    use smartnoise_validator::base::{IndexKey, Jagged, Value};
+    use smartnoise_validator::bindings::Analysis;
+    use smartnoise_validator::components::contingency_table::get_table_key;
+
+    use crate::utilities::fixtures::{approximate_usage, release_analysis};
+
+    #[test]
+    fn test_dp_contingency_table_default_privacy_definition() {
+        let mut analysis = Analysis::new();
+
+        let data = Array::from_shape_fn((1000, 2), |(i, j)| ((i >> j) % 2) as i64);
+        let data = analysis.literal().value(data.into_dyn().into()).value_public(true).build();
+        let categories = analysis.literal()
+            .value(Value::Jagged(Jagged::Int(vec![vec![0, 1], vec![0, 1]]))).value_public(true).build();
+        let null_value = analysis.literal().value(arr1(&[-1, -1]).into_dyn().into()).value_public(true).build();
+        let clamped = analysis.clamp(data).categories(categories).null_value(null_value).build();
+        let marginals = analysis.literal()
+            .value(Value::Jagged(Jagged::Int(vec![vec![0]]))).value_public(true).build();
+
+        let table = analysis.dp_contingency_table(clamped, vec![approximate_usage(1., 0.)])
+            .marginals(marginals).build();
+
+        let release = release_analysis(&analysis).unwrap();
+        let tables = release[&table].value.clone().dataframe().unwrap();
+        let get_table = |columns: &[usize]| tables.get::<IndexKey>(&get_table_key(columns)).unwrap()
+            .clone().array().unwrap().float().unwrap();
+
+        let full = get_table(&[0, 1]);
+        assert_eq!(full.len(), 4);
+        assert!(full.iter().all(|v| (v - 250.).abs() < 50.), "full table: {:?}", full);
+
+        let marginal = get_table(&[0]);
+        assert_eq!(marginal.len(), 2);
+        assert!(marginal.iter().all(|v| (v - 500.).abs() < 50.), "marginal: {:?}", marginal);
+    }
+}
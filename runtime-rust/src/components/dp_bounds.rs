@@ -0,0 +1,162 @@
+use ndarray::{Array, Axis};
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::{DataType, ReleaseNode};
+use smartnoise_validator::components::dp_bounds::{BOUNDS_SENSITIVITY, get_exponential_edges};
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::privacy::{get_epsilon, spread_privacy_usage};
+use smartnoise_validator::utilities::{get_mechanism, take_argument};
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::mechanisms::{discrete_laplace_mechanism, simple_geometric_mechanism};
+
+impl Evaluable for proto::DpBounds {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+        let enforce_constant_time = privacy_definition.protect_elapsed_time;
+
+        // the histogram counts are integers, so they are privatized like counts
+        let mechanism = get_mechanism(&DataType::Int, "automatic", privacy_definition)?;
+
+        let data = take_argument(&mut arguments, "data")?.array()?.float()?;
+        let num_columns = data.len_of(Axis(1));
+        let lower = take_argument(&mut arguments, "lower")?.array()?.vec_float(Some(num_columns as i64))?;
+        let upper = take_argument(&mut arguments, "upper")?.array()?.vec_float(Some(num_columns as i64))?;
+
+        if lower.len() != num_columns || upper.len() != num_columns {
+            return Err("lower and upper must have one bound per column of the data".into())
+        }
+
+        if self.alpha <= 0. || self.alpha >= 0.5 {
+            return Err("alpha must be within (0, 0.5)".into())
+        }
+
+        let usages = spread_privacy_usage(&self.privacy_usage, num_columns)?;
+
+        // one row of [lower, upper] per column of the data
+        let bounds = data.gencolumns().into_iter()
+            .zip(lower.into_iter().zip(upper))
+            .zip(usages.iter())
+            .map(|((column, (lower, upper)), usage)| dp_bounds(
+                &column.to_vec(), lower, upper, self.alpha, get_epsilon(usage)?, &mechanism, enforce_constant_time)
+                .map(|(lower, upper)| vec![lower, upper]))
+            .collect::<Result<Vec<Vec<Float>>>>()?;
+
+        Ok(ReleaseNode {
+            value: Array::from_shape_vec((num_columns, 2), bounds.into_iter().flatten().collect())?.into_dyn().into(),
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+/// Estimate bounds from a noisy histogram over exponentially sized bins within the coarse bounds.
+///
+/// Records outside of the coarse bounds are counted in the outermost bins.
+/// The lower bound is the left edge of the bin where the noisy cumulative count first reaches `alpha` of the noisy total,
+/// and the upper bound is the right edge of the bin where the noisy cumulative count from above first reaches `alpha` of the noisy total.
+///
+/// # Arguments
+/// * `data` - Data to estimate bounds on.
+/// * `lower` - Coarse public lower bound on the data.
+/// * `upper` - Coarse public upper bound on the data.
+/// * `alpha` - Proportion of records that may fall outside of either estimated bound.
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `mechanism` - Mechanism to privatize the counts with, either `discretelaplace` or `simplegeometric`.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+pub fn dp_bounds(
+    data: &[Float], lower: Float, upper: Float, alpha: Float, epsilon: Float,
+    mechanism: &str, enforce_constant_time: bool,
+) -> Result<(Float, Float)> {
+    let edges = get_exponential_edges(lower, upper)?;
+    let num_bins = edges.len() - 1;
+
+    let mut counts = vec![0; num_bins];
+    data.iter().filter(|v| !v.is_nan()).for_each(|v| {
+        let index = edges[1..num_bins].iter().take_while(|edge| *edge <= v).count();
+        counts[index] += 1;
+    });
+
+    let num_records = data.len() as i64;
+    let noisy_counts = counts.into_iter()
+        .map(|count| match mechanism {
+            "discretelaplace" => discrete_laplace_mechanism(count, epsilon as f64, BOUNDS_SENSITIVITY),
+            "simplegeometric" => simple_geometric_mechanism(
+                count, epsilon as f64, BOUNDS_SENSITIVITY, 0, num_records, enforce_constant_time),
+            _ => Err(format!("mechanism {:?} is not supported for bounds estimation", mechanism).into())
+        }.map(|count| count as Float))
+        .collect::<Result<Vec<Float>>>()?;
+
+    let threshold = alpha * noisy_counts.iter().sum::<Float>();
+    // the noisy histogram carries no usable signal, so fall back to the coarse bounds
+    if threshold <= 0. {
+        return Ok((lower, upper))
+    }
+
+    let lower_bin = find_quantile_bin(noisy_counts.iter(), threshold).unwrap_or(0);
+    let upper_bin = num_bins - 1 - find_quantile_bin(noisy_counts.iter().rev(), threshold).unwrap_or(0);
+
+    // noise may cross the bins over, in which case the bounds span the union of both bins
+    Ok((edges[lower_bin.min(upper_bin)], edges[lower_bin.max(upper_bin) + 1]))
+}
+
+/// Index of the first bin where the cumulative count reaches the threshold.
+fn find_quantile_bin<'a>(mut counts: impl Iterator<Item=&'a Float>, threshold: Float) -> Option<usize> {
+    let mut cumulative = 0.;
+    counts.position(|count| {
+        cumulative += count;
+        cumulative >= threshold
+    })
+}
+
+#[cfg(test)]
+mod test_dp_bounds {
+    use ndarray::{Array, ArrayD};
+
+    use smartnoise_validator::bindings::Analysis;
+
+    use crate::components::dp_bounds::dp_bounds;
+    use crate::utilities::fixtures::{approximate_usage, bounded_float_data, release_analysis, unprotected_analysis};
+
+    #[test]
+    fn test_dp_bounds() {
+        let data = (0..1000).map(|v| v as f64 / 10.).collect::<Vec<f64>>();
+        let (lower, upper) = dp_bounds(&data, -1e6, 1e6, 0.01, 100., "discretelaplace", false).unwrap();
+
+        assert!(lower < upper);
+        assert!((0.25..=2.).contains(&lower));
+        assert!((64.0..=128.).contains(&upper));
+    }
+
+    /// release the mean of columns within [0, 10) and [100, 110), under coarse bounds of [-1e3, 1e3]
+    fn release_dp_mean_estimated_bounds(mut analysis: Analysis) -> ArrayD<f64> {
+        let data = Array::from_shape_fn((1000, 2), |(i, j)| (i % 100) as f64 / 10. + 100. * j as f64);
+
+        let imputed = bounded_float_data(&mut analysis, data.into_dyn(), &[-1e3, -1e3], &[1e3, 1e3]);
+
+        let mean = analysis.dp_mean(imputed, vec![approximate_usage(1e3, 0.)]).bounds_fraction(0.5).build();
+
+        let release = release_analysis(&analysis).unwrap();
+        let mean = release[&mean].value.clone().array().unwrap().float().unwrap();
+        assert_eq!(mean.len(), 2);
+        mean
+    }
+
+    #[test]
+    fn test_dp_mean_estimated_bounds() {
+        let mean = release_dp_mean_estimated_bounds(unprotected_analysis());
+        let expected = [4.95, 104.95];
+        assert!(mean.iter().zip(expected.iter()).all(|(l, r)| (l - r).abs() < 1.),
+                "mean: {:?}", mean);
+    }
+
+    #[test]
+    fn test_dp_mean_estimated_bounds_default_privacy_definition() {
+        let mean = release_dp_mean_estimated_bounds(Analysis::new());
+        let expected = [4.95, 104.95];
+        assert!(mean.iter().zip(expected.iter()).all(|(l, r)| (l - r).abs() < 1.),
+                "mean: {:?}", mean);
+    }
+}
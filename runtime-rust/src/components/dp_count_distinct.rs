@@ -0,0 +1,185 @@
+use std::convert::TryInto;
+
+use openssl::sha::Sha256;
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::{Array, IndexKey, ReleaseNode};
+use smartnoise_validator::components::dp_count_distinct::{get_flip_probability, MAX_NUM_LEVELS};
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::privacy::get_epsilon;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::{fill_bytes, noise};
+
+impl Evaluable for proto::DpCountDistinct {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.first_float()?;
+
+        if self.privacy_usage.len() != 1 {
+            return Err(Error::from("DPCountDistinct only supports one privacy parameter"))
+        }
+        let epsilon = get_epsilon(&self.privacy_usage[0])?;
+        let flip_probability = get_flip_probability(epsilon, sensitivity);
+
+        let num_buckets = self.num_buckets as usize;
+        let num_levels = self.num_levels as usize;
+
+        // a fresh salt for every release, so that the buckets of values cannot be predicted
+        let mut salt = [0u8; 16];
+        fill_bytes(&mut salt)?;
+
+        let sketch = match take_argument(&mut arguments, "data")?.array()? {
+            Array::Int(data) => sketch_distinct(data.iter().cloned().map(IndexKey::from), &salt, num_buckets, num_levels)?,
+            Array::Bool(data) => sketch_distinct(data.iter().cloned().map(IndexKey::from), &salt, num_buckets, num_levels)?,
+            Array::Str(data) => sketch_distinct(data.iter().cloned().map(IndexKey::from), &salt, num_buckets, num_levels)?,
+            Array::Float(_) => return Err("data may not be float".into())
+        };
+        let sketch = privatize_sketch(sketch, flip_probability, enforce_constant_time)?;
+
+        Ok(ReleaseNode {
+            value: estimate_count_distinct(&sketch, flip_probability).into(),
+            privacy_usages: Some(self.privacy_usage.clone()),
+            public: true,
+        })
+    }
+}
+
+/// Flajolet-Martin style sketch of the distinct values.
+///
+/// The salted hash of each value selects one bit: the bucket is the low 32 bits of the hash modulo the number of buckets,
+/// and the level is the number of trailing zeros in the high 32 bits of the hash,
+/// so each distinct value lands in level j with probability 2^-(j + 1).
+/// Repeated values set the same bit, so memory is bounded by the size of the sketch.
+///
+/// # Arguments
+/// * `values` - Values to count the distinct elements of.
+/// * `salt` - Salt of the hash.
+/// * `num_buckets` - Number of buckets in each level.
+/// * `num_levels` - Number of levels. The last level collects every value with at least `num_levels - 1` trailing zeros.
+pub fn sketch_distinct(
+    values: impl Iterator<Item=IndexKey>, salt: &[u8], num_buckets: usize, num_levels: usize,
+) -> Result<Vec<Vec<bool>>> {
+    if num_buckets == 0 || num_levels == 0 || num_levels > MAX_NUM_LEVELS as usize {
+        return Err("sketch must have at least one bucket and one level, and at most 32 levels".into())
+    }
+    let mut sketch = vec![vec![false; num_buckets]; num_levels];
+
+    values.for_each(|value| {
+        let hash = hash_value(salt, &value);
+        let bucket = (hash as u32) as usize % num_buckets;
+        let level = ((hash >> 32) as u32).trailing_zeros().min(num_levels as u32 - 1) as usize;
+        sketch[level][bucket] = true;
+    });
+    Ok(sketch)
+}
+
+/// Salted SHA-256 hash of a value, truncated to 64 bits.
+///
+/// Values are encoded explicitly, so the hash is stable across platforms and versions of Rust.
+fn hash_value(salt: &[u8], value: &IndexKey) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    encode_value(&mut hasher, value);
+    u64::from_le_bytes(hasher.finish()[..8].try_into().unwrap())
+}
+
+/// Write a type tag and the bytes of the value, with the lengths of strings and tuples so that encodings are unambiguous.
+fn encode_value(hasher: &mut Sha256, value: &IndexKey) {
+    match value {
+        IndexKey::Str(value) => {
+            hasher.update(b"s");
+            hasher.update(&(value.len() as u64).to_le_bytes());
+            hasher.update(value.as_bytes());
+        }
+        IndexKey::Int(value) => {
+            hasher.update(b"i");
+            hasher.update(&value.to_le_bytes());
+        }
+        IndexKey::Bool(value) => {
+            hasher.update(b"b");
+            hasher.update(&[*value as u8]);
+        }
+        IndexKey::Tuple(values) => {
+            hasher.update(b"t");
+            hasher.update(&(values.len() as u64).to_le_bytes());
+            values.iter().for_each(|value| encode_value(hasher, value));
+        }
+    }
+}
+
+/// Flip each bit of the sketch independently with probability `flip_probability`.
+///
+/// # Arguments
+/// * `sketch` - Sketch of the distinct values.
+/// * `flip_probability` - Probability of flipping each bit.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+pub fn privatize_sketch(
+    sketch: Vec<Vec<bool>>, flip_probability: Float, enforce_constant_time: bool,
+) -> Result<Vec<Vec<bool>>> {
+    sketch.into_iter()
+        .map(|level| level.into_iter()
+            .map(|bit| Ok(bit ^ noise::sample_bit_prob(flip_probability, enforce_constant_time)?))
+            .collect::<Result<Vec<bool>>>())
+        .collect()
+}
+
+/// Estimate the number of distinct values from a sketch released with randomized response.
+///
+/// The number of occupied buckets in each level is debiased, and the count is estimated from
+/// the first level that is at most half full, where the estimate of occupancy is most precise.
+/// A level with b of m buckets occupied holds about -m ln(1 - b / m) distinct values,
+/// and level j holds a 2^-(j + 1) proportion of the distinct values.
+///
+/// # Arguments
+/// * `sketch` - Sketch released with randomized response.
+/// * `flip_probability` - Probability each bit was flipped.
+pub fn estimate_count_distinct(sketch: &[Vec<bool>], flip_probability: Float) -> Float {
+    let occupancies = sketch.iter()
+        .map(|level| {
+            let num_buckets = level.len() as Float;
+            let num_ones = level.iter().filter(|bit| **bit).count() as Float;
+            ((num_ones - num_buckets * flip_probability) / (1. - 2. * flip_probability))
+                .max(0.).min(num_buckets - 1.)
+        })
+        .collect::<Vec<Float>>();
+
+    let (level, occupancy) = occupancies.iter().enumerate()
+        .find(|(_, occupancy)| **occupancy <= sketch[0].len() as Float / 2.)
+        .unwrap_or((occupancies.len() - 1, &occupancies[occupancies.len() - 1]));
+
+    let num_buckets = sketch[level].len() as Float;
+    let level_count = -num_buckets * (1. - occupancy / num_buckets).ln();
+
+    // the last level also collects all deeper levels
+    let exponent = if level == sketch.len() - 1 { level } else { level + 1 };
+    level_count * 2f64.powi(exponent as i32)
+}
+
+#[cfg(test)]
+mod test_dp_count_distinct {
+    use smartnoise_validator::base::IndexKey;
+
+    use crate::components::dp_count_distinct::{estimate_count_distinct, privatize_sketch, sketch_distinct};
+
+    #[test]
+    fn test_count_distinct() {
+        let values = (0..100_000).map(|i| IndexKey::from(format!("value {}", i % 20_000)));
+        let sketch = sketch_distinct(values, b"salt", 1024, 32).unwrap();
+        let sketch = privatize_sketch(sketch, 0.01, false).unwrap();
+        let estimate = estimate_count_distinct(&sketch, 0.01);
+        assert!((estimate - 20_000.).abs() < 3_000., "estimate: {}", estimate);
+    }
+
+    #[test]
+    fn test_salted_sketch() {
+        let values = || (0..1000i64).map(IndexKey::from);
+        let sketch = sketch_distinct(values(), b"salt", 64, 8).unwrap();
+        assert_eq!(sketch, sketch_distinct(values(), b"salt", 64, 8).unwrap());
+        assert_ne!(sketch, sketch_distinct(values(), b"pepper", 64, 8).unwrap());
+    }
+}
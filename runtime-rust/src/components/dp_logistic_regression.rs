@@ -0,0 +1,208 @@
+use ndarray::{Array, Ix2};
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::components::dp_logistic_regression::{get_objective_perturbation_parameters, OBJECTIVE_EPSILON_FRACTION};
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::privacy::get_epsilon;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::{noise, to_nd};
+
+impl Evaluable for proto::DpLogisticRegression {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let data_x = to_nd(take_argument(&mut arguments, "data_x")?.array()?.cast_float()?, 2)?
+            .into_dimensionality::<Ix2>()?;
+        let data_y = take_argument(&mut arguments, "data_y")?.array()?.bool()?;
+        let feature_norm = take_argument(&mut arguments, "feature_norm")?.array()?.first_float()? as f64;
+
+        if data_x.shape()[0] != data_y.len() {
+            return Err("data_x and data_y must share the same number of records".into())
+        }
+        if data_x.shape()[0] == 0 {
+            return Err("data_x may not be empty".into())
+        }
+        if feature_norm <= 0. {
+            return Err("feature_norm must be positive".into())
+        }
+        if self.tolerance <= 0. {
+            return Err("tolerance must be positive".into())
+        }
+
+        if self.privacy_usage.len() != 1 {
+            return Err(Error::from("DPLogisticRegression is not vectorized, only one privacy parameter may be passed"))
+        }
+        let epsilon = get_epsilon(&self.privacy_usage[0])?;
+
+        // prepend the intercept term, clip each row to the feature norm, and then divide by the feature norm,
+        //    so that every row has norm at most one
+        let data_x = data_x.genrows().into_iter()
+            .map(|row| {
+                let row = std::iter::once(1.).chain(row.iter().map(|v| *v as f64)).collect::<Vec<f64>>();
+                let norm = row.iter().map(|v| v.powi(2)).sum::<f64>().sqrt();
+                let clip = (feature_norm / norm).min(1.);
+                row.into_iter().map(|v| v * clip / feature_norm).collect()
+            })
+            .collect::<Vec<Vec<f64>>>();
+        let data_y = data_y.iter()
+            .map(|v| if *v { 1. } else { -1. })
+            .collect::<Vec<f64>>();
+
+        let num_records = data_x.len() as f64;
+        let dimension = data_x[0].len();
+
+        let weights: Vec<f64> = match self.implementation.to_lowercase().as_str() {
+            "objective" => {
+                // approximate minima perturbation: the exact minimizer of the perturbed objective is private,
+                //    and the remaining epsilon is spent on output noise that covers the distance to the approximate minimizer
+                let (epsilon_noise, extra_regularization) = get_objective_perturbation_parameters(
+                    epsilon * OBJECTIVE_EPSILON_FRACTION, self.regularization, num_records)?;
+                let regularization = self.regularization + extra_regularization;
+                let noise = noise::sample_spherical_laplace(dimension, 2. / epsilon_noise, enforce_constant_time)?;
+                let noise_norm = noise.iter().map(|v| v.powi(2)).sum::<f64>().sqrt();
+
+                let distance = get_minimizer_distance(
+                    regularization, 0.5 + noise_norm / num_records, self.tolerance, self.iterations);
+                let output_noise = noise::sample_spherical_laplace(
+                    dimension, 2. * distance / (epsilon * (1. - OBJECTIVE_EPSILON_FRACTION)), enforce_constant_time)?;
+
+                train_logistic_regression(
+                    &data_x, &data_y, regularization, &noise, self.tolerance, self.iterations)
+                    .into_iter().zip(output_noise)
+                    .map(|(weight, noise)| weight + noise)
+                    .collect()
+            },
+            "output" => {
+                // the exact minimizer has L2 sensitivity 2 / (n * regularization),
+                //    and each of the two approximate minimizers is within the minimizer distance of its exact minimizer
+                let distance = get_minimizer_distance(
+                    self.regularization, 0.5, self.tolerance, self.iterations);
+                let sensitivity = 2. / (num_records * self.regularization) + 2. * distance;
+                let noise = noise::sample_spherical_laplace(
+                    dimension, sensitivity / epsilon, enforce_constant_time)?;
+                let zeros = vec![0.; dimension];
+                train_logistic_regression(
+                    &data_x, &data_y, self.regularization, &zeros, self.tolerance, self.iterations)
+                    .into_iter().zip(noise)
+                    .map(|(weight, noise)| weight + noise)
+                    .collect()
+            },
+            _ => return Err("implementation must be one of [objective, output]".into())
+        };
+
+        // undo the rescaling of the rows
+        let coefficients = weights.into_iter()
+            .map(|weight| (weight / feature_norm) as Float)
+            .collect::<Vec<Float>>();
+
+        Ok(ReleaseNode {
+            value: Array::from_shape_vec((1, dimension), coefficients)?.into_dyn().into(),
+            privacy_usages: Some(self.privacy_usage.clone()),
+            public: true,
+        })
+    }
+}
+
+/// Upper bound on the L2 distance between the weights returned by `train_logistic_regression` and the exact minimizer.
+///
+/// The objective is regularization-strongly convex and (1/4 + regularization)-smooth,
+///     so each gradient descent step contracts the distance to the minimizer by a factor of 1/4 / (1/4 + regularization).
+/// Gradient descent starts at zero, which is within `gradient_bound / regularization` of the minimizer.
+/// If gradient descent stops early, the norm of the gradient is at most `tolerance`, so the weights are within `tolerance / regularization`.
+///
+/// # Arguments
+/// * `regularization` - Coefficient of the L2 penalty on the weights.
+/// * `gradient_bound` - Upper bound on the norm of the gradient of the objective at zero.
+/// * `tolerance` - Upper bound on the norm of the gradient at which gradient descent stops.
+/// * `iterations` - Maximum number of gradient descent steps.
+fn get_minimizer_distance(regularization: f64, gradient_bound: f64, tolerance: f64, iterations: u32) -> f64 {
+    let contraction = 0.25 / (0.25 + regularization);
+    let converged = tolerance / regularization;
+    let exhausted = contraction.powf(iterations as f64) * gradient_bound / regularization;
+    converged.max(exhausted)
+}
+
+/// Minimize the perturbed, L2-regularized logistic loss by gradient descent.
+///
+/// The objective is mean(log(1 + exp(-y w'x))) + regularization / 2 ||w||^2 + noise'w / n.
+/// Rows are assumed to have norm at most one, so the objective is (1/4 + regularization)-smooth.
+/// The objective is also regularization-strongly convex,
+///     so once the norm of the gradient is at most `tolerance`, the weights are within `tolerance / regularization` of the minimizer.
+///
+/// # Arguments
+/// * `data_x` - Rows of predictors.
+/// * `data_y` - Targets, either -1 or 1.
+/// * `regularization` - Coefficient of the L2 penalty on the weights.
+/// * `noise` - Linear perturbation of the objective.
+/// * `tolerance` - Upper bound on the norm of the gradient at the returned weights.
+/// * `iterations` - Maximum number of gradient descent steps.
+///
+/// # Return
+/// Approximate minimizer of the objective, or the last iterate if the tolerance is not reached within the given number of steps.
+fn train_logistic_regression(
+    data_x: &[Vec<f64>], data_y: &[f64],
+    regularization: f64, noise: &[f64], tolerance: f64, iterations: u32,
+) -> Vec<f64> {
+    let num_records = data_x.len() as f64;
+    let step_size = 1. / (0.25 + regularization);
+
+    let mut weights = vec![0.; noise.len()];
+    for _ in 0..iterations {
+        let mut gradient = weights.iter().zip(noise.iter())
+            .map(|(weight, noise)| regularization * weight + noise / num_records)
+            .collect::<Vec<f64>>();
+
+        data_x.iter().zip(data_y.iter()).for_each(|(row, y)| {
+            let margin = y * row.iter().zip(weights.iter()).map(|(x, w)| x * w).sum::<f64>();
+            let coefficient = -y / (1. + margin.exp()) / num_records;
+            gradient.iter_mut().zip(row.iter())
+                .for_each(|(g, x)| *g += coefficient * x);
+        });
+
+        if gradient.iter().map(|g| g.powi(2)).sum::<f64>().sqrt() <= tolerance {
+            return weights
+        }
+
+        weights.iter_mut().zip(gradient)
+            .for_each(|(w, g)| *w -= step_size * g);
+    }
+    weights
+}
+
+#[cfg(test)]
+mod test_dp_logistic_regression {
+    use crate::components::dp_logistic_regression::{get_minimizer_distance, train_logistic_regression};
+
+    #[test]
+    fn test_train_logistic_regression() {
+        let data_x = (0..100)
+            .map(|i| vec![0.5, (i as f64 - 50.) / 100.])
+            .collect::<Vec<Vec<f64>>>();
+        let data_y = (0..100)
+            .map(|i| if i < 50 { -1. } else { 1. })
+            .collect::<Vec<f64>>();
+
+        let weights = train_logistic_regression(&data_x, &data_y, 0.01, &[0., 0.], 1e-8, 10000);
+        assert!(weights[1] > 0.);
+
+        // the first order condition holds at the returned weights
+        let gradient_norm = (0..2)
+            .map(|j| 0.01 * weights[j] + data_x.iter().zip(data_y.iter())
+                .map(|(row, y)| -y * row[j] / (1. + (y * (row[0] * weights[0] + row[1] * weights[1])).exp()))
+                .sum::<f64>() / 100.)
+            .map(|g| g.powi(2)).sum::<f64>().sqrt();
+        assert!(gradient_norm <= 1e-8);
+
+        // too few iterations to converge returns the last iterate, within the minimizer distance of the weights
+        let distance = get_minimizer_distance(0.01, 0.5, 1e-8, 10);
+        let last_iterate = train_logistic_regression(&data_x, &data_y, 0.01, &[0., 0.], 1e-8, 10);
+        assert!(weights.iter().zip(last_iterate.iter())
+            .map(|(l, r)| (l - r).powi(2)).sum::<f64>().sqrt() <= distance);
+        assert!(distance > 1e-8 / 0.01);
+    }
+}
@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+
+use ndarray::Array;
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::components::dp_quantiles::{get_num_levels, QUANTILES_SENSITIVITY};
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::privacy::get_epsilon;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::noise;
+
+impl Evaluable for proto::DpQuantiles {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let mut data = take_argument(&mut arguments, "data")?.array()?.vec_float(None)?;
+        let candidates = take_argument(&mut arguments, "candidates")?.array()?.vec_float(None)?;
+
+        if candidates.is_empty() {
+            return Err("candidates may not be empty".into())
+        }
+        if candidates.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err("candidates must be sorted".into())
+        }
+
+        if self.privacy_usage.len() != 1 {
+            return Err(Error::from("DPQuantiles shares one privacy parameter among all quantiles, only one privacy parameter may be passed"))
+        }
+        let epsilon = get_epsilon(&self.privacy_usage[0])?;
+
+        data.retain(|v| !v.is_nan());
+        data.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let quantiles = match self.implementation.to_lowercase().as_str() {
+            "jointexp" => joint_exponential_quantiles(
+                &data, &candidates, &self.alphas, epsilon)?,
+            "recursive" => {
+                let num_levels = get_num_levels(&self.implementation, self.alphas.len())?;
+                recursive_quantiles(
+                    &data, &candidates, &self.alphas, epsilon / num_levels as Float)?
+            }
+            _ => return Err("implementation must be one of [JointExp, Recursive]".into())
+        };
+
+        Ok(ReleaseNode {
+            value: Array::from(quantiles).into_dyn().into(),
+            privacy_usages: Some(self.privacy_usage.clone()),
+            public: true,
+        })
+    }
+}
+
+/// Number of records strictly less than each candidate.
+fn count_below(sorted_data: &[Float], candidates: &[Float]) -> Vec<Float> {
+    let mut index = 0;
+    candidates.iter().map(|candidate| {
+        while index < sorted_data.len() && sorted_data[index] < *candidate {
+            index += 1;
+        }
+        index as Float
+    }).collect()
+}
+
+/// Index of the largest log-weight, after adding independent standard Gumbel noise to each.
+///
+/// This samples an index with probability proportional to the exponentiated log-weights.
+fn sample_gumbel_max(log_weights: &[Float]) -> Result<usize> {
+    let mut max_noisy_weight = f64::NEG_INFINITY;
+    let mut arg_max = None;
+    for (index, log_weight) in log_weights.iter().enumerate() {
+        if log_weight.is_infinite() && log_weight.is_sign_negative() {
+            continue
+        }
+        let noisy_weight = log_weight + noise::sample_gumbel(0.0, 1.0);
+        if arg_max.is_none() || noisy_weight > max_noisy_weight {
+            max_noisy_weight = noisy_weight;
+            arg_max = Some(index);
+        }
+    }
+    arg_max.ok_or_else(|| Error::from("every candidate has zero probability"))
+}
+
+fn log_sum_exp(values: impl Iterator<Item=Float> + Clone) -> Float {
+    let max = values.clone().fold(f64::NEG_INFINITY, f64::max);
+    if max.is_infinite() {
+        return max
+    }
+    max + values.map(|v| (v - max).exp()).sum::<Float>().ln()
+}
+
+/// Jointly sample non-decreasing quantiles from the candidates with the exponential mechanism.
+///
+/// The utility of a tuple of candidates is the negated L1 distance between the number of records in each gap
+/// between consecutive quantiles and the number of records that would ideally fall in the gap.
+/// The distribution over tuples factors over consecutive quantiles,
+/// so it can be sampled backwards from the partial sums computed in a forward pass.
+/// Gillenwater, Joseph and Kulesza (2021), Differentially Private Quantiles
+///
+/// # Arguments
+/// * `sorted_data` - Data sorted in increasing order.
+/// * `candidates` - Candidates sorted in increasing order.
+/// * `alphas` - Quantiles to release, sorted in increasing order.
+/// * `epsilon` - Multiplicative privacy loss parameter.
+pub fn joint_exponential_quantiles(
+    sorted_data: &[Float], candidates: &[Float], alphas: &[Float], epsilon: Float,
+) -> Result<Vec<Float>> {
+    if alphas.is_empty() {
+        return Ok(Vec::new())
+    }
+    let num_records = sorted_data.len() as Float;
+    let counts = count_below(sorted_data, candidates);
+    let scale = epsilon / (2. * QUANTILES_SENSITIVITY);
+
+    // ideal number of records in each of the m + 1 gaps between quantiles
+    let targets = std::iter::once(0.).chain(alphas.iter().cloned()).chain(std::iter::once(1.))
+        .collect::<Vec<Float>>().windows(2)
+        .map(|pair| (pair[1] - pair[0]) * num_records)
+        .collect::<Vec<Float>>();
+
+    let gap_score = |upper: usize, lower: usize, gap: usize|
+        -scale * (counts[upper] - counts[lower] - targets[gap]).abs();
+
+    // log_weights[j][k]: log total weight of the first j + 1 quantiles, with quantile j at candidate k
+    let mut log_weights = vec![counts.iter()
+        .map(|count| -scale * (count - targets[0]).abs())
+        .collect::<Vec<Float>>()];
+    for gap in 1..alphas.len() {
+        let previous = &log_weights[gap - 1];
+        let current = (0..candidates.len())
+            .map(|k| log_sum_exp((0..=k).map(|k_prev| previous[k_prev] + gap_score(k, k_prev, gap))))
+            .collect();
+        log_weights.push(current);
+    }
+
+    let final_weights = log_weights[alphas.len() - 1].iter().zip(counts.iter())
+        .map(|(weight, count)| weight - scale * (num_records - count - targets[alphas.len()]).abs())
+        .collect::<Vec<Float>>();
+
+    let mut indices = vec![sample_gumbel_max(&final_weights)?];
+    for gap in (0..alphas.len() - 1).rev() {
+        let upper = indices[indices.len() - 1];
+        let conditional_weights = (0..=upper)
+            .map(|k| log_weights[gap][k] + gap_score(upper, k, gap + 1))
+            .collect::<Vec<Float>>();
+        indices.push(sample_gumbel_max(&conditional_weights)?);
+    }
+
+    Ok(indices.into_iter().rev().map(|k| candidates[k]).collect())
+}
+
+/// Recursively select the middle quantile with the exponential mechanism, and recurse on the data to either side.
+///
+/// Records on either side of a split are disjoint, so each level of recursion spends `epsilon` once.
+/// Kaplan, Schnapp and Stemmer (2022), Differentially Private Approximate Quantiles
+///
+/// # Arguments
+/// * `sorted_data` - Data sorted in increasing order.
+/// * `candidates` - Candidates sorted in increasing order.
+/// * `alphas` - Quantiles to release, sorted in increasing order.
+/// * `epsilon` - Multiplicative privacy loss parameter of each level of recursion.
+pub fn recursive_quantiles(
+    sorted_data: &[Float], candidates: &[Float], alphas: &[Float], epsilon: Float,
+) -> Result<Vec<Float>> {
+    if alphas.is_empty() {
+        return Ok(Vec::new())
+    }
+    let middle = alphas.len() / 2;
+    let alpha = alphas[middle];
+    let target = alpha * sorted_data.len() as Float;
+    let scale = epsilon / (2. * QUANTILES_SENSITIVITY);
+
+    let counts = count_below(sorted_data, candidates);
+    let log_weights = counts.iter()
+        .map(|count| -scale * (count - target).abs())
+        .collect::<Vec<Float>>();
+    let selected = sample_gumbel_max(&log_weights)?;
+    let split = counts[selected] as usize;
+
+    // rescale the remaining quantiles to either side of the split
+    let lower_alphas = alphas[..middle].iter()
+        .map(|a| if alpha > 0. { a / alpha } else { 0. })
+        .collect::<Vec<Float>>();
+    let upper_alphas = alphas[middle + 1..].iter()
+        .map(|a| if alpha < 1. { (a - alpha) / (1. - alpha) } else { 1. })
+        .collect::<Vec<Float>>();
+
+    let mut quantiles = recursive_quantiles(
+        &sorted_data[..split], &candidates[..=selected], &lower_alphas, epsilon)?;
+    quantiles.push(candidates[selected]);
+    quantiles.extend(recursive_quantiles(
+        &sorted_data[split..], &candidates[selected..], &upper_alphas, epsilon)?);
+    Ok(quantiles)
+}
+
+#[cfg(test)]
+mod test_dp_quantiles {
+    use crate::components::dp_quantiles::{joint_exponential_quantiles, recursive_quantiles};
+
+    fn check_quantiles(quantiles: &[f64]) {
+        let expected = [25., 50., 75.];
+        assert_eq!(quantiles.len(), expected.len());
+        assert!(quantiles.windows(2).all(|pair| pair[0] <= pair[1]));
+        quantiles.iter().zip(expected.iter())
+            .for_each(|(actual, expected)| assert!((actual - expected).abs() <= 5.));
+    }
+
+    #[test]
+    fn test_quantiles() {
+        let data = (0..100).map(|v| v as f64).collect::<Vec<f64>>();
+        let candidates = (0..=100).map(|v| v as f64).collect::<Vec<f64>>();
+        let alphas = [0.25, 0.5, 0.75];
+
+        check_quantiles(&joint_exponential_quantiles(&data, &candidates, &alphas, 100.).unwrap());
+        check_quantiles(&recursive_quantiles(&data, &candidates, &alphas, 100.).unwrap());
+    }
+}
@@ -0,0 +1,100 @@
+use ndarray::ArrayD;
+use noisy_float::types::n64;
+
+use smartnoise_validator::{Integer, proto};
+use smartnoise_validator::base::{Array, ReleaseNode};
+use smartnoise_validator::components::hierarchical_histogram::get_num_levels;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::components::histogram::histogram;
+use crate::NodeArguments;
+
+impl Evaluable for proto::HierarchicalHistogram {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let data = take_argument(&mut arguments, "data")?.array()?;
+        let ndim = data.shape().len();
+
+        let leaves = match (data, take_argument(&mut arguments, "categories")?.array()?) {
+            (Array::Bool(data), Array::Bool(categories)) =>
+                histogram(&data, &categories)?,
+            (Array::Float(data), Array::Float(categories)) =>
+                histogram(&data.mapv(|v| n64(v as f64)), &categories.mapv(|v| n64(v as f64)))?,
+            (Array::Int(data), Array::Int(categories)) =>
+                histogram(&data, &categories)?,
+            (Array::Str(data), Array::Str(categories)) =>
+                histogram(&data, &categories)?,
+            _ => return Err("data and categories must be homogeneously typed".into())
+        };
+
+        let tree = hierarchical_counts(leaves.iter().cloned().collect(), self.branching)?;
+        let num_nodes = tree.len();
+
+        // ensure the tree is of the same dimension as the data
+        Ok(ReleaseNode::new(match ndim {
+            1 => ArrayD::from_shape_vec(vec![num_nodes], tree),
+            2 => ArrayD::from_shape_vec(vec![num_nodes, 1], tree),
+            _ => return Err("invalid data shape for HierarchicalHistogram".into())
+        }?.into()))
+    }
+}
+
+/// Counts of every node of a complete tree over the leaves, from the root down and from left to right within each level.
+pub fn hierarchical_counts(leaves: Vec<Integer>, branching: u32) -> Result<Vec<Integer>> {
+    let num_levels = get_num_levels(leaves.len(), branching)?;
+    let branching = branching as usize;
+
+    // pad the leaves to fill the last level
+    let mut level = leaves;
+    level.resize(branching.pow(num_levels as u32), 0);
+
+    let mut levels = vec![level];
+    while levels[0].len() > 1 {
+        let parents = levels[0].chunks(branching)
+            .map(|children| children.iter().sum())
+            .collect();
+        levels.insert(0, parents);
+    }
+    Ok(levels.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod test_hierarchical_histogram {
+    use ndarray::{arr1, Array};
+
+    use smartnoise_validator::base::{Jagged, Value};
+    use smartnoise_validator::bindings::Analysis;
+
+    use crate::components::hierarchical_histogram::hierarchical_counts;
+    use crate::utilities::fixtures::{approximate_usage, release_analysis};
+
+    #[test]
+    fn test_hierarchical_counts() {
+        assert_eq!(hierarchical_counts(vec![1, 2, 3], 2).unwrap(), vec![6, 3, 3, 1, 2, 3, 0]);
+        assert_eq!(hierarchical_counts(vec![1, 2, 3], 3).unwrap(), vec![6, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_dp_hierarchical_histogram() {
+        // the automatic mechanism is discrete laplace under the default privacy definition, and simple geometric otherwise
+        for protect_floating_point in &[true, false] {
+            let mut analysis = Analysis::new();
+            analysis.privacy_definition.protect_floating_point = *protect_floating_point;
+
+            let data = Array::from_shape_fn((1000, 1), |(i, _)| (i % 4) as i64);
+            let data = analysis.literal().value(data.into_dyn().into()).value_public(true).build();
+            let categories = analysis.literal()
+                .value(Value::Jagged(Jagged::Int(vec![vec![0, 1, 2, 3]]))).value_public(true).build();
+            let null_value = analysis.literal().value(arr1(&[-1]).into_dyn().into()).value_public(true).build();
+            let clamped = analysis.clamp(data).categories(categories).null_value(null_value).build();
+
+            let histogram = analysis.dp_hierarchical_histogram(clamped, vec![approximate_usage(1., 0.)]).build();
+
+            let release = release_analysis(&analysis).unwrap();
+            let counts = release[&histogram].value.clone().array().unwrap().float().unwrap();
+            assert_eq!(counts.len(), 4);
+            assert!(counts.iter().all(|v| (v - 250.).abs() < 50.), "counts: {:?}", counts);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use ndarray::arr1;
+
+use smartnoise_validator::proto;
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::components::mean_confidence_interval::get_interval;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::noise::{sample_gaussian, sample_laplace};
+
+impl Evaluable for proto::MeanConfidenceInterval {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let mut take_float = |name: &str| -> Result<f64> {
+            take_argument(&mut arguments, name)?.array()?.first_float()
+        };
+        let mean = take_float("data")?;
+        let variance = take_float("variance")?;
+        let noise_scale = take_float("noise_scale")?;
+        let variance_scale = take_float("variance_scale")?;
+        let num_records = take_float("num_records")?;
+        let lower = take_float("lower")?;
+        let upper = take_float("upper")?;
+
+        let (interval_lower, interval_upper) = mean_confidence_interval(
+            mean, variance, num_records, (lower, upper),
+            &self.noise, noise_scale, variance_scale, self.confidence, self.num_simulations, enforce_constant_time)?;
+
+        Ok(ReleaseNode::new(arr1(&[interval_lower, interval_upper]).into_dyn().into()))
+    }
+}
+
+/// Confidence interval for the population mean, from a noisy mean and a noisy variance.
+///
+/// The error of the noisy mean is the sampling error of the mean plus the noise of the mechanism.
+/// The sampling error is simulated as gaussian, and the noise is simulated from the same distribution and scale as was added to the mean.
+/// The variance of each sampling error is the noisy variance less a fresh draw of the noise that was added to the variance,
+/// clamped to the largest variance of data within the bounds, so that the uncertainty of the variance is carried into the interval.
+/// Du, Foot, Moniot, Bray and Groce (2020), Differentially Private Confidence Intervals
+///
+/// # Arguments
+/// * `mean` - Noisy mean.
+/// * `variance` - Noisy variance of the data.
+/// * `num_records` - Number of records the mean was computed over.
+/// * `bounds` - Lower and upper bound of the data.
+/// * `noise` - Distribution of the noise, either `laplace` or `gaussian`.
+/// * `noise_scale` - Scale of the laplace distribution, or standard deviation of the gaussian distribution, added to the mean.
+/// * `variance_scale` - Scale of the noise of the same distribution added to the variance.
+/// * `confidence` - Probability that the interval contains the population mean.
+/// * `num_simulations` - Number of simulated errors.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// The lower and upper endpoints of the interval.
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::components::mean_confidence_interval::mean_confidence_interval;
+/// let (lower, upper) = mean_confidence_interval(
+///     0.5, 0.08, 1000., (0., 1.), "laplace", 0.001, 0.01, 0.95, 1000, false).unwrap();
+/// assert!(lower < 0.5 && 0.5 < upper);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn mean_confidence_interval(
+    mean: f64, variance: f64, num_records: f64, bounds: (f64, f64),
+    noise: &str, noise_scale: f64, variance_scale: f64, confidence: f64, num_simulations: u32,
+    enforce_constant_time: bool,
+) -> Result<(f64, f64)> {
+    if num_records < 1. {
+        return Err("num_records: must be positive".into())
+    }
+    let (lower, upper) = bounds;
+    let maximum_variance = (upper - lower).powi(2) / 4.;
+    let noise = noise.to_lowercase();
+
+    let sample_noise = |noise: &str, scale: f64| -> Result<f64> {
+        if scale == 0. {
+            return Ok(0.)
+        }
+        match noise {
+            "laplace" => sample_laplace(0., scale, enforce_constant_time),
+            "gaussian" => sample_gaussian(0., scale, enforce_constant_time),
+            _ => Err(format!("noise: unexpected invalid token {:?}", noise).into())
+        }
+    };
+
+    let errors = (0..num_simulations)
+        .map(|_| {
+            let variance = (variance - sample_noise(&noise, variance_scale)?).max(0.).min(maximum_variance);
+            Ok(sample_noise("gaussian", (variance / num_records).sqrt())?
+                + sample_noise(&noise, noise_scale)?)
+        })
+        .collect::<Result<Vec<f64>>>()?;
+
+    get_interval(mean, errors, confidence, lower, upper)
+}
+
+#[cfg(test)]
+mod test_mean_confidence_interval {
+    use ndarray::Array;
+
+    use smartnoise_validator::bindings::Analysis;
+
+    use crate::components::mean_confidence_interval::mean_confidence_interval;
+    use crate::utilities::fixtures::{approximate_usage, bounded_float_data, release_analysis, unprotected_analysis};
+    use crate::utilities::noise::{sample_laplace, sample_uniform};
+
+    #[test]
+    fn test_coverage() {
+        // uniform data on [0, 1], with a population mean of 0.5 and variance of 1/12
+        let (num_records, noise_scale, variance_scale) = (100, 0.01, 0.05);
+        // the empirical coverage is a little below the confidence, so enough trials keep it clear of the threshold
+        let num_trials = 500;
+
+        let num_covered = (0..num_trials).filter(|_| {
+            let data = (0..num_records).map(|_| sample_uniform(0., 1., false).unwrap()).collect::<Vec<f64>>();
+            let mean = data.iter().sum::<f64>() / num_records as f64;
+            let variance = data.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (num_records - 1) as f64;
+
+            let (lower, upper) = mean_confidence_interval(
+                mean + sample_laplace(0., noise_scale, false).unwrap(),
+                variance + sample_laplace(0., variance_scale, false).unwrap(),
+                num_records as f64, (0., 1.), "laplace", noise_scale, variance_scale,
+                0.95, 500, false).unwrap();
+            lower <= 0.5 && 0.5 <= upper
+        }).count();
+
+        assert!(num_covered as f64 >= 0.9 * num_trials as f64, "covered {} of {}", num_covered, num_trials);
+    }
+
+    #[test]
+    fn test_dp_mean_ci() {
+        check_dp_mean_ci(unprotected_analysis());
+    }
+
+    #[test]
+    fn test_dp_mean_ci_default_privacy_definition() {
+        check_dp_mean_ci(Analysis::new());
+    }
+
+    fn check_dp_mean_ci(mut analysis: Analysis) {
+        let data = Array::from_shape_fn((1000, 1), |(i, _)| (i % 100) as f64 / 100.);
+
+        let imputed = bounded_float_data(&mut analysis, data.into_dyn(), &[0.], &[1.]);
+
+        let interval = analysis.dp_mean_ci(imputed, vec![approximate_usage(1., 0.)]).build();
+
+        let release = release_analysis(&analysis).unwrap();
+
+        let interval = release[&interval].value.clone().array().unwrap().float().unwrap();
+        assert_eq!(interval.len(), 2);
+        assert!(interval[0] < interval[1] && interval[1] - interval[0] < 0.2, "interval: {:?}", interval);
+    }
+}
@@ -2,14 +2,15 @@ use ndarray::{arr0};
 
 use smartnoise_validator::{Float, Integer, proto};
 use smartnoise_validator::base::{Array, ReleaseNode, Value};
+use smartnoise_validator::components::wishart_mechanism::get_wishart_scale;
 use smartnoise_validator::errors::*;
-use smartnoise_validator::utilities::{array::broadcast_ndarray, privacy::{get_delta, get_epsilon, spread_privacy_usage}, take_argument};
+use smartnoise_validator::utilities::{array::broadcast_ndarray, privacy::{get_epsilon, spread_privacy_usage}, take_argument};
 
 use crate::components::Evaluable;
 use crate::NodeArguments;
 use crate::utilities;
 use crate::utilities::{get_num_columns, to_nd, get_num_rows};
-use crate::utilities::mechanisms::exponential_mechanism;
+use crate::utilities::mechanisms::{exponential_mechanism, permute_and_flip, report_noisy_max, sparse_vector};
 
 impl Evaluable for proto::LaplaceMechanism {
     fn evaluate(
@@ -90,20 +91,27 @@ impl Evaluable for proto::GaussianMechanism {
 
         let usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
 
-        let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
-        let delta = usages.iter().map(get_delta).collect::<Result<Vec<f64>>>()?;
+        use proto::privacy_usage::{Distance, DistanceApproximate, DistanceConcentrated};
 
         data.gencolumns_mut().into_iter()
             .zip(sensitivity.gencolumns().into_iter())
-            .zip(epsilon.into_iter().zip(delta.into_iter()))
-            .try_for_each(|((mut data_column, sensitivity), (epsilon, delta))| data_column.iter_mut()
+            .zip(usages.iter())
+            .try_for_each(|((mut data_column, sensitivity), usage)| data_column.iter_mut()
                 .zip(sensitivity.iter())
-                .try_for_each(|(v, sens)|
-
-                    utilities::mechanisms::gaussian_mechanism(
-                        *v as Float, epsilon, delta, *sens as f64, self.analytic,
-                        enforce_constant_time,
-                    ).map(|noise| *v = noise as Float)))?;
+                .try_for_each(|(v, sens)| match usage.distance.as_ref()
+                    .ok_or_else(|| Error::from("distance must be defined"))? {
+
+                    Distance::Approximate(DistanceApproximate { epsilon, delta }) =>
+                        utilities::mechanisms::gaussian_mechanism(
+                            *v as Float, *epsilon, *delta, *sens as f64, self.analytic,
+                            enforce_constant_time,
+                        ),
+                    Distance::Concentrated(DistanceConcentrated { rho }) =>
+                        utilities::mechanisms::concentrated_gaussian_mechanism(
+                            *v as Float, *rho, *sens as f64,
+                            enforce_constant_time,
+                        )
+                }.map(|noise| *v = noise as Float)))?;
 
         Ok(ReleaseNode {
             value: data.into(),
@@ -167,72 +175,305 @@ impl Evaluable for proto::SimpleGeometricMechanism {
     }
 }
 
-impl Evaluable for proto::ExponentialMechanism {
-    fn evaluate(
-        &self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments,
-    ) -> Result<ReleaseNode> {
-        let enforce_constant_time = privacy_definition.as_ref()
-            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+impl Evaluable for proto::DiscreteLaplaceMechanism {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
 
-        let candidates = take_argument(&mut arguments, "candidates")?.array()?;
+        let mut data = take_argument(&mut arguments, "data")?.array()?.int()?;
+        let num_columns = get_num_columns(&data)?;
+        let num_rows = get_num_rows(&data)?;
 
-        // exponential mechanism only works for single columns. Sensitivity will always be one value
-        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.cast_float()?
-            .iter().cloned().collect::<Vec<Float>>();
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.cast_float()?;
+        let sens_num_columns = get_num_columns(&sensitivity)?;
+        let sens_num_rows = get_num_rows(&sensitivity)?;
+        if num_columns != sens_num_columns {
+            return Err(Error::from(format!("data has {:?} columns, while the expected shape has {:?} columns. This is likely an error from substituting data into the graph.", num_columns, sens_num_columns)))
+        }
+        if num_rows != sens_num_rows {
+            return Err(Error::from(format!("data has {:?} rows, while the expected shape has {:?} rows. This is likely an error from substituting data into the graph.", num_rows, sens_num_rows)))
+        }
+        if data.ndim() > 2 {
+            return Err(Error::from("data may not have dimensionality greater than 2"))
+        }
 
-        let usages = spread_privacy_usage(&self.privacy_usage, sensitivity.len())?;
+        let usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
         let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
 
-        let utilities = take_argument(&mut arguments, "utilities")?.array()?.cast_float()?;
+        data.gencolumns_mut().into_iter()
+            .zip(sensitivity.gencolumns().into_iter().zip(epsilon.into_iter()))
+            .try_for_each(|(mut data_column, (sensitivity, epsilon))| data_column.iter_mut()
+                .zip(sensitivity.iter())
+                .try_for_each(|(v, sens)|
+
+                    utilities::mechanisms::discrete_laplace_mechanism(
+                        *v as Integer, epsilon, *sens as f64,
+                    ).map(|noise| *v = noise as Integer)))?;
+
+        Ok(ReleaseNode {
+            value: data.into(),
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+impl Evaluable for proto::DiscreteGaussianMechanism {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
 
-        let num_columns = get_num_columns(&utilities)?;
-        let num_rows = get_num_rows(&utilities)?;
-        let cand_num_columns = candidates.num_columns()? as i64;
-        let cand_num_rows = candidates.num_records()? as i64;
-        if cand_num_columns != 1 {
-            return Err(Error::from(format!("candidates has {:?} columns, but the exponential mechanism only works on single columns. This is likely an error from substituting data into the graph.", cand_num_columns)))
+        let mut data = take_argument(&mut arguments, "data")?.array()?.int()?;
+        let num_columns = get_num_columns(&data)?;
+        let num_rows = get_num_rows(&data)?;
+
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.cast_float()?;
+        let sens_num_columns = get_num_columns(&sensitivity)?;
+        let sens_num_rows = get_num_rows(&sensitivity)?;
+        if num_columns != sens_num_columns {
+            return Err(Error::from(format!("data has {:?} columns, while the expected shape has {:?} columns. This is likely an error from substituting data into the graph.", num_columns, sens_num_columns)))
         }
-        if num_columns != 1 {
-            return Err(Error::from(format!("utilities has {:?} columns, but the exponential mechanism only works on single columns. This is likely an error from substituting data into the graph.", num_columns)))
+        if num_rows != sens_num_rows {
+            return Err(Error::from(format!("data has {:?} rows, while the expected shape has {:?} rows. This is likely an error from substituting data into the graph.", num_rows, sens_num_rows)))
         }
-        if num_rows != cand_num_rows {
-            return Err(Error::from(format!("utilities has {:?} rows, while the candidates has {:?} rows. This is likely an error from substituting data into the graph.", num_rows, cand_num_rows)))
+        if data.ndim() > 2 {
+            return Err(Error::from("data may not have dimensionality greater than 2"))
         }
-        if sensitivity.len() != 1 {
-            return Err(Error::from(format!("sensitivity has length {:?}, but should have length one. This is likely an error from substituting data into the graph.", sensitivity.len())))
+
+        let usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        data.gencolumns_mut().into_iter()
+            .zip(sensitivity.gencolumns().into_iter())
+            .zip(usages.iter())
+            .try_for_each(|((mut data_column, sensitivity), usage)| data_column.iter_mut()
+                .zip(sensitivity.iter())
+                .try_for_each(|(v, sens)|
+
+                    utilities::mechanisms::discrete_gaussian_mechanism(
+                        *v as Integer, usage, *sens as f64,
+                    ).map(|noise| *v = noise as Integer)))?;
+
+        Ok(ReleaseNode {
+            value: data.into(),
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+impl Evaluable for proto::ExponentialMechanism {
+    fn evaluate(
+        &self, privacy_definition: &Option<proto::PrivacyDefinition>, arguments: NodeArguments,
+    ) -> Result<ReleaseNode> {
+        evaluate_selection(&self.privacy_usage, privacy_definition, arguments, Selection::Exponential)
+    }
+}
+
+impl Evaluable for proto::ReportNoisyMax {
+    fn evaluate(
+        &self, privacy_definition: &Option<proto::PrivacyDefinition>, arguments: NodeArguments,
+    ) -> Result<ReleaseNode> {
+        evaluate_selection(&self.privacy_usage, privacy_definition, arguments, Selection::ReportNoisyMax(&self.noise))
+    }
+}
+
+impl Evaluable for proto::PermuteAndFlip {
+    fn evaluate(
+        &self, privacy_definition: &Option<proto::PrivacyDefinition>, arguments: NodeArguments,
+    ) -> Result<ReleaseNode> {
+        evaluate_selection(&self.privacy_usage, privacy_definition, arguments, Selection::PermuteAndFlip)
+    }
+}
+
+/// Mechanisms that release one of the candidates, scored by the utilities
+enum Selection<'a> {
+    Exponential,
+    ReportNoisyMax(&'a str),
+    PermuteAndFlip,
+}
+
+fn evaluate_selection(
+    privacy_usage: &[proto::PrivacyUsage],
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    mut arguments: NodeArguments,
+    selection: Selection,
+) -> Result<ReleaseNode> {
+    let enforce_constant_time = privacy_definition.as_ref()
+        .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+    let candidates = take_argument(&mut arguments, "candidates")?.array()?;
+
+    // selection mechanisms only work for single columns. Sensitivity will always be one value
+    let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.cast_float()?
+        .iter().cloned().collect::<Vec<Float>>();
+
+    let usages = spread_privacy_usage(privacy_usage, sensitivity.len())?;
+    let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
+
+    let utilities = take_argument(&mut arguments, "utilities")?.array()?.cast_float()?;
+
+    let num_columns = get_num_columns(&utilities)?;
+    let num_rows = get_num_rows(&utilities)?;
+    let cand_num_columns = candidates.num_columns()? as i64;
+    let cand_num_rows = candidates.num_records()? as i64;
+    if cand_num_columns != 1 {
+        return Err(Error::from(format!("candidates has {:?} columns, but selection mechanisms only work on single columns. This is likely an error from substituting data into the graph.", cand_num_columns)))
+    }
+    if num_columns != 1 {
+        return Err(Error::from(format!("utilities has {:?} columns, but selection mechanisms only work on single columns. This is likely an error from substituting data into the graph.", num_columns)))
+    }
+    if num_rows != cand_num_rows {
+        return Err(Error::from(format!("utilities has {:?} rows, while the candidates has {:?} rows. This is likely an error from substituting data into the graph.", num_rows, cand_num_rows)))
+    }
+    if sensitivity.len() != 1 {
+        return Err(Error::from(format!("sensitivity has length {:?}, but should have length one. This is likely an error from substituting data into the graph.", sensitivity.len())))
+    }
+    if utilities.ndim() > 2 {
+        return Err(Error::from("utilities may not have dimensionality greater than 2"))
+    }
+    if candidates.shape().len() > 2 {
+        return Err(Error::from("candidates may not have dimensionality greater than 2"))
+    }
+
+    macro_rules! apply_selection {
+        ($candidates:ident) => {
+            {
+                let mut release_vec = $candidates.gencolumns().into_iter()
+                    .zip(utilities.gencolumns().into_iter())
+                    .zip(sensitivity.iter().zip(epsilon.iter()))
+                    .map(|((cands, utils), (sens, eps))| {
+                        let utils = utils.into_iter().map(|v| *v as f64).collect();
+                        match selection {
+                            Selection::Exponential => exponential_mechanism(
+                                *eps, *sens as f64, &cands.to_vec(), utils, enforce_constant_time),
+                            Selection::ReportNoisyMax(noise) => report_noisy_max(
+                                *eps, *sens as f64, &cands.to_vec(), utils, noise, enforce_constant_time),
+                            Selection::PermuteAndFlip => permute_and_flip(
+                                *eps, *sens as f64, &cands.to_vec(), utils, enforce_constant_time),
+                        }
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                Value::from(arr0(release_vec.remove(0)).into_dyn())
+            }
         }
-        if utilities.ndim() > 2 {
-            return Err(Error::from("utilities may not have dimensionality greater than 2"))
+    }
+
+    Ok(ReleaseNode {
+        value: match candidates {
+            Array::Float(candidates) => apply_selection!(candidates),
+            Array::Int(candidates) => apply_selection!(candidates),
+            Array::Str(candidates) => apply_selection!(candidates),
+            Array::Bool(candidates) => apply_selection!(candidates)
+        },
+        privacy_usages: Some(usages),
+        public: true,
+    })
+}
+
+impl Evaluable for proto::SparseVector {
+    fn evaluate(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        mut arguments: NodeArguments
+    ) -> Result<ReleaseNode> {
+
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let data = take_argument(&mut arguments, "data")?.array()?.cast_float()?;
+        let num_columns = get_num_columns(&data)?;
+        if num_columns != 1 {
+            return Err(Error::from(format!("data has {:?} columns, but the sparse vector technique only works on single columns. This is likely an error from substituting data into the graph.", num_columns)))
         }
-        if candidates.shape().len() > 2 {
-            return Err(Error::from("candidates may not have dimensionality greater than 2"))
+        if data.ndim() > 2 {
+            return Err(Error::from("data may not have dimensionality greater than 2"))
         }
 
-        macro_rules! apply_exponential {
-            ($candidates:ident) => {
-                {
-                    let mut release_vec = $candidates.gencolumns().into_iter()
-                        .zip(utilities.gencolumns().into_iter())
-                        .zip(sensitivity.iter().zip(epsilon.iter()))
-                        .map(|((cands, utils), (sens, eps))| exponential_mechanism(
-                            *eps, *sens as f64,
-                            &cands.to_vec(),
-                            utils.into_iter().map(|v| *v as f64).collect(),
-                            enforce_constant_time))
-                        .collect::<Result<Vec<_>>>()?;
-
-                    Value::from(arr0(release_vec.remove(0)).into_dyn())
-                }
+        // the sensitivity of the sparse vector technique is the largest sensitivity of any query
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.cast_float()?
+            .iter().cloned().fold(0., Float::max);
+
+        let threshold = take_argument(&mut arguments, "threshold")?.array()?.first_float()?;
+
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+
+        let values = data.iter().map(|v| *v as f64).collect::<Vec<f64>>();
+
+        let (indices, noisy_values) = sparse_vector(
+            &values, threshold as f64, epsilon, sensitivity as f64,
+            self.cutoff, self.release_values, enforce_constant_time)?;
+
+        let value = match noisy_values {
+            Some(noisy_values) => {
+                let mut release = vec![Float::NAN; values.len()];
+                indices.into_iter().zip(noisy_values.into_iter())
+                    .for_each(|(index, noisy_value)| release[index] = noisy_value as Float);
+                ndarray::Array::from(release).into_dyn().into()
             }
+            None => ndarray::Array::from(indices.into_iter()
+                .map(|index| index as Integer).collect::<Vec<Integer>>()).into_dyn().into()
+        };
+
+        Ok(ReleaseNode {
+            value,
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
+impl Evaluable for proto::WishartMechanism {
+    fn evaluate(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        mut arguments: NodeArguments
+    ) -> Result<ReleaseNode> {
+
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let data = to_nd(take_argument(&mut arguments, "data")?.array()?.cast_float()?, 2)?
+            .into_dimensionality::<ndarray::Ix2>()?;
+        let lower = take_argument(&mut arguments, "lower")?.array()?.cast_float()?
+            .iter().map(|v| *v as f64).collect::<Vec<f64>>();
+        let upper = take_argument(&mut arguments, "upper")?.array()?.cast_float()?
+            .iter().map(|v| *v as f64).collect::<Vec<f64>>();
+
+        // the public number of records, as the number of rows in the data may be private
+        let num_records = take_argument(&mut arguments, "num_records")?.array()?.first_int()?;
+
+        let num_columns = data.shape()[1];
+        if lower.len() != num_columns || upper.len() != num_columns {
+            return Err("lower and upper must have one entry for each column of the data".into())
         }
+        if num_records <= 0 {
+            return Err("num_records must be positive".into())
+        }
+
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+        let scale = get_wishart_scale(epsilon, &lower, &upper)?;
+
+        // unnormalized second moment about the midpoint of the bounds
+        let center = lower.iter().zip(upper.iter())
+            .map(|(l, u)| (l + u) / 2.)
+            .collect::<Vec<f64>>();
+        let mut moment = utilities::noise::sample_wishart(
+            num_columns, num_columns + 1, scale, enforce_constant_time)?;
+        data.genrows().into_iter().for_each(|row| {
+            // clamping keeps the sensitivity bounded, even if the data were substituted
+            let centered = row.iter().zip(center.iter()).zip(lower.iter().zip(upper.iter()))
+                .map(|((v, c), (l, u))| (*v as f64).max(*l).min(*u) - c)
+                .collect::<Vec<f64>>();
+            moment.iter_mut().zip(centered.iter()).for_each(|(moment_row, left)| moment_row.iter_mut()
+                .zip(centered.iter())
+                .for_each(|(entry, right)| *entry += left * right));
+        });
+
+        let moment = moment.into_iter().flatten()
+            .map(|v| (v / num_records as f64) as Float)
+            .collect::<Vec<Float>>();
 
         Ok(ReleaseNode {
-            value: match candidates {
-                Array::Float(candidates) => apply_exponential!(candidates),
-                Array::Int(candidates) => apply_exponential!(candidates),
-                Array::Str(candidates) => apply_exponential!(candidates),
-                Array::Bool(candidates) => apply_exponential!(candidates)
-            },
+            value: ndarray::Array::from_shape_vec((num_columns, num_columns), moment)?.into_dyn().into(),
             privacy_usages: Some(usages),
             public: true,
         })
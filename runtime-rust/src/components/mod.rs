@@ -15,31 +15,50 @@ use smartnoise_validator::base::ReleaseNode;
 use smartnoise_validator::proto;
 
 //pub mod bin;
+pub mod assign_clusters;
+pub mod bound_contributions;
 pub mod cast;
+pub mod chi_square_test;
 pub mod clamp;
 pub mod count;
 pub mod covariance;
 pub mod column_bind;
+pub mod consistent_marginals;
+pub mod constrained_inference;
+pub mod contingency_table;
 pub mod digitize;
+pub mod dp_bounds;
+pub mod dp_count_distinct;
 pub mod dp_gumbel_median;
+pub mod dp_logistic_regression;
+pub mod dp_quantiles;
 pub mod filter;
+pub mod hierarchical_histogram;
 pub mod histogram;
 pub mod impute;
 pub mod index;
 // pub mod linreg_noisy_stats;
 pub mod materialize;
 pub mod mean;
+pub mod mean_confidence_interval;
 pub mod mechanisms;
+pub mod mwem_synthesizer;
 pub mod partition;
+pub mod principal_components;
 pub mod quantile;
+pub mod range_query;
 pub mod raw_moment;
 pub mod reshape;
 pub mod resize;
+pub mod solve_normal_equations;
+pub mod standardized_moment;
+pub mod stability_mechanism;
 pub mod sum;
 pub mod theil_sen;
 pub mod to_dataframe;
 pub mod transforms;
 pub mod union;
+pub mod update_centroids;
 pub mod variance;
 
 /// Evaluable component trait
@@ -85,16 +104,19 @@ impl Evaluable for proto::component::Variant {
 
         evaluate!(
             // INSERT COMPONENT LIST
-            Cast, Clamp, ColumnBind, Count, Covariance, Digitize, Filter, Histogram, Impute, Index,
-            Materialize, Mean, Partition,
-            Quantile, RawMoment, Reshape, Resize, Sum, ToDataframe, Union, Variance,
+            AssignClusters, BoundContributions, Cast, ChiSquareTest, Clamp, ColumnBind, ConsistentMarginals,
+            ConstrainedInference, ContingencyTable, Count, Covariance, Digitize, Filter,
+            HierarchicalHistogram, Histogram, Impute, Index, Materialize, Mean, MeanConfidenceInterval, Partition,
+            PrincipalComponents, Quantile, RangeQuery, RawMoment, Reshape, Resize,
+            SolveNormalEquations, StandardizedMoment, Sum, ToDataframe, Union, UpdateCentroids, Variance,
 
-            ExponentialMechanism, GaussianMechanism,
+            DiscreteGaussianMechanism, DiscreteLaplaceMechanism, ExponentialMechanism, GaussianMechanism,
             LaplaceMechanism, SnappingMechanism,
-            SimpleGeometricMechanism,
+            SimpleGeometricMechanism, PermuteAndFlip, ReportNoisyMax, SparseVector, StabilityMechanism, WishartMechanism,
 
             Abs, Add, LogicalAnd, Divide, Equal, GreaterThan, LessThan, Log, Modulo, Multiply,
-            Negate, Negative, LogicalOr, Power, RowMax, RowMin, Subtract, TheilSen, DpGumbelMedian
+            Negate, Negative, LogicalOr, Power, RowMax, RowMin, Subtract, TheilSen, DpGumbelMedian,
+            DpBounds, DpCountDistinct, DpLogisticRegression, DpQuantiles, MwemSynthesizer
         );
 
         Err(format!("Component type not implemented: {:?}", self).into())
@@ -0,0 +1,235 @@
+use indexmap::map::IndexMap;
+use ndarray::{Array, ArrayD};
+
+use smartnoise_validator::{Float, Integer, proto};
+use smartnoise_validator::base::{Array as BaseArray, DataType, IndexKey, Jagged, ReleaseNode, Value};
+use smartnoise_validator::components::contingency_table::{get_marginal_indices, get_table_size};
+use smartnoise_validator::components::mwem_synthesizer::get_round_epsilon;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::privacy::get_epsilon;
+use smartnoise_validator::utilities::{get_mechanism, take_argument};
+
+use crate::components::contingency_table::{contingency_table, take_marginals, to_index_keys};
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::mechanisms::{discrete_laplace_mechanism, exponential_mechanism, simple_geometric_mechanism};
+
+impl Evaluable for proto::MwemSynthesizer {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+        let enforce_constant_time = privacy_definition.protect_elapsed_time;
+        let mechanism = get_mechanism(&DataType::Int, "automatic", privacy_definition)?;
+
+        if self.privacy_usage.len() != 1 {
+            return Err(Error::from("MWEM only supports one privacy parameter"))
+        }
+        let epsilon = get_epsilon(&self.privacy_usage[0])?;
+
+        let data = match take_argument(&mut arguments, "data")?.array()? {
+            BaseArray::Bool(data) => to_index_keys(data),
+            BaseArray::Int(data) => to_index_keys(data),
+            BaseArray::Str(data) => to_index_keys(data),
+            BaseArray::Float(_) => return Err("data: may not be float".into())
+        };
+        let categories = take_argument(&mut arguments, "categories")?.jagged()?;
+        let marginals = take_marginals(&mut arguments)?;
+
+        let counts = mwem(
+            &data, &categories.to_index_keys()?, &marginals,
+            self.num_rounds, epsilon, &mechanism, enforce_constant_time)?;
+
+        Ok(ReleaseNode {
+            value: Value::Dataframe(match categories {
+                Jagged::Bool(categories) => synthesize_columns(categories, &counts),
+                Jagged::Int(categories) => synthesize_columns(categories, &counts),
+                Jagged::Str(categories) => synthesize_columns(categories, &counts),
+                Jagged::Float(_) => return Err("categories: may not be float".into())
+            }),
+            privacy_usages: Some(self.privacy_usage.clone()),
+            public: true,
+        })
+    }
+}
+
+/// Synthetic records with the given number of records in each cell of the full table, keyed by column index.
+fn synthesize_columns<T: Clone>(categories: Vec<Vec<T>>, counts: &[Integer]) -> IndexMap<IndexKey, Value>
+    where ArrayD<T>: Into<Value> {
+    let mut columns = vec![Vec::new(); categories.len()];
+    counts.iter().enumerate().for_each(|(mut cell, count)| {
+        // the last column varies fastest
+        for (column, column_categories) in categories.iter().enumerate().rev() {
+            let num_values = columns[column].len() + *count as usize;
+            columns[column].resize(num_values, column_categories[cell % column_categories.len()].clone());
+            cell /= column_categories.len();
+        }
+    });
+
+    columns.into_iter().enumerate()
+        .map(|(column, values)| (IndexKey::from(column as Integer), Array::from(values).into_dyn().into()))
+        .collect()
+}
+
+/// Number of synthetic records in each cell of the full table, from the multiplicative weights exponential mechanism.
+///
+/// A distribution over the cells of the full table starts uniform.
+/// Each round, the exponential mechanism selects the cell of a marginal in the workload
+/// whose count is worst approximated by the distribution,
+/// a discrete mechanism measures the count of the cell,
+/// and multiplicative weights updates the distribution towards every measurement so far.
+/// The distribution averaged over all rounds is rounded to the number of records.
+/// Hardt, Ligett and McSherry (2012), A Simple and Practical Algorithm for Differentially Private Data Release
+///
+/// # Arguments
+/// * `data` - Values of each column.
+/// * `categories` - Ordered categories of each column.
+/// * `marginals` - Workload of marginals, each the columns of the marginal in increasing order.
+/// * `num_rounds` - Number of rounds.
+/// * `epsilon` - Multiplicative privacy loss parameter of the whole release.
+/// * `mechanism` - Mechanism to measure the counts with, either `discretelaplace` or `simplegeometric`.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Example
+/// ```
+/// use smartnoise_validator::base::IndexKey;
+/// use smartnoise_runtime::components::mwem_synthesizer::mwem;
+/// let keys = |values: Vec<i64>| values.into_iter().map(IndexKey::from).collect::<Vec<IndexKey>>();
+/// let data = vec![keys(vec![0; 100]), keys(vec![1; 100])];
+/// let categories = vec![keys(vec![0, 1]), keys(vec![0, 1])];
+/// let counts = mwem(&data, &categories, &[vec![0], vec![1]], 10, 1., "discretelaplace", false).unwrap();
+/// assert_eq!(counts.len(), 4);
+/// assert_eq!(counts.iter().sum::<i64>(), 100);
+/// ```
+pub fn mwem(
+    data: &[Vec<IndexKey>], categories: &[Vec<IndexKey>], marginals: &[Vec<usize>],
+    num_rounds: u32, epsilon: Float, mechanism: &str, enforce_constant_time: bool,
+) -> Result<Vec<Integer>> {
+    let round_epsilon = get_round_epsilon(epsilon, num_rounds)?;
+    if marginals.is_empty() {
+        return Err("marginals: the workload must contain at least one marginal".into())
+    }
+
+    let shape = categories.iter().map(Vec::len).collect::<Vec<usize>>();
+    let num_cells = get_table_size(&shape, &(0..shape.len()).collect::<Vec<usize>>())?;
+    let num_records = data.first().map(Vec::len).unwrap_or(0);
+    if num_records == 0 {
+        return Ok(vec![0; num_cells])
+    }
+    let num_records_float = num_records as f64;
+
+    // true counts of every marginal, following the full table
+    let true_counts = contingency_table(data, categories, marginals)?;
+    let marginal_indices = marginals.iter()
+        .map(|marginal| get_marginal_indices(&shape, marginal))
+        .collect::<Result<Vec<Vec<usize>>>>()?;
+    let marginal_sizes = marginals.iter()
+        .map(|marginal| get_table_size(&shape, marginal))
+        .collect::<Result<Vec<usize>>>()?;
+
+    // every cell of every marginal is a candidate query, identified by its marginal and cell
+    let candidates = marginal_sizes.iter().enumerate()
+        .flat_map(|(marginal, size)| (0..*size).map(move |cell| (marginal, cell)))
+        .zip(true_counts[num_cells..].iter())
+        .map(|((marginal, cell), count)| (marginal, cell, *count))
+        .collect::<Vec<(usize, usize, Integer)>>();
+
+    let sum_marginal = |weights: &[f64], marginal: usize| {
+        let mut counts = vec![0.; marginal_sizes[marginal]];
+        weights.iter().zip(marginal_indices[marginal].iter())
+            .for_each(|(weight, index)| counts[*index] += weight);
+        counts
+    };
+
+    let mut weights = vec![num_records_float / num_cells as f64; num_cells];
+    let mut average = vec![0.; num_cells];
+    let mut measurements: Vec<(usize, usize, f64)> = Vec::new();
+
+    for _ in 0..num_rounds {
+        // select the worst approximated cell
+        let approximations = (0..marginals.len())
+            .map(|marginal| sum_marginal(&weights, marginal))
+            .collect::<Vec<Vec<f64>>>();
+        let errors = candidates.iter()
+            .map(|(marginal, cell, count)| (*count as f64 - approximations[*marginal][*cell]).abs())
+            .collect::<Vec<f64>>();
+        // shifting the utilities does not change the distribution, but prevents overflow
+        let max_error = errors.iter().cloned().fold(0., f64::max);
+        let utilities = errors.into_iter().map(|error| error - max_error).collect();
+        let (marginal, cell, count) = exponential_mechanism(
+            round_epsilon, 1., &candidates, utilities, enforce_constant_time)?;
+
+        // measure the count of the cell
+        let measurement = match mechanism {
+            "discretelaplace" => discrete_laplace_mechanism(count, round_epsilon, 1.)?,
+            "simplegeometric" => simple_geometric_mechanism(
+                count, round_epsilon, 1., 0, num_records as Integer, enforce_constant_time)?,
+            _ => return Err(format!("mechanism {:?} is not supported for MWEM", mechanism).into())
+        };
+        measurements.push((marginal, cell, measurement as f64));
+
+        // update the distribution towards every measurement
+        for (marginal, cell, measurement) in &measurements {
+            let approximation = sum_marginal(&weights, *marginal)[*cell];
+            let factor = ((measurement - approximation) / (2. * num_records_float)).exp();
+            weights.iter_mut().zip(marginal_indices[*marginal].iter())
+                .filter(|(_, index)| *index == cell)
+                .for_each(|(weight, _)| *weight *= factor);
+
+            let total = weights.iter().sum::<f64>();
+            weights.iter_mut().for_each(|weight| *weight *= num_records_float / total);
+        }
+
+        average.iter_mut().zip(weights.iter()).for_each(|(average, weight)| *average += weight / num_rounds as f64);
+    }
+
+    Ok(round_counts(&average, num_records))
+}
+
+/// Round weights to integer counts that sum to the number of records, by largest remainder.
+fn round_counts(weights: &[f64], num_records: usize) -> Vec<Integer> {
+    let mut counts = weights.iter().map(|weight| weight.floor() as Integer).collect::<Vec<Integer>>();
+    let remainder = num_records as Integer - counts.iter().sum::<Integer>();
+
+    let mut order = (0..weights.len()).collect::<Vec<usize>>();
+    order.sort_by(|l, r| (weights[*r] - weights[*r].floor())
+        .partial_cmp(&(weights[*l] - weights[*l].floor()))
+        .unwrap_or(std::cmp::Ordering::Equal));
+    order.into_iter().take(remainder.max(0) as usize).for_each(|cell| counts[cell] += 1);
+    counts
+}
+
+#[cfg(test)]
+mod test_mwem_synthesizer {
+    use ndarray::{arr1, Array};
+
+    use smartnoise_validator::base::{IndexKey, Jagged, Value};
+    use smartnoise_validator::bindings::Analysis;
+
+    use crate::utilities::fixtures::{approximate_usage, release_analysis};
+
+    #[test]
+    fn test_mwem_synthesizer_default_privacy_definition() {
+        let mut analysis = Analysis::new();
+
+        let data = Array::from_shape_fn((1000, 2), |(i, j)| ((i >> j) % 2) as i64);
+        let data = analysis.literal().value(data.into_dyn().into()).value_public(true).build();
+        let categories = analysis.literal()
+            .value(Value::Jagged(Jagged::Int(vec![vec![0, 1], vec![0, 1]]))).value_public(true).build();
+        let null_value = analysis.literal().value(arr1(&[-1, -1]).into_dyn().into()).value_public(true).build();
+        let clamped = analysis.clamp(data).categories(categories).null_value(null_value).build();
+        let marginals = analysis.literal()
+            .value(Value::Jagged(Jagged::Int(vec![vec![0], vec![1]]))).value_public(true).build();
+
+        let synthetic = analysis.mwem_synthesizer(clamped, marginals, vec![approximate_usage(1., 0.)]).build();
+
+        let release = release_analysis(&analysis).unwrap();
+        let columns = release[&synthetic].value.clone().dataframe().unwrap();
+        assert_eq!(columns.len(), 2);
+        (0..2).for_each(|column| {
+            let values = columns.get::<IndexKey>(&(column as i64).into()).unwrap()
+                .clone().array().unwrap().int().unwrap();
+            assert_eq!(values.len(), 1000);
+            assert!(values.iter().all(|v| *v == 0 || *v == 1));
+        });
+    }
+}
@@ -0,0 +1,78 @@
+use ndarray::Array;
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::components::principal_components::principal_components;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::to_nd;
+
+impl Evaluable for proto::PrincipalComponents {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let data = to_nd(take_argument(&mut arguments, "data")?.array()?.cast_float()?, 2)?;
+
+        // the matrix may be given as its upper triangle, so take the width from the eigenvectors
+        let components = principal_components(data, self.num_components as usize)?;
+        let num_columns = components.first().map(Vec::len).unwrap_or(0);
+        let components = components.into_iter()
+            .flatten()
+            .map(|v| v as Float)
+            .collect::<Vec<Float>>();
+
+        Ok(ReleaseNode::new(Array::from_shape_vec(
+            (self.num_components as usize, num_columns), components)?.into_dyn().into()))
+    }
+}
+
+#[cfg(test)]
+mod test_principal_components {
+    use ndarray::Array;
+
+    use smartnoise_validator::bindings::Analysis;
+    use smartnoise_validator::proto;
+
+    use crate::utilities::fixtures::{approximate_usage, bounded_float_data, release_analysis, unprotected_analysis};
+
+    /// add the first principal component of data that varies mostly along the first column
+    fn build_dp_pca(analysis: &mut Analysis, implementation: &str) -> u32 {
+        let data = Array::from_shape_fn((1000, 2), |(i, j)| match j {
+            0 => (i % 10) as f64,
+            _ => 5. + (i % 3) as f64 * 0.1
+        });
+
+        let imputed = bounded_float_data(analysis, data.into_dyn(), &[0., 0.], &[10., 10.]);
+
+        analysis.dp_pca(imputed, 1, vec![approximate_usage(1e3, 1e-6)])
+            .implementation(implementation.to_string()).mechanism("AnalyticGaussian".to_string()).build()
+    }
+
+    #[test]
+    fn test_dp_pca() {
+        for implementation in &["analyze-gauss", "wishart"] {
+            let mut analysis = unprotected_analysis();
+            let components = build_dp_pca(&mut analysis, implementation);
+            let release = release_analysis(&analysis).unwrap();
+
+            let component = release[&components].value.clone().array().unwrap().float().unwrap();
+            assert_eq!(component.shape(), &[1, 2]);
+            assert!((component[[0, 0]] - 1.).abs() < 0.05 && component[[0, 1]].abs() < 0.3,
+                    "{}: {:?}", implementation, component);
+        }
+    }
+
+    #[test]
+    fn test_dp_pca_default_privacy_definition() {
+        // both implementations sample continuous noise, which floating-point protections reject
+        for implementation in &["analyze-gauss", "wishart"] {
+            let mut analysis = Analysis::new();
+            let components = build_dp_pca(&mut analysis, implementation);
+            let (release, warnings) = crate::release(
+                Some(analysis.privacy_definition.clone()), analysis.components.clone(),
+                analysis.release.clone(), proto::FilterLevel::All).unwrap();
+            assert!(!release.contains_key(&components) && !warnings.is_empty(), "{}", implementation);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use ndarray::Array;
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::components::range_query::{cumulative_distribution, get_ranges, range_query};
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+
+impl Evaluable for proto::RangeQuery {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let counts = take_argument(&mut arguments, "data")?.array()?.cast_float()?
+            .iter().map(|v| *v as f64).collect::<Vec<f64>>();
+
+        let answers = match take_argument(&mut arguments, "ranges") {
+            Ok(ranges) => range_query(&counts, &get_ranges(ranges.array()?.int()?)?)?,
+            Err(_) => cumulative_distribution(&counts)
+        };
+
+        Ok(ReleaseNode::new(Array::from(answers.into_iter()
+            .map(|v| v as Float)
+            .collect::<Vec<Float>>()).into_dyn().into()))
+    }
+}
+
+#[cfg(test)]
+mod test_range_query {
+    use ndarray::{arr1, Array};
+
+    use smartnoise_validator::base::{Jagged, Value};
+    use smartnoise_validator::bindings::Analysis;
+
+    use crate::utilities::fixtures::{approximate_usage, release_analysis};
+
+    #[test]
+    fn test_dp_range_query_default_privacy_definition() {
+        let mut analysis = Analysis::new();
+
+        let data = Array::from_shape_fn((1000, 1), |(i, _)| (i % 4) as i64);
+        let data = analysis.literal().value(data.into_dyn().into()).value_public(true).build();
+        let categories = analysis.literal()
+            .value(Value::Jagged(Jagged::Int(vec![vec![0, 1, 2, 3]]))).value_public(true).build();
+        let null_value = analysis.literal().value(arr1(&[-1]).into_dyn().into()).value_public(true).build();
+        let clamped = analysis.clamp(data).categories(categories).null_value(null_value).build();
+
+        let cdf = analysis.dp_range_query(clamped, vec![approximate_usage(1., 0.)]).build();
+
+        let release = release_analysis(&analysis).unwrap();
+        let cdf = release[&cdf].value.clone().array().unwrap().float().unwrap();
+        assert_eq!(cdf.len(), 4);
+        let expected = [0.25, 0.5, 0.75, 1.];
+        assert!(cdf.iter().zip(expected.iter()).all(|(l, r)| (l - r).abs() < 0.05), "cdf: {:?}", cdf);
+    }
+}
+
@@ -33,7 +33,7 @@ impl Evaluable for proto::Resize {
             .and_then(|v| v.array().ok()?.first_int().ok()).map(|v| v as i64);
         let minimum_rows = arguments.remove::<IndexKey>(&"minimum_rows".into())
             .and_then(|v| v.array().ok()?.first_int().ok()).map(|v| v as i64);
-        let p = arguments.remove::<IndexKey>(&"p".into())
+        let p = arguments.remove::<IndexKey>(&"sample_proportion".into())
             .and_then(|v| v.array().ok()?.first_float().ok()).map(|v| v as f64);
 
         // parse options for the number of rows
@@ -42,7 +42,7 @@ impl Evaluable for proto::Resize {
             (Some(number_rows), None, None) => RowResizeConfig::NumRows(number_rows),
             (None, Some(minimum_rows), None) => RowResizeConfig::MinRows(minimum_rows),
             (None, None, None) => RowResizeConfig::None,
-            _ => return Err(Error::from("minimum_rows is exclusive from number_rows and p"))
+            _ => return Err(Error::from("minimum_rows is exclusive from number_rows and sample_proportion"))
         };
 
         // If "categories" constraint has been propagated, data are treated as categorical (regardless of atomic type)
@@ -118,9 +118,12 @@ fn get_sample_count(p: f64, n_actual: i64, privacy_definition: &proto::PrivacyDe
     let c = p.ceil();
     let s = p / c;
 
+    // the validator amplifies privacy usages under the sampling scheme matching the neighboring definition
     match Neighboring::from_i32(privacy_definition.neighboring)
         .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))? {
+        // a binomial sample count makes the sample equivalent to Poisson sampling
         AddRemove => sample_binomial(c as i64 * n_actual, s, privacy_definition.protect_elapsed_time),
+        // a fixed sample count makes the sample a sample without replacement
         Substitute => Ok((s * c * n_actual as f64).floor() as i64)
     }
 }
@@ -252,10 +255,10 @@ pub fn resize_float(
                 Axis(0), &(0..p.ceil() as i64).map(|_| data.view())
                     .collect::<Vec<ArrayViewD<Float>>>())?;
 
-            // sample(X_c, max(m, n))
+            // sample(X_c, min(m, n))
             let sample = slow_select(
                 &stacked, Axis(0), &create_sampling_indices(
-                    number_rows.max(sample_count),
+                    number_rows.min(sample_count),
                     stacked.len_of(Axis(0)) as i64, enforce_constant_time)?).to_owned();
 
             // Aug(phi, max(0, n - m, ...)
@@ -384,10 +387,10 @@ pub fn resize_integer(
                 Axis(0), &(0..p.ceil() as usize).map(|_| data.view())
                     .collect::<Vec<ArrayViewD<Integer>>>())?;
 
-            // sample(X_c, max(m, n))
+            // sample(X_c, min(m, n))
             let sample = slow_select(
                 &stacked, Axis(0), &create_sampling_indices(
-                    number_rows.max(sample_count),
+                    number_rows.min(sample_count),
                     stacked.len_of(Axis(0)) as i64, enforce_constant_time)?).to_owned();
 
             // Aug(phi, max(0, n - m, ...)
@@ -516,10 +519,10 @@ pub fn resize_categorical<T>(
                 Axis(0), &(0..p.ceil() as usize).map(|_| data.view())
                     .collect::<Vec<ArrayViewD<T>>>())?;
 
-            // sample(X_c, max(m, n))
+            // sample(X_c, min(m, n))
             let sample = slow_select(
                 &stacked, Axis(0), &create_sampling_indices(
-                    number_rows.max(sample_count),
+                    number_rows.min(sample_count),
                     stacked.len_of(Axis(0)) as i64, enforce_constant_time)?).to_owned();
 
             // Aug(phi, max(0, n - m, ...)
@@ -565,3 +568,30 @@ pub fn create_sampling_indices(k: i64, n: i64, enforce_constant_time: bool) -> R
     // create set of sampling indices
     create_subset(&index_vec, &weight_vec, k as usize, enforce_constant_time)
 }
+
+#[cfg(test)]
+mod test_resize {
+    use ndarray::{arr1, arr2};
+
+    use smartnoise_validator::proto;
+
+    use crate::components::resize::{resize_float, RowResizeConfig};
+
+    #[test]
+    fn test_sampling_number_rows() {
+        let data = arr2(&[[1.], [2.], [3.], [4.], [5.], [6.], [7.], [8.], [9.], [10.]]).into_dyn();
+        let privacy_definition = Some(proto::PrivacyDefinition {
+            neighboring: proto::privacy_definition::Neighboring::Substitute as i32,
+            ..Default::default()
+        });
+
+        // the sample count is floor(p * n) under substitution, which may be above or below number_rows
+        for (number_rows, proportion) in vec![(8, 0.5), (3, 0.5), (8, 2.), (25, 2.)] {
+            let resized = resize_float(
+                data.clone(), RowResizeConfig::Generalized(number_rows, proportion), None, "Uniform",
+                arr1(&[0.]).into_dyn(), arr1(&[10.]).into_dyn(), None, None,
+                &privacy_definition).unwrap();
+            assert_eq!(resized.shape(), &[number_rows as usize, 1]);
+        }
+    }
+}
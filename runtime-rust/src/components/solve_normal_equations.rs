@@ -0,0 +1,68 @@
+use ndarray::Array;
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::components::solve_normal_equations::solve_normal_equations;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+
+impl Evaluable for proto::SolveNormalEquations {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let covariance = take_argument(&mut arguments, "covariance")?.array()?.cast_float()?;
+        let means = take_argument(&mut arguments, "means")?.array()?.cast_float()?;
+
+        let coefficients = solve_normal_equations(covariance, means)?.into_iter()
+            .map(|v| v as Float)
+            .collect::<Vec<Float>>();
+
+        Ok(ReleaseNode::new(Array::from_shape_vec((1, coefficients.len()), coefficients)?.into_dyn().into()))
+    }
+}
+
+#[cfg(test)]
+mod test_solve_normal_equations {
+    use ndarray::{Array, ArrayD};
+
+    use smartnoise_validator::bindings::Analysis;
+
+    use crate::utilities::fixtures::{approximate_usage, bounded_float_data, release_analysis, unprotected_analysis};
+
+    /// release the coefficients of a regression of y = 2 x_1 - x_2 + 3
+    fn release_dp_linear_regression(mut analysis: Analysis) -> ArrayD<f64> {
+        let data_x = Array::from_shape_fn((1000, 2), |(i, j)| ((i * (j + 3)) % 10) as f64);
+        let data_y = data_x.genrows().into_iter()
+            .map(|row| 2. * row[0] - row[1] + 3.)
+            .collect::<Vec<f64>>();
+
+        let data_x = bounded_float_data(&mut analysis, data_x.into_dyn(), &[0., 0.], &[10., 10.]);
+        let data_y = bounded_float_data(
+            &mut analysis, Array::from(data_y).insert_axis(ndarray::Axis(1)).into_dyn(), &[-10.], &[30.]);
+
+        let regression = analysis.dp_linear_regression(data_x, data_y, vec![approximate_usage(1e4, 0.)])
+            .implementation("sufficient-statistics".to_string())
+            .build();
+
+        let release = release_analysis(&analysis).unwrap();
+        let coefficients = release[&regression].value.clone().array().unwrap().float().unwrap();
+        assert_eq!(coefficients.shape(), &[1, 3]);
+        coefficients
+    }
+
+    #[test]
+    fn test_dp_linear_regression() {
+        let coefficients = release_dp_linear_regression(unprotected_analysis());
+        let expected = [2., -1., 3.];
+        assert!(coefficients.iter().zip(expected.iter()).all(|(l, r)| (l - r).abs() < 0.5),
+                "coefficients: {:?}", coefficients);
+    }
+
+    #[test]
+    fn test_dp_linear_regression_default_privacy_definition() {
+        // the sufficient statistics are privatized with the snapping mechanism
+        let coefficients = release_dp_linear_regression(Analysis::new());
+        assert!(coefficients.iter().all(|v| v.is_finite()), "coefficients: {:?}", coefficients);
+    }
+}
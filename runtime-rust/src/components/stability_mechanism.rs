@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use indexmap::indexmap;
+use ndarray::Array;
+
+use smartnoise_validator::{Float, proto};
+use smartnoise_validator::base::{Array as BaseArray, ReleaseNode, Value};
+use smartnoise_validator::components::stability_mechanism::get_threshold;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::privacy::{get_delta, get_epsilon};
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::mechanisms::laplace_mechanism;
+
+impl Evaluable for proto::StabilityMechanism {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.first_float()?;
+
+        if self.privacy_usage.len() != 1 {
+            return Err(Error::from("the stability mechanism only supports one privacy parameter"))
+        }
+        let epsilon = get_epsilon(&self.privacy_usage[0])?;
+        let delta = get_delta(&self.privacy_usage[0])?;
+
+        let (categories, counts): (Value, Vec<Float>) = match take_argument(&mut arguments, "data")?.array()? {
+            BaseArray::Int(data) => {
+                let (categories, counts) = stability_histogram(
+                    data.iter().cloned(), epsilon, delta, sensitivity, enforce_constant_time)?;
+                (Array::from(categories).into_dyn().into(), counts)
+            }
+            BaseArray::Bool(data) => {
+                let (categories, counts) = stability_histogram(
+                    data.iter().cloned(), epsilon, delta, sensitivity, enforce_constant_time)?;
+                (Array::from(categories).into_dyn().into(), counts)
+            }
+            BaseArray::Str(data) => {
+                let (categories, counts) = stability_histogram(
+                    data.iter().cloned(), epsilon, delta, sensitivity, enforce_constant_time)?;
+                (Array::from(categories).into_dyn().into(), counts)
+            }
+            BaseArray::Float(_) => return Err("data may not be float".into())
+        };
+
+        Ok(ReleaseNode {
+            value: Value::Dataframe(indexmap![
+                "categories".into() => categories,
+                "counts".into() => Array::from(counts).into_dyn().into()
+            ]),
+            privacy_usages: Some(self.privacy_usage.clone()),
+            public: true,
+        })
+    }
+}
+
+/// Noisy counts of the categories in the data whose noisy counts exceed the threshold.
+///
+/// Categories are released in sorted order, so that the order does not depend on the order of the records.
+///
+/// # Arguments
+/// * `data` - Category of each record.
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `delta` - Additive privacy loss parameter.
+/// * `sensitivity` - L1 sensitivity of the histogram.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::components::stability_mechanism::stability_histogram;
+/// let data = vec!["a"; 1000].into_iter().chain(std::iter::once("b"));
+/// let (categories, counts) = stability_histogram(data, 1., 1e-6, 1., false).unwrap();
+/// assert_eq!(categories, vec!["a"]);
+/// assert_eq!(counts.len(), 1);
+/// ```
+pub fn stability_histogram<T: Eq + Hash + Ord>(
+    data: impl Iterator<Item=T>,
+    epsilon: Float, delta: Float, sensitivity: Float,
+    enforce_constant_time: bool,
+) -> Result<(Vec<T>, Vec<Float>)> {
+    let threshold = get_threshold(epsilon, delta, sensitivity)?;
+
+    let mut counts = HashMap::new();
+    data.for_each(|category| *counts.entry(category).or_insert(0) += 1);
+
+    let mut released = counts.into_iter()
+        .map(|(category, count)| Ok((
+            category,
+            laplace_mechanism(count as Float, epsilon, sensitivity, enforce_constant_time)?)))
+        .collect::<Result<Vec<(T, Float)>>>()?.into_iter()
+        .filter(|(_, noisy_count)| *noisy_count > threshold)
+        .collect::<Vec<(T, Float)>>();
+    released.sort_by(|(l, _), (r, _)| l.cmp(r));
+
+    Ok(released.into_iter().unzip())
+}
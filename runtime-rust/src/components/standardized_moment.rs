@@ -0,0 +1,125 @@
+use ndarray::ArrayD;
+
+use smartnoise_validator::{proto, Float};
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::components::standardized_moment::{MOMENT_NAMES, standardized_moment as standardize};
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+
+impl Evaluable for proto::StandardizedMoment {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        if self.order as usize > MOMENT_NAMES.len() {
+            return Err("order: must be 3 for skewness or 4 for kurtosis".into())
+        }
+        let moments = MOMENT_NAMES[..self.order as usize].iter()
+            .map(|name| take_argument(&mut arguments, name)?.array()?.float())
+            .collect::<Result<Vec<ArrayD<Float>>>>()?;
+
+        Ok(ReleaseNode::new(standardized_moment(self.order, &moments)?.into()))
+    }
+}
+
+/// Accepts raw moments of data within `[0, 1]` and returns the standardized moment for each column.
+///
+/// # Arguments
+/// * `order` - Order of the standardized moment, 3 for skewness or 4 for kurtosis.
+/// * `moments` - Raw moments of orders one through the order, each with one value per column.
+///
+/// # Return
+/// Standardized moment for each column.
+///
+/// # Example
+/// ```
+/// use ndarray::arr2;
+/// use smartnoise_runtime::components::standardized_moment::standardized_moment;
+/// let moments = vec![
+///     arr2(&[[0.5, 0.25]]).into_dyn(), arr2(&[[1. / 3., 0.25]]).into_dyn(),
+///     arr2(&[[0.25, 0.25]]).into_dyn(), arr2(&[[0.2, 0.25]]).into_dyn()];
+/// let kurtosis = standardized_moment(4, &moments).unwrap();
+/// assert!((kurtosis[[0, 0]] - 1.8).abs() < 1e-12);
+/// assert!((kurtosis[[0, 1]] - 7. / 3.).abs() < 1e-12);
+/// ```
+pub fn standardized_moment(order: u32, moments: &[ArrayD<Float>]) -> Result<ArrayD<Float>> {
+    let first = moments.first()
+        .ok_or_else(|| Error::from("moments: must not be empty"))?;
+    if moments.iter().any(|moment| moment.shape() != first.shape()) {
+        return Err("moments must have the same shape".into())
+    }
+
+    let columns = moments.iter()
+        .map(|moment| moment.iter().cloned().collect::<Vec<Float>>())
+        .collect::<Vec<Vec<Float>>>();
+
+    let values = (0..first.len())
+        .map(|index| standardize(order, &columns.iter()
+            .map(|moment| moment[index])
+            .collect::<Vec<Float>>()))
+        .collect::<Result<Vec<Float>>>()?;
+
+    ArrayD::from_shape_vec(first.shape(), values)
+        .map_err(|_| "unable to shape the standardized moments".into())
+}
+
+#[cfg(test)]
+mod test_standardized_moment {
+    use ndarray::Array;
+
+    use smartnoise_validator::bindings::Analysis;
+    use smartnoise_validator::proto;
+
+    use crate::utilities::fixtures::{approximate_usage, bounded_float_data, release_analysis, unprotected_analysis};
+
+    /// Release the kurtosis of evenly spread data on [0, 1), with a kurtosis of about 9 / 5, and check its report.
+    fn check_dp_kurtosis(mut analysis: Analysis, privacy_usage: proto::PrivacyUsage, mechanism: &str) {
+        // the sensitivity of each raw moment is 1 / n, so many records keep the noise well within the tolerance
+        let data = Array::from_shape_fn((10000, 1), |(i, _)| (i % 100) as f64 / 100.);
+
+        let imputed = bounded_float_data(&mut analysis, data.into_dyn(), &[0.], &[1.]);
+
+        let kurtosis = analysis.dp_kurtosis(imputed, vec![privacy_usage])
+            .mechanism(mechanism.to_string()).build();
+
+        let release = release_analysis(&analysis).unwrap();
+
+        let value = release[&kurtosis].value.clone().array().unwrap().float().unwrap();
+        assert!((value[[0, 0]] - 1.8).abs() < 0.2, "kurtosis: {:?}", value);
+
+        let report = smartnoise_validator::generate_report(
+            analysis.privacy_definition.clone(), analysis.components.clone(), release).unwrap();
+        assert!(report.contains("accuracyValue"), "report: {}", report);
+    }
+
+    #[test]
+    fn test_dp_kurtosis() {
+        check_dp_kurtosis(unprotected_analysis(), proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                rho: 10.
+            }))
+        }, "Gaussian");
+    }
+
+    #[test]
+    fn test_dp_kurtosis_default_privacy_definition() {
+        check_dp_kurtosis(Analysis::new(), approximate_usage(10., 0.), "Automatic");
+    }
+
+    #[test]
+    fn test_dp_skewness_default_privacy_definition() {
+        // evenly spread data on [0, 1) is symmetric, with a skewness of zero
+        let data = Array::from_shape_fn((10000, 1), |(i, _)| (i % 100) as f64 / 100.);
+
+        let mut analysis = Analysis::new();
+
+        let imputed = bounded_float_data(&mut analysis, data.into_dyn(), &[0.], &[1.]);
+
+        let skewness = analysis.dp_skewness(imputed, vec![approximate_usage(10., 0.)]).build();
+
+        let release = release_analysis(&analysis).unwrap();
+
+        let value = release[&skewness].value.clone().array().unwrap().float().unwrap();
+        assert!(value[[0, 0]].abs() < 0.2, "skewness: {:?}", value);
+    }
+}
@@ -0,0 +1,98 @@
+use ndarray::{Array, Ix2};
+
+use smartnoise_validator::proto;
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::utilities::take_argument;
+
+use crate::components::Evaluable;
+use crate::NodeArguments;
+use crate::utilities::to_nd;
+
+impl Evaluable for proto::UpdateCentroids {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let centroids = to_nd(take_argument(&mut arguments, "centroids")?.array()?.cast_float()?, 2)?
+            .into_dimensionality::<Ix2>()?;
+        let lower = take_argument(&mut arguments, "lower")?.array()?.cast_float()?;
+        let upper = take_argument(&mut arguments, "upper")?.array()?.cast_float()?;
+
+        let (num_clusters, num_columns) = (centroids.shape()[0], centroids.shape()[1]);
+        if lower.len() != num_columns || upper.len() != num_columns {
+            return Err("lower and upper must have one entry for each column of the centroids".into())
+        }
+
+        let mut updated = Vec::with_capacity(num_clusters * num_columns);
+        for (cluster, centroid) in centroids.genrows().into_iter().enumerate() {
+            let count = take_argument(&mut arguments, &format!("count_{}", cluster))?
+                .array()?.cast_float()?;
+            if count.len() != 1 {
+                return Err(format!("count_{} must be a single value", cluster).into())
+            }
+            let count = count.iter().sum::<f64>();
+            let sum = take_argument(&mut arguments, &format!("sum_{}", cluster))?
+                .array()?.cast_float()?;
+            if sum.len() != num_columns {
+                return Err(format!("sum_{} must have one entry for each column of the centroids", cluster).into())
+            }
+
+            // empty clusters keep their previous centroid
+            if count < 1. {
+                updated.extend(centroid.iter().cloned());
+                continue
+            }
+
+            updated.extend(sum.iter().zip(lower.iter().zip(upper.iter()))
+                .map(|(s, (l, u))| (s / count).max(*l).min(*u)));
+        }
+
+        Ok(ReleaseNode::new(Array::from_shape_vec((num_clusters, num_columns), updated)?
+            .into_dyn().into()))
+    }
+}
+
+#[cfg(test)]
+mod test_update_centroids {
+    use ndarray::{arr2, Array, ArrayD};
+
+    use smartnoise_validator::bindings::Analysis;
+
+    use crate::utilities::fixtures::{approximate_usage, bounded_float_data, release_analysis, unprotected_analysis};
+
+    /// release centroids of two well-separated clusters, about (1, 1) and (9, 9)
+    fn release_dp_k_means(mut analysis: Analysis) -> ArrayD<f64> {
+        let data = Array::from_shape_fn((200, 2), |(i, _)| if i < 100 {
+            1. + (i % 3) as f64 * 0.1
+        } else {
+            9. - (i % 3) as f64 * 0.1
+        });
+
+        let imputed = bounded_float_data(&mut analysis, data.into_dyn(), &[0., 0.], &[10., 10.]);
+
+        let initial_centroids = analysis.literal()
+            .value(arr2(&[[2., 3.], [7., 6.]]).into_dyn().into()).value_public(true).build();
+        let centroids = analysis.dp_k_means(imputed, 2, vec![approximate_usage(1e4, 0.)])
+            .initial_centroids(initial_centroids).iterations(2).build();
+
+        let release = release_analysis(&analysis).unwrap();
+        let centroids = release[&centroids].value.clone().array().unwrap().float().unwrap();
+        assert_eq!(centroids.shape(), &[2, 2]);
+        centroids
+    }
+
+    #[test]
+    fn test_dp_k_means() {
+        let centroids = release_dp_k_means(unprotected_analysis());
+        let expected = [1.1, 1.1, 8.9, 8.9];
+        assert!(centroids.iter().zip(expected.iter()).all(|(l, r)| (l - r).abs() < 0.1),
+                "centroids: {:?}", centroids);
+    }
+
+    #[test]
+    fn test_dp_k_means_default_privacy_definition() {
+        // the sums are privatized with the snapping mechanism, and the counts with the discrete laplace mechanism
+        let centroids = release_dp_k_means(Analysis::new());
+        let expected = [1.1, 1.1, 8.9, 8.9];
+        assert!(centroids.iter().zip(expected.iter()).all(|(l, r)| (l - r).abs() < 0.5),
+                "centroids: {:?}", centroids);
+    }
+}
@@ -23,6 +23,7 @@ use std::vec::Vec;
 
 use smartnoise_validator::base::{Value, ReleaseNode, Release, IndexKey, ComponentExpansion, ValueProperties};
 use smartnoise_validator::utilities::{get_sinks, get_input_properties, get_dependents};
+use smartnoise_validator::{filter, ledger};
 
 use crate::components::Evaluable;
 
@@ -42,6 +43,12 @@ pub type NodeArguments = IndexMap<IndexKey, Value>;
 /// When a component is executed, the output of the node is stored in the release
 /// When the graph completes execution, the release is filtered and returned
 ///
+/// If the data owner has configured a ledger in the environment of the runtime,
+///     the budget of the dataset is checked before execution, and the privacy usage of the analysis is debited after execution.
+/// See [configured_ledger](../smartnoise_validator/ledger/fn.configured_ledger.html).
+///
+/// If the privacy definition sets a `filter_cap`, the analysis is refused unless the privacy filter admits every submission.
+///
 /// # Arguments
 /// * `analysis` - a computational graph and definition of privacy, in prost protobuf format
 /// * `release` - a collection of precomputed values for components in the graph
@@ -50,10 +57,34 @@ pub type NodeArguments = IndexMap<IndexKey, Value>;
 /// # Return
 /// a collection of computed values for components in the graph
 pub fn release(
+    privacy_definition: Option<proto::PrivacyDefinition>,
+    computation_graph: HashMap<u32, proto::Component>,
+    release: Release,
+    filter_level: proto::FilterLevel
+) -> Result<(Release, Vec<Error>)> {
+    release_with_ledger(
+        privacy_definition, computation_graph, release, filter_level,
+        ledger::configured_ledger()?.as_ref())
+}
+
+/// Execute the computation, checking and debiting the budget of the dataset in `ledger`, if given.
+///
+/// Mechanisms whose values are already present in the release are not debited again.
+///
+/// # Arguments
+/// * `analysis` - a computational graph and definition of privacy, in prost protobuf format
+/// * `release` - a collection of precomputed values for components in the graph
+/// * `filter_level` - configure the amount of information included in the return
+/// * `ledger` - account of the dataset, supplied by the data owner
+///
+/// # Return
+/// a collection of computed values for components in the graph
+pub fn release_with_ledger(
     privacy_definition: Option<proto::PrivacyDefinition>,
     mut computation_graph: HashMap<u32, proto::Component>,
     mut release: Release,
-    filter_level: proto::FilterLevel
+    filter_level: proto::FilterLevel,
+    ledger: Option<&proto::Ledger>
 ) -> Result<(Release, Vec<Error>)> {
 
     if let Some(privacy_definition) = &privacy_definition {
@@ -62,6 +93,23 @@ pub fn release(
         }
     }
 
+    // refuse to run submissions that the privacy filter rejects
+    if let Some(privacy_definition) = &privacy_definition {
+        filter::filter_analysis(privacy_definition, &computation_graph, &release)?;
+    }
+
+    // refuse to run if the budget of the dataset in the ledger is exhausted
+    let ledger_state = match (&privacy_definition, ledger) {
+        (Some(privacy_definition), Some(ledger)) => {
+            ledger::check_analysis(
+                privacy_definition, ledger,
+                computation_graph.clone(), release.clone())?;
+            Some((privacy_definition, ledger, computation_graph.clone(), release.clone()))
+        },
+        (None, Some(_)) => return Err("a privacy definition is required to debit the ledger".into()),
+        _ => None
+    };
+
     // core state for the graph execution algorithm
     let mut traversal: Vec<u32> = get_sinks(&computation_graph).into_iter().collect();
 
@@ -173,6 +221,11 @@ pub fn release(
         // no nodes were added to the traversal. Begin node execution
         traversal.pop();
 
+        // the expansion may have released the current component as a literal
+        if release.contains_key(&component_id) {
+            continue;
+        }
+
         // the expansion may have overwritten the current component
         let component = computation_graph.get(&component_id).unwrap();
 
@@ -236,6 +289,16 @@ pub fn release(
         release.insert(component_id, evaluation);
     }
 
+    // debit the privacy usage from the ledger. If the budget would be exceeded, the release is withheld
+    if let Some((privacy_definition, ledger, original_graph, prior_release)) = ledger_state {
+        // nodes added by expansions are re-derived by the validator
+        let original_release = release.iter()
+            .filter(|(node_id, _)| original_graph.contains_key(node_id))
+            .map(|(node_id, release_node)| (*node_id, release_node.clone()))
+            .collect::<Release>();
+        ledger::debit_analysis(privacy_definition, ledger, original_graph, &prior_release, original_release)?;
+    }
+
     // remove all omitted nodes (temporarily added to the graph while executing)
     release.retain(|node_id, _| !computation_graph.get(node_id)
         .map(|v| v.omit)
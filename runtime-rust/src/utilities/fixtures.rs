@@ -0,0 +1,46 @@
+//! Shared fixtures for end-to-end tests of runtime components.
+
+use ndarray::{arr1, ArrayD};
+
+use smartnoise_validator::base::Release;
+use smartnoise_validator::bindings::Analysis;
+use smartnoise_validator::errors::*;
+use smartnoise_validator::proto;
+
+/// Analysis under the default privacy definition, but with floating-point protections disabled,
+///     as the continuous mechanisms are rejected under floating-point protections.
+pub fn unprotected_analysis() -> Analysis {
+    let mut analysis = Analysis::new();
+    analysis.privacy_definition.protect_floating_point = false;
+    analysis
+}
+
+/// Add public float data to the analysis, clamped and imputed to the bounds of each column.
+///
+/// # Return
+/// Id of the node with the clamped and imputed data.
+pub fn bounded_float_data(analysis: &mut Analysis, data: ArrayD<f64>, lower: &[f64], upper: &[f64]) -> u32 {
+    let data = analysis.literal().value(data.into()).value_public(true).build();
+    let lower = analysis.literal().value(arr1(lower).into_dyn().into()).value_public(true).build();
+    let upper = analysis.literal().value(arr1(upper).into_dyn().into()).value_public(true).build();
+    let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+    analysis.impute(clamped).lower(lower).upper(upper).build()
+}
+
+/// Privacy usage with the given epsilon and delta.
+pub fn approximate_usage(epsilon: f64, delta: f64) -> proto::PrivacyUsage {
+    proto::PrivacyUsage {
+        distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+            epsilon, delta
+        }))
+    }
+}
+
+/// Release the analysis, and check that the release raised no warnings.
+pub fn release_analysis(analysis: &Analysis) -> Result<Release> {
+    let (release, warnings) = crate::release(
+        Some(analysis.privacy_definition.clone()), analysis.components.clone(),
+        analysis.release.clone(), proto::FilterLevel::All)?;
+    assert!(warnings.is_empty(), "warnings: {:?}", warnings);
+    Ok(release)
+}
@@ -3,7 +3,10 @@ use smartnoise_validator::errors::*;
 use crate::utilities;
 use smartnoise_validator::Float;
 use crate::utilities::{noise};
-use smartnoise_validator::components::gaussian_mechanism::get_analytic_gaussian_sigma;
+use smartnoise_validator::components::gaussian_mechanism::get_gaussian_sigma;
+use smartnoise_validator::components::discrete_gaussian_mechanism::get_discrete_gaussian_sigma;
+use smartnoise_validator::components::sparse_vector::get_sparse_vector_epsilons;
+use smartnoise_validator::proto;
 use std::ops::{Div};
 
 /// Returns noise drawn according to the Laplace mechanism
@@ -159,15 +162,54 @@ pub fn gaussian_mechanism(
         return Err(format!("epsilon ({}), delta ({}) and sensitivity ({}) must all be positive", epsilon, delta, sensitivity).into());
     }
 
-    let scale = if analytic {
-        get_analytic_gaussian_sigma(epsilon, delta, sensitivity)
-    } else {
-        sensitivity * (2. * (1.25 / delta).ln()).sqrt() / epsilon
+    let usage = proto::PrivacyUsage {
+        distance: Some(proto::privacy_usage::Distance::Approximate(
+            proto::privacy_usage::DistanceApproximate { epsilon, delta }))
     };
+    let scale = get_gaussian_sigma(&usage, sensitivity, analytic)?;
     // this uses mpfr noise if available
     Ok(value + noise::sample_gaussian(0., scale, enforce_constant_time)?)
 }
 
+/// Returns noise drawn according to the Gaussian mechanism, calibrated to zero-concentrated differential privacy.
+///
+/// Noise is drawn with scale sensitivity / sqrt(2 rho) and centered about 0.
+/// For more information, see
+/// [Bun & Steinke (2016)](https://arxiv.org/pdf/1605.02065.pdf), Proposition 1.6.
+///
+/// NOTE: this implementation of Gaussian draws in likely non-private due to floating-point attacks
+///
+/// # Arguments
+/// * `value` - Statistic to be privatized.
+/// * `rho` - Privacy loss parameter under zero-concentrated differential privacy.
+/// * `sensitivity` - Upper bound on the L2 sensitivity of the function you want to privatize.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// A draw from Gaussian distribution with scale defined as above.
+///
+/// # Examples
+/// ```
+/// use smartnoise_runtime::utilities::mechanisms::concentrated_gaussian_mechanism;
+/// let n = concentrated_gaussian_mechanism(22.3, 0.05, 2.0, false);
+/// ```
+pub fn concentrated_gaussian_mechanism(
+    value: f64,
+    rho: f64, sensitivity: f64,
+    enforce_constant_time: bool
+) -> Result<f64> {
+    if rho <= 0. || sensitivity <= 0. {
+        return Err(format!("rho ({}) and sensitivity ({}) must both be positive", rho, sensitivity).into());
+    }
+
+    let usage = proto::PrivacyUsage {
+        distance: Some(proto::privacy_usage::Distance::Concentrated(
+            proto::privacy_usage::DistanceConcentrated { rho }))
+    };
+    let scale = get_gaussian_sigma(&usage, sensitivity, false)?;
+    Ok(value + noise::sample_gaussian(0., scale, enforce_constant_time)?)
+}
+
 /// Returns noise drawn according to the Geometric mechanism.
 ///
 /// Uses the Geometric mechanism as originally proposed in
@@ -217,6 +259,85 @@ pub fn simple_geometric_mechanism(
     Ok(if noised < min {min} else if noised > max { max } else { noised })
 }
 
+/// Returns noise drawn according to the discrete laplace mechanism.
+///
+/// Noise is sampled exactly from the discrete laplace (two-sided geometric) distribution with scale sensitivity / epsilon,
+/// as described in [Canonne, Kamath & Steinke (2020)](https://arxiv.org/pdf/2004.00010.pdf).
+/// Unlike the `simple_geometric_mechanism`, neither the input nor the output is clamped.
+///
+/// # Arguments
+/// * `value` - Statistic to be privatized.
+/// * `epsilon` - Multiplicative privacy loss parameter
+/// * `sensitivity` - L1 sensitivity of function you want to privatize. A statistic with zero sensitivity, like a count over a known number of records, is returned without noise.
+///
+/// # Return
+/// Value of the statistic with discrete laplace noise added.
+///
+/// # Examples
+/// ```
+/// use smartnoise_runtime::utilities::mechanisms::discrete_laplace_mechanism;
+/// let n = discrete_laplace_mechanism(4, 0.1, 1.);
+/// assert_eq!(discrete_laplace_mechanism(4, 0.1, 0.).unwrap(), 4);
+/// ```
+#[allow(clippy::float_cmp)]
+pub fn discrete_laplace_mechanism(
+    value: i64, epsilon: f64, sensitivity: f64,
+) -> Result<i64> {
+    if epsilon <= 0. || sensitivity < 0. {
+        return Err(format!("epsilon ({}) must be positive and sensitivity ({}) must be non-negative", epsilon, sensitivity).into());
+    }
+    if sensitivity == 0. {
+        return Ok(value)
+    }
+
+    let noise_sample = noise::sample_discrete_laplace(sensitivity / epsilon)?;
+
+    value.checked_add(noise_sample)
+        .ok_or_else(|| Error::from("discrete laplace noise overflowed the data type"))
+}
+
+/// Returns noise drawn according to the discrete gaussian mechanism.
+///
+/// Noise is sampled exactly from the discrete gaussian distribution, as described in
+/// [Canonne, Kamath & Steinke (2020)](https://arxiv.org/pdf/2004.00010.pdf).
+///
+/// # Arguments
+/// * `value` - Statistic to be privatized.
+/// * `usage` - Either an approximate or zero-concentrated privacy usage.
+/// * `sensitivity` - Upper bound on the L2 sensitivity of the function you want to privatize. A statistic with zero sensitivity is returned without noise.
+///
+/// # Return
+/// Value of the statistic with discrete gaussian noise added.
+///
+/// # Examples
+/// ```
+/// use smartnoise_runtime::utilities::mechanisms::discrete_gaussian_mechanism;
+/// use smartnoise_validator::proto;
+/// let usage = proto::PrivacyUsage {
+///     distance: Some(proto::privacy_usage::Distance::Concentrated(
+///         proto::privacy_usage::DistanceConcentrated { rho: 0.5 }))
+/// };
+/// let n = discrete_gaussian_mechanism(4, &usage, 1.);
+/// assert_eq!(discrete_gaussian_mechanism(4, &usage, 0.).unwrap(), 4);
+/// ```
+#[allow(clippy::float_cmp)]
+pub fn discrete_gaussian_mechanism(
+    value: i64, usage: &proto::PrivacyUsage, sensitivity: f64,
+) -> Result<i64> {
+    if sensitivity < 0. {
+        return Err(format!("sensitivity ({}) must be non-negative", sensitivity).into());
+    }
+    if sensitivity == 0. {
+        return Ok(value)
+    }
+
+    let sigma = get_discrete_gaussian_sigma(usage, sensitivity)?;
+    let noise_sample = noise::sample_discrete_gaussian(sigma)?;
+
+    value.checked_add(noise_sample)
+        .ok_or_else(|| Error::from("discrete gaussian noise overflowed the data type"))
+}
+
 /// Returns data element according to the Exponential mechanism.
 ///
 /// # Arguments
@@ -289,4 +410,163 @@ pub fn exponential_mechanism<T>(
 
     // sample element relative to probability
     utilities::sample_from_set(candidate_set, &weight_vec, enforce_constant_time)
-}
\ No newline at end of file
+}
+
+/// Returns the candidate with the largest utility, after adding independent noise to each utility.
+///
+/// Noise is scaled by 2 sensitivity / epsilon, and is either laplace or exponential.
+/// Report noisy max with exponential noise is identical to the permute-and-flip mechanism,
+/// [Ding et al. (2021)](https://arxiv.org/abs/2105.07260).
+///
+/// NOTE: This implementation is likely non-private because of the difference between theory on
+///       the real numbers and floating-point numbers.
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - L1 sensitivity of utility function.
+/// * `candidate_set` - Data from which user wants an element returned.
+/// * `utilities` - Utility of each candidate.
+/// * `noise` - Either `laplace` or `exponential`.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::utilities::mechanisms::report_noisy_max;
+/// let xs: Vec<f64> = vec![1., 2., 3., 4., 5.];
+/// let ans = report_noisy_max(1.0, 1.0, &xs, xs.clone(), "laplace", false);
+/// # ans.unwrap();
+/// ```
+pub fn report_noisy_max<T>(
+    epsilon: f64,
+    sensitivity: f64,
+    candidate_set: &[T],
+    utilities: Vec<f64>,
+    noise: &str,
+    enforce_constant_time: bool
+) -> Result<T> where T: Clone {
+    if epsilon <= 0. || sensitivity <= 0. {
+        return Err(format!("epsilon ({}) and sensitivity ({}) must be positive", epsilon, sensitivity).into());
+    }
+    if candidate_set.is_empty() || candidate_set.len() != utilities.len() {
+        return Err("candidates and utilities must be non-empty and of the same length".into())
+    }
+    let scale = 2. * sensitivity / epsilon;
+
+    let noisy_utilities = utilities.into_iter()
+        .map(|utility| Ok(utility + match noise.to_lowercase().as_str() {
+            "laplace" => noise::sample_laplace(0., scale, enforce_constant_time)?,
+            "exponential" => noise::sample_exponential(scale, enforce_constant_time)?,
+            _ => return Err("noise: must be one of [Laplace, Exponential]".into())
+        }))
+        .collect::<Result<Vec<f64>>>()?;
+
+    let index = noisy_utilities.iter().enumerate()
+        .fold((0, f64::NEG_INFINITY), |(arg_max, max), (i, v)|
+            if *v > max { (i, *v) } else { (arg_max, max) }).0;
+    Ok(candidate_set[index].clone())
+}
+
+/// Returns a candidate according to the permute-and-flip mechanism.
+///
+/// Candidates are visited in a uniformly random order,
+///     and each is accepted with probability exp(epsilon (utility - max utility) / (2 sensitivity)).
+/// The candidate with the largest utility is always accepted, so the procedure terminates after at most one pass.
+/// [McKenna & Sheldon (2020)](https://arxiv.org/abs/2010.12603)
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - L1 sensitivity of utility function.
+/// * `candidate_set` - Data from which user wants an element returned.
+/// * `utilities` - Utility of each candidate.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::utilities::mechanisms::permute_and_flip;
+/// let xs: Vec<f64> = vec![1., 2., 3., 4., 5.];
+/// let ans = permute_and_flip(1.0, 1.0, &xs, xs.clone(), false);
+/// # ans.unwrap();
+/// ```
+pub fn permute_and_flip<T>(
+    epsilon: f64,
+    sensitivity: f64,
+    candidate_set: &[T],
+    utilities: Vec<f64>,
+    enforce_constant_time: bool
+) -> Result<T> where T: Clone {
+    if epsilon <= 0. || sensitivity <= 0. {
+        return Err(format!("epsilon ({}) and sensitivity ({}) must be positive", epsilon, sensitivity).into());
+    }
+    if candidate_set.is_empty() || candidate_set.len() != utilities.len() {
+        return Err("candidates and utilities must be non-empty and of the same length".into())
+    }
+
+    let max_utility = utilities.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let order = noise::shuffle((0..candidate_set.len()).collect(), enforce_constant_time)?;
+
+    for index in order {
+        let probability = (epsilon * (utilities[index] - max_utility) / (2. * sensitivity)).exp().min(1.);
+        if noise::sample_bit_prob(probability, enforce_constant_time)? {
+            return Ok(candidate_set[index].clone())
+        }
+    }
+    Err("permute-and-flip failed to accept the candidate with the largest utility".into())
+}
+
+/// Returns the queries found above a threshold by the sparse vector technique.
+///
+/// The privacy usage is independent of the number of queries, and depends only on the cutoff.
+/// If values are released, each reported query is additionally measured with the Laplace mechanism.
+/// Lyu, Su and Li (2017), Understanding the Sparse Vector Technique for Differential Privacy
+///
+/// # Arguments
+/// * `values` - Answers to the queries, in the order they are asked.
+/// * `threshold` - Public threshold.
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - Upper bound on the sensitivity of each query.
+/// * `cutoff` - Maximum number of above-threshold queries to report.
+/// * `release_values` - Whether to also release noisy answers to the reported queries.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// Indices of the above-threshold queries, and their noisy answers if `release_values` is set.
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::utilities::mechanisms::sparse_vector;
+/// let xs: Vec<f64> = vec![1., 12., 3., 15.];
+/// let ans = sparse_vector(&xs, 10., 1.0, 1.0, 1, true, false);
+/// # ans.unwrap();
+/// ```
+pub fn sparse_vector(
+    values: &[f64],
+    threshold: f64,
+    epsilon: f64,
+    sensitivity: f64,
+    cutoff: u32,
+    release_values: bool,
+    enforce_constant_time: bool
+) -> Result<(Vec<usize>, Option<Vec<f64>>)> {
+    if sensitivity < 0. {
+        return Err(format!("sensitivity ({}) must be non-negative", sensitivity).into());
+    }
+    let (epsilon_threshold, epsilon_queries, epsilon_values) =
+        get_sparse_vector_epsilons(epsilon, cutoff, release_values)?;
+
+    let indices = noise::sample_above_threshold(
+        values, threshold,
+        sensitivity / epsilon_threshold,
+        2. * cutoff as f64 * sensitivity / epsilon_queries,
+        cutoff as usize, enforce_constant_time)?;
+
+    if !release_values {
+        return Ok((indices, None))
+    }
+
+    // each of at most `cutoff` reported queries is measured with an equal share of the budget
+    let noisy_values = indices.iter()
+        .map(|index| laplace_mechanism(
+            values[*index], epsilon_values / cutoff as f64, sensitivity, enforce_constant_time))
+        .collect::<Result<Vec<f64>>>()?;
+    Ok((indices, Some(noisy_values)))
+}
@@ -11,6 +11,8 @@ use smartnoise_validator::utilities::array::{slow_select, slow_stack};
 
 pub mod mechanisms;
 pub mod noise;
+#[cfg(test)]
+pub mod fixtures;
 
 ///  Accepts an ndarray and returns the number of columns.
 ///
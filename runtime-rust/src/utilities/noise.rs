@@ -110,29 +110,42 @@ pub fn sample_bit_prob(prob: f64, enforce_constant_time: bool) -> Result<bool> {
     // decompose probability into mantissa and exponent integers to quickly identify the value in the first_heads_index
     let (_sign, exponent, mantissa) = prob.decompose_raw();
 
-    // repeatedly flip fair coin (up to 1023 times) and identify index (0-based) of first heads
-    let first_heads_index = censored_specific_geom(enforce_constant_time)?;
-
-    // if prob == 1., return after retrieving censored_specific_geom, to protect constant time
-    if exponent == 1023 { return Ok(true) }
-
     // number of leading zeros in binary representation of prob
     //    cast is non-saturating because exponent only uses first 11 bits
-    //    exponent is bounded within [0, 1022] by check for valid probability
+    //    exponent is bounded within [0, 1023] by check for valid probability
     let num_leading_zeros = 1022_i16 - exponent as i16;
 
-    // 0 is the most significant/leftmost implicit bit in the mantissa/fraction/significand
-    // 52 is the least significant/rightmost
-    Ok(match first_heads_index - num_leading_zeros {
-        // index into the leading zeros of the binary representation
-        i if i < 0 => false,
-        // bit index 0 is implicitly set in ieee-754 when the exponent is nonzero
-        i if i == 0 => exponent != 0,
-        // all other digits out-of-bounds are not float-approximated/are-implicitly-zero
-        i if i > 52 => false,
-        // retrieve the bit at `i` slots shifted from the left
-        i => mantissa & (1_u64 << (52 - i as usize)) != 0
-    })
+    sample_bit_digits(|first_heads_index| {
+        // if prob == 1., every digit of 0.111... is set
+        if exponent == 1023 { return true }
+
+        // 0 is the most significant/leftmost implicit bit in the mantissa/fraction/significand
+        // 52 is the least significant/rightmost
+        match first_heads_index - num_leading_zeros {
+            // index into the leading zeros of the binary representation
+            i if i < 0 => false,
+            // bit index 0 is implicitly set in ieee-754 when the exponent is nonzero
+            i if i == 0 => exponent != 0,
+            // all other digits out-of-bounds are not float-approximated/are-implicitly-zero
+            i if i > 52 => false,
+            // retrieve the bit at `i` slots shifted from the left
+            i => mantissa & (1_u64 << (52 - i as usize)) != 0
+        }
+    }, enforce_constant_time)
+}
+
+/// Sample a single bit from the binary digits of its probability of success.
+///
+/// The index of the first heads in a sequence of fair coin flips is `i` with probability 2^-(i + 1),
+///     so the digit at that index is set with probability equal to the sum of the set digits, each times its place value.
+/// The index is censored at 1022, so the probability is exact for every float, and within 2^-1022 otherwise.
+///
+/// # Arguments
+/// * `digit` - Whether the binary digit of the probability at a 0-based index after the point is set.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+fn sample_bit_digits(digit: impl Fn(i16) -> bool, enforce_constant_time: bool) -> Result<bool> {
+    // repeatedly flip fair coin (up to 1023 times) and identify index (0-based) of first heads
+    Ok(digit(censored_specific_geom(enforce_constant_time)?))
 }
 
 /// Sample from the binomial distribution.
@@ -465,6 +478,160 @@ pub fn sample_laplace(shift: f64, scale: f64, enforce_constant_time: bool) -> Re
     Ok(Laplace::new(shift, scale).inverse(probability))
 }
 
+/// Sample from the exponential distribution with the given scale.
+///
+/// # Arguments
+///
+/// * `scale` - The scale parameter (mean) of the exponential distribution.
+///
+/// # Return
+/// Draw from Exponential(scale).
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::utilities::noise::sample_exponential;
+/// let n = sample_exponential(2.0, false);
+/// # n.unwrap();
+/// ```
+#[allow(clippy::float_cmp)]
+pub fn sample_exponential(scale: f64, enforce_constant_time: bool) -> Result<f64> {
+    // reject zero, which has an infinite inverse
+    let mut probability = 0.;
+    while probability == 0. {
+        probability = sample_uniform(0., 1., enforce_constant_time)?;
+    }
+    Ok(-scale * probability.ln())
+}
+
+/// Find the first queries whose noisy answers exceed a noisy threshold.
+///
+/// The threshold is perturbed once, and each query is perturbed independently before the comparison.
+/// Sampling halts as soon as `cutoff` queries are found above the threshold.
+/// Lyu, Su and Li (2017), Understanding the Sparse Vector Technique for Differential Privacy, Algorithm 1
+///
+/// # Arguments
+/// * `values` - Answers to the queries, in the order they are asked.
+/// * `threshold` - Public threshold.
+/// * `threshold_scale` - Scale of the Laplace noise added to the threshold.
+/// * `query_scale` - Scale of the Laplace noise added to each query answer.
+/// * `cutoff` - Maximum number of above-threshold queries to find.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// Indices of the above-threshold queries, in the order they were found.
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::utilities::noise::sample_above_threshold;
+/// let indices = sample_above_threshold(&[1., 12., 3., 15.], 10., 1., 2., 1, false);
+/// # indices.unwrap();
+/// ```
+pub fn sample_above_threshold(
+    values: &[f64], threshold: f64,
+    threshold_scale: f64, query_scale: f64,
+    cutoff: usize, enforce_constant_time: bool,
+) -> Result<Vec<usize>> {
+    let noisy_threshold = sample_laplace(threshold, threshold_scale, enforce_constant_time)?;
+
+    let mut indices = Vec::new();
+    for (index, value) in values.iter().enumerate() {
+        if indices.len() == cutoff {
+            break
+        }
+        if sample_laplace(*value, query_scale, enforce_constant_time)? >= noisy_threshold {
+            indices.push(index);
+        }
+    }
+    Ok(indices)
+}
+
+/// Sample a vector with density proportional to exp(-||b||_2 / scale).
+///
+/// The direction is uniform on the sphere, and the norm is Gamma(dimension, scale),
+/// drawn as a sum of `dimension` exponential random variables.
+/// Chaudhuri, Monteleoni and Sarwate (2011), Section 3
+///
+/// # Arguments
+/// * `dimension` - Length of the sampled vector.
+/// * `scale` - Scale of the norm of the vector.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// A draw from the spherical laplace distribution.
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::utilities::noise::sample_spherical_laplace;
+/// let n = sample_spherical_laplace(3, 2.0, false);
+/// # n.unwrap();
+/// ```
+#[allow(clippy::float_cmp)]
+pub fn sample_spherical_laplace(dimension: usize, scale: f64, enforce_constant_time: bool) -> Result<Vec<f64>> {
+    if dimension == 0 {
+        return Err("dimension must be positive".into())
+    }
+    if scale < 0. {
+        return Err("scale must be non-negative".into())
+    }
+
+    let norm = (0..dimension)
+        .map(|_| sample_exponential(scale, enforce_constant_time))
+        .sum::<Result<f64>>()?;
+
+    // reject the zero vector, which has no direction
+    let mut direction = vec![0.; dimension];
+    let mut direction_norm = 0.;
+    while direction_norm == 0. {
+        direction = (0..dimension)
+            .map(|_| sample_gaussian(0., 1., enforce_constant_time))
+            .collect::<Result<Vec<f64>>>()?;
+        direction_norm = direction.iter().map(|v| v.powi(2)).sum::<f64>().sqrt();
+    }
+
+    Ok(direction.into_iter().map(|v| v * norm / direction_norm).collect())
+}
+
+/// Sample a matrix from the Wishart distribution W_d(degrees_of_freedom, scale * I).
+///
+/// The draw is the sum of outer products of `degrees_of_freedom` gaussian vectors with covariance scale * I.
+///
+/// # Arguments
+/// * `dimension` - Number of rows and columns of the sampled matrix.
+/// * `degrees_of_freedom` - Number of gaussian vectors to sum over.
+/// * `scale` - Variance of each entry of the gaussian vectors.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// A symmetric, positive semi-definite draw from the Wishart distribution.
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::utilities::noise::sample_wishart;
+/// let n = sample_wishart(3, 4, 2.0, false);
+/// # n.unwrap();
+/// ```
+pub fn sample_wishart(
+    dimension: usize, degrees_of_freedom: usize, scale: f64, enforce_constant_time: bool,
+) -> Result<Vec<Vec<f64>>> {
+    if dimension == 0 {
+        return Err("dimension must be positive".into())
+    }
+    if scale < 0. {
+        return Err("scale must be non-negative".into())
+    }
+
+    let mut matrix = vec![vec![0.; dimension]; dimension];
+    for _ in 0..degrees_of_freedom {
+        let sample = (0..dimension)
+            .map(|_| sample_gaussian(0., scale.sqrt(), enforce_constant_time))
+            .collect::<Result<Vec<f64>>>()?;
+        matrix.iter_mut().zip(sample.iter()).for_each(|(row, left)| row.iter_mut()
+            .zip(sample.iter())
+            .for_each(|(entry, right)| *entry += left * right));
+    }
+    Ok(matrix)
+}
+
 /// Sample from Gaussian distribution centered at shift and scaled by scale.
 ///
 /// # Arguments
@@ -653,6 +820,261 @@ pub fn sample_simple_geometric_mechanism(
     })
 }
 
+/// Exact rational representation of a finite float, as a numerator and a positive denominator.
+#[cfg(feature = "use-mpfr")]
+fn float_to_rational(value: f64) -> Result<(rug::Integer, rug::Integer)> {
+    let (mantissa, exponent) = Float::with_val(53, value).to_integer_exp()
+        .ok_or_else(|| Error::from("value must be finite"))?;
+
+    // every finite float is a dyadic rational, mantissa * 2^exponent
+    Ok(if exponent >= 0 {
+        (mantissa << exponent as u32, rug::Integer::from(1))
+    } else {
+        (mantissa, rug::Integer::from(1) << (-exponent) as u32)
+    })
+}
+
+/// Sample a bit with probability `numer / denom`.
+///
+/// The binary digits of the rational probability are computed exactly,
+///     and sampled from in the same way as the digits of a float in `sample_bit_prob`.
+#[cfg(feature = "use-mpfr")]
+fn sample_bit_rational(numer: &rug::Integer, denom: &rug::Integer) -> Result<bool> {
+    if *denom <= 0 || *numer < 0 || numer > denom {
+        return Err("probability is not within [0, 1]".into())
+    }
+
+    sample_bit_digits(|index| {
+        // every digit of 0.111... is set
+        if numer == denom { return true }
+        // the digit at `index` is the parity of floor(numer / denom * 2^(index + 1)), where both are non-negative
+        (rug::Integer::from(numer << (index as u32 + 1)) / denom).is_odd()
+    }, false)
+}
+
+/// Sample a bit with probability exactly exp(-numer / denom).
+///
+/// [Canonne, Kamath & Steinke (2020)](https://arxiv.org/pdf/2004.00010.pdf), Algorithm 1
+#[cfg(feature = "use-mpfr")]
+fn sample_bit_exp(numer: &rug::Integer, denom: &rug::Integer) -> Result<bool> {
+    if *denom <= 0 || *numer < 0 {
+        return Err("exponent must be non-negative".into())
+    }
+
+    // exp(-gamma) = exp(-1)^floor(gamma) * exp(-(gamma - floor(gamma)))
+    let (whole, fract) = numer.clone().div_rem_floor(denom.clone());
+    let one = rug::Integer::from(1);
+    let mut i = rug::Integer::new();
+    while i < whole {
+        if !sample_bit_exp_unit(&one, &one)? {
+            return Ok(false)
+        }
+        i += 1;
+    }
+    sample_bit_exp_unit(&fract, denom)
+}
+
+/// Sample a bit with probability exactly exp(-numer / denom), where numer / denom is within [0, 1].
+///
+/// The index of the first failed trial, where trial k succeeds with probability gamma / k, is odd with probability exp(-gamma).
+#[cfg(feature = "use-mpfr")]
+fn sample_bit_exp_unit(numer: &rug::Integer, denom: &rug::Integer) -> Result<bool> {
+    let mut k = rug::Integer::from(1);
+    loop {
+        if sample_bit_rational(numer, &rug::Integer::from(denom * &k))? {
+            k += 1;
+        } else {
+            return Ok(k.is_odd())
+        }
+    }
+}
+
+/// Sample from the discrete laplace distribution with scale `scale_numer / scale_denom`.
+///
+/// P[X = x] is proportional to exp(-|x| scale_denom / scale_numer), for all integers x.
+///
+/// [Canonne, Kamath & Steinke (2020)](https://arxiv.org/pdf/2004.00010.pdf), Algorithm 2
+#[cfg(feature = "use-mpfr")]
+fn sample_discrete_laplace_rational(scale_numer: &rug::Integer, scale_denom: &rug::Integer) -> Result<rug::Integer> {
+    if *scale_numer <= 0 || *scale_denom <= 0 {
+        return Err("scale must be positive".into())
+    }
+    let one = rug::Integer::from(1);
+    loop {
+        // sample the remainder of |X| * scale_denom modulo scale_numer
+        let remainder = {
+            let mut rng = GeneratorOpenSSL {};
+            let mut state = ThreadRandState::new_custom(&mut rng);
+            scale_numer.clone().random_below(&mut state)
+        };
+        if !sample_bit_exp(&remainder, scale_numer)? {
+            continue
+        }
+
+        // sample the quotient from the geometric distribution
+        let mut quotient = rug::Integer::new();
+        while sample_bit_exp(&one, &one)? {
+            quotient += 1;
+        }
+
+        let magnitude = (remainder + quotient * scale_numer).div_rem_floor(scale_denom.clone()).0;
+        let negative = sample_bit()?;
+        // reject negative zero, so that zero is not sampled twice as often
+        if negative && magnitude == 0 {
+            continue
+        }
+        return Ok(if negative { -magnitude } else { magnitude })
+    }
+}
+
+/// Sample from the discrete laplace distribution centered at zero.
+///
+/// P[X = x] is proportional to exp(-|x| / scale), for all integers x.
+/// Sampling is exact: the scale is represented as a rational, and all randomness is drawn from uniform integers.
+/// Unlike `sample_simple_geometric_mechanism`, the noise is not censored.
+///
+/// [Canonne, Kamath & Steinke (2020)](https://arxiv.org/pdf/2004.00010.pdf), Algorithm 2
+///
+/// # Arguments
+/// * `scale` - The scale parameter of the discrete laplace distribution.
+///
+/// # Return
+/// A draw from the discrete laplace distribution.
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::utilities::noise::sample_discrete_laplace;
+/// let n = sample_discrete_laplace(2.5);
+/// # n.unwrap();
+/// ```
+#[cfg(feature = "use-mpfr")]
+pub fn sample_discrete_laplace(scale: f64) -> Result<i64> {
+    if !scale.is_finite() || scale <= 0. {
+        return Err("scale must be positive and finite".into())
+    }
+    let (scale_numer, scale_denom) = float_to_rational(scale)?;
+
+    sample_discrete_laplace_rational(&scale_numer, &scale_denom)?.to_i64()
+        .ok_or_else(|| Error::from("discrete laplace sample is not representable as a 64-bit integer"))
+}
+
+#[cfg(not(feature = "use-mpfr"))]
+pub fn sample_discrete_laplace(_scale: f64) -> Result<i64> {
+    Err(Error::from("Crate must be compiled with gmp-mpfr to use the discrete laplace mechanism."))
+}
+
+/// Sample from the discrete gaussian distribution centered at zero.
+///
+/// P[X = x] is proportional to exp(-x^2 / (2 sigma^2)), for all integers x.
+/// Sampling is exact: the noise scale is represented as a rational, and all randomness is drawn from uniform integers.
+///
+/// [Canonne, Kamath & Steinke (2020)](https://arxiv.org/pdf/2004.00010.pdf), Algorithm 3
+///
+/// # Arguments
+/// * `sigma` - The scale parameter of the discrete gaussian distribution.
+///
+/// # Return
+/// A draw from the discrete gaussian distribution.
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::utilities::noise::sample_discrete_gaussian;
+/// let n = sample_discrete_gaussian(3.5);
+/// # n.unwrap();
+/// ```
+#[cfg(feature = "use-mpfr")]
+pub fn sample_discrete_gaussian(sigma: f64) -> Result<i64> {
+    if !sigma.is_finite() || sigma <= 0. {
+        return Err("sigma must be positive and finite".into())
+    }
+
+    // sigma^2 = sigma_numer^2 / sigma_denom^2, exactly
+    let (sigma_numer, sigma_denom) = float_to_rational(sigma)?;
+    let sigma2_numer = rug::Integer::from(sigma_numer.square_ref());
+    let sigma2_denom = rug::Integer::from(sigma_denom.square_ref());
+
+    // scale of the discrete laplace proposal distribution, floor(sigma) + 1
+    let scale = sigma_numer.clone().div_rem_floor(sigma_denom.clone()).0 + 1;
+    let one = rug::Integer::from(1);
+
+    loop {
+        let candidate = sample_discrete_laplace_rational(&scale, &one)?;
+
+        // accept with probability exp(-(|candidate| - sigma^2 / scale)^2 / (2 sigma^2))
+        //     = exp(-(|candidate| * sigma2_denom * scale - sigma2_numer)^2 / (2 * sigma2_numer * sigma2_denom * scale^2))
+        let numer = rug::Integer::from(
+            rug::Integer::from(candidate.abs_ref()) * &sigma2_denom * &scale - &sigma2_numer).square();
+        let denom = rug::Integer::from(&sigma2_numer * &sigma2_denom) * rug::Integer::from(scale.square_ref()) * 2;
+
+        if sample_bit_exp(&numer, &denom)? {
+            return candidate.to_i64()
+                .ok_or_else(|| Error::from("discrete gaussian sample is not representable as a 64-bit integer"))
+        }
+    }
+}
+
+#[cfg(not(feature = "use-mpfr"))]
+pub fn sample_discrete_gaussian(_sigma: f64) -> Result<i64> {
+    Err(Error::from("Crate must be compiled with gmp-mpfr to use the discrete gaussian mechanism."))
+}
+
+#[cfg(all(test, feature = "use-mpfr"))]
+mod test_sample_discrete {
+    use crate::utilities::noise::{sample_bit_exp, sample_bit_rational, sample_discrete_gaussian, sample_discrete_laplace};
+
+    #[test]
+    fn test_sample_bit_rational() {
+        let n = 10_000;
+        let count = (0..n)
+            .filter(|_| sample_bit_rational(&rug::Integer::from(1), &rug::Integer::from(3)).unwrap())
+            .count();
+        assert!((count as f64 / n as f64 - 1. / 3.).abs() < 0.02);
+
+        let (zero, one) = (rug::Integer::from(0), rug::Integer::from(1));
+        assert!((0..100).all(|_| !sample_bit_rational(&zero, &one).unwrap()));
+        assert!((0..100).all(|_| sample_bit_rational(&one, &one).unwrap()));
+        assert!(sample_bit_rational(&rug::Integer::from(2), &one).is_err());
+    }
+
+    #[test]
+    fn test_sample_bit_exp() {
+        let n = 10_000;
+        // exp(-3/2) ≈ 0.2231
+        let count = (0..n)
+            .filter(|_| sample_bit_exp(&rug::Integer::from(3), &rug::Integer::from(2)).unwrap())
+            .count();
+        assert!((count as f64 / n as f64 - (-1.5f64).exp()).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_sample_discrete_gaussian() {
+        let n = 10_000;
+        let sigma = 3.5;
+        let samples = (0..n)
+            .map(|_| sample_discrete_gaussian(sigma).unwrap() as f64)
+            .collect::<Vec<f64>>();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64;
+
+        // the variance of the discrete gaussian is at most sigma^2, and very close to it for sigma > 1
+        assert!(mean.abs() < 0.2);
+        assert!((variance - sigma.powi(2)).abs() < 1.);
+    }
+
+    #[test]
+    fn test_sample_discrete_laplace() {
+        let n = 10_000;
+        let scale = 2.5;
+        let variance = (0..n)
+            .map(|_| (sample_discrete_laplace(scale).unwrap() as f64).powi(2))
+            .sum::<f64>() / n as f64;
+
+        // variance of the discrete laplace is 2 a / (1 - a)^2, where a = exp(-1 / scale)
+        let a = (-1. / scale).exp();
+        assert!((variance - 2. * a / (1. - a).powi(2)).abs() < 1.);
+    }
+}
+
 /// Apply noise to value according to the Snapping mechanism.
 /// Sensitivity is assumed to be 1 in L1 space.
 ///
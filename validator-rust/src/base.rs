@@ -72,6 +72,12 @@ impl Value {
             _ => Err("value must be a dataframe".into())
         }
     }
+    pub fn ref_dataframe(&self) -> Result<&IndexMap<IndexKey, Value>> {
+        match self {
+            Value::Dataframe(dataframe) => Ok(dataframe),
+            _ => Err("value must be a dataframe".into())
+        }
+    }
     pub fn partitions(self) -> Result<IndexMap<IndexKey, Value>> {
         match self {
             Value::Partitions(partitions) => Ok(partitions),
@@ -1154,52 +1160,121 @@ pub type NodeProperties = IndexMap<base::IndexKey, ValueProperties>;
 
 
 impl proto::PrivacyUsage {
-    pub(crate) fn actual_to_effective(&self, s: f64, mut c_stability: u32, group_size: u32) -> Result<Self> {
-        if group_size == 0 {
-            return Err(Error::from("group size must be greater than zero"))
-        }
-        use proto::privacy_usage::{DistanceApproximate, Distance::Approximate};
+    /// Convert the privacy usage of a release into the privacy usage the mechanism should be calibrated to.
+    ///
+    /// Inverse of `effective_to_actual`.
+    pub(crate) fn actual_to_effective(
+        &self, s: f64, c_stability: u32, privacy_definition: &proto::PrivacyDefinition
+    ) -> Result<Self> {
+        let group_size = get_group_size(c_stability, privacy_definition)?;
+        use proto::privacy_usage::{DistanceApproximate, DistanceConcentrated, Distance::{Approximate, Concentrated}};
 
-        c_stability *= group_size;
         Ok(proto::PrivacyUsage {
             distance: Some(match self.distance.as_ref().ok_or_else(|| "distance must be defined")? {
-                Approximate(DistanceApproximate { epsilon, delta }) =>
-                    Approximate(DistanceApproximate {
-                    epsilon: match s {
-                        s if s == 1. => epsilon / c_stability as f64,
+                Approximate(DistanceApproximate { epsilon, delta }) => {
+                    let inclusion = get_inclusion_probability(s, group_size, privacy_definition)?;
+                    let effective_epsilon = match s {
+                        s if s == 1. => epsilon / group_size,
                         _ if *epsilon > 100. =>
                             return Err(Error::from("large epsilon (>100) with privacy amplification by subsampling is numerically unstable")),
-                        s => (((epsilon.exp() - 1.) / s) + 1.).ln() / c_stability as f64
-                    },
-                    delta: delta / s / ((c_stability as f64 * epsilon).exp() - 1.) / (epsilon.exp() - 1.),
-                })
+                        _ => ((epsilon.exp() - 1.) / inclusion + 1.).ln() / group_size
+                    };
+                    Approximate(DistanceApproximate {
+                        epsilon: effective_epsilon,
+                        delta: delta / inclusion / get_group_delta_factor(effective_epsilon, group_size),
+                    })
+                },
+                // group privacy of zCDP scales rho by the square of the group size
+                Concentrated(DistanceConcentrated { rho }) => match s {
+                    s if s == 1. => Concentrated(DistanceConcentrated {
+                        rho: rho / group_size.powi(2)
+                    }),
+                    _ => return Err(Error::from("privacy amplification by subsampling is not supported for zero-concentrated privacy usages"))
+                }
             })
         })
     }
 
-    pub(crate) fn effective_to_actual(&self, s: f64, mut c_stability: u32, group_size: u32) -> Result<Self> {
-        if group_size == 0 {
-            return Err(Error::from("group size must be greater than zero"))
-        }
-        use proto::privacy_usage::{DistanceApproximate, Distance::Approximate};
+    /// Convert the privacy usage a mechanism was calibrated to into the privacy usage of the release.
+    ///
+    /// The usage is scaled to the group of records an individual may influence (group privacy),
+    /// and then amplified if the data was sampled.
+    /// Data is sampled by the Resize component with Poisson sampling under add/remove neighboring,
+    /// and without replacement under substitute neighboring.
+    /// Balle, Barthe and Gaboardi (2018), Privacy Amplification by Subsampling, Theorems 8 and 9
+    ///
+    /// # Arguments
+    /// * `s` - proportion of the data that was sampled
+    /// * `c_stability` - number of records an individual may influence
+    /// * `privacy_definition` - definition of privacy, containing the group size and neighboring definition
+    pub(crate) fn effective_to_actual(
+        &self, s: f64, c_stability: u32, privacy_definition: &proto::PrivacyDefinition
+    ) -> Result<Self> {
+        let group_size = get_group_size(c_stability, privacy_definition)?;
+        use proto::privacy_usage::{DistanceApproximate, DistanceConcentrated, Distance::{Approximate, Concentrated}};
 
-        c_stability *= group_size;
         Ok(proto::PrivacyUsage {
             distance: Some(match self.distance.as_ref().ok_or_else(|| "distance must be defined")? {
-                Approximate(DistanceApproximate { epsilon, delta }) => Approximate(DistanceApproximate {
-                    epsilon: match s {
-                        s if s == 1. => epsilon * c_stability as f64,
-                        _ if epsilon * c_stability as f64 > 100. =>
-                            return Err(Error::from("large epsilon * c_stability (>100) with privacy amplification by subsampling is numerically unstable")),
-                        s => (((epsilon * c_stability as f64).exp() - 1.) * s + 1.).ln()
-                    },
-                    delta: delta * s * ((c_stability as f64 * epsilon).exp() - 1.) / (epsilon.exp() - 1.),
-                })
+                Approximate(DistanceApproximate { epsilon, delta }) => {
+                    let inclusion = get_inclusion_probability(s, group_size, privacy_definition)?;
+                    Approximate(DistanceApproximate {
+                        epsilon: match s {
+                            s if s == 1. => epsilon * group_size,
+                            _ if epsilon * group_size > 100. =>
+                                return Err(Error::from("large epsilon * c_stability (>100) with privacy amplification by subsampling is numerically unstable")),
+                            _ => (((epsilon * group_size).exp() - 1.) * inclusion + 1.).ln()
+                        },
+                        delta: delta * inclusion * get_group_delta_factor(*epsilon, group_size),
+                    })
+                },
+                Concentrated(DistanceConcentrated { rho }) => match s {
+                    s if s == 1. => Concentrated(DistanceConcentrated {
+                        rho: rho * group_size.powi(2)
+                    }),
+                    _ => return Err(Error::from("privacy amplification by subsampling is not supported for zero-concentrated privacy usages"))
+                }
             })
         })
     }
 }
 
+/// Number of records that may differ between neighboring datasets.
+fn get_group_size(c_stability: u32, privacy_definition: &proto::PrivacyDefinition) -> Result<f64> {
+    if privacy_definition.group_size == 0 {
+        return Err(Error::from("group size must be greater than zero"))
+    }
+    Ok((c_stability * privacy_definition.group_size) as f64)
+}
+
+/// Probability that any of the `group_size` records that differ between neighboring datasets is sampled.
+///
+/// Under Poisson sampling each record is sampled independently.
+/// Under sampling without replacement the union bound is used.
+#[allow(clippy::float_cmp)]
+fn get_inclusion_probability(s: f64, group_size: f64, privacy_definition: &proto::PrivacyDefinition) -> Result<f64> {
+    use proto::privacy_definition::Neighboring;
+    if s == 1. {
+        return Ok(1.)
+    }
+    if s <= 0. || s > 1. {
+        return Err(Error::from("sample proportion must be within (0, 1]"))
+    }
+    Ok(match Neighboring::from_i32(privacy_definition.neighboring)
+        .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))? {
+        Neighboring::AddRemove => 1. - (1. - s).powf(group_size),
+        Neighboring::Substitute => (s * group_size).min(1.)
+    })
+}
+
+/// Multiplier on delta from group privacy, sum_{i=0}^{k-1} e^{i epsilon}.
+#[allow(clippy::float_cmp)]
+fn get_group_delta_factor(epsilon: f64, group_size: f64) -> f64 {
+    if group_size == 1. || epsilon == 0. {
+        return group_size
+    }
+    ((group_size * epsilon).exp() - 1.) / (epsilon.exp() - 1.)
+}
+
 
 impl Add<proto::PrivacyUsage> for proto::PrivacyUsage {
     type Output = Result<proto::PrivacyUsage>;
@@ -1214,7 +1289,11 @@ impl Add<proto::PrivacyUsage> for proto::PrivacyUsage {
             (Distance::Approximate(lhs), Distance::Approximate(rhs)) => proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
                 epsilon: lhs.epsilon + rhs.epsilon,
                 delta: lhs.delta + rhs.delta,
-            })
+            }),
+            (Distance::Concentrated(lhs), Distance::Concentrated(rhs)) => proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                rho: lhs.rho + rhs.rho,
+            }),
+            _ => return Err("approximate and concentrated privacy usages may not be added".into())
         });
         Ok(self)
     }
@@ -1229,6 +1308,9 @@ impl Mul<f64> for proto::PrivacyUsage {
             proto::privacy_usage::Distance::Approximate(approximate) => proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
                 epsilon: approximate.epsilon * rhs,
                 delta: approximate.delta * rhs,
+            }),
+            proto::privacy_usage::Distance::Concentrated(concentrated) => proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                rho: concentrated.rho * rhs,
             })
         });
         Ok(self)
@@ -1243,6 +1325,9 @@ impl Div<f64> for proto::PrivacyUsage {
             proto::privacy_usage::Distance::Approximate(approximate) => proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
                 epsilon: approximate.epsilon / rhs,
                 delta: approximate.delta / rhs,
+            }),
+            proto::privacy_usage::Distance::Concentrated(concentrated) => proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                rho: concentrated.rho / rhs,
             })
         });
         Ok(self)
@@ -1338,3 +1423,66 @@ pub mod test_data {
         ]).into()
     }
 }
+
+#[cfg(test)]
+mod test_amplification {
+    use crate::proto;
+
+    fn usage(epsilon: f64, delta: f64) -> proto::PrivacyUsage {
+        proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon, delta
+            }))
+        }
+    }
+
+    fn definition(neighboring: proto::privacy_definition::Neighboring) -> proto::PrivacyDefinition {
+        proto::PrivacyDefinition {
+            group_size: 1,
+            neighboring: neighboring as i32,
+            ..Default::default()
+        }
+    }
+
+    fn epsilon_delta(usage: proto::PrivacyUsage) -> (f64, f64) {
+        match usage.distance.unwrap() {
+            proto::privacy_usage::Distance::Approximate(distance) => (distance.epsilon, distance.delta),
+            _ => panic!("expected an approximate usage")
+        }
+    }
+
+    #[test]
+    fn test_amplification() {
+        use proto::privacy_definition::Neighboring::{AddRemove, Substitute};
+
+        for neighboring in &[AddRemove, Substitute] {
+            let (epsilon, delta) = epsilon_delta(usage(1., 1e-6)
+                .effective_to_actual(0.1, 1, &definition(*neighboring)).unwrap());
+            assert!((epsilon - (1. + 0.1 * (1f64.exp() - 1.)).ln()).abs() < 1e-12);
+            assert!((delta - 1e-7).abs() < 1e-18);
+        }
+
+        // a group of two records is sampled more often under Poisson sampling than a single record
+        let (poisson, _) = epsilon_delta(usage(1., 0.)
+            .effective_to_actual(0.1, 2, &definition(AddRemove)).unwrap());
+        let (without_replacement, _) = epsilon_delta(usage(1., 0.)
+            .effective_to_actual(0.1, 2, &definition(Substitute)).unwrap());
+        assert!((poisson - (1. + 0.19 * (2f64.exp() - 1.)).ln()).abs() < 1e-12);
+        assert!((without_replacement - (1. + 0.2 * (2f64.exp() - 1.)).ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_amplification_inverse() {
+        use proto::privacy_definition::Neighboring::{AddRemove, Substitute};
+
+        for neighboring in &[AddRemove, Substitute] {
+            for (s, c_stability) in &[(1., 1), (1., 3), (0.3, 1), (0.3, 2)] {
+                let definition = definition(*neighboring);
+                let effective = usage(0.5, 1e-6).actual_to_effective(*s, *c_stability, &definition).unwrap();
+                let (epsilon, delta) = epsilon_delta(effective.effective_to_actual(*s, *c_stability, &definition).unwrap());
+                assert!((epsilon - 0.5).abs() < 1e-12);
+                assert!((delta - 1e-6).abs() < 1e-15);
+            }
+        }
+    }
+}
@@ -50,7 +50,11 @@ impl Analysis {
                 protect_elapsed_time: false,
                 protect_memory_utilization: false,
                 protect_floating_point: true,
-                protect_sensitivity: true
+                protect_sensitivity: true,
+                composition: proto::privacy_definition::Composition::Basic as i32,
+                slack_delta: 0.,
+                approximate_total: false,
+                filter_cap: None
             },
             components: HashMap::new(),
             component_count: 0,
@@ -0,0 +1,60 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, Integer, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Jagged, Nature, NatureCategorical, Value, ValueProperties};
+use crate::components::Component;
+use crate::errors::*;
+use crate::utilities::prepend;
+
+impl Component for proto::AssignClusters {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        if data_property.nullity {
+            return Err("data: may not contain nullity. Impute first.".into())
+        }
+
+        // only the shape of the centroids is needed, so later iterations of k-means validate before their centroids are released
+        let centroids_property = properties.get::<IndexKey>(&"centroids".into())
+            .ok_or("centroids: missing")?.array()
+            .map_err(prepend("centroids:"))?;
+        if !centroids_property.releasable {
+            return Err("centroids: must be public".into())
+        }
+
+        if centroids_property.num_columns()? != data_property.num_columns()? {
+            return Err("centroids: must have one column for each column of the data".into())
+        }
+        let num_clusters = centroids_property.num_records()?;
+        if num_clusters == 0 {
+            return Err("centroids: must contain at least one centroid".into())
+        }
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_columns: Some(1),
+            aggregator: None,
+            nature: Some(Nature::Categorical(NatureCategorical {
+                categories: Jagged::Int(vec![(0..num_clusters as Integer).collect()])
+            })),
+            data_type: DataType::Int,
+            node_id: node_id as i64,
+            dimensionality: Some(1),
+            ..data_property
+        }).into())
+    }
+}
@@ -0,0 +1,99 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Value, ValueProperties};
+use crate::components::Component;
+use crate::errors::*;
+use crate::utilities::prepend;
+
+impl Component for proto::BoundContributions {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated().map_err(prepend("data:"))?;
+
+        let by_property = properties.get::<IndexKey>(&"by".into())
+            .ok_or("by: missing")?.array()
+            .map_err(prepend("by:"))?;
+        check_key_property(&data_property, by_property).map_err(prepend("by:"))?;
+
+        let multiplier = match properties.get::<IndexKey>(&"partition".into()) {
+            Some(partition_property) => {
+                let partition_property = partition_property.array()
+                    .map_err(prepend("partition:"))?;
+                check_key_property(&data_property, partition_property).map_err(prepend("partition:"))?;
+
+                if self.max_partitions == 0 {
+                    return Err("max_partitions: must be greater than zero".into())
+                }
+                get_contribution_multiplier(self.max_contributions, Some(self.max_partitions))?
+            }
+            None => get_contribution_multiplier(self.max_contributions, None)?
+        };
+
+        // each individual may now influence at most `multiplier` rows
+        data_property.c_stability = data_property.c_stability.checked_mul(multiplier)
+            .ok_or_else(|| Error::from("c_stability may not overflow"))?;
+
+        // rows may be dropped
+        data_property.num_records = None;
+        data_property.dataset_id = Some(node_id as i64);
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+/// Check that a key is a single categorical column, row-aligned with the data.
+fn check_key_property(data_property: &ArrayProperties, key_property: &ArrayProperties) -> Result<()> {
+    key_property.assert_is_not_aggregated()?;
+
+    if key_property.num_columns()? != 1 {
+        return Err("must have exactly one column".into())
+    }
+    if key_property.data_type == DataType::Float {
+        return Err("atomic type may not be float".into())
+    }
+    if key_property.data_type == DataType::Unknown {
+        return Err("atomic type must be known".into())
+    }
+    if key_property.dataset_id != data_property.dataset_id {
+        return Err("must share the same dataset id as data".into())
+    }
+    if key_property.group_id != data_property.group_id {
+        return Err("data from separate partitions may not be mixed".into())
+    }
+    Ok(())
+}
+
+/// Number of rows an individual may influence after contributions are bounded.
+///
+/// # Arguments
+/// * `max_contributions` - Maximum number of rows for each individual, or each individual in each partition.
+/// * `max_partitions` - Maximum number of partitions for each individual, if bounding within partitions.
+pub fn get_contribution_multiplier(max_contributions: u32, max_partitions: Option<u32>) -> Result<u32> {
+    if max_contributions == 0 {
+        return Err("max_contributions: must be greater than zero".into())
+    }
+    max_contributions.checked_mul(max_partitions.unwrap_or(1))
+        .ok_or_else(|| "max_contributions * max_partitions may not overflow".into())
+}
+
+#[cfg(test)]
+mod test_bound_contributions {
+    use crate::components::bound_contributions::get_contribution_multiplier;
+
+    #[test]
+    fn test_contribution_multiplier() {
+        assert_eq!(get_contribution_multiplier(3, None).unwrap(), 3);
+        assert_eq!(get_contribution_multiplier(3, Some(2)).unwrap(), 6);
+        assert!(get_contribution_multiplier(0, None).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{Array, ArrayProperties, DataframeProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::Component;
+use crate::components::contingency_table::get_layout;
+use crate::errors::*;
+use crate::utilities::prepend;
+
+impl Component for proto::ChiSquareTest {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // the test is post-processing of released counts
+        if !data_property.releasable {
+            return Err("data: must be public".into())
+        }
+        if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be numeric".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+
+        get_common_scale(public_arguments.get::<IndexKey>(&"noise_scale".into())
+            .ok_or_else(|| Error::from("noise_scale: must be public"))?
+            .ref_array()?).map_err(prepend("noise_scale:"))?;
+
+        if !["laplace", "gaussian", "discretelaplace", "discretegaussian"].contains(&self.noise.to_lowercase().as_str()) {
+            return Err(format!("noise: unexpected invalid token {:?}", self.noise).into())
+        }
+        if self.num_simulations == 0 {
+            return Err("num_simulations: must be greater than zero".into())
+        }
+
+        match self.test.to_lowercase().as_str() {
+            "goodnessoffit" => {
+                if let Some(proportions) = public_arguments.get::<IndexKey>(&"proportions".into()) {
+                    let proportions = proportions.ref_array()?.ref_float()
+                        .map_err(prepend("proportions:"))?
+                        .iter().cloned().collect::<Vec<f64>>();
+                    check_proportions(&proportions)?;
+                    if let Some(num_records) = data_property.num_records {
+                        if proportions.len() as i64 != num_records {
+                            return Err("proportions: must contain one proportion for every count".into())
+                        }
+                    }
+                } else if properties.contains_key::<IndexKey>(&"proportions".into()) {
+                    return Err("proportions: must be public".into())
+                }
+            }
+            "independence" => {
+                if !properties.contains_key::<IndexKey>(&"categories".into()) {
+                    return Err("categories: must be defined for an independence test".into())
+                }
+                let (shape, _) = get_layout(&properties)?;
+                if shape.len() != 2 {
+                    return Err("categories: an independence test must be over two columns".into())
+                }
+                if let Some(num_records) = data_property.num_records {
+                    if num_records != (shape[0] * shape[1]) as i64 {
+                        return Err("data: must contain one count for every cell of the two-way table".into())
+                    }
+                }
+            }
+            _ => bail!("test: unexpected invalid token {:?}", self.test.as_str())
+        };
+
+        let scalar_property = |upper: Option<f64>| ValueProperties::Array(ArrayProperties {
+            num_records: Some(1),
+            num_columns: Some(1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(0.)]),
+                upper: Vector1DNull::Float(vec![upper]),
+            })),
+            data_type: DataType::Float,
+            dataset_id: Some(node_id as i64),
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(0),
+            group_id: data_property.group_id.clone(),
+            naturally_ordered: true,
+            sample_proportion: None,
+        });
+
+        Ok(ValueProperties::Dataframe(DataframeProperties {
+            children: indexmap![
+                "statistic".into() => scalar_property(None),
+                "p_value".into() => scalar_property(Some(1.))
+            ]
+        }).into())
+    }
+}
+
+/// Scale of the noise shared by every count.
+///
+/// # Arguments
+/// * `noise_scale` - Scale of the noise of each count, or one scale for every count.
+pub fn get_common_scale(noise_scale: &Array) -> Result<f64> {
+    let noise_scale = noise_scale.clone().cast_float()?;
+    let scale = *noise_scale.first().ok_or("must not be empty")?;
+    if !scale.is_finite() || scale < 0. {
+        return Err("must be non-negative".into())
+    }
+    if noise_scale.iter().any(|v| (v - scale).abs() > scale * 1e-12) {
+        return Err("every count must receive noise of the same scale".into())
+    }
+    Ok(scale)
+}
+
+fn check_proportions(proportions: &[f64]) -> Result<()> {
+    if proportions.is_empty() {
+        return Err("proportions: must contain at least one proportion".into())
+    }
+    if proportions.iter().any(|proportion| !proportion.is_finite() || *proportion < 0.) {
+        return Err("proportions: must be non-negative".into())
+    }
+    if (proportions.iter().sum::<f64>() - 1.).abs() > 1e-6 {
+        return Err("proportions: must sum to one".into())
+    }
+    Ok(())
+}
+
+/// Chi-square goodness-of-fit statistic of noisy counts.
+///
+/// The number of records is estimated by the sum of the noisy counts,
+/// and categories with a proportion of zero are ignored.
+///
+/// # Arguments
+/// * `counts` - Noisy count of each category.
+/// * `proportions` - Proportion of each category under the null hypothesis.
+pub fn goodness_of_fit_statistic(counts: &[f64], proportions: &[f64]) -> f64 {
+    let num_records = counts.iter().sum::<f64>().max(1.);
+    counts.iter().zip(proportions.iter())
+        .filter(|(_, proportion)| **proportion > 0.)
+        .map(|(count, proportion)| {
+            let expected = num_records * proportion;
+            (count - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Proportion of each cell of a two-way table under independence,
+/// from the marginal proportions of the noisy counts clamped to be non-negative.
+///
+/// # Arguments
+/// * `counts` - Noisy count of each cell, with the categories of the second column varying fastest.
+/// * `shape` - Number of categories of the first and second columns.
+pub fn independence_proportions(counts: &[f64], shape: (usize, usize)) -> Vec<f64> {
+    let mut rows = vec![0.; shape.0];
+    let mut columns = vec![0.; shape.1];
+    counts.iter().enumerate().for_each(|(cell, count)| {
+        rows[cell / shape.1] += count.max(0.);
+        columns[cell % shape.1] += count.max(0.);
+    });
+    let total = rows.iter().sum::<f64>();
+
+    // without any positive counts, every cell is equally likely
+    if total <= 0. {
+        return vec![1. / counts.len() as f64; counts.len()]
+    }
+    (0..counts.len())
+        .map(|cell| rows[cell / shape.1] * columns[cell % shape.1] / total.powi(2))
+        .collect()
+}
+
+/// Chi-square independence statistic of the noisy counts of a two-way table.
+///
+/// # Arguments
+/// * `counts` - Noisy count of each cell, with the categories of the second column varying fastest.
+/// * `shape` - Number of categories of the first and second columns.
+pub fn independence_statistic(counts: &[f64], shape: (usize, usize)) -> f64 {
+    goodness_of_fit_statistic(counts, &independence_proportions(counts, shape))
+}
+
+/// Monte Carlo p-value: the proportion of simulated statistics at least as large as the observed statistic,
+/// counting the observed statistic among the simulations so that the p-value is never zero.
+///
+/// # Arguments
+/// * `statistic` - Observed statistic.
+/// * `simulated` - Statistics simulated under the null hypothesis.
+pub fn get_p_value(statistic: f64, simulated: &[f64]) -> f64 {
+    let num_extreme = simulated.iter().filter(|v| **v >= statistic).count();
+    (1 + num_extreme) as f64 / (1 + simulated.len()) as f64
+}
+
+#[cfg(test)]
+mod test_chi_square_test {
+    use crate::components::chi_square_test::{get_p_value, goodness_of_fit_statistic, independence_proportions, independence_statistic};
+
+    #[test]
+    fn test_statistics() {
+        // counts that match the null hypothesis exactly
+        assert!(goodness_of_fit_statistic(&[25., 25., 50.], &[0.25, 0.25, 0.5]).abs() < 1e-12);
+        assert!((goodness_of_fit_statistic(&[30., 20., 50.], &[0.25, 0.25, 0.5]) - 2.).abs() < 1e-12);
+
+        // the table is the outer product of its marginals
+        let counts = [10., 30., 20., 60.];
+        assert!(independence_statistic(&counts, (2, 2)).abs() < 1e-12);
+        let proportions = independence_proportions(&[-5., -5., -5., -5.], (2, 2));
+        assert_eq!(proportions, vec![0.25; 4]);
+
+        assert_eq!(get_p_value(1., &[0., 2., 3.]), 0.75);
+        assert_eq!(get_p_value(4., &[0., 2., 3.]), 0.25);
+    }
+}
@@ -3,7 +3,7 @@ use crate::errors::*;
 use crate::{proto, base, Warnable};
 
 use crate::components::{Component};
-use crate::base::{Value, ValueProperties, ArrayProperties};
+use crate::base::{Value, ValueProperties, ArrayProperties, Nature, NatureContinuous, Vector1DNull};
 use crate::utilities::{get_common_value};
 use indexmap::map::IndexMap;
 use noisy_float::types::n64;
@@ -51,8 +51,7 @@ impl Component for proto::ColumnBind {
             c_stability: get_common_value(&array_props.iter().map(|v| v.c_stability).collect())
                 .ok_or_else(|| Error::from("column bind must share c-stability constants"))?,
             aggregator: None,
-            // TODO: merge natures
-            nature: None,
+            nature: merge_continuous_natures(&array_props),
             data_type: get_common_value(&array_props.iter().map(|v| v.data_type.clone()).collect())
                 .ok_or_else(|| "data_types must be equivalent when binding into homogeneous array")?,
             dataset_id,
@@ -67,4 +66,30 @@ impl Component for proto::ColumnBind {
                 .ok_or_else(|| Error::from("sample proportions must be shared among arguments"))?.and_then(|v| v.to_f64()),
         })))
     }
-}
\ No newline at end of file
+}
+/// Concatenate the bounds of each argument, if every argument has bounds of the same atomic type.
+fn merge_continuous_natures(array_props: &[&ArrayProperties]) -> Option<Nature> {
+    let natures = array_props.iter()
+        .map(|v| v.nature.as_ref()?.continuous().ok())
+        .collect::<Option<Vec<&NatureContinuous>>>()?;
+
+    macro_rules! concatenate {
+        ($variant:ident, $bound:ident) => {
+            natures.iter()
+                .map(|nature| match &nature.$bound {
+                    Vector1DNull::$variant(bound) => Some(bound.clone()),
+                    _ => None
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(|bounds| Vector1DNull::$variant(bounds.into_iter().flatten().collect()))
+        }
+    }
+
+    let (lower, upper) = match natures.first()?.lower {
+        Vector1DNull::Float(_) => (concatenate!(Float, lower)?, concatenate!(Float, upper)?),
+        Vector1DNull::Int(_) => (concatenate!(Int, lower)?, concatenate!(Int, upper)?),
+        _ => return None
+    };
+
+    Some(Nature::Continuous(NatureContinuous { lower, upper }))
+}
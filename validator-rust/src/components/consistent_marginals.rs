@@ -0,0 +1,179 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataframeProperties, DataType, IndexKey, Value, ValueProperties};
+use crate::components::Component;
+use crate::components::contingency_table::{get_layout, get_marginal_indices, get_num_cells, get_table_key, get_table_size};
+use crate::errors::*;
+use crate::utilities::prepend;
+
+/// Largest number of conjugate gradient iterations when solving for the full table.
+const MAX_ITERATIONS: usize = 1000;
+
+impl Component for proto::ConsistentMarginals {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // the inference is post-processing of released tables
+        if !data_property.releasable {
+            return Err("data: must be public".into())
+        }
+        if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be numeric".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+        if !properties.contains_key::<IndexKey>(&"categories".into()) {
+            return Err("categories: missing".into())
+        }
+
+        let (shape, marginals) = get_layout(&properties)?;
+        if data_property.num_records()? != get_num_cells(&shape, &marginals)? as i64 {
+            return Err("data: must contain one count for every cell of the full table and each marginal".into())
+        }
+
+        let all_columns = (0..shape.len()).collect::<Vec<usize>>();
+        Ok(ValueProperties::Dataframe(DataframeProperties {
+            children: std::iter::once(&all_columns).chain(marginals.iter())
+                .map(|columns| Ok((get_table_key(columns), ValueProperties::Array(ArrayProperties {
+                    num_records: Some(get_table_size(&shape, columns)? as i64),
+                    num_columns: Some(1),
+                    nullity: false,
+                    releasable: true,
+                    c_stability: 1,
+                    aggregator: None,
+                    nature: None,
+                    data_type: DataType::Float,
+                    dataset_id: Some(node_id as i64),
+                    node_id: node_id as i64,
+                    is_not_empty: true,
+                    dimensionality: Some(1),
+                    group_id: data_property.group_id.clone(),
+                    naturally_ordered: true,
+                    sample_proportion: None,
+                }))))
+                .collect::<Result<IndexMap<IndexKey, ValueProperties>>>()?
+        }).into())
+    }
+}
+
+/// Least-squares estimate of the full table from noisy counts of the full table and its marginals,
+/// from which the full table and every marginal are summed.
+///
+/// Assumes every table has been perturbed with independent noise of the same variance.
+/// The normal equations are solved with conjugate gradients,
+/// where each marginal is summed from the full table and broadcast back over the cells it is summed from.
+/// Barak et al. (2007), Privacy, Accuracy, and Consistency Too
+///
+/// # Arguments
+/// * `tables` - Noisy counts of the full table, followed by every marginal.
+/// * `shape` - Number of categories of each column.
+/// * `marginals` - Columns of each marginal, in increasing order.
+///
+/// # Returns
+/// The consistent full table, followed by every consistent marginal.
+pub fn consistent_marginals(tables: &[f64], shape: &[usize], marginals: &[Vec<usize>]) -> Result<Vec<Vec<f64>>> {
+    if tables.len() != get_num_cells(shape, marginals)? {
+        return Err("tables must contain one count for every cell of the full table and each marginal".into())
+    }
+    let num_cells = get_table_size(shape, &(0..shape.len()).collect::<Vec<usize>>())?;
+
+    let marginal_indices = marginals.iter()
+        .map(|marginal| get_marginal_indices(shape, marginal))
+        .collect::<Result<Vec<Vec<usize>>>>()?;
+    let marginal_sizes = marginals.iter()
+        .map(|marginal| get_table_size(shape, marginal))
+        .collect::<Result<Vec<usize>>>()?;
+
+    let sum_marginal = |table: &[f64], indices: &[usize], size: usize| {
+        let mut marginal = vec![0.; size];
+        table.iter().zip(indices.iter()).for_each(|(count, index)| marginal[*index] += count);
+        marginal
+    };
+
+    // left-hand side of the normal equations: the table, plus each marginal of it broadcast back over the table
+    let apply = |table: &[f64]| {
+        let mut result = table.to_vec();
+        marginal_indices.iter().zip(marginal_sizes.iter()).for_each(|(indices, size)| {
+            let marginal = sum_marginal(table, indices, *size);
+            result.iter_mut().zip(indices.iter()).for_each(|(v, index)| *v += marginal[*index]);
+        });
+        result
+    };
+
+    // right-hand side of the normal equations: the noisy table, plus each noisy marginal broadcast back over the table
+    let mut rhs = tables[..num_cells].to_vec();
+    let mut offset = num_cells;
+    marginal_indices.iter().zip(marginal_sizes.iter()).for_each(|(indices, size)| {
+        let noisy_marginal = &tables[offset..offset + size];
+        rhs.iter_mut().zip(indices.iter()).for_each(|(v, index)| *v += noisy_marginal[*index]);
+        offset += size;
+    });
+
+    let dot = |l: &[f64], r: &[f64]| l.iter().zip(r.iter()).map(|(l, r)| l * r).sum::<f64>();
+    let tolerance = 1e-20 * dot(&rhs, &rhs).max(1.);
+
+    // conjugate gradients, starting from the noisy full table
+    let mut estimate = tables[..num_cells].to_vec();
+    let mut residual = rhs.iter().zip(apply(&estimate).iter())
+        .map(|(b, a)| b - a).collect::<Vec<f64>>();
+    let mut direction = residual.clone();
+    let mut residual_norm = dot(&residual, &residual);
+
+    for _ in 0..MAX_ITERATIONS {
+        if residual_norm <= tolerance {
+            break
+        }
+        let applied = apply(&direction);
+        let step = residual_norm / dot(&direction, &applied);
+        estimate.iter_mut().zip(direction.iter()).for_each(|(v, d)| *v += step * d);
+        residual.iter_mut().zip(applied.iter()).for_each(|(v, a)| *v -= step * a);
+
+        let next_residual_norm = dot(&residual, &residual);
+        let momentum = next_residual_norm / residual_norm;
+        direction.iter_mut().zip(residual.iter()).for_each(|(d, r)| *d = r + momentum * *d);
+        residual_norm = next_residual_norm;
+    }
+
+    let mut consistent = marginal_indices.iter().zip(marginal_sizes.iter())
+        .map(|(indices, size)| sum_marginal(&estimate, indices, *size))
+        .collect::<Vec<Vec<f64>>>();
+    consistent.insert(0, estimate);
+    Ok(consistent)
+}
+
+#[cfg(test)]
+mod test_consistent_marginals {
+    use crate::components::consistent_marginals::consistent_marginals;
+
+    #[test]
+    fn test_consistent_marginals() {
+        let shape = [2, 3];
+        let marginals = vec![vec![0], vec![1]];
+
+        // consistent tables are unchanged
+        let tables = [1., 2., 3., 4., 5., 6., 6., 15., 5., 7., 9.];
+        let consistent = consistent_marginals(&tables, &shape, &marginals).unwrap();
+        assert!(consistent.concat().iter().zip(tables.iter()).all(|(l, r)| (l - r).abs() < 1e-9));
+
+        // inconsistent tables become consistent
+        let tables = [1., 2., 3., 4., 5., 6., 8., 13., 5., 8., 9.];
+        let consistent = consistent_marginals(&tables, &shape, &marginals).unwrap();
+        let total = consistent[0].iter().sum::<f64>();
+        assert!((consistent[1].iter().sum::<f64>() - total).abs() < 1e-9);
+        assert!((consistent[2].iter().sum::<f64>() - total).abs() < 1e-9);
+        assert!((consistent[0][..3].iter().sum::<f64>() - consistent[1][0]).abs() < 1e-9);
+        assert!((consistent[0][0] + consistent[0][3] - consistent[2][0]).abs() < 1e-9);
+
+        assert!(consistent_marginals(&tables[1..], &shape, &marginals).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Value, ValueProperties};
+use crate::components::Component;
+use crate::components::hierarchical_histogram::{get_num_levels, get_num_nodes};
+use crate::errors::*;
+use crate::utilities::prepend;
+
+impl Component for proto::ConstrainedInference {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // the inference is post-processing of a released tree
+        if !data_property.releasable {
+            return Err("data: must be public".into())
+        }
+        if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be numeric".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+
+        let num_nodes = get_num_nodes(get_num_levels(self.num_leaves as usize, self.branching)?, self.branching);
+        if data_property.num_records()? != num_nodes as i64 {
+            return Err("data: must contain one count for every node of the tree".into())
+        }
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_records: Some(self.num_leaves as i64),
+            num_columns: Some(1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type: DataType::Float,
+            dataset_id: Some(node_id as i64),
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(1),
+            group_id: data_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+        }).into())
+    }
+}
+
+/// Least-squares estimate of the leaves of a noisy tree of counts, subject to every parent equalling the sum of its children.
+///
+/// Assumes every node has been perturbed with independent noise of the same variance.
+/// The tree is ordered from the root down, and from left to right within each level.
+/// Hay, Rastogi, Miklau and Suciu (2010), Boosting the Accuracy of Differentially Private Histograms Through Consistency
+///
+/// # Arguments
+/// * `tree` - Noisy counts of every node in a complete tree.
+/// * `branching` - Number of children of each node.
+/// * `num_leaves` - Number of leaves to return, counting from the left.
+///
+/// # Returns
+/// The consistent estimate of each leaf.
+pub fn constrained_inference(tree: &[f64], branching: u32, num_leaves: usize) -> Result<Vec<f64>> {
+    let num_levels = get_num_levels(num_leaves, branching)?;
+    if tree.len() != get_num_nodes(num_levels, branching) {
+        return Err("tree must contain one count for every node".into())
+    }
+
+    let width = |level: usize| (branching as usize).pow(level as u32);
+    let offsets = (0..=num_levels)
+        .map(|level| get_num_nodes(level, branching) - width(level))
+        .collect::<Vec<usize>>();
+    let branching_float = branching as f64;
+
+    // bottom-up: weighted average of each node with the sum of its children
+    let mut merged = tree.to_vec();
+    for level in (0..num_levels).rev() {
+        // leaves have height one
+        let height = (num_levels - level + 1) as i32;
+        let weight = (branching_float.powi(height) - branching_float.powi(height - 1))
+            / (branching_float.powi(height) - 1.);
+
+        for node in 0..width(level) {
+            let children = offsets[level + 1] + node * branching as usize;
+            let child_sum = merged[children..children + branching as usize].iter().sum::<f64>();
+            merged[offsets[level] + node] = weight * tree[offsets[level] + node] + (1. - weight) * child_sum;
+        }
+    }
+
+    // top-down: spread the difference between each parent and the sum of its children evenly among the children
+    let mut estimate = merged.clone();
+    for level in 0..num_levels {
+        for node in 0..width(level) {
+            let children = offsets[level + 1] + node * branching as usize;
+            let child_sum = merged[children..children + branching as usize].iter().sum::<f64>();
+            let difference = (estimate[offsets[level] + node] - child_sum) / branching_float;
+            (children..children + branching as usize)
+                .for_each(|child| estimate[child] = merged[child] + difference);
+        }
+    }
+
+    Ok(estimate[offsets[num_levels]..offsets[num_levels] + num_leaves].to_vec())
+}
+
+#[cfg(test)]
+mod test_constrained_inference {
+    use crate::components::constrained_inference::constrained_inference;
+
+    #[test]
+    fn test_constrained_inference() {
+        // consistent trees are unchanged
+        let tree = [10., 3., 7., 1., 2., 3., 4.];
+        let leaves = constrained_inference(&tree, 2, 4).unwrap();
+        assert!(leaves.iter().zip([1., 2., 3., 4.].iter()).all(|(l, r)| (l - r).abs() < 1e-12));
+
+        // inconsistent trees become consistent with the root estimate
+        let tree = [11., 3., 7.5, 1., 2.2, 3., 4.];
+        let leaves = constrained_inference(&tree, 2, 3).unwrap();
+        assert_eq!(leaves.len(), 3);
+
+        assert!(constrained_inference(&tree, 2, 5).is_err());
+    }
+}
@@ -0,0 +1,310 @@
+use indexmap::map::IndexMap;
+use ndarray::Array;
+
+use crate::{base, Integer, proto, Warnable};
+use crate::base::{AggregatorProperties, DataType, IndexKey, Jagged, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Sensitivity};
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+
+impl Component for proto::ContingencyTable {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated()?;
+
+        if data_property.data_type == DataType::Unknown {
+            return Err("data_type must be known".into())
+        }
+        if data_property.data_type == DataType::Float {
+            return Err("data: contingency tables over floats are not supported".into())
+        }
+
+        check_categories(&public_arguments, &properties, &data_property.data_type)?;
+        let (shape, marginals) = get_layout(&properties)?;
+
+        if properties.contains_key::<IndexKey>(&"marginals".into()) {
+            let marginals_value = public_arguments.get::<IndexKey>(&"marginals".into())
+                .ok_or_else(|| Error::from("marginals: must be public"))?
+                .ref_jagged().map_err(prepend("marginals:"))?;
+            // the layout is deduplicated
+            if marginals_value.num_records() != marginals.iter().map(|marginal| marginal.len() as i64).collect::<Vec<i64>>() {
+                return Err("marginals: may not contain duplicate columns".into())
+            }
+        }
+        if shape.len() as i64 != data_property.num_columns()? {
+            return Err("categories: must be defined for every column of the data".into())
+        }
+        let num_cells = get_num_cells(&shape, &marginals)?;
+
+        // save a snapshot of the state when aggregating
+        data_property.aggregator = Some(AggregatorProperties::new(
+            proto::component::Variant::ContingencyTable(self.clone()),
+            properties, 1));
+
+        data_property.nature = Some(Nature::Continuous(NatureContinuous {
+            lower: Vector1DNull::Int(vec![Some(0)]),
+            upper: Vector1DNull::Int(vec![data_property.num_records]),
+        }));
+        data_property.data_type = DataType::Int;
+        data_property.num_records = Some(num_cells as i64);
+        data_property.num_columns = Some(1);
+        data_property.dimensionality = Some(1);
+        data_property.dataset_id = Some(node_id as i64);
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+impl Expandable for proto::ContingencyTable {
+    /// Add the categories of the data as an argument, so that the cells are ordered by category
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        if !properties.contains_key::<IndexKey>(&"categories".into()) {
+            let mut component = component.clone();
+            let id_categories = insert_categories(&mut expansion, properties, component.submission, maximum_id)?;
+            component.insert_argument(&"categories".into(), id_categories);
+            expansion.computation_graph.insert(component_id, component);
+        }
+
+        Ok(expansion)
+    }
+}
+
+impl Sensitivity for proto::ContingencyTable {
+    /// Each record is counted once in the full table and once in every marginal,
+    /// so the sensitivity is that of a histogram for every table.
+    fn compute_sensitivity(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        properties: &NodeProperties,
+        sensitivity_type: &SensitivitySpace,
+    ) -> Result<Value> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        data_property.assert_is_not_aggregated()?;
+
+        use proto::privacy_definition::Neighboring;
+        let neighboring_type = Neighboring::from_i32(privacy_definition.neighboring)
+            .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))?;
+
+        let (shape, marginals) = get_layout(properties)?;
+        let num_cells = get_num_cells(&shape, &marginals)?;
+        let num_tables = marginals.len() + 1;
+
+        // number of counts that a neighboring dataset may change by one
+        let num_changed = match neighboring_type {
+            // one cell of every table
+            Neighboring::AddRemove => num_tables,
+            // two cells of every table
+            Neighboring::Substitute => 2 * num_tables
+        };
+
+        Ok(match sensitivity_type {
+            SensitivitySpace::KNorm(1) => Array::from_shape_vec(
+                vec![num_cells, 1], vec![num_changed as Integer; num_cells])?.into_dyn().into(),
+            SensitivitySpace::KNorm(2) => Array::from_shape_vec(
+                vec![num_cells, 1], vec![(num_changed as f64).sqrt(); num_cells])?.into_dyn().into(),
+            SensitivitySpace::KNorm(_) => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into()),
+            _ => return Err("ContingencyTable sensitivity is only implemented for KNorm".into())
+        })
+    }
+}
+
+/// Insert a literal of the categories of the data into the expansion, and return its id.
+///
+/// # Arguments
+/// * `expansion` - Expansion to insert the literal into.
+/// * `properties` - Properties of the arguments, including the data.
+/// * `submission` - Submission id of the component being expanded.
+/// * `maximum_id` - Largest id in the graph.
+pub fn insert_categories(
+    expansion: &mut base::ComponentExpansion, properties: &NodeProperties, submission: u32, maximum_id: u32,
+) -> Result<u32> {
+    let categories = properties.get::<IndexKey>(&"data".into())
+        .ok_or("data: missing")?.array()?.categories()?;
+
+    let id_categories = maximum_id + 1;
+    let (patch_node, release) = get_literal(Value::Jagged(categories), submission)?;
+    expansion.computation_graph.insert(id_categories, patch_node);
+    expansion.properties.insert(id_categories, infer_property(&release.value, None, id_categories)?);
+    expansion.releases.insert(id_categories, release);
+    Ok(id_categories)
+}
+
+/// Check that the categories argument, if any, is a public set of categories of the data for each column.
+///
+/// # Arguments
+/// * `public_arguments` - Public arguments to a contingency table.
+/// * `properties` - Properties of the arguments to a contingency table.
+/// * `data_type` - Atomic type of the data.
+pub fn check_categories(
+    public_arguments: &IndexMap<IndexKey, &Value>, properties: &NodeProperties, data_type: &DataType,
+) -> Result<()> {
+    if properties.contains_key::<IndexKey>(&"categories".into()) {
+        let categories = public_arguments.get::<IndexKey>(&"categories".into())
+            .ok_or_else(|| Error::from("categories: must be public"))?
+            .ref_jagged().map_err(prepend("categories:"))?;
+        if &categories.data_type() != data_type {
+            return Err("categories: must be of the same atomic type as the data".into())
+        }
+        if categories.num_records() != categories.deduplicate()?.num_records() {
+            return Err("categories: may not contain duplicates".into())
+        }
+    }
+    Ok(())
+}
+
+/// Number of categories of each column, and the columns of each marginal.
+///
+/// Both are read from the categorical nature of the arguments, which is known for public literals.
+/// Categories default to the categories of the data, and marginals default to none.
+///
+/// # Arguments
+/// * `properties` - Properties of the arguments to a contingency table.
+pub fn get_layout(properties: &NodeProperties) -> Result<(Vec<usize>, Vec<Vec<usize>>)> {
+    let shape = get_categories(properties)?.num_records().into_iter()
+        .map(|num_categories| num_categories as usize)
+        .collect::<Vec<usize>>();
+    if shape.contains(&0) {
+        return Err("categories: every column must have at least one category".into())
+    }
+
+    let marginals = match get_literal_jagged(properties, "marginals")? {
+        Some(Jagged::Int(marginals)) => marginals.into_iter()
+            .map(|marginal| marginal.into_iter()
+                .map(|column| if column < 0 || column as usize >= shape.len() {
+                    Err(Error::from("marginals: column indices must be within the columns of the data"))
+                } else { Ok(column as usize) })
+                .collect::<Result<Vec<usize>>>())
+            .collect::<Result<Vec<Vec<usize>>>>()?,
+        Some(_) => return Err("marginals: must be integer column indices".into()),
+        None => Vec::new()
+    };
+    check_marginals(shape.len(), &marginals)?;
+
+    Ok((shape, marginals))
+}
+
+/// Categories of each column, defaulting to the categories of the data.
+///
+/// # Arguments
+/// * `properties` - Properties of the arguments to a contingency table.
+pub fn get_categories(properties: &NodeProperties) -> Result<Jagged> {
+    match get_literal_jagged(properties, "categories")? {
+        Some(categories) => Ok(categories),
+        None => properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.categories()
+    }
+}
+
+/// Value of a public jagged literal argument, as recorded in its categorical nature.
+fn get_literal_jagged(properties: &NodeProperties, name: &str) -> Result<Option<Jagged>> {
+    properties.get::<IndexKey>(&name.into())
+        .map(|property| match &property.jagged()
+            .map_err(|err| Error::from(format!("{}: {}", name, err)))?.nature {
+            Some(Nature::Categorical(nature)) => Ok(nature.categories.clone()),
+            _ => Err(Error::from(format!("{}: must be a public literal", name)))
+        })
+        .transpose()
+}
+
+fn check_marginals(num_columns: usize, marginals: &[Vec<usize>]) -> Result<()> {
+    for (i, marginal) in marginals.iter().enumerate() {
+        if marginal.is_empty() {
+            return Err("marginals: each marginal must contain at least one column".into())
+        }
+        if marginal.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("marginals: column indices of each marginal must be strictly increasing".into())
+        }
+        if marginal.len() == num_columns {
+            return Err("marginals: the full table is always released, and may not be a marginal".into())
+        }
+        if marginals[..i].contains(marginal) {
+            return Err("marginals: may not contain duplicates".into())
+        }
+    }
+    Ok(())
+}
+
+/// Number of cells in the table over the given columns.
+pub fn get_table_size(shape: &[usize], columns: &[usize]) -> Result<usize> {
+    columns.iter().try_fold(1usize, |size, column| size.checked_mul(shape[*column])
+        .ok_or_else(|| Error::from("contingency table is too large")))
+}
+
+/// Number of cells in the full table and every marginal.
+pub fn get_num_cells(shape: &[usize], marginals: &[Vec<usize>]) -> Result<usize> {
+    let all_columns = (0..shape.len()).collect::<Vec<usize>>();
+    std::iter::once(&all_columns).chain(marginals.iter())
+        .try_fold(0usize, |num_cells, columns| num_cells.checked_add(get_table_size(shape, columns)?)
+            .ok_or_else(|| Error::from("contingency table is too large")))
+}
+
+/// Key of the table over the given columns in a released dataframe of tables.
+pub fn get_table_key(columns: &[usize]) -> IndexKey {
+    IndexKey::Tuple(columns.iter().map(|column| IndexKey::from(*column as Integer)).collect())
+}
+
+/// Index of the cell of the marginal that each cell of the full table is summed into.
+///
+/// Cells are ordered by the categories of their columns, with the last column varying fastest.
+///
+/// # Arguments
+/// * `shape` - Number of categories of each column.
+/// * `marginal` - Columns of the marginal, in increasing order.
+pub fn get_marginal_indices(shape: &[usize], marginal: &[usize]) -> Result<Vec<usize>> {
+    let num_cells = get_table_size(shape, &(0..shape.len()).collect::<Vec<usize>>())?;
+
+    // the step in the marginal index taken when the category of each column of the full table increments
+    let mut strides = vec![0; shape.len()];
+    let mut stride = 1;
+    for column in marginal.iter().rev() {
+        strides[*column] = stride;
+        stride *= shape[*column];
+    }
+
+    Ok((0..num_cells).map(|mut cell| {
+        let mut index = 0;
+        for column in (0..shape.len()).rev() {
+            index += (cell % shape[column]) * strides[column];
+            cell /= shape[column];
+        }
+        index
+    }).collect())
+}
+
+#[cfg(test)]
+mod test_contingency_table {
+    use crate::components::contingency_table::{get_marginal_indices, get_num_cells};
+
+    #[test]
+    fn test_marginal_indices() {
+        // 2 x 3 table
+        let shape = [2, 3];
+        assert_eq!(get_marginal_indices(&shape, &[0]).unwrap(), vec![0, 0, 0, 1, 1, 1]);
+        assert_eq!(get_marginal_indices(&shape, &[1]).unwrap(), vec![0, 1, 2, 0, 1, 2]);
+        assert_eq!(get_num_cells(&shape, &[vec![0], vec![1]]).unwrap(), 6 + 2 + 3);
+    }
+}
@@ -0,0 +1,251 @@
+use ::itertools::izip;
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+
+use crate::{base, proto, Warnable};
+use crate::base::{DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties};
+use crate::components::{Accuracy, Mechanism, Sensitivity};
+use crate::components::{Component, Expandable};
+use crate::errors::*;
+use crate::utilities::{expand_mechanism, prepend};
+use crate::utilities::privacy::{concentrated_to_approximate_epsilon, gaussian_rdp_curve, get_delta, privacy_usage_check, spread_privacy_usage};
+
+impl Component for proto::DiscreteGaussianMechanism {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        _node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+
+        if privacy_definition.protect_elapsed_time {
+            return Err("Elapsed time protections are enabled. The discrete gaussian sampler does not run in constant time.".into())
+        }
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into());
+        }
+
+        let mut data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be integer".into());
+        }
+        let aggregator = data_property.aggregator.clone()
+            .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+        // sensitivity must be computable
+        aggregator.component.compute_sensitivity(
+            privacy_definition,
+            &aggregator.properties,
+            &SensitivitySpace::KNorm(2))?.array()?.cast_float()?;
+
+        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
+            .fold1(|l, r| l? + r?).ok_or("privacy_usage: must be defined")??;
+
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        if let Some(proto::privacy_usage::Distance::Approximate(_)) = privacy_usage.distance {
+            if get_delta(&privacy_usage)? == 0.0 {
+                return Err("delta: may not be zero".into())
+            }
+        }
+
+        data_property.releasable = true;
+        data_property.aggregator = None;
+
+        Ok(Warnable(data_property.into(), warnings))
+    }
+}
+
+impl Expandable for proto::DiscreteGaussianMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_mechanism(
+            &SensitivitySpace::KNorm(2),
+            privacy_definition,
+            self.privacy_usage.as_ref(),
+            component,
+            properties,
+            component_id,
+            maximum_id,
+        )
+    }
+}
+
+impl Mechanism for proto::DiscreteGaussianMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Some(release_usage.unwrap_or(&self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    #[allow(clippy::float_cmp)]
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        if data_property.sample_proportion.unwrap_or(1.) != 1. {
+            return Err("Rényi curves of the subsampled discrete gaussian mechanism are not implemented".into())
+        }
+        let group_size = (data_property.c_stability * privacy_definition.group_size) as f64;
+
+        // the discrete gaussian has the same Rényi divergences as the continuous gaussian on integer shifts
+        // Canonne, Kamath and Steinke (2020), Theorem 4
+        Some(release_usage.unwrap_or(&self.privacy_usage).iter()
+            .map(|usage| Ok(gaussian_rdp_curve(
+                get_discrete_gaussian_sigma(usage, 1.)? / group_size, orders)))
+            .collect::<Result<Vec<Vec<f64>>>>()).transpose()
+    }
+}
+
+
+impl Accuracy for proto::DiscreteGaussianMechanism {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        mut public_arguments: IndexMap<base::IndexKey, &Value>
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        // take max sensitivity of each column
+        let sensitivities: Vec<_> = public_arguments.remove(&IndexKey::from("sensitivity"))
+            .ok_or_else(|| Error::from("sensitivity: missing in accuracy"))?.clone()
+            .array()?.cast_float()?
+            .gencolumns().into_iter()
+            .map(|sensitivity_col| sensitivity_col.into_iter().copied().fold1(|l, r| l.max(r)).unwrap())
+            .collect();
+
+        let usages = spread_privacy_usage(&self.privacy_usage, sensitivities.len())?;
+
+        use proto::privacy_usage::{Distance, DistanceApproximate, DistanceConcentrated};
+
+        Some(izip!(sensitivities.into_iter(), accuracies.values.iter(), usages.iter()).map(|(sensitivity, accuracy, usage)| {
+            let sigma = accuracy.value / (2. * (2. / accuracy.alpha).ln()).sqrt();
+            let rho = (sensitivity / sigma).powi(2) / 2.;
+
+            Ok(proto::PrivacyUsage {
+                distance: Some(match usage.distance.as_ref().ok_or("distance must be defined")? {
+                    Distance::Approximate(DistanceApproximate { delta, .. }) =>
+                        Distance::Approximate(DistanceApproximate {
+                            epsilon: concentrated_to_approximate_epsilon(rho, *delta)?,
+                            delta: *delta,
+                        }),
+                    Distance::Concentrated(_) =>
+                        Distance::Concentrated(DistanceConcentrated { rho })
+                })
+            })
+        }).collect()).transpose()
+    }
+
+    fn privacy_usage_to_accuracy(
+        &self,
+        mut public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        // take max sensitivity of each column
+        let sensitivities: Vec<_> = public_arguments.remove(&IndexKey::from("sensitivity"))
+            .ok_or_else(|| Error::from("sensitivity: missing in accuracy"))?.clone()
+            .array()?.cast_float()?
+            .gencolumns().into_iter()
+            .map(|sensitivity_col| sensitivity_col.into_iter().copied().fold1(|l, r| l.max(r)).unwrap())
+            .collect();
+
+        let usages = spread_privacy_usage(&self.privacy_usage, sensitivities.len())?;
+
+        Some(sensitivities.into_iter().zip(usages.iter()).map(|(sensitivity, usage)| {
+            let sigma = get_discrete_gaussian_sigma(usage, sensitivity)?;
+
+            // the discrete gaussian is subgaussian, so P[|X| >= t] <= 2 exp(-t^2 / (2 sigma^2))
+            // Canonne, Kamath and Steinke (2020), Section 2.1
+            Ok(proto::Accuracy {
+                value: sigma * (2. * (2. / alpha).ln()).sqrt(),
+                alpha,
+            })
+        }).collect()).transpose()
+    }
+}
+
+/// Noise scale of the discrete gaussian mechanism for a privacy usage
+///
+/// A discrete gaussian with sigma = sensitivity / sqrt(2 rho) satisfies rho-zCDP.
+/// Approximate privacy usages are satisfied by the largest rho whose conversion to approximate differential privacy is within the usage.
+/// Canonne, Kamath and Steinke (2020), Theorem 4
+///
+/// # Arguments
+/// * `usage` - Either an approximate or zero-concentrated privacy usage.
+/// * `sensitivity` - Upper bound on the L2 sensitivity of the function you want to privatize.
+pub fn get_discrete_gaussian_sigma(usage: &proto::PrivacyUsage, sensitivity: f64) -> Result<f64> {
+    use proto::privacy_usage::{Distance, DistanceApproximate, DistanceConcentrated};
+
+    let rho = match usage.distance.as_ref().ok_or("distance must be defined")? {
+        Distance::Approximate(DistanceApproximate { epsilon, delta }) => {
+            if *delta <= 0. {
+                return Err("delta: must be positive".into())
+            }
+            // invert epsilon = rho + 2 sqrt(rho ln(1 / delta))
+            let log_inv_delta = (1. / delta).ln();
+            ((log_inv_delta + epsilon).sqrt() - log_inv_delta.sqrt()).powi(2)
+        }
+        Distance::Concentrated(DistanceConcentrated { rho }) => *rho
+    };
+
+    if rho <= 0. {
+        return Err("privacy usage must be positive".into())
+    }
+    Ok(sensitivity / (2. * rho).sqrt())
+}
+
+#[cfg(test)]
+mod test_discrete_gaussian {
+    use crate::components::discrete_gaussian_mechanism::get_discrete_gaussian_sigma;
+    use crate::proto;
+    use crate::utilities::privacy::concentrated_to_approximate_epsilon;
+
+    #[test]
+    fn test_discrete_gaussian_sigma() {
+        let usage = proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon: 1., delta: 1e-6
+            }))
+        };
+        let sigma = get_discrete_gaussian_sigma(&usage, 1.).unwrap();
+
+        // the implied rho converts back to the requested epsilon
+        let rho = 1. / (2. * sigma.powi(2));
+        assert!((concentrated_to_approximate_epsilon(rho, 1e-6).unwrap() - 1.).abs() < 1e-10);
+    }
+}
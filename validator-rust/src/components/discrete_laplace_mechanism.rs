@@ -0,0 +1,173 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+
+use crate::{base, proto, Warnable};
+use crate::base::{DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties};
+use crate::components::{Accuracy, Mechanism, Sensitivity};
+use crate::components::{Component, Expandable};
+use crate::errors::*;
+use crate::utilities::{expand_mechanism, prepend};
+use crate::utilities::privacy::{get_epsilon, get_pure_rdp_curves, privacy_usage_check, pure_rdp_curve, spread_privacy_usage};
+
+impl Component for proto::DiscreteLaplaceMechanism {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        _node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+
+        if privacy_definition.protect_elapsed_time {
+            return Err("Elapsed time protections are enabled. The discrete laplace sampler does not run in constant time.".into())
+        }
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into())
+        }
+
+        let mut data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be integer".into())
+        }
+
+        let aggregator = data_property.aggregator.clone()
+            .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+        // sensitivity must be computable
+        aggregator.component.compute_sensitivity(
+            privacy_definition,
+            &aggregator.properties,
+            &SensitivitySpace::KNorm(1))?;
+
+        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
+            .fold1(|l, r| l? + r?).ok_or("privacy_usage: must be defined")??;
+
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        data_property.releasable = true;
+        data_property.aggregator = None;
+
+        Ok(Warnable(data_property.into(), warnings))
+    }
+}
+
+
+impl Expandable for proto::DiscreteLaplaceMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_mechanism(
+            &SensitivitySpace::KNorm(1),
+            privacy_definition,
+            self.privacy_usage.as_ref(),
+            component,
+            properties,
+            component_id,
+            maximum_id
+        )
+    }
+}
+
+impl Mechanism for proto::DiscreteLaplaceMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Some(release_usage.unwrap_or(&self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or(&self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
+}
+
+
+impl Accuracy for proto::DiscreteLaplaceMechanism {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        mut public_arguments: IndexMap<base::IndexKey, &Value>
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        // take max sensitivity of each column
+        let sensitivities: Vec<_> = public_arguments.remove(&IndexKey::from("sensitivity"))
+            .ok_or_else(|| Error::from("sensitivity: missing in accuracy"))?.clone()
+            .array()?.cast_float()?
+            .gencolumns().into_iter()
+            .map(|sensitivity_col| sensitivity_col.into_iter().copied().fold1(|l, r| l.max(r)).unwrap())
+            .collect();
+
+        Ok(Some(sensitivities.into_iter().zip(accuracies.values.iter())
+            .map(|(sensitivity, accuracy)| proto::PrivacyUsage {
+                distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                    epsilon: (2. / accuracy.alpha).ln() * (sensitivity / accuracy.value),
+                    delta: 0.,
+                }))
+            })
+            .collect()))
+    }
+
+    fn privacy_usage_to_accuracy(
+        &self,
+        mut public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        // take max sensitivity of each column
+        let sensitivities: Vec<_> = public_arguments.remove(&IndexKey::from("sensitivity"))
+            .ok_or_else(|| Error::from("sensitivity: missing in accuracy"))?.clone()
+            .array()?.cast_float()?
+            .gencolumns().into_iter()
+            .map(|sensitivity_col| sensitivity_col.into_iter().copied().fold1(|l, r| l.max(r)).unwrap())
+            .collect();
+
+        let usages = spread_privacy_usage(&self.privacy_usage, sensitivities.len())?;
+        let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
+
+        // P[|X| >= k] = 2 exp(-k / scale) / (1 + exp(-1 / scale)) <= 2 exp(-k / scale)
+        Ok(Some(sensitivities.into_iter().zip(epsilon)
+            .map(|(sensitivity, epsilon)| proto::Accuracy {
+                value: ((2. / alpha).ln() * (sensitivity / epsilon)).ceil(),
+                alpha
+            })
+            .collect()))
+    }
+}
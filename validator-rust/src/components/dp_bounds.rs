@@ -0,0 +1,400 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+use ndarray::Axis;
+
+use crate::{base, Integer, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Mechanism, Report};
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::privacy::{get_pure_rdp_curves, privacy_usage_check, pure_rdp_curve, spread_privacy_usage};
+
+/// Number of powers of two spanned by the bin edges, beneath the magnitude of the coarse bounds.
+pub const NUM_SCALES: i32 = 32;
+
+/// L1 sensitivity of the histogram.
+///
+/// Bins are disjoint, so substituting a record changes at most two counts by one.
+pub const BOUNDS_SENSITIVITY: f64 = 2.;
+
+/// Proportion of records that may fall outside of bounds estimated during expansion.
+pub const DEFAULT_BOUNDS_ALPHA: f64 = 0.01;
+
+impl Component for proto::DpBounds {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into())
+        }
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated().map_err(prepend("data:"))?;
+        data_property.assert_is_not_empty().map_err(prepend("data:"))?;
+
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        if data_property.nullity {
+            return Err("data: may not contain nullity. Impute first.".into())
+        }
+        let num_columns = data_property.num_columns()?;
+
+        let lower = match public_arguments.get::<IndexKey>(&"lower".into()) {
+            Some(lower) => lower.ref_array()?.clone().vec_float(Some(num_columns)).map_err(prepend("lower:"))?,
+            None => if properties.contains_key::<IndexKey>(&"lower".into()) {
+                return Err("lower: must be public".into())
+            } else {
+                data_property.lower_float()
+                    .map_err(prepend("lower: must be supplied when the data is unbounded:"))?
+            }
+        };
+        let upper = match public_arguments.get::<IndexKey>(&"upper".into()) {
+            Some(upper) => upper.ref_array()?.clone().vec_float(Some(num_columns)).map_err(prepend("upper:"))?,
+            None => if properties.contains_key::<IndexKey>(&"upper".into()) {
+                return Err("upper: must be public".into())
+            } else {
+                data_property.upper_float()
+                    .map_err(prepend("upper: must be supplied when the data is unbounded:"))?
+            }
+        };
+        if lower.len() != num_columns as usize || upper.len() != num_columns as usize {
+            return Err("lower and upper: must have one bound per column of the data".into())
+        }
+        lower.iter().zip(upper.iter())
+            .try_for_each(|(lower, upper)| get_exponential_edges(*lower, *upper).map(|_| ()))?;
+
+        if self.alpha <= 0. || self.alpha >= 0.5 {
+            return Err("alpha: must be within (0, 0.5)".into())
+        }
+
+        let privacy_usage = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?.into_iter()
+            .map(Ok).fold1(|l, r| l? + r?).ok_or("privacy_usage: must be defined")??;
+
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        // each estimate lies within the coarse bounds of its column
+        let coarse_lower = lower.iter().cloned().fold(f64::INFINITY, f64::min);
+        let coarse_upper = upper.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        Ok(Warnable(ArrayProperties {
+            num_records: Some(num_columns),
+            num_columns: Some(2),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(coarse_lower); 2]),
+                upper: Vector1DNull::Float(vec![Some(coarse_upper); 2]),
+            })),
+            data_type: DataType::Float,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(2),
+            group_id: data_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+        }.into(), warnings))
+    }
+}
+
+impl Expandable for proto::DpBounds {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy definition must be defined")?;
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // update the privacy usage, with one usage per column
+        let mut updated_component = component.clone();
+        if let Some(proto::component::Variant::DpBounds(variant)) = &mut updated_component.variant {
+            variant.privacy_usage = spread_privacy_usage(&self.privacy_usage, data_property.num_columns()? as usize)?.iter()
+                .map(|usage| usage.actual_to_effective(
+                    data_property.sample_proportion.unwrap_or(1.),
+                    data_property.c_stability,
+                    privacy_definition))
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+        } else { return Err(Error::from("Variant must be defined")) }
+
+        if !properties.contains_key::<IndexKey>(&"lower".into()) {
+            maximum_id += 1;
+            let id_lower = maximum_id;
+            let (patch_node, release) = get_literal(Value::Array(data_property.lower()?), component.submission)?;
+            expansion.computation_graph.insert(id_lower, patch_node);
+            expansion.properties.insert(id_lower, infer_property(&release.value, None, id_lower)?);
+            expansion.releases.insert(id_lower, release);
+            updated_component.insert_argument(&"lower".into(), id_lower);
+        }
+
+        if !properties.contains_key::<IndexKey>(&"upper".into()) {
+            maximum_id += 1;
+            let id_upper = maximum_id;
+            let (patch_node, release) = get_literal(Value::Array(data_property.upper()?), component.submission)?;
+            expansion.computation_graph.insert(id_upper, patch_node);
+            expansion.properties.insert(id_upper, infer_property(&release.value, None, id_upper)?);
+            expansion.releases.insert(id_upper, release);
+            updated_component.insert_argument(&"upper".into(), id_upper);
+        }
+
+        expansion.computation_graph.insert(component_id, updated_component);
+
+        Ok(expansion)
+    }
+}
+
+impl Mechanism for proto::DpBounds {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Some(release_usage.unwrap_or(&self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or(&self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
+}
+
+impl Report for proto::DpBounds {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+        let num_columns = data_property.num_columns()?;
+
+        let coarse_lower = public_arguments.get::<IndexKey>(&"lower".into())
+            .map(|v| v.ref_array()?.clone().vec_float(Some(num_columns)))
+            .transpose()?;
+        let coarse_upper = public_arguments.get::<IndexKey>(&"upper".into())
+            .map(|v| v.ref_array()?.clone().vec_float(Some(num_columns)))
+            .transpose()?;
+
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+        let release = release.ref_array()?.ref_float()?;
+
+        (0..num_columns as usize).map(|column_number| {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            Ok(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPBounds".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: value_to_json(&release.index_axis(Axis(0), column_number).to_owned().into_dyn().into())?,
+                privacy_loss: privacy_usage_to_json(&privacy_usages[column_number]),
+                accuracy: None,
+                submission: component.submission,
+                node_id: Some(node_id),
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "Wilson et al. (2020)".to_string(),
+                    mechanism: "Geometric".to_string(),
+                    argument: serde_json::json!({
+                        "alpha": self.alpha,
+                        "constraint": {
+                            "lower": coarse_lower.as_ref().map(|lower| lower[column_number]),
+                            "upper": coarse_upper.as_ref().map(|upper| upper[column_number])
+                        }
+                    }),
+                },
+            })
+        }).collect::<Result<Vec<JSONRelease>>>().map(Some)
+    }
+}
+
+/// Bin edges, spaced exponentially within the coarse bounds.
+///
+/// Edges are signed powers of two, from the magnitude of the coarse bounds down by `NUM_SCALES` powers,
+/// as well as zero and the coarse bounds themselves.
+/// Bounds on the data are typically only known to within an order of magnitude,
+/// so relative precision is retained both near zero and near the coarse bounds.
+///
+/// # Arguments
+/// * `lower` - Coarse public lower bound on the data.
+/// * `upper` - Coarse public upper bound on the data.
+pub fn get_exponential_edges(lower: f64, upper: f64) -> Result<Vec<f64>> {
+    if !lower.is_finite() || !upper.is_finite() {
+        return Err("lower and upper must be finite".into())
+    }
+    if lower >= upper {
+        return Err("lower must be less than upper".into())
+    }
+
+    let maximum_exponent = lower.abs().max(upper.abs()).log2().ceil() as i32;
+
+    let mut edges = vec![lower, upper];
+    if lower < 0. && upper > 0. {
+        edges.push(0.);
+    }
+    (maximum_exponent - NUM_SCALES..=maximum_exponent)
+        .map(|exponent| 2f64.powi(exponent))
+        .flat_map(|magnitude| vec![-magnitude, magnitude])
+        .filter(|edge| lower < *edge && *edge < upper)
+        .for_each(|edge| edges.push(edge));
+
+    edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    edges.dedup();
+    Ok(edges)
+}
+
+/// Chain DPBounds into Clamp, and re-insert the component with the clamped data in place of the data.
+///
+/// The component is expanded again once the bounds are known,
+/// so the statistic is computed under the estimated bounds instead of the coarse bounds of the data.
+///
+/// # Arguments
+/// * `component` - Component to clamp the "data" argument of, with the remaining privacy usage already set.
+/// * `bounds_usage` - Privacy usage spent estimating the bounds.
+/// * `component_id` - Id of the component in the graph.
+/// * `maximum_id` - Largest id in the graph.
+pub fn expand_estimated_bounds(
+    mut component: proto::Component,
+    bounds_usage: Vec<proto::PrivacyUsage>,
+    component_id: u32,
+    mut maximum_id: u32,
+) -> Result<base::ComponentExpansion> {
+    let mut expansion = base::ComponentExpansion::default();
+
+    let id_data = *component.arguments().get::<IndexKey>(&"data".into())
+        .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+    // dp bounds
+    maximum_id += 1;
+    let id_bounds = maximum_id;
+    expansion.computation_graph.insert(id_bounds, proto::Component {
+        arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_data])),
+        variant: Some(proto::component::Variant::DpBounds(proto::DpBounds {
+            alpha: DEFAULT_BOUNDS_ALPHA,
+            privacy_usage: bounds_usage,
+        })),
+        omit: true,
+        submission: component.submission,
+    });
+    expansion.traversal.push(id_bounds);
+
+    // index the lower and upper bound
+    let mut insert_bound = |index: Integer, maximum_id: &mut u32| -> Result<u32> {
+        *maximum_id += 1;
+        let id_index = *maximum_id;
+        let (patch_node, release) = get_literal(index.into(), component.submission)?;
+        expansion.computation_graph.insert(id_index, patch_node);
+        expansion.properties.insert(id_index, infer_property(&release.value, None, id_index)?);
+        expansion.releases.insert(id_index, release);
+
+        *maximum_id += 1;
+        let id_bound = *maximum_id;
+        expansion.computation_graph.insert(id_bound, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_bounds,
+                "indices".into() => id_index
+            ])),
+            variant: Some(proto::component::Variant::Index(proto::Index {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_bound);
+        Ok(id_bound)
+    };
+    let id_lower = insert_bound(0, &mut maximum_id)?;
+    let id_upper = insert_bound(1, &mut maximum_id)?;
+
+    // clamp
+    maximum_id += 1;
+    let id_clamp = maximum_id;
+    expansion.computation_graph.insert(id_clamp, proto::Component {
+        arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+            "data".into() => id_data,
+            "lower".into() => id_lower,
+            "upper".into() => id_upper
+        ])),
+        variant: Some(proto::component::Variant::Clamp(proto::Clamp {})),
+        omit: true,
+        submission: component.submission,
+    });
+    expansion.traversal.push(id_clamp);
+
+    component.insert_argument(&"data".into(), id_clamp);
+    expansion.computation_graph.insert(component_id, component);
+
+    Ok(expansion)
+}
+
+#[cfg(test)]
+mod test_dp_bounds {
+    use crate::components::dp_bounds::get_exponential_edges;
+
+    #[test]
+    fn test_exponential_edges() {
+        let edges = get_exponential_edges(-3., 1000.).unwrap();
+        assert_eq!(edges[0], -3.);
+        assert_eq!(edges[edges.len() - 1], 1000.);
+        assert!(edges.contains(&0.));
+        assert!(edges.contains(&-2.));
+        assert!(edges.contains(&512.));
+        assert!(edges.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert!(get_exponential_edges(1., 1.).is_err());
+    }
+}
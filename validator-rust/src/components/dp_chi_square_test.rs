@@ -0,0 +1,166 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto};
+use crate::base::{DataType, IndexKey, NodeProperties, Value};
+use crate::components::{Expandable, Report};
+use crate::components::contingency_table::insert_categories;
+use crate::errors::*;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::{expand_consistent_counts, get_mechanism, prepend};
+
+impl Expandable for proto::DpChiSquareTest {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+
+        let argument_ids = component.arguments();
+        let data_id = *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        // the simulated counts receive noise from the same distribution as the released counts
+        let mechanism = get_mechanism(&DataType::Int, &self.mechanism, privacy_definition)?;
+        let noise = match mechanism.as_str() {
+            "laplace" => "Laplace",
+            "gaussian" | "analyticgaussian" => "Gaussian",
+            // the noise of the simple geometric mechanism is only censored to the range of the counts
+            "discretelaplace" | "simplegeometric" => "DiscreteLaplace",
+            "discretegaussian" => "DiscreteGaussian",
+            _ => bail!("Unexpected invalid token {:?}", self.mechanism.as_str()),
+        };
+
+        let mut test_arguments = IndexMap::new();
+        let id_aggregate = match self.test.to_lowercase().as_str() {
+            "goodnessoffit" => {
+                if data_property.num_columns()? != 1 {
+                    return Err("data: a goodness-of-fit test must be over one column".into())
+                }
+                argument_ids.get::<IndexKey>(&"proportions".into())
+                    .map(|v| test_arguments.insert("proportions".into(), *v));
+
+                // counts of each category
+                maximum_id += 1;
+                let id_histogram = maximum_id;
+                expansion.computation_graph.insert(id_histogram, proto::Component {
+                    arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => data_id])),
+                    variant: Some(proto::component::Variant::Histogram(proto::Histogram {})),
+                    omit: true,
+                    submission: component.submission,
+                });
+                expansion.traversal.push(id_histogram);
+                id_histogram
+            }
+            "independence" => {
+                if data_property.num_columns()? != 2 {
+                    return Err("data: an independence test must be over two columns".into())
+                }
+                if argument_ids.contains_key::<IndexKey>(&"proportions".into()) {
+                    return Err("proportions: may only be set for a goodness-of-fit test".into())
+                }
+                maximum_id = insert_categories(&mut expansion, properties, component.submission, maximum_id)?;
+                let id_categories = maximum_id;
+                test_arguments.insert("categories".into(), id_categories);
+
+                // counts of each cell of the two-way table
+                maximum_id += 1;
+                let id_table = maximum_id;
+                expansion.computation_graph.insert(id_table, proto::Component {
+                    arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                        "data".into() => data_id,
+                        "categories".into() => id_categories])),
+                    variant: Some(proto::component::Variant::ContingencyTable(proto::ContingencyTable {})),
+                    omit: true,
+                    submission: component.submission,
+                });
+                expansion.traversal.push(id_table);
+                id_table
+            }
+            _ => bail!("Unexpected invalid token {:?}", self.test.as_str()),
+        };
+
+        // scale of the noise of the counts, which is the same for every count
+        maximum_id += 1;
+        let id_noise_scale = maximum_id;
+        expansion.computation_graph.insert(id_noise_scale, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_aggregate])),
+            variant: Some(proto::component::Variant::NoiseScale(proto::NoiseScale {
+                mechanism: mechanism.clone(),
+                privacy_usage: self.privacy_usage.clone(),
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_noise_scale);
+
+        test_arguments.insert("noise_scale".into(), id_noise_scale);
+
+        // noisy counts, then the test statistic and p-value
+        expand_consistent_counts(
+            &mut expansion, privacy_definition, component, component_id, maximum_id, id_aggregate,
+            data_property.num_records, &mechanism, &self.privacy_usage,
+            proto::component::Variant::ChiSquareTest(proto::ChiSquareTest {
+                test: self.test.clone(),
+                noise: noise.to_string(),
+                num_simulations: self.num_simulations,
+            }),
+            test_arguments)?;
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpChiSquareTest {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let release = release.ref_dataframe()?;
+        let get_column = |name: &str| release.get::<IndexKey>(&name.into())
+            .ok_or_else(|| Error::from(format!("{}: missing from release", name)))
+            .and_then(value_to_json);
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPChiSquareTest".to_string(),
+            variables: serde_json::json!(variable_names.cloned()
+                .unwrap_or_else(Vec::new).iter()
+                .map(|v| v.to_string()).collect::<Vec<String>>()),
+            release_info: serde_json::json!({
+                "statistic": get_column("statistic")?,
+                "p_value": get_column("p_value")?
+            }),
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "Monte Carlo chi-square test".to_string(),
+                cite: "Gaboardi, Lim, Rogers and Vadhan (2016)".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "test": self.test,
+                    "num_simulations": self.num_simulations
+                }),
+            },
+        }]))
+    }
+}
@@ -0,0 +1,106 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto};
+use crate::base::{IndexKey, NodeProperties, Value};
+use crate::components::{Expandable, Report};
+use crate::components::contingency_table::insert_categories;
+use crate::errors::*;
+use crate::utilities::{expand_consistent_counts, prepend};
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+
+impl Expandable for proto::DpContingencyTable {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+
+        let argument_ids = component.arguments();
+        let num_records = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.num_records;
+
+        // the categories are needed both to count and to infer, so they are fixed up front
+        let id_categories = match argument_ids.get::<IndexKey>(&"categories".into()) {
+            Some(id_categories) => *id_categories,
+            None => {
+                maximum_id = insert_categories(&mut expansion, properties, component.submission, maximum_id)?;
+                maximum_id
+            }
+        };
+        let id_marginals = argument_ids.get::<IndexKey>(&"marginals".into()).cloned();
+
+        // counts of the full table, followed by each marginal
+        maximum_id += 1;
+        let id_table = maximum_id;
+        let mut table_arguments = indexmap![
+            "data".into() => *argument_ids.get::<IndexKey>(&"data".into())
+                .ok_or_else(|| Error::from("data must be provided as an argument"))?,
+            "categories".into() => id_categories];
+        id_marginals.map(|v| table_arguments.insert("marginals".into(), v));
+
+        expansion.computation_graph.insert(id_table, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(table_arguments)),
+            variant: Some(proto::component::Variant::ContingencyTable(proto::ContingencyTable {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_table);
+
+        // noise, with the same variance on every cell of every table, then consistent tables
+        let mut consistent_arguments = indexmap!["categories".into() => id_categories];
+        id_marginals.map(|v| consistent_arguments.insert("marginals".into(), v));
+
+        expand_consistent_counts(
+            &mut expansion, privacy_definition, component, component_id, maximum_id, id_table, num_records,
+            &self.mechanism, &self.privacy_usage,
+            proto::component::Variant::ConsistentMarginals(proto::ConsistentMarginals {}),
+            consistent_arguments)?;
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpContingencyTable {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let release_info = release.ref_dataframe()?.iter()
+            .map(|(key, table)| Ok((key.to_string(), value_to_json(table)?)))
+            .collect::<Result<serde_json::Map<String, serde_json::Value>>>()?;
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPContingencyTable".to_string(),
+            variables: serde_json::json!(variable_names.cloned()
+                .unwrap_or_else(Vec::new).iter()
+                .map(|v| v.to_string()).collect::<Vec<String>>()),
+            release_info: serde_json::Value::Object(release_info),
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "consistent marginals".to_string(),
+                cite: "Barak et al. (2007)".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({}),
+            },
+        }]))
+    }
+}
@@ -2,10 +2,10 @@ use indexmap::map::IndexMap;
 use ndarray::arr0;
 
 use crate::{base, Integer, proto};
-use crate::base::{IndexKey, NodeProperties, Value, ValueProperties};
+use crate::base::{DataType, IndexKey, NodeProperties, Value, ValueProperties};
 use crate::components::{Expandable, Report};
 use crate::errors::*;
-use crate::utilities::{get_literal};
+use crate::utilities::{get_literal, get_mechanism};
 use crate::utilities::inference::infer_property;
 use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
 
@@ -24,10 +24,8 @@ impl Expandable for proto::DpCount {
         let privacy_definition = privacy_definition.as_ref()
             .ok_or_else(|| Error::from("privacy_definition must be known"))?;
 
-        let mechanism = if self.mechanism.to_lowercase().as_str() == "automatic" {
-            if privacy_definition.protect_floating_point
-            { "snapping" } else { "laplace" }.to_string()
-        } else { self.mechanism.to_lowercase() };
+        // counts are integers, so the automatic choice is the same as for integer sums
+        let mechanism = get_mechanism(&DataType::Int, &self.mechanism, privacy_definition)?;
 
         // count
         maximum_id += 1;
@@ -121,6 +119,12 @@ impl Expandable for proto::DpCount {
                     privacy_usage: self.privacy_usage.clone(),
                     analytic: true
                 }),
+                "discretegaussian" => proto::component::Variant::DiscreteGaussianMechanism(proto::DiscreteGaussianMechanism {
+                    privacy_usage: self.privacy_usage.clone()
+                }),
+                "discretelaplace" => proto::component::Variant::DiscreteLaplaceMechanism(proto::DiscreteLaplaceMechanism {
+                    privacy_usage: self.privacy_usage.clone()
+                }),
                 "snapping" => {
                     argument_ids.get::<IndexKey>(&"lower".into())
                         .map(|lower| arguments.insert("lower".into(), *lower));
@@ -166,7 +170,7 @@ impl Report for proto::DpCount {
             privacy_loss: privacy_usage_to_json(&self.privacy_usage[0].clone()),
             accuracy: None,
             submission: component.submission,
-            node_id,
+            node_id: Some(node_id),
             postprocess: false,
             algorithm_info: AlgorithmInfo {
                 name: "".to_string(),
@@ -179,3 +183,40 @@ impl Report for proto::DpCount {
         }]))
     }
 }
+
+#[cfg(test)]
+mod test_dp_count {
+    use ndarray::arr1;
+
+    use crate::bindings::Analysis;
+    use crate::proto;
+    use crate::utilities::propagate_properties;
+
+    fn expanded_mechanism(protect_floating_point: bool, protect_elapsed_time: bool) -> proto::component::Variant {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = protect_floating_point;
+        analysis.privacy_definition.protect_elapsed_time = protect_elapsed_time;
+        let data = analysis.literal().value(arr1(&[1, 2, 3]).into_dyn().into()).value_public(true).build();
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        let count = analysis.dp_count(data, lower, vec![proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon: 1., delta: 0.
+            }))
+        }]).build();
+
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, false).unwrap();
+        computation_graph[&count].variant.clone().unwrap()
+    }
+
+    #[test]
+    fn test_default_mechanism() {
+        assert!(matches!(expanded_mechanism(true, false), proto::component::Variant::DiscreteLaplaceMechanism(_)));
+        assert!(matches!(expanded_mechanism(false, false), proto::component::Variant::SimpleGeometricMechanism(_)));
+        // the discrete laplace sampler does not run in constant time
+        assert!(matches!(expanded_mechanism(true, true), proto::component::Variant::SimpleGeometricMechanism(_)));
+    }
+}
@@ -0,0 +1,454 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Accuracy, Component, Expandable, Mechanism, Report};
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::privacy::{get_epsilon, get_pure_rdp_curves, privacy_usage_check, pure_rdp_curve, spread_privacy_usage};
+
+/// Largest number of levels in the sketch.
+///
+/// The level of a value is the number of trailing zeros in 32 bits of its hash.
+pub const MAX_NUM_LEVELS: u32 = 32;
+
+impl Component for proto::DpCountDistinct {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into())
+        }
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated().map_err(prepend("data:"))?;
+
+        if data_property.data_type == DataType::Float || data_property.data_type == DataType::Unknown {
+            return Err("data: atomic type must be integer, string or boolean".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must have exactly one column".into())
+        }
+
+        check_sketch(self.num_buckets, self.num_levels)?;
+
+        if let Some(sensitivity) = public_arguments.get::<IndexKey>(&"sensitivity".into()) {
+            let sensitivity = sensitivity.ref_array()?.first_float().map_err(prepend("sensitivity:"))?;
+            if sensitivity < get_sensitivity(privacy_definition)? {
+                return Err("sensitivity: may not be less than the sensitivity of the sketch".into())
+            }
+        } else if properties.contains_key::<IndexKey>(&"sensitivity".into()) {
+            return Err("sensitivity: must be public".into())
+        }
+
+        if let Some(upper) = public_arguments.get::<IndexKey>(&"upper".into()) {
+            if upper.ref_array()?.first_float().map_err(prepend("upper:"))? < 1. {
+                return Err("upper: must be at least one".into())
+            }
+        } else if properties.contains_key::<IndexKey>(&"upper".into()) {
+            return Err("upper: must be public".into())
+        }
+
+        if self.privacy_usage.len() != 1 {
+            return Err("privacy_usage: must be of length one".into())
+        }
+
+        let warnings = privacy_usage_check(
+            &self.privacy_usage[0],
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        Ok(Warnable(ArrayProperties {
+            num_records: Some(1),
+            num_columns: Some(1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(0.)]),
+                upper: Vector1DNull::Float(vec![None]),
+            })),
+            data_type: DataType::Float,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(0),
+            group_id: data_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+        }.into(), warnings))
+    }
+}
+
+impl Expandable for proto::DpCountDistinct {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy definition must be defined")?;
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // update the privacy usage
+        let mut updated_component = component.clone();
+        if let Some(proto::component::Variant::DpCountDistinct(variant)) = &mut updated_component.variant {
+            variant.privacy_usage = self.privacy_usage.iter()
+                .map(|usage| usage.actual_to_effective(
+                    data_property.sample_proportion.unwrap_or(1.),
+                    data_property.c_stability,
+                    privacy_definition))
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+        } else { return Err(Error::from("Variant must be defined")) }
+
+        if !properties.contains_key::<IndexKey>(&"sensitivity".into()) {
+            maximum_id += 1;
+            let id_sensitivity = maximum_id;
+            let (patch_node, release) = get_literal(
+                get_sensitivity(privacy_definition)?.into(), component.submission)?;
+            expansion.computation_graph.insert(id_sensitivity, patch_node);
+            expansion.properties.insert(id_sensitivity, infer_property(&release.value, None, id_sensitivity)?);
+            expansion.releases.insert(id_sensitivity, release);
+            updated_component.insert_argument(&"sensitivity".into(), id_sensitivity);
+        }
+
+        // there may be at most as many distinct values as records
+        if !properties.contains_key::<IndexKey>(&"upper".into()) {
+            if let Some(num_records) = data_property.num_records {
+                maximum_id += 1;
+                let id_upper = maximum_id;
+                let (patch_node, release) = get_literal(
+                    (num_records.max(1) as f64).into(), component.submission)?;
+                expansion.computation_graph.insert(id_upper, patch_node);
+                expansion.properties.insert(id_upper, infer_property(&release.value, None, id_upper)?);
+                expansion.releases.insert(id_upper, release);
+                updated_component.insert_argument(&"upper".into(), id_upper);
+            }
+        }
+
+        expansion.computation_graph.insert(component_id, updated_component);
+
+        Ok(expansion)
+    }
+}
+
+impl Mechanism for proto::DpCountDistinct {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
+}
+
+impl Accuracy for proto::DpCountDistinct {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let sensitivity = get_sensitivity_argument(&public_arguments)?;
+        let upper = get_upper_argument(&public_arguments)?;
+
+        let epsilon = accuracies.values.iter()
+            .map(|accuracy| {
+                let scale = get_accuracy_scale(self.num_buckets, self.num_levels, accuracy.alpha)?;
+                let occupancy_accuracy = get_occupancy_accuracy(self.num_buckets, accuracy.value, upper)?;
+                // occupancy_accuracy = scale / (1 - 2p), where p = 1 / (1 + exp(epsilon / sensitivity))
+                if occupancy_accuracy <= scale {
+                    return Err(Error::from(format!(
+                        "accuracy: value must be greater than {}, the accuracy without noise",
+                        get_count_accuracy(self.num_buckets, scale, upper)?)))
+                }
+                let flip_probability = (1. - scale / occupancy_accuracy) / 2.;
+                Ok(sensitivity * ((1. - flip_probability) / flip_probability).ln())
+            })
+            .collect::<Result<Vec<f64>>>()?.into_iter()
+            .fold(0., f64::max);
+
+        Ok(Some(vec![proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon,
+                delta: 0.,
+            }))
+        }]))
+    }
+
+    /// With probability 1 - alpha, the estimated number of distinct values is within the accuracy.
+    ///
+    /// The accuracy of the debiased occupancy of every level, in buckets, is propagated to a count
+    /// through the upper bound on the number of distinct values.
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        let sensitivity = get_sensitivity_argument(&public_arguments)?;
+        let upper = get_upper_argument(&public_arguments)?;
+
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+        let flip_probability = get_flip_probability(epsilon, sensitivity);
+
+        let occupancy_accuracy = get_accuracy_scale(self.num_buckets, self.num_levels, alpha)? / (1. - 2. * flip_probability);
+
+        Ok(Some(vec![proto::Accuracy {
+            value: get_count_accuracy(self.num_buckets, occupancy_accuracy, upper)?,
+            alpha,
+        }]))
+    }
+}
+
+impl Report for proto::DpCountDistinct {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let variables = match variable_names {
+            Some(names) => names.iter().map(|name| name.to_string()).collect(),
+            None => vec!["[Unknown]".to_string()]
+        };
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPCountDistinct".to_string(),
+            variables: serde_json::json!(variables),
+            release_info: value_to_json(release)?,
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "Flajolet and Martin (1985)".to_string(),
+                mechanism: "RandomizedResponse".to_string(),
+                argument: serde_json::json!({
+                    "num_buckets": self.num_buckets,
+                    "num_levels": self.num_levels,
+                }),
+            },
+        }]))
+    }
+}
+
+fn check_sketch(num_buckets: u32, num_levels: u32) -> Result<()> {
+    if num_buckets < 2 {
+        return Err("num_buckets: must be at least two".into())
+    }
+    if num_levels == 0 || num_levels > MAX_NUM_LEVELS {
+        return Err(format!("num_levels: must be within [1, {}]", MAX_NUM_LEVELS).into())
+    }
+    Ok(())
+}
+
+fn get_sensitivity_argument(public_arguments: &IndexMap<base::IndexKey, &Value>) -> Result<f64> {
+    public_arguments.get::<IndexKey>(&"sensitivity".into())
+        .ok_or_else(|| Error::from("sensitivity: missing in accuracy"))?
+        .ref_array()?.first_float()
+}
+
+fn get_upper_argument(public_arguments: &IndexMap<base::IndexKey, &Value>) -> Result<f64> {
+    public_arguments.get::<IndexKey>(&"upper".into())
+        .ok_or_else(|| Error::from("upper: must be supplied when the number of records is unknown, to bound the accuracy"))?
+        .ref_array()?.first_float()
+}
+
+/// Number of bits of the sketch that may differ between neighboring datasets.
+///
+/// Each distinct value sets exactly one bit, so adding or removing a record sets or clears at most one bit,
+/// and substituting a record may additionally set another.
+///
+/// # Arguments
+/// * `privacy_definition` - Definition of privacy, including the neighboring relation.
+pub fn get_sensitivity(privacy_definition: &proto::PrivacyDefinition) -> Result<f64> {
+    use proto::privacy_definition::Neighboring;
+    let neighboring_type = Neighboring::from_i32(privacy_definition.neighboring)
+        .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))?;
+
+    Ok(match neighboring_type {
+        Neighboring::AddRemove => 1.,
+        Neighboring::Substitute => 2.
+    })
+}
+
+/// Probability that randomized response flips each bit of the sketch.
+///
+/// Each bit is kept with probability exp(epsilon / sensitivity) times the probability it is flipped,
+/// so the release of the sketch is epsilon-DP.
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - Number of bits that may differ between neighboring datasets.
+pub fn get_flip_probability(epsilon: f64, sensitivity: f64) -> f64 {
+    1. / (1. + (epsilon / sensitivity).exp())
+}
+
+/// Accuracy of the debiased occupancy of every level, scaled by the debiasing factor 1 - 2p.
+///
+/// Each debiased bit is bounded within a range of width 1 / (1 - 2p),
+/// so by Hoeffding's inequality and a union bound over the levels,
+/// the debiased occupancy of every level is within sqrt(m ln(2L / alpha) / 2) / (1 - 2p) of its expectation.
+///
+/// # Arguments
+/// * `num_buckets` - Number of buckets m in each level.
+/// * `num_levels` - Number of levels L.
+/// * `alpha` - Probability that the bound does not hold.
+pub fn get_accuracy_scale(num_buckets: u32, num_levels: u32, alpha: f64) -> Result<f64> {
+    check_sketch(num_buckets, num_levels)?;
+    if alpha <= 0. || alpha >= 1. {
+        return Err("alpha: must be within (0, 1)".into())
+    }
+    Ok((num_buckets as f64 * (2. * num_levels as f64 / alpha).ln() / 2.).sqrt())
+}
+
+/// Accuracy of the estimated number of distinct values, from the accuracy of the debiased occupancy of every level.
+///
+/// The count is read from the first level j whose debiased occupancy b is at most m / 2, as 2^(j + 1) (-m ln(1 - b / m)).
+/// Both b and its expectation are at most m / 2 + d, where the slope of -m ln(1 - b / m) is at most 1 / (1/2 - d / m).
+/// Level j is only read when level j - 1 is more than half full,
+/// so level j - 1 holds more than -m ln(1/2 + d / m) of its expected N 2^-j values,
+/// and the scale 2^(j + 1) is at most max(2, 2N / (m ln(2m / (m + 2d)))).
+///
+/// # Arguments
+/// * `num_buckets` - Number of buckets m in each level.
+/// * `occupancy_accuracy` - Accuracy d of the debiased occupancy of every level.
+/// * `upper` - Upper bound N on the number of distinct values.
+pub fn get_count_accuracy(num_buckets: u32, occupancy_accuracy: f64, upper: f64) -> Result<f64> {
+    let num_buckets = num_buckets as f64;
+    if occupancy_accuracy >= num_buckets / 2. {
+        return Err("accuracy: the occupancy of a level is not resolved within half of the buckets. Increase the privacy usage or the number of buckets".into())
+    }
+    let level_scale = (2. * upper / (num_buckets * (2. * num_buckets / (num_buckets + 2. * occupancy_accuracy)).ln())).max(2.);
+    Ok(level_scale * occupancy_accuracy / (0.5 - occupancy_accuracy / num_buckets))
+}
+
+/// Accuracy of the debiased occupancy of every level that bounds the estimated count within `count_accuracy`.
+///
+/// Inverts `get_count_accuracy`, which is increasing in the occupancy accuracy, by bisection.
+///
+/// # Arguments
+/// * `num_buckets` - Number of buckets m in each level.
+/// * `count_accuracy` - Desired accuracy of the estimated number of distinct values.
+/// * `upper` - Upper bound N on the number of distinct values.
+pub fn get_occupancy_accuracy(num_buckets: u32, count_accuracy: f64, upper: f64) -> Result<f64> {
+    let (mut lower_accuracy, mut upper_accuracy) = (0., num_buckets as f64 / 2.);
+    for _ in 0..100 {
+        let midpoint = (lower_accuracy + upper_accuracy) / 2.;
+        if get_count_accuracy(num_buckets, midpoint, upper)? < count_accuracy {
+            lower_accuracy = midpoint
+        } else {
+            upper_accuracy = midpoint
+        }
+    }
+    Ok(lower_accuracy)
+}
+
+#[cfg(test)]
+mod test_dp_count_distinct {
+    use crate::base::Value;
+    use crate::components::Accuracy;
+    use crate::components::dp_count_distinct::{get_accuracy_scale, get_count_accuracy, get_flip_probability, get_occupancy_accuracy};
+    use crate::proto;
+    use crate::utilities::privacy::get_epsilon;
+
+    #[test]
+    fn test_accuracy() {
+        let flip_probability = get_flip_probability(1., 1.);
+        assert!((flip_probability - 1. / (1. + 1f64.exp())).abs() < 1e-12);
+        assert!(get_flip_probability(2., 1.) < flip_probability);
+
+        let scale = get_accuracy_scale(1024, 32, 0.05).unwrap();
+        assert!(scale > 0.);
+        assert!(get_accuracy_scale(1024, 32, 0.01).unwrap() > scale);
+        assert!(get_accuracy_scale(1, 32, 0.05).is_err());
+        assert!(get_accuracy_scale(1024, 33, 0.05).is_err());
+    }
+
+    #[test]
+    fn test_count_accuracy() {
+        let accuracy = get_count_accuracy(1024, 50., 1e6).unwrap();
+        // the count accuracy is larger for more distinct values, and for less accurate occupancies
+        assert!(get_count_accuracy(1024, 50., 1e7).unwrap() > accuracy);
+        assert!(get_count_accuracy(1024, 100., 1e6).unwrap() > accuracy);
+        assert!(get_count_accuracy(1024, 512., 1e6).is_err());
+
+        let occupancy_accuracy = get_occupancy_accuracy(1024, accuracy, 1e6).unwrap();
+        assert!((occupancy_accuracy - 50.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_accuracy_round_trip() {
+        let component = proto::DpCountDistinct {
+            num_buckets: 1024,
+            num_levels: 32,
+            privacy_usage: vec![proto::PrivacyUsage {
+                distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                    epsilon: 1., delta: 0.
+                }))
+            }],
+        };
+        let sensitivity: Value = 1.0.into();
+        let upper: Value = 1e6.into();
+        let public_arguments = || indexmap!["sensitivity".into() => &sensitivity, "upper".into() => &upper];
+
+        let accuracies = component.privacy_usage_to_accuracy(public_arguments(), 0.05).unwrap().unwrap();
+        let usages = component.accuracy_to_privacy_usage(&proto::Accuracies { values: accuracies }, public_arguments())
+            .unwrap().unwrap();
+        assert!((get_epsilon(&usages[0]).unwrap() - 1.).abs() < 1e-6);
+    }
+}
@@ -194,7 +194,7 @@ impl Report for proto::DpCovariance {
             privacy_loss: serde_json::json![privacy_usage],
             accuracy: None,
             submission: component.submission,
-            node_id,
+            node_id: Some(node_id),
             postprocess: false,
             algorithm_info: AlgorithmInfo {
                 name: "".to_string(),
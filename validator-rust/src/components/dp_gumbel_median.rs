@@ -80,7 +80,7 @@ impl Expandable for proto::DpGumbelMedian {
             variant.privacy_usage = vec![self.privacy_usage[0].actual_to_effective(
                 data_property.sample_proportion.unwrap_or(1.),
                 data_property.c_stability,
-                privacy_definition.group_size)?];
+                privacy_definition)?];
             // this case should never happen
         } else { return Err(Error::from("Variant must be defined")) }
         expansion.computation_graph.insert(component_id, updated_component);
@@ -127,7 +127,7 @@ impl Report for proto::DpGumbelMedian {
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
                 accuracy: None,
                 submission: component.submission,
-                node_id,
+                node_id: Some(node_id),
                 postprocess: false,
                 algorithm_info: AlgorithmInfo {
                     name: "".to_string(),
@@ -0,0 +1,104 @@
+use std::convert::TryFrom;
+
+use indexmap::map::IndexMap;
+
+use crate::{base, proto};
+use crate::base::{IndexKey, NodeProperties, Value};
+use crate::components::{Expandable, Report};
+use crate::errors::*;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::{expand_consistent_counts, prepend};
+
+impl Expandable for proto::DpHierarchicalHistogram {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+
+        let argument_ids = component.arguments();
+        let num_records = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.num_records;
+
+        let num_leaves = match public_arguments.get::<IndexKey>(&"categories".into()) {
+            Some(categories) => categories.ref_array()?.num_records()?,
+            None => properties.get::<IndexKey>(&"data".into())
+                .ok_or("data: missing")?.array()
+                .map_err(prepend("data:"))?
+                .categories()?.num_records()[0] as usize
+        };
+
+        // tree of counts
+        maximum_id += 1;
+        let id_tree = maximum_id;
+        let mut tree_arguments = indexmap!["data".into() => *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?];
+        argument_ids.get::<IndexKey>(&"categories".into())
+            .map(|v| tree_arguments.insert("categories".into(), *v));
+
+        expansion.computation_graph.insert(id_tree, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(tree_arguments)),
+            variant: Some(proto::component::Variant::HierarchicalHistogram(proto::HierarchicalHistogram {
+                branching: self.branching
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_tree);
+
+        // noise, with the same variance on every node, then consistent leaves
+        expand_consistent_counts(
+            &mut expansion, privacy_definition, component, component_id, maximum_id, id_tree, num_records,
+            &self.mechanism, &self.privacy_usage,
+            proto::component::Variant::ConstrainedInference(proto::ConstrainedInference {
+                branching: self.branching,
+                num_leaves: u32::try_from(num_leaves)?,
+            }),
+            IndexMap::new())?;
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpHierarchicalHistogram {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPHierarchicalHistogram".to_string(),
+            variables: serde_json::json!(variable_names.cloned()
+                .unwrap_or_else(Vec::new).iter()
+                .map(|v| v.to_string()).collect::<Vec<String>>()),
+            release_info: value_to_json(release)?,
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "constrained inference".to_string(),
+                cite: "Hay, Rastogi, Miklau and Suciu (2010)".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "branching": self.branching
+                }),
+            },
+        }]))
+    }
+}
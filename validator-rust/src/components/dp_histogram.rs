@@ -14,7 +14,7 @@ impl Expandable for proto::DpHistogram {
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
         component: &proto::Component,
-        _public_arguments: &IndexMap<IndexKey, &Value>,
+        public_arguments: &IndexMap<IndexKey, &Value>,
         properties: &base::NodeProperties,
         component_id: u32,
         mut maximum_id: u32,
@@ -30,6 +30,24 @@ impl Expandable for proto::DpHistogram {
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?;
 
+        // the stability mechanism discovers the categories from the data
+        if self.mechanism.to_lowercase() == "stability" {
+            if argument_ids.contains_key::<IndexKey>(&"categories".into()) || argument_ids.contains_key::<IndexKey>(&"edges".into()) {
+                return Err("categories and edges may not be set when using the stability mechanism".into())
+            }
+            let variant = proto::StabilityMechanism {
+                privacy_usage: self.privacy_usage.clone()
+            };
+            let mechanism_component = proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => data_id])),
+                variant: Some(proto::component::Variant::StabilityMechanism(variant.clone())),
+                omit: component.omit,
+                submission: component.submission,
+            };
+            return variant.expand_component(
+                privacy_definition, &mechanism_component, public_arguments, properties, component_id, maximum_id)
+        }
+
         let privacy_definition = privacy_definition.as_ref()
             .ok_or_else(|| Error::from("privacy_definition must be known"))?;
 
@@ -117,6 +135,9 @@ impl Expandable for proto::DpHistogram {
                     privacy_usage: self.privacy_usage.clone(),
                     analytic: true
                 }),
+                "discretegaussian" => proto::component::Variant::DiscreteGaussianMechanism(proto::DiscreteGaussianMechanism {
+                    privacy_usage: self.privacy_usage.clone()
+                }),
                 "snapping" => {
                     argument_ids.get::<IndexKey>(&"lower".into())
                         .map(|lower| arguments.insert("lower".into(), *lower));
@@ -146,11 +167,17 @@ impl Report for proto::DpHistogram {
         &self,
         node_id: u32,
         component: &proto::Component,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: NodeProperties,
         release: &Value,
         variable_names: Option<&Vec<base::IndexKey>>,
     ) -> Result<Option<Vec<JSONRelease>>> {
+        if self.mechanism.to_lowercase() == "stability" {
+            return proto::StabilityMechanism {
+                privacy_usage: self.privacy_usage.clone()
+            }.summarize(node_id, component, public_arguments, properties, release, variable_names)
+        }
+
         let data_property = properties.get::<base::IndexKey>(&"data".into())
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?.clone();
@@ -178,7 +205,7 @@ impl Report for proto::DpHistogram {
                     privacy_loss: privacy_usage_to_json(&privacy_usage),
                     accuracy: None,
                     submission: component.submission,
-                    node_id,
+                    node_id: Some(node_id),
                     postprocess: false,
                     algorithm_info: AlgorithmInfo {
                         name: "".to_string(),
@@ -0,0 +1,259 @@
+use indexmap::map::IndexMap;
+use ndarray::{Array, Axis};
+
+use crate::{base, Float, Integer, proto};
+use crate::base::{ArrayProperties, DataType, IndexKey, Value};
+use crate::components::Expandable;
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::privacy::spread_privacy_usage;
+
+impl Expandable for proto::DpkMeans {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        if data_property.nullity {
+            return Err("data: may not contain nullity. Impute first.".into())
+        }
+        let lower = data_property.lower_float().map_err(prepend("data:"))?;
+        let upper = data_property.upper_float().map_err(prepend("data:"))?;
+
+        if self.num_clusters == 0 {
+            return Err("num_clusters: must be greater than zero".into())
+        }
+        if self.iterations == 0 {
+            return Err("iterations: must be greater than zero".into())
+        }
+
+        if let Some(centroids_property) = properties.get::<IndexKey>(&"initial_centroids".into()) {
+            let centroids_property = centroids_property.array()
+                .map_err(prepend("initial_centroids:"))?;
+            if !centroids_property.releasable {
+                return Err("initial_centroids: must be public".into())
+            }
+            if centroids_property.num_records()? != self.num_clusters as i64 {
+                return Err("initial_centroids: must have one row for each cluster".into())
+            }
+            if centroids_property.num_columns()? != data_property.num_columns()? {
+                return Err("initial_centroids: must have one column for each column of data".into())
+            }
+        }
+
+        // every cluster is disjoint, so each iteration spends one count and one sum
+        let privacy_usages = spread_privacy_usage(
+            &self.privacy_usage, 2 * self.iterations as usize)?;
+
+        let mut insert_literal = |value: Value, maximum_id: &mut u32| -> Result<u32> {
+            *maximum_id += 1;
+            let id_literal = *maximum_id;
+            let (patch_node, release) = get_literal(value, component.submission)?;
+            expansion.computation_graph.insert(id_literal, patch_node);
+            expansion.properties.insert(id_literal, infer_property(&release.value, None, id_literal)?);
+            expansion.releases.insert(id_literal, release);
+            Ok(id_literal)
+        };
+
+        let id_data = *component.arguments().get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+        let mut id_centroids = match component.arguments().get::<IndexKey>(&"initial_centroids".into()) {
+            Some(id) => *id,
+            None => insert_literal(
+                get_initial_centroids(&lower, &upper, self.num_clusters)?.into(),
+                &mut maximum_id)?
+        };
+        // bounds are a single row, with one column for each column of the centroids
+        let id_lower = insert_literal(Array::from(lower).insert_axis(Axis(0)).into_dyn().into(), &mut maximum_id)?;
+        let id_upper = insert_literal(Array::from(upper).insert_axis(Axis(0)).into_dyn().into(), &mut maximum_id)?;
+
+        let cluster_ids = (0..self.num_clusters)
+            .map(|cluster| insert_literal(
+                Value::from_index_key(IndexKey::from(cluster as Integer))?,
+                &mut maximum_id))
+            .collect::<Result<Vec<u32>>>()?;
+
+        let mut graph = Vec::new();
+        for iteration in 0..self.iterations {
+            let count_usage = privacy_usages[2 * iteration as usize].clone();
+            let sum_usage = privacy_usages[2 * iteration as usize + 1].clone();
+
+            // assign each record to the nearest centroid
+            maximum_id += 1;
+            let id_assignments = maximum_id;
+            graph.push((id_assignments, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                    "data".into() => id_data,
+                    "centroids".into() => id_centroids
+                ])),
+                variant: Some(proto::component::Variant::AssignClusters(proto::AssignClusters {})),
+                omit: true,
+                submission: component.submission,
+            }));
+
+            // partition the data by cluster
+            maximum_id += 1;
+            let id_partition = maximum_id;
+            graph.push((id_partition, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                    "data".into() => id_data,
+                    "by".into() => id_assignments
+                ])),
+                variant: Some(proto::component::Variant::Partition(proto::Partition {})),
+                omit: true,
+                submission: component.submission,
+            }));
+
+            let mut update_arguments = indexmap![
+                "centroids".into() => id_centroids,
+                "lower".into() => id_lower,
+                "upper".into() => id_upper
+            ];
+
+            for (cluster, id_cluster) in cluster_ids.iter().enumerate() {
+                maximum_id += 1;
+                let id_index = maximum_id;
+                graph.push((id_index, proto::Component {
+                    arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                        "data".into() => id_partition,
+                        "names".into() => *id_cluster
+                    ])),
+                    variant: Some(proto::component::Variant::Index(proto::Index {})),
+                    omit: true,
+                    submission: component.submission,
+                }));
+
+                // dp count
+                maximum_id += 1;
+                let id_dp_count = maximum_id;
+                graph.push((id_dp_count, proto::Component {
+                    arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_index])),
+                    variant: Some(proto::component::Variant::DpCount(proto::DpCount {
+                        distinct: false,
+                        mechanism: "automatic".to_string(),
+                        privacy_usage: vec![count_usage.clone()],
+                    })),
+                    omit: true,
+                    submission: component.submission,
+                }));
+                update_arguments.insert(format!("count_{}", cluster).into(), id_dp_count);
+
+                // dp sum
+                maximum_id += 1;
+                let id_dp_sum = maximum_id;
+                graph.push((id_dp_sum, proto::Component {
+                    arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_index])),
+                    variant: Some(proto::component::Variant::DpSum(proto::DpSum {
+                        mechanism: "automatic".to_string(),
+                        privacy_usage: vec![sum_usage.clone()],
+                    })),
+                    omit: true,
+                    submission: component.submission,
+                }));
+                update_arguments.insert(format!("sum_{}", cluster).into(), id_dp_sum);
+            }
+
+            // the final update takes the place of the k-means component
+            let is_last = iteration + 1 == self.iterations;
+            let id_update = if is_last { component_id } else {
+                maximum_id += 1;
+                maximum_id
+            };
+            graph.push((id_update, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(update_arguments)),
+                variant: Some(proto::component::Variant::UpdateCentroids(proto::UpdateCentroids {})),
+                omit: if is_last { component.omit } else { true },
+                submission: component.submission,
+            }));
+            id_centroids = id_update;
+        }
+
+        graph.into_iter().for_each(|(id, node)| {
+            expansion.computation_graph.insert(id, node);
+            expansion.traversal.push(id);
+        });
+
+        Ok(expansion)
+    }
+}
+
+/// Starting centroids, spaced evenly along the diagonal of the data bounds.
+///
+/// The j-th of k centroids is lower + (j + 1) / (k + 1) * (upper - lower), so that no centroid lies on the boundary.
+fn get_initial_centroids(lower: &[Float], upper: &[Float], num_clusters: u32) -> Result<ndarray::ArrayD<Float>> {
+    let centroids = (0..num_clusters)
+        .flat_map(|cluster| {
+            let fraction = (cluster + 1) as Float / (num_clusters + 1) as Float;
+            lower.iter().zip(upper.iter())
+                .map(move |(l, u)| l + fraction * (u - l))
+        })
+        .collect::<Vec<Float>>();
+
+    Ok(Array::from_shape_vec((num_clusters as usize, lower.len()), centroids)?.into_dyn())
+}
+
+#[cfg(test)]
+mod test_dp_k_means {
+    use ndarray::{arr1, arr2, Array};
+
+    use crate::bindings::Analysis;
+    use crate::components::dp_k_means::get_initial_centroids;
+    use crate::proto;
+    use crate::utilities::propagate_properties;
+
+    #[test]
+    fn test_initial_centroids() {
+        let centroids = get_initial_centroids(&[0., -1.], &[3., 2.], 2).unwrap();
+        assert_eq!(centroids.shape(), &[2, 2]);
+        let expected = [1., 0., 2., 1.];
+        assert!(centroids.iter().zip(expected.iter()).all(|(l, r)| (l - r).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_initial_centroids_shape() {
+        let expand = |initial_centroids: ndarray::ArrayD<f64>| {
+            let mut analysis = Analysis::new();
+            analysis.privacy_definition.protect_floating_point = false;
+            let data = analysis.literal()
+                .value(Array::from_shape_fn((10, 2), |(i, j)| (i + j) as f64).into_dyn().into())
+                .value_public(true).build();
+            let data = analysis.to_float(data).build();
+            let lower = analysis.literal().value(arr1(&[0., 0.]).into_dyn().into()).value_public(true).build();
+            let upper = analysis.literal().value(arr1(&[10., 10.]).into_dyn().into()).value_public(true).build();
+            let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+            let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+            let initial_centroids = analysis.literal()
+                .value(initial_centroids.into()).value_public(true).build();
+            analysis.dp_k_means(imputed, 2, vec![proto::PrivacyUsage {
+                distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                    epsilon: 1., delta: 0.
+                }))
+            }]).initial_centroids(initial_centroids).build();
+
+            let mut computation_graph = analysis.components.clone();
+            let mut release = analysis.release.clone();
+            propagate_properties(
+                &Some(analysis.privacy_definition.clone()),
+                &mut computation_graph, &mut release, None, false)
+        };
+
+        assert!(expand(arr2(&[[1., 2.], [3., 4.]]).into_dyn()).is_ok());
+        assert!(expand(arr2(&[[1., 2.], [3., 4.], [5., 6.]]).into_dyn()).is_err());
+        assert!(expand(arr2(&[[1.], [3.]]).into_dyn()).is_err());
+    }
+}
@@ -12,7 +12,7 @@ use crate::utilities::privacy::spread_privacy_usage;
 impl Expandable for proto::DpLinearRegression {
     fn expand_component(
         &self,
-        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         component: &proto::Component,
         public_arguments: &IndexMap<IndexKey, &Value>,
         _properties: &base::NodeProperties,
@@ -21,6 +21,10 @@ impl Expandable for proto::DpLinearRegression {
     ) -> Result<base::ComponentExpansion> {
         const DEFAULT_K: u32 = 100;
 
+        if self.implementation.to_lowercase().as_str() == "sufficient-statistics" {
+            return self.expand_sufficient_statistics(privacy_definition, component, component_id, maximum_id)
+        }
+
         let mut privacy_usages = spread_privacy_usage(&self.privacy_usage, 2)?;
         let slope_privacy_usage = privacy_usages.remove(0);
         let intercept_privacy_usage = privacy_usages.remove(0);
@@ -60,7 +64,7 @@ impl Expandable for proto::DpLinearRegression {
                         id_k
                     });
             },
-            _ => return Err(Error::from("Invalid implementation argument"))
+            _ => return Err(Error::from("Invalid implementation argument. Must be one of [theil-sen, theil-sen-k-match, sufficient-statistics]"))
         }
 
         // theil-sen transform
@@ -174,6 +178,122 @@ impl Expandable for proto::DpLinearRegression {
 }
 
 
+impl proto::DpLinearRegression {
+    /// Expand into noisy sufficient statistics, and solve the normal equations as post-processing.
+    ///
+    /// The covariance of the predictors and target is released jointly, so X'X and X'y share one sensitivity.
+    /// Under the laplace mechanism, the noise scales of the statistics are passed along to estimate accuracy.
+    fn expand_sufficient_statistics(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| Error::from("privacy_definition must be known"))?;
+
+        let mut privacy_usages = spread_privacy_usage(&self.privacy_usage, 2)?;
+        let covariance_privacy_usage = privacy_usages.remove(0);
+        let means_privacy_usage = privacy_usages.remove(0);
+
+        let mut expansion = base::ComponentExpansion::default();
+
+        let get_id = |name: &str| -> Result<u32> {
+            component.arguments().get::<base::IndexKey>(&name.into())
+                .ok_or_else(|| Error::from(format!("{} must be provided as an argument", name)))
+                .map(|v| *v)
+        };
+
+        // bind the target as the last column
+        maximum_id += 1;
+        let id_data = maximum_id;
+        expansion.computation_graph.insert(id_data, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data_x".into() => get_id("data_x")?,
+                "data_y".into() => get_id("data_y")?
+            ])),
+            variant: Some(proto::component::Variant::ColumnBind(proto::ColumnBind {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_data);
+
+        let mut insert_node = |arguments: IndexMap<IndexKey, u32>, variant: proto::component::Variant| {
+            maximum_id += 1;
+            expansion.computation_graph.insert(maximum_id, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(arguments)),
+                variant: Some(variant),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(maximum_id);
+            maximum_id
+        };
+
+        // the noise scale only approximates the noise of the snapping mechanism, so accuracy is only estimated under the laplace mechanism
+        let get_mechanism = |privacy_usage: &proto::PrivacyUsage| if privacy_definition.protect_floating_point {
+            proto::component::Variant::SnappingMechanism(proto::SnappingMechanism {
+                privacy_usage: vec![privacy_usage.clone()]
+            })
+        } else {
+            proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
+                privacy_usage: vec![privacy_usage.clone()]
+            })
+        };
+        let get_noise_scale = |privacy_usage: &proto::PrivacyUsage| proto::component::Variant::NoiseScale(proto::NoiseScale {
+            mechanism: "Laplace".to_string(),
+            privacy_usage: vec![privacy_usage.clone()],
+        });
+
+        // covariance, as the flattened upper triangle, which the solve accepts directly
+        let id_covariance = insert_node(
+            indexmap!["data".into() => id_data],
+            proto::component::Variant::Covariance(proto::Covariance { finite_sample_correction: false }));
+        let id_dp_covariance = insert_node(
+            indexmap!["data".into() => id_covariance],
+            get_mechanism(&covariance_privacy_usage));
+
+        // means
+        let id_means = insert_node(
+            indexmap!["data".into() => id_data],
+            proto::component::Variant::Mean(proto::Mean {}));
+        let id_dp_means = insert_node(
+            indexmap!["data".into() => id_means],
+            get_mechanism(&means_privacy_usage));
+
+        let mut solve_arguments = indexmap![
+            IndexKey::from("covariance") => id_dp_covariance,
+            IndexKey::from("means") => id_dp_means
+        ];
+
+        if !privacy_definition.protect_floating_point {
+            let id_covariance_scale = insert_node(
+                indexmap!["data".into() => id_covariance],
+                get_noise_scale(&covariance_privacy_usage));
+            let id_means_scale = insert_node(
+                indexmap!["data".into() => id_means],
+                get_noise_scale(&means_privacy_usage));
+
+            solve_arguments.insert("covariance_scale".into(), id_covariance_scale);
+            solve_arguments.insert("means_scale".into(), id_means_scale);
+        }
+
+        // solve the normal equations
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(solve_arguments)),
+            variant: Some(proto::component::Variant::SolveNormalEquations(proto::SolveNormalEquations {
+                noise: "Laplace".to_string()
+            })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+        expansion.traversal.push(component_id);
+
+        Ok(expansion)
+    }
+}
+
 impl Report for proto::DpLinearRegression {
     fn summarize(
         &self,
@@ -195,7 +315,7 @@ impl Report for proto::DpLinearRegression {
             privacy_loss: serde_json::json!(privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
             accuracy: None,
             submission: component.submission,
-            node_id,
+            node_id: Some(node_id),
             postprocess: false,
             algorithm_info: AlgorithmInfo {
                 name: "".to_string(),
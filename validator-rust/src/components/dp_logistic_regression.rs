@@ -0,0 +1,377 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism, Named, Report};
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::privacy::{get_pure_rdp_curves, privacy_usage_check, pure_rdp_curve};
+
+impl Component for proto::DpLogisticRegression {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+
+        if privacy_definition.protect_floating_point {
+            return Err("Floating-point protections are enabled. The spherical laplace noise of logistic regression is susceptible to floating-point attacks.".into())
+        }
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into())
+        }
+
+        let data_property_x = properties.get::<IndexKey>(&"data_x".into())
+            .ok_or("data_x: missing")?.array()
+            .map_err(prepend("data_x:"))?.clone();
+
+        let data_property_y = properties.get::<IndexKey>(&"data_y".into())
+            .ok_or("data_y: missing")?.array()
+            .map_err(prepend("data_y:"))?.clone();
+
+        data_property_x.assert_is_not_aggregated().map_err(prepend("data_x:"))?;
+        data_property_y.assert_is_not_aggregated().map_err(prepend("data_y:"))?;
+        data_property_x.assert_is_not_empty().map_err(prepend("data_x:"))?;
+
+        if data_property_x.data_type != DataType::Float {
+            return Err("data_x: atomic type must be float".into())
+        }
+        if data_property_y.data_type != DataType::Bool {
+            return Err("data_y: atomic type must be boolean".into())
+        }
+        if data_property_x.nullity || data_property_y.nullity {
+            return Err("data_x and data_y: may not contain nullity. Impute first.".into())
+        }
+        if data_property_y.num_columns()? != 1 {
+            return Err("data_y: must have exactly one column".into())
+        }
+
+        // the number of records must be public for the sensitivity to be known
+        if data_property_x.num_records()? != data_property_y.num_records()? {
+            return Err("data_x and data_y: must be same length".into())
+        }
+
+        if data_property_x.group_id != data_property_y.group_id {
+            return Err("data from separate partitions may not be mixed".into())
+        }
+        if data_property_x.dataset_id != data_property_y.dataset_id {
+            return Err("data_x and data_y: must share the same dataset id".into())
+        }
+
+        if properties.contains_key::<IndexKey>(&"feature_norm".into()) {
+            // the feature norm scales the rows, so it must not depend on the data
+            let feature_norm = public_arguments.get::<IndexKey>(&"feature_norm".into())
+                .ok_or_else(|| Error::from("feature_norm: must be public"))?
+                .ref_array()?.first_float()?;
+            if feature_norm <= 0. {
+                return Err("feature_norm: must be positive".into())
+            }
+        } else {
+            // bounds are necessary to compute the feature norm
+            data_property_x.lower_float().map_err(prepend("data_x:"))?;
+            data_property_x.upper_float().map_err(prepend("data_x:"))?;
+        }
+
+        if self.regularization <= 0. {
+            return Err("regularization: must be positive".into())
+        }
+        if self.tolerance <= 0. {
+            return Err("tolerance: must be positive".into())
+        }
+
+        match self.implementation.to_lowercase().as_str() {
+            "objective" | "output" => (),
+            _ => return Err("implementation: must be one of [objective, output]".into())
+        }
+
+        if self.privacy_usage.len() != 1 {
+            return Err("privacy_usage: must be of length one".into())
+        }
+
+        let warnings = privacy_usage_check(
+            &self.privacy_usage[0],
+            data_property_x.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        Ok(Warnable(ArrayProperties {
+            num_records: Some(1),
+            num_columns: Some(data_property_x.num_columns()? + 1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type: DataType::Float,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(2),
+            group_id: data_property_x.group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+        }.into(), warnings))
+    }
+}
+
+impl Expandable for proto::DpLogisticRegression {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy definition must be defined")?;
+
+        let data_property_x: ArrayProperties = properties.get::<IndexKey>(&"data_x".into())
+            .ok_or("data_x: missing")?.array()
+            .map_err(prepend("data_x:"))?.clone();
+
+        // update the privacy usage
+        let mut updated_component = component.clone();
+        if let Some(proto::component::Variant::DpLogisticRegression(variant)) = &mut updated_component.variant {
+            variant.privacy_usage = self.privacy_usage.iter()
+                .map(|usage| usage.actual_to_effective(
+                    data_property_x.sample_proportion.unwrap_or(1.),
+                    data_property_x.c_stability,
+                    privacy_definition))
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+        } else { return Err(Error::from("Variant must be defined")) }
+
+        if !properties.contains_key::<IndexKey>(&"feature_norm".into()) {
+            let feature_norm = get_feature_norm(
+                &data_property_x.lower_float()?,
+                &data_property_x.upper_float()?);
+
+            maximum_id += 1;
+            let id_feature_norm = maximum_id;
+            let (patch_node, release) = get_literal(Value::from(feature_norm), component.submission)?;
+            expansion.computation_graph.insert(id_feature_norm, patch_node);
+            expansion.properties.insert(id_feature_norm, infer_property(&release.value, None, id_feature_norm)?);
+            expansion.releases.insert(id_feature_norm, release);
+            updated_component.insert_argument(&"feature_norm".into(), id_feature_norm);
+        }
+
+        expansion.computation_graph.insert(component_id, updated_component);
+
+        Ok(expansion)
+    }
+}
+
+impl Mechanism for proto::DpLogisticRegression {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data_x".into())
+            .ok_or("data_x: missing")?.array()
+            .map_err(prepend("data_x:"))?;
+
+        Some(release_usage.unwrap_or(&self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data_x".into())
+            .ok_or("data_x: missing")?.array()
+            .map_err(prepend("data_x:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or(&self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
+}
+
+impl Named for proto::DpLogisticRegression {
+    fn get_names(
+        &self,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        argument_variables: IndexMap<base::IndexKey, Vec<IndexKey>>,
+        _release: Option<&Value>,
+    ) -> Result<Vec<IndexKey>> {
+        let mut names = vec![IndexKey::from("intercept")];
+        names.extend(argument_variables.get::<IndexKey>(&"data_x".into())
+            .ok_or("data_x: variable names are unknown")?.iter().cloned());
+        Ok(names)
+    }
+}
+
+impl Report for proto::DpLogisticRegression {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let feature_norm = public_arguments.get::<IndexKey>(&"feature_norm".into())
+            .map(|v| v.ref_array()?.first_float())
+            .transpose()?;
+
+        let variables = match variable_names {
+            Some(names) => names.iter().map(|name| name.to_string()).collect(),
+            None => vec!["[Unknown]".to_string()]
+        };
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPLogisticRegression".to_string(),
+            variables: serde_json::json!(variables),
+            release_info: value_to_json(release)?,
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: match self.implementation.to_lowercase().as_str() {
+                    "objective" => "Chaudhuri, Monteleoni and Sarwate (2011); Iyengar et al. (2019)",
+                    _ => "Chaudhuri, Monteleoni and Sarwate (2011)"
+                }.to_string(),
+                mechanism: format!("{} perturbation", self.implementation.to_lowercase()),
+                argument: serde_json::json!({
+                    "regularization": self.regularization,
+                    "tolerance": self.tolerance,
+                    "constraint": {
+                        "feature_norm": feature_norm
+                    }
+                }),
+            },
+        }]))
+    }
+}
+
+/// Upper bound on the L2 norm of a row, with an additional intercept term of one.
+fn get_feature_norm(lower: &[f64], upper: &[f64]) -> f64 {
+    let squared_norm: f64 = lower.iter().zip(upper.iter())
+        .map(|(l, u)| l.abs().max(u.abs()).powi(2))
+        .sum();
+    (squared_norm + 1.).sqrt()
+}
+
+/// Fraction of epsilon that objective perturbation spends on perturbing the objective.
+///
+/// The remainder is spent on output noise that covers the distance between the approximate and exact minimizers.
+/// Iyengar et al. (2019), Approximate Minima Perturbation
+pub const OBJECTIVE_EPSILON_FRACTION: f64 = 0.9;
+
+/// Privacy parameters of objective perturbation for L2-regularized logistic regression.
+///
+/// Assumes each row has been rescaled to have L2 norm at most one.
+/// If the regularization is too small for the requested epsilon, additional regularization is added and half of the epsilon is spent on the noise.
+/// Chaudhuri, Monteleoni and Sarwate (2011), Algorithm 2
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `regularization` - Coefficient of the L2 penalty on the weights.
+/// * `num_records` - Number of rows in the training data.
+///
+/// # Returns
+/// The epsilon to calibrate the noise vector to, and the additional regularization.
+pub fn get_objective_perturbation_parameters(
+    epsilon: f64, regularization: f64, num_records: f64,
+) -> Result<(f64, f64)> {
+    if epsilon <= 0. {
+        return Err("epsilon: must be positive".into())
+    }
+    if regularization <= 0. {
+        return Err("regularization: must be positive".into())
+    }
+    if num_records <= 0. {
+        return Err("num_records: must be positive".into())
+    }
+
+    // upper bound on the second derivative of the logistic loss
+    let c = 0.25;
+    let ratio = c / (num_records * regularization);
+    let epsilon_noise = epsilon - (1. + 2. * ratio + ratio.powi(2)).ln();
+
+    Ok(if epsilon_noise > 0. {
+        (epsilon_noise, 0.)
+    } else {
+        (epsilon / 2., c / (num_records * ((epsilon / 4.).exp() - 1.)) - regularization)
+    })
+}
+
+#[cfg(test)]
+mod test_dp_logistic_regression {
+    use indexmap::IndexMap;
+    use ndarray::{arr1, arr2};
+
+    use crate::base::{IndexKey, Value};
+    use crate::components::Component;
+    use crate::components::dp_logistic_regression::get_objective_perturbation_parameters;
+    use crate::proto;
+    use crate::utilities::inference::infer_property;
+
+    #[test]
+    fn test_objective_perturbation_parameters() {
+        // large datasets spend nearly all of epsilon on the noise
+        let (epsilon, extra) = get_objective_perturbation_parameters(1., 1., 1000.).unwrap();
+        assert!(epsilon > 0.99 && epsilon < 1.);
+        assert!(extra.abs() < 1e-12);
+
+        // small datasets with little regularization need additional regularization
+        let (epsilon, extra) = get_objective_perturbation_parameters(0.1, 1e-3, 10.).unwrap();
+        assert!((epsilon - 0.05).abs() < 1e-12);
+        assert!(extra > 0.);
+    }
+
+    #[test]
+    fn test_public_feature_norm() {
+        let data_x: Value = arr2(&[[1., 2.], [3., 4.]]).into_dyn().into();
+        let data_y: Value = arr1(&[true, false]).into_dyn().into();
+        let feature_norm: Value = 6.0.into();
+
+        let properties = indexmap![
+            IndexKey::from("data_x") => infer_property(&data_x, None, 0).unwrap(),
+            IndexKey::from("data_y") => infer_property(&data_y, None, 1).unwrap(),
+            IndexKey::from("feature_norm") => infer_property(&feature_norm, None, 2).unwrap()
+        ];
+        let component = proto::DpLogisticRegression {
+            regularization: 1.,
+            implementation: "objective".to_string(),
+            iterations: 1000,
+            tolerance: 1e-8,
+            privacy_usage: vec![proto::PrivacyUsage {
+                distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                    epsilon: 1., delta: 0.
+                }))
+            }],
+        };
+        let privacy_definition = Some(proto::PrivacyDefinition { group_size: 1, ..Default::default() });
+
+        // a feature norm with known properties, but without a public value, is rejected
+        assert!(component.propagate_property(
+            &privacy_definition, IndexMap::new(), properties.clone(), 3).is_err());
+        component.propagate_property(
+            &privacy_definition, indexmap![IndexKey::from("feature_norm") => &feature_norm], properties, 3).unwrap();
+    }
+}
@@ -3,6 +3,7 @@ use indexmap::map::IndexMap;
 use crate::{base, proto};
 use crate::base::{IndexKey, NodeProperties, Value};
 use crate::components::{Expandable, Report};
+use crate::components::dp_bounds::expand_estimated_bounds;
 use crate::errors::*;
 use crate::utilities::{array::get_ith_column, prepend, privacy::spread_privacy_usage, get_literal};
 use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
@@ -26,6 +27,27 @@ impl Expandable for proto::DpMean {
         component_id: u32,
         mut maximum_id: u32,
     ) -> Result<base::ComponentExpansion> {
+        if self.bounds_fraction < 0. || self.bounds_fraction >= 1. {
+            return Err("bounds_fraction: must be within [0, 1)".into())
+        }
+
+        // estimate bounds on the data, then clamp and expand again with the remaining privacy usage
+        if self.bounds_fraction > 0. {
+            let bounds_usage = self.privacy_usage.iter().cloned()
+                .map(|v| v * self.bounds_fraction)
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+            let mut updated_component = component.clone();
+            if let Some(proto::component::Variant::DpMean(variant)) = &mut updated_component.variant {
+                variant.bounds_fraction = 0.;
+                variant.privacy_usage = self.privacy_usage.iter().cloned()
+                    .map(|v| v * (1. - self.bounds_fraction))
+                    .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+            } else { return Err(Error::from("Variant must be defined")) }
+
+            return expand_estimated_bounds(updated_component, bounds_usage, component_id, maximum_id)
+        }
+
         let mut expansion = base::ComponentExpansion::default();
         let argument_ids = component.arguments();
 
@@ -283,7 +305,7 @@ impl Report for proto::DpMean {
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
                 accuracy: None,
                 submission: component.submission,
-                node_id,
+                node_id: Some(node_id),
                 postprocess: false,
                 algorithm_info: AlgorithmInfo {
                     name: "".to_string(),
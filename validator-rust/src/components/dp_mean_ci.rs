@@ -0,0 +1,190 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, Float, proto};
+use crate::base::{DataType, IndexKey, NodeProperties, Value};
+use crate::components::{Expandable, Report};
+use crate::components::mean_confidence_interval::check_confidence;
+use crate::errors::*;
+use crate::utilities::{get_literal, get_mechanism, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json};
+
+impl Expandable for proto::DpMeanCi {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+
+        let argument_ids = component.arguments();
+        let data_id = *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+        check_confidence(self.confidence)?;
+        let num_records = data_property.num_records()?;
+
+        // the mean and variance are float statistics, regardless of the atomic type of the data
+        let mechanism = get_mechanism(&DataType::Float, &self.mechanism, privacy_definition)?;
+
+        // the simulated errors receive noise from the same distributions as the released mean and variance
+        let noise = match mechanism.as_str() {
+            "laplace" | "snapping" => "Laplace",
+            "gaussian" | "analyticgaussian" => "Gaussian",
+            _ => bail!("Unexpected invalid token {:?}", self.mechanism.as_str()),
+        };
+
+        // half of the budget releases the mean, and half releases the variance
+        let half_usage = self.privacy_usage.iter().cloned()
+            .map(|v| v * 0.5)
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+        let get_variant = || match mechanism.as_str() {
+            "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
+                privacy_usage: half_usage.clone()
+            }),
+            "snapping" => proto::component::Variant::SnappingMechanism(proto::SnappingMechanism {
+                privacy_usage: half_usage.clone()
+            }),
+            _ => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                privacy_usage: half_usage.clone(),
+                analytic: mechanism == "analyticgaussian",
+            })
+        };
+        let get_noise_scale = || proto::component::Variant::NoiseScale(proto::NoiseScale {
+            mechanism: mechanism.clone(),
+            privacy_usage: half_usage.clone(),
+        });
+
+        let mut insert_node = |arguments: IndexMap<IndexKey, u32>, variant: proto::component::Variant| {
+            maximum_id += 1;
+            expansion.computation_graph.insert(maximum_id, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(arguments)),
+                variant: Some(variant),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(maximum_id);
+            maximum_id
+        };
+
+        // noisy mean, and the scale of its noise
+        let id_mean = insert_node(
+            indexmap!["data".into() => data_id],
+            proto::component::Variant::Mean(proto::Mean {}));
+        let id_dp_mean = insert_node(indexmap!["data".into() => id_mean], get_variant());
+        let id_noise_scale = insert_node(indexmap!["data".into() => id_mean], get_noise_scale());
+
+        // noisy variance, and the scale of its noise
+        let id_variance = insert_node(
+            indexmap!["data".into() => data_id],
+            proto::component::Variant::Variance(proto::Variance { finite_sample_correction: true }));
+        let id_dp_variance = insert_node(indexmap!["data".into() => id_variance], get_variant());
+        let id_variance_scale = insert_node(indexmap!["data".into() => id_variance], get_noise_scale());
+
+        let mut interval_arguments = indexmap![
+            IndexKey::from("data") => id_dp_mean,
+            IndexKey::from("variance") => id_dp_variance,
+            IndexKey::from("noise_scale") => id_noise_scale,
+            IndexKey::from("variance_scale") => id_variance_scale
+        ];
+        let literals: Vec<(&str, Float)> = vec![
+            ("num_records", num_records as Float),
+            ("lower", data_property.lower_float()?[0]),
+            ("upper", data_property.upper_float()?[0]),
+        ];
+        for (name, value) in literals {
+            maximum_id += 1;
+            let id_literal = maximum_id;
+            let (patch_node, release) = get_literal(value.into(), component.submission)?;
+            expansion.computation_graph.insert(id_literal, patch_node);
+            expansion.properties.insert(id_literal, infer_property(&release.value, None, id_literal)?);
+            expansion.releases.insert(id_literal, release);
+            interval_arguments.insert(name.into(), id_literal);
+        }
+
+        // interval
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(interval_arguments)),
+            variant: Some(proto::component::Variant::MeanConfidenceInterval(proto::MeanConfidenceInterval {
+                confidence: self.confidence,
+                noise: noise.to_string(),
+                num_simulations: self.num_simulations,
+            })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+        expansion.traversal.push(component_id);
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpMeanCi {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPMeanCI".to_string(),
+            variables: serde_json::json!(variable_names.cloned()
+                .unwrap_or_else(Vec::new).iter()
+                .map(|v| v.to_string()).collect::<Vec<String>>()),
+            release_info: interval_to_json(release)?,
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "NoisyVar".to_string(),
+                cite: "Du, Foot, Moniot, Bray and Groce (2020)".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "confidence": self.confidence,
+                    "n": data_property.num_records,
+                    "constraint": {
+                        "lowerbound": data_property.lower_float().ok().map(|v| v[0]),
+                        "upperbound": data_property.upper_float().ok().map(|v| v[0])
+                    }
+                }),
+            },
+        }]))
+    }
+}
+
+/// Summarize a released interval by its endpoints.
+pub fn interval_to_json(release: &Value) -> Result<serde_json::Value> {
+    let endpoints = release.ref_array()?.ref_float()?.iter().cloned().collect::<Vec<Float>>();
+    if endpoints.len() != 2 {
+        return Err("interval must contain a lower and an upper endpoint".into())
+    }
+    Ok(serde_json::json!({
+        "lower": endpoints[0],
+        "upper": endpoints[1]
+    }))
+}
@@ -79,7 +79,7 @@ impl Report for proto::DpMinimum {
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
                 accuracy: None,
                 submission: component.submission,
-                node_id,
+                node_id: Some(node_id),
                 postprocess: false,
                 algorithm_info: AlgorithmInfo {
                     name: "".to_string(),
@@ -0,0 +1,131 @@
+use std::convert::TryFrom;
+
+use indexmap::map::IndexMap;
+
+use crate::{base, proto};
+use crate::base::{IndexKey, NodeProperties, Value};
+use crate::components::{Expandable, Report};
+use crate::errors::*;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::prepend;
+
+impl Expandable for proto::Dppca {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+        let num_columns = u32::try_from(data_property.num_columns()?)?;
+
+        if self.num_components == 0 || self.num_components > num_columns {
+            return Err("num_components: must be between one and the number of columns".into())
+        }
+
+        let id_data = *component.arguments().get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+        maximum_id += 1;
+        let id_matrix = maximum_id;
+        match self.implementation.to_lowercase().as_str() {
+            "analyze-gauss" => {
+                match self.mechanism.to_lowercase().as_str() {
+                    "gaussian" | "analyticgaussian" => (),
+                    _ => return Err("mechanism: must be one of [Gaussian, AnalyticGaussian]".into())
+                }
+
+                // symmetric noise on the covariance matrix
+                expansion.computation_graph.insert(id_matrix, proto::Component {
+                    arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_data])),
+                    variant: Some(proto::component::Variant::DpCovariance(proto::DpCovariance {
+                        mechanism: self.mechanism.clone(),
+                        privacy_usage: self.privacy_usage.clone(),
+                        finite_sample_correction: false,
+                    })),
+                    omit: true,
+                    submission: component.submission,
+                });
+            },
+            "wishart" => {
+                expansion.computation_graph.insert(id_matrix, proto::Component {
+                    arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_data])),
+                    variant: Some(proto::component::Variant::WishartMechanism(proto::WishartMechanism {
+                        privacy_usage: self.privacy_usage.clone(),
+                    })),
+                    omit: true,
+                    submission: component.submission,
+                });
+            },
+            _ => return Err("implementation: must be one of [analyze-gauss, wishart]".into())
+        }
+        expansion.traversal.push(id_matrix);
+
+        // eigendecomposition
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_matrix])),
+            variant: Some(proto::component::Variant::PrincipalComponents(proto::PrincipalComponents {
+                num_components: self.num_components
+            })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+        expansion.traversal.push(component_id);
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::Dppca {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        let (cite, mechanism) = match self.implementation.to_lowercase().as_str() {
+            "wishart" => ("Jiang, Xie and Zhang (2016)", "wishart".to_string()),
+            _ => ("Dwork, Talwar, Thakurta and Zhang (2014)", self.mechanism.to_lowercase())
+        };
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPPCA".to_string(),
+            variables: serde_json::json!(variable_names.cloned()
+                .unwrap_or_else(Vec::new).iter()
+                .map(|v| v.to_string()).collect::<Vec<String>>()),
+            release_info: value_to_json(release)?,
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: self.implementation.to_lowercase(),
+                cite: cite.to_string(),
+                mechanism,
+                argument: serde_json::json!({
+                    "num_components": self.num_components,
+                    "constraint": {
+                        "lowerbound": data_property.lower_float()?,
+                        "upperbound": data_property.upper_float()?
+                    }
+                }),
+            },
+        }]))
+    }
+}
@@ -7,6 +7,9 @@ use crate::errors::*;
 use crate::utilities::{array::get_ith_column, prepend, privacy::spread_privacy_usage};
 use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
 
+/// mechanisms that release one of the candidates, scored by the quantile utilities
+const SELECTION_MECHANISMS: [&str; 4] = ["exponential", "reportnoisymaxlaplace", "reportnoisymaxexponential", "permuteandflip"];
+
 impl Expandable for proto::DpQuantile {
     fn expand_component(
         &self,
@@ -36,11 +39,14 @@ impl Expandable for proto::DpQuantile {
             self.mechanism.to_lowercase()
         };
 
+        // selection mechanisms score each of the candidates
+        let is_selection = SELECTION_MECHANISMS.contains(&mechanism.as_str());
+
         // quantile
         let mut quantile_args = indexmap![IndexKey::from("data") => data_id];
-        if mechanism.as_str() == "exponential" {
+        if is_selection {
             quantile_args.insert("candidates".into(), *argument_ids.get::<IndexKey>(&"candidates".into())
-                .ok_or_else(|| Error::from("candidates is a required argument to DPQuantile when the exponential mechanism is used."))?);
+                .ok_or_else(|| Error::from("candidates is a required argument to DPQuantile when a selection mechanism is used."))?);
         }
         maximum_id += 1;
         let id_quantile = maximum_id;
@@ -57,10 +63,10 @@ impl Expandable for proto::DpQuantile {
 
         // sanitizing
         let mut sanitize_args = IndexMap::new();
-        if self.mechanism.to_lowercase().as_str() == "exponential" {
+        if is_selection {
             sanitize_args.insert("utilities".into(), id_quantile);
             sanitize_args.insert("candidates".into(), *argument_ids.get::<IndexKey>(&"candidates".into())
-                .ok_or_else(|| Error::from("candidates is a required argument to DPQuantile when the exponential mechanism is used."))?);
+                .ok_or_else(|| Error::from("candidates is a required argument to DPQuantile when a selection mechanism is used."))?);
         } else {
             sanitize_args.insert("data".into(), id_quantile);
         }
@@ -80,6 +86,17 @@ impl Expandable for proto::DpQuantile {
             "exponential" => proto::component::Variant::ExponentialMechanism(proto::ExponentialMechanism {
                 privacy_usage: self.privacy_usage.clone()
             }),
+            "reportnoisymaxlaplace" => proto::component::Variant::ReportNoisyMax(proto::ReportNoisyMax {
+                privacy_usage: self.privacy_usage.clone(),
+                noise: "laplace".to_string()
+            }),
+            "reportnoisymaxexponential" => proto::component::Variant::ReportNoisyMax(proto::ReportNoisyMax {
+                privacy_usage: self.privacy_usage.clone(),
+                noise: "exponential".to_string()
+            }),
+            "permuteandflip" => proto::component::Variant::PermuteAndFlip(proto::PermuteAndFlip {
+                privacy_usage: self.privacy_usage.clone()
+            }),
             "snapping" => {
                 argument_ids.get::<IndexKey>(&"lower".into())
                     .map(|lower| sanitize_args.insert("lower".into(), *lower));
@@ -143,7 +160,7 @@ impl Report for proto::DpQuantile {
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
                 accuracy: None,
                 submission: component.submission,
-                node_id,
+                node_id: Some(node_id),
                 postprocess: false,
                 algorithm_info: AlgorithmInfo {
                     name: "".to_string(),
@@ -0,0 +1,161 @@
+use indexmap::map::IndexMap;
+use statrs::function::erf;
+
+use crate::{base, proto};
+use crate::base::{IndexKey, NodeProperties, Value};
+use crate::components::{Expandable, Report};
+use crate::components::dp_mean_ci::interval_to_json;
+use crate::components::dp_quantiles::{DEFAULT_NUM_CANDIDATES, get_accuracy_scale};
+use crate::components::mean_confidence_interval::check_confidence;
+use crate::errors::*;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json};
+use crate::utilities::prepend;
+use crate::utilities::privacy::{get_epsilon, spread_privacy_usage};
+
+impl Expandable for proto::DpQuantileCi {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        _maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| Error::from("privacy_definition must be known"))?;
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+        let num_records = data_property.num_records()?;
+
+        let num_candidates = match public_arguments.get::<IndexKey>(&"candidates".into()) {
+            Some(candidates) => candidates.ref_array()?.num_records()?,
+            None => DEFAULT_NUM_CANDIDATES
+        };
+
+        // rank error of the mechanism, as derived from the effective privacy usage
+        let usage = spread_privacy_usage(&self.privacy_usage, 1)?.remove(0)
+            .actual_to_effective(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition)?;
+        check_confidence(self.confidence)?;
+        let rank_error = get_accuracy_scale(
+            "JointExp", 2, num_candidates, (1. - self.confidence) / 2.)? / get_epsilon(&usage)?;
+
+        let (alpha_lower, alpha_upper) = get_interval_alphas(
+            self.alpha, self.confidence, num_records, rank_error)?;
+
+        // both endpoints are released jointly, so the interval is never inverted
+        let mut updated_component = component.clone();
+        updated_component.variant = Some(proto::component::Variant::DpQuantiles(proto::DpQuantiles {
+            alphas: vec![alpha_lower, alpha_upper],
+            implementation: "JointExp".to_string(),
+            privacy_usage: self.privacy_usage.clone(),
+        }));
+        expansion.computation_graph.insert(component_id, updated_component);
+        expansion.traversal.push(component_id);
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpQuantileCi {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPQuantileCI".to_string(),
+            variables: serde_json::json!(variable_names.cloned()
+                .unwrap_or_else(Vec::new).iter()
+                .map(|v| v.to_string()).collect::<Vec<String>>()),
+            release_info: interval_to_json(release)?,
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "Order statistics".to_string(),
+                cite: "Gillenwater, Joseph and Kulesza (2021)".to_string(),
+                mechanism: "Exponential".to_string(),
+                argument: serde_json::json!({
+                    "alpha": self.alpha,
+                    "confidence": self.confidence,
+                    "n": data_property.num_records
+                }),
+            },
+        }]))
+    }
+}
+
+/// Quantiles of the data to release as the endpoints of a confidence interval for a population quantile.
+///
+/// The probability that the interval misses is split evenly between the sampling error and the mechanism.
+/// The sampling error of the rank of the population quantile among the records is binomial,
+/// and is bounded by its normal approximation, plus one record for the discreteness of the order statistics.
+/// The endpoints are widened further by the rank error of the mechanism.
+///
+/// # Arguments
+/// * `alpha` - Desired quantile, defined on `[0,1]`.
+/// * `confidence` - Probability that the interval contains the population quantile.
+/// * `num_records` - Number of records in the data.
+/// * `rank_error` - Rank error of the mechanism in records, which holds with probability `(1 + confidence) / 2`.
+pub fn get_interval_alphas(alpha: f64, confidence: f64, num_records: i64, rank_error: f64) -> Result<(f64, f64)> {
+    if !(0. ..=1.).contains(&alpha) {
+        return Err("alpha: must be within [0, 1]".into())
+    }
+    check_confidence(confidence)?;
+    if num_records < 1 {
+        return Err("data: must contain at least one record".into())
+    }
+    let num_records = num_records as f64;
+
+    // each endpoint misses with probability (1 - confidence) / 4 due to sampling
+    let z = 2.0_f64.sqrt() * erf::erf_inv(1. - (1. - confidence) / 2.);
+    let half_width = z * (alpha * (1. - alpha) / num_records).sqrt() + (rank_error + 1.) / num_records;
+
+    Ok(((alpha - half_width).max(0.), (alpha + half_width).min(1.)))
+}
+
+#[cfg(test)]
+mod test_dp_quantile_ci {
+    use crate::components::dp_quantile_ci::get_interval_alphas;
+
+    #[test]
+    fn test_interval_alphas() {
+        let (lower, upper) = get_interval_alphas(0.5, 0.95, 10000, 0.).unwrap();
+        // z of the 1 - 0.05 / 4 quantile of the standard normal is about 2.241
+        assert!((0.5 - lower - (2.241 * 0.005 + 0.0001)).abs() < 1e-4);
+        assert!((upper - 0.5 - (0.5 - lower)).abs() < 1e-12);
+
+        // the rank error of the mechanism widens the interval
+        let (wide_lower, wide_upper) = get_interval_alphas(0.5, 0.95, 10000, 100.).unwrap();
+        assert!((lower - wide_lower - 0.01).abs() < 1e-12);
+        assert!((wide_upper - upper - 0.01).abs() < 1e-12);
+
+        // the endpoints are valid quantiles
+        assert_eq!(get_interval_alphas(0.99, 0.95, 100, 10.).unwrap().1, 1.);
+        assert!(get_interval_alphas(1.5, 0.95, 100, 10.).is_err());
+        assert!(get_interval_alphas(0.5, 0.95, 0, 10.).is_err());
+    }
+}
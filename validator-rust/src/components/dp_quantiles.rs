@@ -0,0 +1,419 @@
+use indexmap::map::IndexMap;
+use ndarray::Array;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Accuracy, Component, Expandable, Mechanism, Report};
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::privacy::{get_epsilon, get_pure_rdp_curves, privacy_usage_check, pure_rdp_curve, spread_privacy_usage};
+
+/// Number of evenly spaced candidates used when candidates are not provided.
+pub const DEFAULT_NUM_CANDIDATES: usize = 1000;
+
+/// Sensitivity of the utility functions of both implementations.
+///
+/// Substituting a record moves it between two gaps, and adding or removing a record
+/// changes one gap count by one and the target gap sizes by at most one in total.
+pub const QUANTILES_SENSITIVITY: f64 = 2.;
+
+impl Component for proto::DpQuantiles {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into())
+        }
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated().map_err(prepend("data:"))?;
+        data_property.assert_is_not_empty().map_err(prepend("data:"))?;
+
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        if data_property.nullity {
+            return Err("data: may not contain nullity. Impute first.".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must have exactly one column".into())
+        }
+        // bounds are needed to construct the default candidates
+        data_property.lower_float().map_err(prepend("data:"))?;
+        data_property.upper_float().map_err(prepend("data:"))?;
+
+        check_alphas(&self.alphas)?;
+        get_num_levels(&self.implementation, self.alphas.len())?;
+
+        if let Some(candidates) = public_arguments.get::<IndexKey>(&"candidates".into()) {
+            let candidates = candidates.ref_array()?.clone().vec_float(None)
+                .map_err(prepend("candidates:"))?;
+            check_candidates(&candidates)?;
+        } else if properties.contains_key::<IndexKey>(&"candidates".into()) {
+            return Err("candidates: must be public".into())
+        }
+
+        if self.privacy_usage.len() != 1 {
+            return Err("privacy_usage: must be of length one".into())
+        }
+
+        let warnings = privacy_usage_check(
+            &self.privacy_usage[0],
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        Ok(Warnable(ArrayProperties {
+            num_records: Some(self.alphas.len() as i64),
+            num_columns: Some(1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type: DataType::Float,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(1),
+            group_id: data_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+        }.into(), warnings))
+    }
+}
+
+impl Expandable for proto::DpQuantiles {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy definition must be defined")?;
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // update the privacy usage
+        let mut updated_component = component.clone();
+        if let Some(proto::component::Variant::DpQuantiles(variant)) = &mut updated_component.variant {
+            variant.privacy_usage = self.privacy_usage.iter()
+                .map(|usage| usage.actual_to_effective(
+                    data_property.sample_proportion.unwrap_or(1.),
+                    data_property.c_stability,
+                    privacy_definition))
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+        } else { return Err(Error::from("Variant must be defined")) }
+
+        if !properties.contains_key::<IndexKey>(&"candidates".into()) {
+            let candidates = get_default_candidates(
+                data_property.lower_float()?[0],
+                data_property.upper_float()?[0],
+                DEFAULT_NUM_CANDIDATES)?;
+
+            maximum_id += 1;
+            let id_candidates = maximum_id;
+            let (patch_node, release) = get_literal(
+                Array::from(candidates).into_dyn().into(), component.submission)?;
+            expansion.computation_graph.insert(id_candidates, patch_node);
+            expansion.properties.insert(id_candidates, infer_property(&release.value, None, id_candidates)?);
+            expansion.releases.insert(id_candidates, release);
+            updated_component.insert_argument(&"candidates".into(), id_candidates);
+        }
+
+        expansion.computation_graph.insert(component_id, updated_component);
+
+        Ok(expansion)
+    }
+}
+
+impl Mechanism for proto::DpQuantiles {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
+}
+
+impl Accuracy for proto::DpQuantiles {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let num_candidates = get_num_candidates(&public_arguments)?;
+
+        // every quantile is released under the same budget, so the least accurate requirement binds
+        let epsilon = accuracies.values.iter()
+            .map(|accuracy| get_accuracy_scale(
+                &self.implementation, self.alphas.len(), num_candidates, accuracy.alpha)
+                .map(|scale| scale / accuracy.value))
+            .collect::<Result<Vec<f64>>>()?.into_iter()
+            .fold(0., f64::max);
+
+        Ok(Some(vec![proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon,
+                delta: 0.,
+            }))
+        }]))
+    }
+
+    /// With probability 1 - alpha, the rank error of every quantile is within the accuracy of the best candidates.
+    ///
+    /// Accuracy is measured in number of records.
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        let num_candidates = get_num_candidates(&public_arguments)?;
+
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+
+        let value = get_accuracy_scale(
+            &self.implementation, self.alphas.len(), num_candidates, alpha)? / epsilon;
+
+        Ok(Some(self.alphas.iter()
+            .map(|_| proto::Accuracy { value, alpha })
+            .collect()))
+    }
+}
+
+impl Report for proto::DpQuantiles {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let num_candidates = get_num_candidates(&public_arguments).ok();
+
+        let variables = match variable_names {
+            Some(names) => names.iter().map(|name| name.to_string()).collect(),
+            None => vec!["[Unknown]".to_string()]
+        };
+
+        let cite = match self.implementation.to_lowercase().as_str() {
+            "recursive" => "Kaplan, Schnapp and Stemmer (2022)",
+            _ => "Gillenwater, Joseph and Kulesza (2021)"
+        };
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPQuantiles".to_string(),
+            variables: serde_json::json!(variables),
+            release_info: value_to_json(release)?,
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: self.implementation.clone(),
+                cite: cite.to_string(),
+                mechanism: "Exponential".to_string(),
+                argument: serde_json::json!({
+                    "alphas": self.alphas,
+                    "num_candidates": num_candidates
+                }),
+            },
+        }]))
+    }
+}
+
+fn check_alphas(alphas: &[f64]) -> Result<()> {
+    if alphas.is_empty() {
+        return Err("alphas: must contain at least one quantile".into())
+    }
+    if alphas.iter().any(|alpha| !(0. ..=1.).contains(alpha)) {
+        return Err("alphas: each alpha must be within [0, 1]".into())
+    }
+    if alphas.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err("alphas: must be sorted in increasing order".into())
+    }
+    Ok(())
+}
+
+fn check_candidates(candidates: &[f64]) -> Result<()> {
+    if candidates.is_empty() {
+        return Err("candidates: must contain at least one value".into())
+    }
+    if candidates.iter().any(|v| !v.is_finite()) {
+        return Err("candidates: must be finite".into())
+    }
+    if candidates.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err("candidates: must be sorted in increasing order".into())
+    }
+    Ok(())
+}
+
+fn get_num_candidates(public_arguments: &IndexMap<base::IndexKey, &Value>) -> Result<usize> {
+    let candidates = public_arguments.get::<IndexKey>(&"candidates".into())
+        .ok_or_else(|| Error::from("candidates: missing"))?;
+    Ok(candidates.ref_array()?.num_records()?)
+}
+
+/// Evenly spaced candidates spanning [lower, upper], inclusive.
+///
+/// # Arguments
+/// * `lower` - Lower bound of the data.
+/// * `upper` - Upper bound of the data.
+/// * `num_candidates` - Number of candidates to return.
+pub fn get_default_candidates(lower: f64, upper: f64, num_candidates: usize) -> Result<Vec<f64>> {
+    if lower > upper {
+        return Err("lower may not be greater than upper".into())
+    }
+    if num_candidates < 2 {
+        return Err("num_candidates: must be at least two".into())
+    }
+    let step = (upper - lower) / (num_candidates - 1) as f64;
+    Ok((0..num_candidates)
+        .map(|i| if i == num_candidates - 1 { upper } else { lower + step * i as f64 })
+        .collect())
+}
+
+/// Number of levels of recursion the implementation splits the budget over.
+///
+/// `JointExp` releases all quantiles with one mechanism.
+/// `Recursive` releases the middle quantile at each level, so ceil(log2(m + 1)) levels are needed for m quantiles.
+///
+/// # Arguments
+/// * `implementation` - One of `JointExp` or `Recursive`.
+/// * `num_alphas` - Number of quantiles to release.
+pub fn get_num_levels(implementation: &str, num_alphas: usize) -> Result<usize> {
+    match implementation.to_lowercase().as_str() {
+        "jointexp" => Ok(1),
+        "recursive" => {
+            let mut num_levels = 0;
+            while (1 << num_levels) <= num_alphas {
+                num_levels += 1;
+            }
+            Ok(num_levels)
+        }
+        _ => Err("implementation: must be one of [JointExp, Recursive]".into())
+    }
+}
+
+/// Product of the accuracy in records and epsilon.
+///
+/// `JointExp` is an exponential mechanism over the C(k + m - 1, m) non-decreasing m-tuples of k candidates,
+/// whose utility bounds the rank error of every quantile.
+/// Gillenwater, Joseph and Kulesza (2021), Differentially Private Quantiles
+///
+/// `Recursive` makes m selections from at most k candidates, each with epsilon / levels,
+/// and errors accumulate over the levels of recursion.
+/// Kaplan, Schnapp and Stemmer (2022), Differentially Private Approximate Quantiles
+///
+/// # Arguments
+/// * `implementation` - One of `JointExp` or `Recursive`.
+/// * `num_alphas` - Number of quantiles to release.
+/// * `num_candidates` - Number of candidates each quantile is selected from.
+/// * `alpha` - Probability that the bound fails.
+pub fn get_accuracy_scale(implementation: &str, num_alphas: usize, num_candidates: usize, alpha: f64) -> Result<f64> {
+    if alpha <= 0. || alpha >= 1. {
+        return Err("alpha: must be within (0, 1)".into())
+    }
+    if num_candidates == 0 {
+        return Err("candidates: must contain at least one value".into())
+    }
+    let num_levels = get_num_levels(implementation, num_alphas)?;
+
+    Ok(match implementation.to_lowercase().as_str() {
+        "jointexp" => {
+            let ln_num_outcomes: f64 = (1..=num_alphas)
+                .map(|i| ((num_candidates - 1 + i) as f64 / i as f64).ln())
+                .sum();
+            2. * QUANTILES_SENSITIVITY * (ln_num_outcomes - alpha.ln())
+        }
+        _ => {
+            let num_levels = num_levels as f64;
+            2. * QUANTILES_SENSITIVITY * num_levels.powi(2)
+                * ((num_alphas * num_candidates) as f64 / alpha).ln()
+        }
+    })
+}
+
+#[cfg(test)]
+mod test_dp_quantiles {
+    use crate::components::dp_quantiles::{get_accuracy_scale, get_default_candidates, get_num_levels};
+
+    #[test]
+    fn test_default_candidates() {
+        let candidates = get_default_candidates(0., 1., 5).unwrap();
+        assert_eq!(candidates, vec![0., 0.25, 0.5, 0.75, 1.]);
+
+        assert!(get_default_candidates(1., 0., 5).is_err());
+    }
+
+    #[test]
+    fn test_num_levels() {
+        assert_eq!(get_num_levels("JointExp", 9).unwrap(), 1);
+        assert_eq!(get_num_levels("Recursive", 1).unwrap(), 1);
+        assert_eq!(get_num_levels("Recursive", 3).unwrap(), 2);
+        assert_eq!(get_num_levels("Recursive", 4).unwrap(), 3);
+        assert!(get_num_levels("Bisection", 1).is_err());
+    }
+
+    #[test]
+    fn test_accuracy_scale() {
+        // a single quantile over k candidates is the exponential mechanism over k outcomes
+        let scale = get_accuracy_scale("JointExp", 1, 100, 0.05).unwrap();
+        assert!((scale - 4. * (100. / 0.05_f64).ln()).abs() < 1e-9);
+
+        // more quantiles require more epsilon for the same accuracy
+        assert!(get_accuracy_scale("JointExp", 9, 100, 0.05).unwrap() > scale);
+        assert!(get_accuracy_scale("Recursive", 9, 100, 0.05).unwrap() > scale);
+    }
+}
@@ -0,0 +1,97 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto};
+use crate::base::{IndexKey, NodeProperties, Value};
+use crate::components::{Expandable, Report};
+use crate::errors::*;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+
+impl Expandable for proto::DpRangeQuery {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        _properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let argument_ids = component.arguments();
+
+        // consistent histogram, released once for every query
+        maximum_id += 1;
+        let id_histogram = maximum_id;
+        let mut histogram_arguments = indexmap!["data".into() => *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?];
+        argument_ids.get::<IndexKey>(&"categories".into())
+            .map(|v| histogram_arguments.insert("categories".into(), *v));
+
+        expansion.computation_graph.insert(id_histogram, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(histogram_arguments)),
+            variant: Some(proto::component::Variant::DpHierarchicalHistogram(proto::DpHierarchicalHistogram {
+                branching: self.branching,
+                mechanism: self.mechanism.clone(),
+                privacy_usage: self.privacy_usage.clone(),
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_histogram);
+
+        // range queries or cdf
+        let mut query_arguments = indexmap!["data".into() => id_histogram];
+        argument_ids.get::<IndexKey>(&"ranges".into())
+            .map(|v| query_arguments.insert("ranges".into(), *v));
+
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(query_arguments)),
+            variant: Some(proto::component::Variant::RangeQuery(proto::RangeQuery {})),
+            omit: component.omit,
+            submission: component.submission,
+        });
+        expansion.traversal.push(component_id);
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpRangeQuery {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let ranges = public_arguments.get::<IndexKey>(&"ranges".into())
+            .map(|ranges| value_to_json(ranges))
+            .transpose()?;
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPRangeQuery".to_string(),
+            variables: serde_json::json!(variable_names.cloned()
+                .unwrap_or_else(Vec::new).iter()
+                .map(|v| v.to_string()).collect::<Vec<String>>()),
+            release_info: value_to_json(release)?,
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: if ranges.is_some() { "range query" } else { "cdf" }.to_string(),
+                cite: "Hay, Rastogi, Miklau and Suciu (2010)".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "branching": self.branching,
+                    "ranges": ranges
+                }),
+            },
+        }]))
+    }
+}
@@ -0,0 +1,40 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto};
+use crate::base::{IndexKey, NodeProperties, Value};
+use crate::components::{Expandable, Report};
+use crate::components::standardized_moment::{expand_standardized_moment, summarize_standardized_moment};
+use crate::errors::*;
+use crate::utilities::json::JSONRelease;
+
+impl Expandable for proto::DpSkewness {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_standardized_moment(
+            privacy_definition, 3, &self.mechanism, &self.privacy_usage,
+            component, properties, component_id, maximum_id)
+    }
+}
+
+impl Report for proto::DpSkewness {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        summarize_standardized_moment(
+            "DPSkewness", 3, &self.mechanism, &self.privacy_usage,
+            node_id, component, properties, release, variable_names)
+    }
+}
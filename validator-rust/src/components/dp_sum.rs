@@ -1,10 +1,10 @@
 use indexmap::map::IndexMap;
 
 use crate::{base, proto};
-use crate::base::{Array, ArrayProperties, DataType, IndexKey, NodeProperties, Value};
+use crate::base::{Array, IndexKey, NodeProperties, Value};
 use crate::components::{Expandable, Report};
 use crate::errors::*;
-use crate::utilities::{array::get_ith_column, prepend, privacy::spread_privacy_usage};
+use crate::utilities::{array::get_ith_column, get_mechanism, prepend, privacy::spread_privacy_usage};
 use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
 
 impl Expandable for proto::DpSum {
@@ -40,8 +40,7 @@ impl Expandable for proto::DpSum {
 
         let privacy_definition = privacy_definition.as_ref()
             .ok_or_else(|| Error::from("privacy_definition must be known"))?;
-        let mechanism = get_mechanism(
-            &data_property, &self.mechanism, privacy_definition.protect_floating_point)?;
+        let mechanism = get_mechanism(&data_property.data_type, &self.mechanism, privacy_definition)?;
 
         if mechanism.as_str() == "simplegeometric" {
             let sum_max_id = *argument_ids.get::<IndexKey>(&"upper".into())
@@ -78,6 +77,12 @@ impl Expandable for proto::DpSum {
                     privacy_usage: self.privacy_usage.clone(),
                     analytic: true
                 }),
+                "discretegaussian" => proto::component::Variant::DiscreteGaussianMechanism(proto::DiscreteGaussianMechanism {
+                    privacy_usage: self.privacy_usage.clone()
+                }),
+                "discretelaplace" => proto::component::Variant::DiscreteLaplaceMechanism(proto::DiscreteLaplaceMechanism {
+                    privacy_usage: self.privacy_usage.clone()
+                }),
                 "snapping" => {
                     argument_ids.get::<IndexKey>(&"lower".into())
                         .map(|lower| arguments.insert("lower".into(), *lower));
@@ -142,7 +147,7 @@ impl Report for proto::DpSum {
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
                 accuracy: None,
                 submission: component.submission,
-                node_id,
+                node_id: Some(node_id),
                 postprocess: false,
                 algorithm_info: AlgorithmInfo {
                     name: "".to_string(),
@@ -161,18 +166,3 @@ impl Report for proto::DpSum {
         Ok(Some(releases))
     }
 }
-
-fn get_mechanism(data_property: &ArrayProperties, mechanism: &str, protect_floating_point: bool) -> Result<String> {
-    let mechanism = mechanism.to_lowercase();
-
-    Ok(if mechanism == "automatic" {
-        match data_property.data_type {
-            DataType::Int => "simplegeometric",
-            DataType::Float => if protect_floating_point { "snapping" } else { "laplace" },
-            _ => return Err("cannot sum non-integer data".into())
-        }.to_string()
-    } else {
-        mechanism
-    })
-
-}
\ No newline at end of file
@@ -7,7 +7,7 @@ use crate::components::{Component, Expandable, Mechanism, Sensitivity};
 use crate::errors::*;
 use crate::utilities::{get_literal, prepend, check_sensitivity_properties};
 use crate::utilities::inference::infer_property;
-use crate::utilities::privacy::privacy_usage_check;
+use crate::utilities::privacy::{exponential_rdp_curve, get_pure_rdp_curves, privacy_usage_check};
 
 impl Component for proto::ExponentialMechanism {
     fn propagate_property(
@@ -17,166 +17,260 @@ impl Component for proto::ExponentialMechanism {
         properties: base::NodeProperties,
         node_id: u32,
     ) -> Result<Warnable<ValueProperties>> {
-        let privacy_definition = privacy_definition.as_ref()
-            .ok_or_else(|| "privacy_definition must be defined")?;
+        propagate_selection_property(&self.privacy_usage, privacy_definition, properties, node_id)
+    }
+}
 
-        if privacy_definition.group_size == 0 {
-            return Err("group size must be greater than zero".into());
-        }
+impl Expandable for proto::ExponentialMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_selection_mechanism(
+            &self.privacy_usage, privacy_definition, component,
+            properties, component_id, maximum_id)
+    }
+}
 
-        let utilities_property: ArrayProperties = properties
-            .get(&IndexKey::from("utilities"))
+impl Mechanism for proto::ExponentialMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"utilities".into())
             .ok_or("utilities: missing")?.array()
-            .map_err(prepend("utilities:"))?.clone();
+            .map_err(prepend("utilities:"))?;
 
-        if utilities_property.data_type != DataType::Float {
-            return Err("utilities: data_type must be float".into());
-        }
+        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
 
-        let candidates_property: ArrayProperties = properties
-            .get(&IndexKey::from("candidates"))
-            .ok_or_else(|| Error::from("candidates: missing"))?.array()?.clone();
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"utilities".into())
+            .ok_or("utilities: missing")?.array()
+            .map_err(prepend("utilities:"))?;
 
-        if !candidates_property.releasable {
-            return Err(Error::from("candidates: must be public"))
-        }
+        get_pure_rdp_curves(
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            privacy_definition, data_property, orders, exponential_rdp_curve).map(Some)
+    }
+}
 
-        if utilities_property.num_records()? != candidates_property.num_records()? {
-            return Err("utilities and candidates must share the same number of records".into());
-        }
-        if utilities_property.num_columns()? != candidates_property.num_columns()? {
-            return Err("utilities and candidates must share the same number of columns".into());
-        }
+/// Derive properties of the release of a selection mechanism.
+///
+/// Selection mechanisms release one of the public `candidates`, scored by private `utilities`.
+pub(crate) fn propagate_selection_property(
+    privacy_usage: &[proto::PrivacyUsage],
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    properties: base::NodeProperties,
+    node_id: u32,
+) -> Result<Warnable<ValueProperties>> {
+    let privacy_definition = privacy_definition.as_ref()
+        .ok_or_else(|| "privacy_definition must be defined")?;
 
-        if utilities_property.num_columns()? != 1 {
-            return Err(Error::from("exponential mechanism only works with one column at a time"))
-        }
+    if privacy_definition.group_size == 0 {
+        return Err("group size must be greater than zero".into());
+    }
 
-        let aggregator = utilities_property.aggregator.clone()
-            .ok_or_else(|| Error::from("aggregator: missing"))?;
+    let utilities_property: ArrayProperties = properties
+        .get(&IndexKey::from("utilities"))
+        .ok_or("utilities: missing")?.array()
+        .map_err(prepend("utilities:"))?.clone();
 
-        // sensitivity must be computable
-        let sensitivity_values = aggregator.component.compute_sensitivity(
-            privacy_definition,
-            &aggregator.properties,
-            &SensitivitySpace::Exponential)?;
+    if utilities_property.data_type != DataType::Float {
+        return Err("utilities: data_type must be float".into());
+    }
 
-        // make sure sensitivities are an f64 array
-        sensitivity_values.array()?.float()?;
-
-        let output_property = ArrayProperties {
-            num_records: Some(1),
-            num_columns: Some(1),
-            nullity: false,
-            releasable: true,
-            c_stability: 1,
-            aggregator: None,
-            nature: None,
-            data_type: candidates_property.data_type.clone(),
-            dataset_id: None,
-            node_id: node_id as i64,
-            is_not_empty: true,
-            dimensionality: Some(0),
-            group_id: utilities_property.group_id,
-            naturally_ordered: true,
-            sample_proportion: None
-        };
+    let candidates_property: ArrayProperties = properties
+        .get(&IndexKey::from("candidates"))
+        .ok_or_else(|| Error::from("candidates: missing"))?.array()?.clone();
 
-        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
-            .fold1(|l, r| l? + r?)
-            .ok_or_else(|| "privacy_usage: must be defined")??;
+    if !candidates_property.releasable {
+        return Err(Error::from("candidates: must be public"))
+    }
 
-        let warnings = privacy_usage_check(
-            &privacy_usage,
-            output_property.num_records,
-            privacy_definition.strict_parameter_checks)?;
+    if utilities_property.num_records()? != candidates_property.num_records()? {
+        return Err("utilities and candidates must share the same number of records".into());
+    }
+    if utilities_property.num_columns()? != candidates_property.num_columns()? {
+        return Err("utilities and candidates must share the same number of columns".into());
+    }
 
-        Ok(Warnable(output_property.into(), warnings))
+    if utilities_property.num_columns()? != 1 {
+        return Err(Error::from("selection mechanisms only work with one column at a time"))
     }
+
+    let aggregator = utilities_property.aggregator.clone()
+        .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+    // sensitivity must be computable
+    let sensitivity_values = aggregator.component.compute_sensitivity(
+        privacy_definition,
+        &aggregator.properties,
+        &SensitivitySpace::Exponential)?;
+
+    // make sure sensitivities are an f64 array
+    sensitivity_values.array()?.float()?;
+
+    let output_property = ArrayProperties {
+        num_records: Some(1),
+        num_columns: Some(1),
+        nullity: false,
+        releasable: true,
+        c_stability: 1,
+        aggregator: None,
+        nature: None,
+        data_type: candidates_property.data_type.clone(),
+        dataset_id: None,
+        node_id: node_id as i64,
+        is_not_empty: true,
+        dimensionality: Some(0),
+        group_id: utilities_property.group_id,
+        naturally_ordered: true,
+        sample_proportion: None
+    };
+
+    let privacy_usage = privacy_usage.iter().cloned().map(Ok)
+        .fold1(|l, r| l? + r?)
+        .ok_or_else(|| "privacy_usage: must be defined")??;
+
+    let warnings = privacy_usage_check(
+        &privacy_usage,
+        output_property.num_records,
+        privacy_definition.strict_parameter_checks)?;
+
+    Ok(Warnable(output_property.into(), warnings))
 }
 
-impl Expandable for proto::ExponentialMechanism {
-    fn expand_component(
-        &self,
-        privacy_definition: &Option<proto::PrivacyDefinition>,
-        component: &proto::Component,
-        _public_arguments: &IndexMap<IndexKey, &Value>,
-        properties: &base::NodeProperties,
-        component_id: u32,
-        mut maximum_id: u32,
-    ) -> Result<base::ComponentExpansion> {
-        let mut expansion = base::ComponentExpansion::default();
+/// Expand a selection mechanism by converting its privacy usage to the effective usage, and attaching its sensitivity.
+pub(crate) fn expand_selection_mechanism(
+    privacy_usage: &[proto::PrivacyUsage],
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    component: &proto::Component,
+    properties: &base::NodeProperties,
+    component_id: u32,
+    mut maximum_id: u32,
+) -> Result<base::ComponentExpansion> {
+    let mut expansion = base::ComponentExpansion::default();
 
-        let utilities_property: ArrayProperties = properties.get::<IndexKey>(&"utilities".into())
-            .ok_or("utilities: missing")?.array()
-            .map_err(prepend("utilities:"))?.clone();
+    let utilities_property: ArrayProperties = properties.get::<IndexKey>(&"utilities".into())
+        .ok_or("utilities: missing")?.array()
+        .map_err(prepend("utilities:"))?.clone();
 
-        let privacy_definition = privacy_definition.as_ref()
-            .ok_or_else(|| "privacy definition must be defined")?;
+    let privacy_definition = privacy_definition.as_ref()
+        .ok_or_else(|| "privacy definition must be defined")?;
 
-        // noising
-        let mut noise_component = component.clone();
+    // noising
+    let mut noise_component = component.clone();
 
-        if self.privacy_usage.len() != 1 {
-            return Err(Error::from("privacy usage must be of length one"));
-        }
+    if privacy_usage.len() != 1 {
+        return Err(Error::from("privacy usage must be of length one"));
+    }
+
+    // update the privacy usage
+    let effective_usage = vec![privacy_usage[0].actual_to_effective(
+        utilities_property.sample_proportion.unwrap_or(1.),
+        utilities_property.c_stability,
+        privacy_definition)?];
+
+    match &mut noise_component.variant {
+        Some(proto::component::Variant::ExponentialMechanism(variant)) =>
+            variant.privacy_usage = effective_usage,
+        Some(proto::component::Variant::ReportNoisyMax(variant)) =>
+            variant.privacy_usage = effective_usage,
+        Some(proto::component::Variant::PermuteAndFlip(variant)) =>
+            variant.privacy_usage = effective_usage,
+        // this case should never happen
+        _ => return Err(Error::from("Variant must be a selection mechanism"))
+    }
 
-        // update the privacy usage
-        if let Some(proto::component::Variant::ExponentialMechanism(variant)) = &mut noise_component.variant {
-            variant.privacy_usage = vec![self.privacy_usage[0].actual_to_effective(
-                utilities_property.sample_proportion.unwrap_or(1.),
-                utilities_property.c_stability,
-                privacy_definition.group_size)?];
-            // this case should never happen
-        } else { return Err(Error::from("Variant must be defined")) }
-
-        if let Some(sensitivity_property) = properties.get(&IndexKey::from("sensitivity")) {
-            if privacy_definition.protect_sensitivity {
-                return Err(Error::from("custom sensitivities may only be passed if protect_sensitivity is disabled"))
-            }
-            check_sensitivity_properties(sensitivity_property.array()?, &utilities_property)?;
-        } else {
-            let aggregator = utilities_property.aggregator
-                .ok_or_else(|| Error::from("aggregator: missing"))?;
-
-            let sensitivity = aggregator.component.compute_sensitivity(
-                privacy_definition,
-                &aggregator.properties,
-                &SensitivitySpace::Exponential)?;
-
-            // exponential sensitivity cannot currently be modified by lipschitz constants
-
-            maximum_id += 1;
-            let id_sensitivity = maximum_id;
-            let (patch_node, release) = get_literal(sensitivity, component.submission)?;
-            expansion.computation_graph.insert(id_sensitivity, patch_node);
-            expansion.properties.insert(id_sensitivity, infer_property(&release.value, None, id_sensitivity)?);
-            expansion.releases.insert(id_sensitivity, release);
-            noise_component.insert_argument(&"sensitivity".into(), id_sensitivity);
+    if let Some(sensitivity_property) = properties.get(&IndexKey::from("sensitivity")) {
+        if privacy_definition.protect_sensitivity {
+            return Err(Error::from("custom sensitivities may only be passed if protect_sensitivity is disabled"))
         }
+        check_sensitivity_properties(sensitivity_property.array()?, &utilities_property)?;
+    } else {
+        let aggregator = utilities_property.aggregator
+            .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+        let sensitivity = aggregator.component.compute_sensitivity(
+            privacy_definition,
+            &aggregator.properties,
+            &SensitivitySpace::Exponential)?;
 
-        expansion.computation_graph.insert(component_id, noise_component);
+        // exponential sensitivity cannot currently be modified by lipschitz constants
 
-        Ok(expansion)
+        maximum_id += 1;
+        let id_sensitivity = maximum_id;
+        let (patch_node, release) = get_literal(sensitivity, component.submission)?;
+        expansion.computation_graph.insert(id_sensitivity, patch_node);
+        expansion.properties.insert(id_sensitivity, infer_property(&release.value, None, id_sensitivity)?);
+        expansion.releases.insert(id_sensitivity, release);
+        noise_component.insert_argument(&"sensitivity".into(), id_sensitivity);
     }
+
+    expansion.computation_graph.insert(component_id, noise_component);
+
+    Ok(expansion)
 }
 
-impl Mechanism for proto::ExponentialMechanism {
-    fn get_privacy_usage(
-        &self,
-        privacy_definition: &proto::PrivacyDefinition,
-        release_usage: Option<&Vec<proto::PrivacyUsage>>,
-        properties: &NodeProperties,
-    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
-        let data_property = properties.get::<IndexKey>(&"data".into())
-            .ok_or("data: missing")?.array()
-            .map_err(prepend("data:"))?;
+/// Retrieve the sensitivity of the utilities and the number of candidates, for the accuracy of a selection mechanism.
+pub(crate) fn get_selection_accuracy_arguments(
+    public_arguments: &IndexMap<base::IndexKey, &Value>
+) -> Result<(f64, f64)> {
+    let sensitivity = public_arguments.get(&IndexKey::from("sensitivity"))
+        .ok_or_else(|| Error::from("sensitivity: missing in accuracy"))?
+        .ref_array()?.clone().cast_float()?
+        .iter().copied().fold1(|l, r| l.max(r))
+        .ok_or_else(|| Error::from("sensitivity: must not be empty"))?;
 
-        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
-            .map(|usage| usage.effective_to_actual(
-                data_property.sample_proportion.unwrap_or(1.),
-                data_property.c_stability,
-                privacy_definition.group_size))
-            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    let num_candidates = public_arguments.get(&IndexKey::from("candidates"))
+        .ok_or_else(|| Error::from("candidates: missing in accuracy"))?
+        .ref_array()?.num_records()?;
+
+    Ok((sensitivity as f64, num_candidates as f64))
+}
+
+#[cfg(test)]
+mod test_exponential_mechanism {
+    use ndarray::arr1;
+
+    use crate::components::Mechanism;
+    use crate::proto;
+    use crate::proto::privacy_usage::{Distance, DistanceApproximate};
+    use crate::utilities::inference::infer_property;
+
+    #[test]
+    fn test_privacy_usage() {
+        let utilities = infer_property(&arr1(&[1., 2., 3.]).into_dyn().into(), None, 0).unwrap();
+        let candidates = infer_property(&arr1(&[1, 2, 3]).into_dyn().into(), None, 1).unwrap();
+        let usage = proto::PrivacyUsage {
+            distance: Some(Distance::Approximate(DistanceApproximate { epsilon: 0.5, delta: 0. }))
+        };
+        let mechanism = proto::ExponentialMechanism { privacy_usage: vec![usage.clone()] };
+        let privacy_definition = proto::PrivacyDefinition { group_size: 1, ..Default::default() };
+
+        // the mechanism has no data argument, so the usage is read from the properties of the utilities
+        let privacy_usage = mechanism.get_privacy_usage(
+            &privacy_definition, None,
+            &indexmap!["utilities".into() => utilities, "candidates".into() => candidates]).unwrap();
+        assert_eq!(privacy_usage, Some(vec![usage]));
     }
 }
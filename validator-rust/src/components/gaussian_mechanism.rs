@@ -9,7 +9,7 @@ use crate::components::{Accuracy, Mechanism, Sensitivity};
 use crate::components::{Component, Expandable};
 use crate::errors::*;
 use crate::utilities::{expand_mechanism, prepend};
-use crate::utilities::privacy::{get_delta, get_epsilon, privacy_usage_check, spread_privacy_usage};
+use crate::utilities::privacy::{gaussian_rdp_curve, get_delta, get_epsilon, privacy_usage_check, spread_privacy_usage, subsampled_gaussian_rdp_curve};
 
 impl Component for proto::GaussianMechanism {
     fn propagate_property(
@@ -57,18 +57,21 @@ impl Component for proto::GaussianMechanism {
             data_property.num_records,
             privacy_definition.strict_parameter_checks)?;
 
-        let epsilon = get_epsilon(&privacy_usage)?;
-        if !self.analytic && epsilon > 1.0 {
-            let message = Error::from(format!(
-                "Warning: A privacy parameter of epsilon = {} is in use. \
-                Privacy is only guaranteed for the Gaussian mechanism for epsilon between 0 and 1. \
-                Use the 'AnalyticGaussian' instead.", epsilon));
+        // the noise scale of zero-concentrated usages does not depend on epsilon or delta
+        if let Some(proto::privacy_usage::Distance::Approximate(_)) = privacy_usage.distance {
+            let epsilon = get_epsilon(&privacy_usage)?;
+            if !self.analytic && epsilon > 1.0 {
+                let message = Error::from(format!(
+                    "Warning: A privacy parameter of epsilon = {} is in use. \
+                    Privacy is only guaranteed for the Gaussian mechanism for epsilon between 0 and 1. \
+                    Use the 'AnalyticGaussian' instead.", epsilon));
 
-            return Err(message)
-        }
+                return Err(message)
+            }
 
-        if get_delta(&privacy_usage)? == 0.0 {
-            return Err("delta: may not be zero".into())
+            if get_delta(&privacy_usage)? == 0.0 {
+                return Err("delta: may not be zero".into())
+            }
         }
 
         data_property.releasable = true;
@@ -116,9 +119,43 @@ impl Mechanism for proto::GaussianMechanism {
             .map(|usage| usage.effective_to_actual(
                 data_property.sample_proportion.unwrap_or(1.),
                 data_property.c_stability,
-                privacy_definition.group_size))
+                privacy_definition))
             .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
     }
+
+    #[allow(clippy::float_cmp)]
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        let sample_proportion = data_property.sample_proportion.unwrap_or(1.);
+        let group_size = (data_property.c_stability * privacy_definition.group_size) as f64;
+
+        Some(release_usage.unwrap_or(&self.privacy_usage).iter()
+            .map(|usage| {
+                // noise scale relative to the sensitivity of the aggregate on groups of individuals
+                let sigma = get_gaussian_sigma(usage, 1., self.analytic)? / group_size;
+
+                if sample_proportion == 1. {
+                    return Ok(gaussian_rdp_curve(sigma, orders))
+                }
+                if group_size != 1. {
+                    return Err("Rényi curves of the subsampled gaussian mechanism require a c-stability and group size of one".into())
+                }
+                if privacy_definition.neighboring != proto::privacy_definition::Neighboring::AddRemove as i32 {
+                    return Err("Rényi curves of the subsampled gaussian mechanism require Poisson sampling, which is only used under add/remove neighboring".into())
+                }
+                subsampled_gaussian_rdp_curve(sigma, sample_proportion, orders)
+            })
+            .collect::<Result<Vec<Vec<f64>>>>()).transpose()
+    }
 }
 
 
@@ -137,23 +174,31 @@ impl Accuracy for proto::GaussianMechanism {
             .collect();
 
         let usages = spread_privacy_usage(&self.privacy_usage, sensitivities.len())?;
-        let delta = usages.iter().map(get_delta).collect::<Result<Vec<f64>>>()?;
-        let iter = izip!(sensitivities.into_iter(), accuracies.values.iter(), delta.into_iter());
 
-        use proto::privacy_usage::{Distance, DistanceApproximate};
-
-        Some(iter.map(|(sensitivity, accuracy, delta)| {
-            let sigma: f64 = if self.analytic {
-                return Err(Error::from("converting to privacy usage is not implemented for the analytic gaussian"))
-            } else {
-                (2.0 * (1.25 / delta).ln()).sqrt() * sensitivity as f64 / accuracy.value
-            };
+        use proto::privacy_usage::{Distance, DistanceApproximate, DistanceConcentrated};
 
+        Some(izip!(sensitivities.into_iter(), accuracies.values.iter(), usages.iter()).map(|(sensitivity, accuracy, usage)| {
             Ok(proto::PrivacyUsage {
-                distance: Some(Distance::Approximate(DistanceApproximate {
-                    epsilon: sigma * 2.0_f64.sqrt() * erf::erf_inv(1.0_f64 - accuracy.alpha),
-                    delta,
-                }))
+                distance: Some(match usage.distance.as_ref().ok_or_else(|| "distance must be defined")? {
+                    Distance::Approximate(DistanceApproximate { delta, .. }) => {
+                        let sigma: f64 = if self.analytic {
+                            return Err(Error::from("converting to privacy usage is not implemented for the analytic gaussian"))
+                        } else {
+                            (2.0 * (1.25 / delta).ln()).sqrt() * sensitivity as f64 / accuracy.value
+                        };
+
+                        Distance::Approximate(DistanceApproximate {
+                            epsilon: sigma * 2.0_f64.sqrt() * erf::erf_inv(1.0_f64 - accuracy.alpha),
+                            delta: *delta,
+                        })
+                    }
+                    Distance::Concentrated(_) => {
+                        let sigma = accuracy.value / (2.0_f64.sqrt() * erf::erf_inv(1.0_f64 - accuracy.alpha));
+                        Distance::Concentrated(DistanceConcentrated {
+                            rho: (sensitivity as f64 / sigma).powi(2) / 2.
+                        })
+                    }
+                })
             })
         }).collect()).transpose()
     }
@@ -172,25 +217,38 @@ impl Accuracy for proto::GaussianMechanism {
             .collect();
 
         let usages = spread_privacy_usage(&self.privacy_usage, sensitivities.len())?;
-        let epsilons = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
-        let deltas = usages.iter().map(get_delta).collect::<Result<Vec<f64>>>()?;
-        let iter = izip!(sensitivities.into_iter(), epsilons.into_iter(), deltas.into_iter());
-
-        Ok(Some(iter.map(|(sensitivity, epsilon, delta)| {
-            let sigma: f64 = if self.analytic {
-                get_analytic_gaussian_sigma(epsilon, delta, sensitivity as f64)
-            } else {
-                sensitivity as f64 * (2.0 * (1.25 / delta).ln()).sqrt() / epsilon
-            };
-
-            proto::Accuracy {
+
+        Some(sensitivities.into_iter().zip(usages.iter()).map(|(sensitivity, usage)| {
+            let sigma = get_gaussian_sigma(usage, sensitivity as f64, self.analytic)?;
+
+            Ok(proto::Accuracy {
                 value: sigma * 2.0_f64.sqrt() * erf::erf_inv(1.0_f64 - alpha),
                 alpha,
-            }
-        }).collect()))
+            })
+        }).collect()).transpose()
     }
 }
 
+/// Noise scale of the gaussian mechanism for a privacy usage
+///
+/// # Arguments
+/// * `usage` - Either an approximate or zero-concentrated privacy usage.
+/// * `sensitivity` - Upper bound on the L2 sensitivity of the function you want to privatize.
+/// * `analytic` - Whether to use the analytic gaussian for approximate privacy usages.
+pub fn get_gaussian_sigma(usage: &proto::PrivacyUsage, sensitivity: f64, analytic: bool) -> Result<f64> {
+    use proto::privacy_usage::{Distance, DistanceApproximate, DistanceConcentrated};
+
+    Ok(match usage.distance.as_ref().ok_or_else(|| "distance must be defined")? {
+        Distance::Approximate(DistanceApproximate { epsilon, delta }) => if analytic {
+            get_analytic_gaussian_sigma(*epsilon, *delta, sensitivity)
+        } else {
+            sensitivity * (2.0 * (1.25 / delta).ln()).sqrt() / epsilon
+        },
+        // a gaussian with sigma = sensitivity / sqrt(2 rho) satisfies rho-zCDP
+        Distance::Concentrated(DistanceConcentrated { rho }) => sensitivity / (2. * rho).sqrt()
+    })
+}
+
 /// Integrate gaussian from -inf to t
 /// P(N(0,1)≤t)
 ///
@@ -0,0 +1,159 @@
+use indexmap::map::IndexMap;
+use ndarray::{arr1, Array};
+
+use crate::{base, Integer, proto, Warnable};
+use crate::base::{AggregatorProperties, DataType, IndexKey, Jagged, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Sensitivity};
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+
+impl Component for proto::HierarchicalHistogram {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated()?;
+
+        if data_property.data_type == DataType::Unknown {
+            return Err("data_type must be known".into())
+        }
+
+        let categories = data_property.categories()?;
+        if categories.num_columns() != 1 {
+            return Err("data must contain one column".into())
+        }
+        let num_leaves = categories.num_records()[0] as usize;
+        if num_leaves < 2 {
+            return Err("categories: must contain at least two categories".into())
+        }
+
+        let num_nodes = get_num_nodes(get_num_levels(num_leaves, self.branching)?, self.branching);
+
+        // save a snapshot of the state when aggregating
+        data_property.aggregator = Some(AggregatorProperties::new(
+            proto::component::Variant::HierarchicalHistogram(self.clone()),
+            properties, 1));
+
+        data_property.nature = Some(Nature::Continuous(NatureContinuous {
+            lower: Vector1DNull::Int(vec![Some(0)]),
+            upper: Vector1DNull::Int(vec![data_property.num_records]),
+        }));
+        data_property.data_type = DataType::Int;
+        data_property.num_records = Some(num_nodes as i64);
+        data_property.dataset_id = Some(node_id as i64);
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+impl Expandable for proto::HierarchicalHistogram {
+    /// Add the categories of the data as an argument, so that the leaves are ordered by category
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        if !properties.contains_key::<IndexKey>(&"categories".into()) {
+            let categories = properties.get::<IndexKey>(&"data".into())
+                .ok_or("data: missing")?.array()?.categories()?;
+            let value = match categories {
+                Jagged::Int(jagged) => arr1(&jagged[0]).into_dyn().into(),
+                Jagged::Float(jagged) => arr1(&jagged[0]).into_dyn().into(),
+                Jagged::Bool(jagged) => arr1(&jagged[0]).into_dyn().into(),
+                Jagged::Str(jagged) => arr1(&jagged[0]).into_dyn().into(),
+            };
+
+            maximum_id += 1;
+            let id_categories = maximum_id;
+            let (patch_node, release) = get_literal(value, component.submission)?;
+            expansion.computation_graph.insert(id_categories, patch_node);
+            expansion.properties.insert(id_categories, infer_property(&release.value, None, id_categories)?);
+            expansion.releases.insert(id_categories, release);
+
+            let mut component = component.clone();
+            component.insert_argument(&"categories".into(), id_categories);
+            expansion.computation_graph.insert(component_id, component);
+        }
+
+        Ok(expansion)
+    }
+}
+
+impl Sensitivity for proto::HierarchicalHistogram {
+    /// Each record is counted once on every level of the tree, so the sensitivity is that of a histogram on every level below the root.
+    fn compute_sensitivity(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        properties: &NodeProperties,
+        sensitivity_type: &SensitivitySpace,
+    ) -> Result<Value> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated()?;
+
+        use proto::privacy_definition::Neighboring;
+        let neighboring_type = Neighboring::from_i32(privacy_definition.neighboring)
+            .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))?;
+
+        let num_leaves = data_property.categories()?.num_records()[0] as usize;
+        let num_levels = get_num_levels(num_leaves, self.branching)?;
+        let num_nodes = get_num_nodes(num_levels, self.branching);
+
+        // number of counts that a neighboring dataset may change by one
+        let num_changed = match neighboring_type {
+            // the root and one node on every level
+            Neighboring::AddRemove => num_levels + 1,
+            // the root is constant, and two nodes on every level
+            Neighboring::Substitute => 2 * num_levels
+        };
+
+        // the L1 sensitivity is integral, so that the counts may also be privatized by the simple geometric mechanism
+        Ok(match sensitivity_type {
+            SensitivitySpace::KNorm(1) => Array::from_shape_vec(
+                vec![num_nodes, 1], vec![num_changed as Integer; num_nodes])?.into_dyn().into(),
+            SensitivitySpace::KNorm(2) => Array::from_shape_vec(
+                vec![num_nodes, 1], vec![(num_changed as f64).sqrt(); num_nodes])?.into_dyn().into(),
+            SensitivitySpace::KNorm(_) => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into()),
+            _ => return Err("HierarchicalHistogram sensitivity is only implemented for KNorm".into())
+        })
+    }
+}
+
+/// Number of levels below the root of a tree with at least `num_leaves` leaves.
+pub fn get_num_levels(num_leaves: usize, branching: u32) -> Result<usize> {
+    if branching < 2 {
+        return Err("branching: must be at least two".into())
+    }
+    if num_leaves == 0 {
+        return Err("number of leaves must be positive".into())
+    }
+
+    let mut num_levels = 0;
+    let mut width = 1;
+    while width < num_leaves {
+        width *= branching as usize;
+        num_levels += 1;
+    }
+    Ok(num_levels)
+}
+
+/// Number of nodes in a complete tree, including the root.
+pub fn get_num_nodes(num_levels: usize, branching: u32) -> usize {
+    (0..=num_levels).map(|level| (branching as usize).pow(level as u32)).sum()
+}
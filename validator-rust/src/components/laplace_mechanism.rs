@@ -6,7 +6,7 @@ use crate::base::{DataType, IndexKey, NodeProperties, SensitivitySpace, Value, V
 use crate::components::{Accuracy, Component, Expandable, Mechanism, Sensitivity};
 use crate::errors::*;
 use crate::utilities::{expand_mechanism, prepend};
-use crate::utilities::privacy::{get_epsilon, privacy_usage_check, spread_privacy_usage};
+use crate::utilities::privacy::{get_epsilon, get_pure_rdp_curves, laplace_rdp_curve, privacy_usage_check, spread_privacy_usage};
 
 impl Component for proto::LaplaceMechanism {
     fn propagate_property(
@@ -97,9 +97,25 @@ impl Mechanism for proto::LaplaceMechanism {
             .map(|usage| usage.effective_to_actual(
                 data_property.sample_proportion.unwrap_or(1.),
                 data_property.c_stability,
-                privacy_definition.group_size))
+                privacy_definition))
             .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
     }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            privacy_definition, data_property, orders, laplace_rdp_curve).map(Some)
+    }
 }
 
 
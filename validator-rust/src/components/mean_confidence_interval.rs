@@ -0,0 +1,136 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::Component;
+use crate::errors::*;
+use crate::utilities::prepend;
+
+impl Component for proto::MeanConfidenceInterval {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        // the interval is post-processing of the released mean and variance
+        for name in &["data", "variance"] {
+            let property = properties.get::<IndexKey>(&(*name).into())
+                .ok_or_else(|| Error::from(format!("{}: missing", name)))?.array()
+                .map_err(prepend(&format!("{}:", name)))?;
+            if !property.releasable {
+                return Err(format!("{}: must be public", name).into())
+            }
+            if property.data_type != DataType::Float {
+                return Err(format!("{}: atomic type must be float", name).into())
+            }
+            if property.num_columns()? != 1 {
+                return Err(format!("{}: must contain one column", name).into())
+            }
+        }
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        let get_public = |name: &str| public_arguments.get::<IndexKey>(&name.into())
+            .ok_or_else(|| Error::from(format!("{}: must be public", name)))?
+            .ref_array()?.first_float().map_err(prepend(&format!("{}:", name)));
+
+        for name in &["noise_scale", "variance_scale"] {
+            let scale = get_public(name)?;
+            if !scale.is_finite() || scale < 0. {
+                return Err(format!("{}: must be non-negative", name).into())
+            }
+        }
+        if get_public("num_records")? < 1. {
+            return Err("num_records: must be positive".into())
+        }
+        let lower = get_public("lower")?;
+        let upper = get_public("upper")?;
+        if lower > upper {
+            return Err("lower: must not be greater than upper".into())
+        }
+
+        check_confidence(self.confidence)?;
+        if !["laplace", "gaussian"].contains(&self.noise.to_lowercase().as_str()) {
+            return Err(format!("noise: unexpected invalid token {:?}", self.noise).into())
+        }
+        if self.num_simulations == 0 {
+            return Err("num_simulations: must be greater than zero".into())
+        }
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_records: Some(2),
+            num_columns: Some(1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(lower)]),
+                upper: Vector1DNull::Float(vec![Some(upper)]),
+            })),
+            data_type: DataType::Float,
+            dataset_id: Some(node_id as i64),
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(1),
+            group_id: data_property.group_id.clone(),
+            naturally_ordered: true,
+            sample_proportion: None,
+        }).into())
+    }
+}
+
+/// Check that a confidence level is a probability strictly between zero and one.
+pub fn check_confidence(confidence: f64) -> Result<()> {
+    if confidence.is_nan() || confidence <= 0. || confidence >= 1. {
+        return Err("confidence: must be within (0, 1)".into())
+    }
+    Ok(())
+}
+
+/// Confidence interval of an estimate, from errors simulated from the distribution of its error.
+///
+/// The estimate minus the upper and lower central quantiles of the errors bound the target,
+/// and the interval is clamped to the bounds of the data.
+///
+/// # Arguments
+/// * `estimate` - Released estimate.
+/// * `errors` - Simulated errors of the estimate.
+/// * `confidence` - Probability that the interval contains the target.
+/// * `lower` - Lower bound of the data.
+/// * `upper` - Upper bound of the data.
+pub fn get_interval(estimate: f64, mut errors: Vec<f64>, confidence: f64, lower: f64, upper: f64) -> Result<(f64, f64)> {
+    check_confidence(confidence)?;
+    if errors.is_empty() {
+        return Err("errors: must contain at least one simulation".into())
+    }
+    errors.sort_by(|l, r| l.partial_cmp(r).unwrap_or(std::cmp::Ordering::Equal));
+
+    // empirical quantiles, rounded outward so that the interval is not too narrow
+    let tail = (1. - confidence) / 2.;
+    let last = errors.len() - 1;
+    let error_lower = errors[((tail * last as f64).floor() as usize).min(last)];
+    let error_upper = errors[(((1. - tail) * last as f64).ceil() as usize).min(last)];
+
+    Ok(((estimate - error_upper).max(lower).min(upper), (estimate - error_lower).max(lower).min(upper)))
+}
+
+#[cfg(test)]
+mod test_mean_confidence_interval {
+    use crate::components::mean_confidence_interval::get_interval;
+
+    #[test]
+    fn test_interval() {
+        let errors = (0..=100).map(|i| i as f64 - 50.).collect::<Vec<f64>>();
+        assert_eq!(get_interval(0., errors.clone(), 0.5, -100., 100.).unwrap(), (-25., 25.));
+
+        // the interval is clamped to the bounds of the data
+        assert_eq!(get_interval(90., errors.clone(), 0.5, -100., 100.).unwrap(), (65., 100.));
+
+        assert!(get_interval(0., errors, 1., -100., 100.).is_err());
+        assert!(get_interval(0., Vec::new(), 0.9, -100., 100.).is_err());
+    }
+}
@@ -12,47 +12,83 @@ use crate::errors::*;
 
 mod transforms;
 //mod bin;
+mod assign_clusters;
+mod bound_contributions;
 mod cast;
+pub mod chi_square_test;
 mod clamp;
 mod count;
 mod covariance;
 mod column_bind;
+pub mod consistent_marginals;
+pub mod constrained_inference;
+pub mod contingency_table;
 mod digitize;
+pub mod dp_bounds;
 mod dp_count;
+pub mod dp_count_distinct;
 mod dp_variance;
+mod dp_chi_square_test;
+mod dp_contingency_table;
 mod dp_covariance;
 mod dp_gumbel_median;
+mod dp_hierarchical_histogram;
 mod dp_histogram;
+mod dp_k_means;
+mod dp_kurtosis;
 mod dp_linear_regression;
+pub mod dp_logistic_regression;
 mod dp_maximum;
 mod dp_median;
 mod dp_minimum;
 mod dp_mean;
+mod dp_mean_ci;
+mod dp_pca;
 mod dp_quantile;
+mod dp_quantile_ci;
+pub mod dp_quantiles;
+mod dp_range_query;
 mod dp_raw_moment;
+mod dp_skewness;
 mod dp_sum;
 mod filter;
+pub mod hierarchical_histogram;
 mod histogram;
 mod impute;
 pub mod index;
+pub mod mwem_synthesizer;
 mod raw_moment;
 mod literal;
 mod map;
 mod materialize;
 pub mod partition;
+mod permute_and_flip;
+pub mod principal_components;
 mod quantile;
+pub mod range_query;
+mod report_noisy_max;
 mod reshape;
 mod mean;
+pub mod mean_confidence_interval;
+pub mod noise_scale;
+pub mod discrete_gaussian_mechanism;
+mod discrete_laplace_mechanism;
 mod exponential_mechanism;
 pub mod gaussian_mechanism;
 mod laplace_mechanism;
 mod simple_geometric_mechanism;
 pub mod snapping_mechanism;
+pub mod sparse_vector;
+pub mod stability_mechanism;
+pub mod wishart_mechanism;
 mod resize;
+pub mod solve_normal_equations;
+pub mod standardized_moment;
 mod theil_sen;
 mod to_dataframe;
 mod sum;
 mod union;
+mod update_centroids;
 mod variance;
 
 use crate::base::{IndexKey, Value, NodeProperties, SensitivitySpace, ValueProperties};
@@ -147,6 +183,28 @@ pub trait Mechanism {
         release_usage: Option<&Vec<proto::PrivacyUsage>>,
         properties: &NodeProperties
     ) -> Result<Option<Vec<proto::PrivacyUsage>>>;
+
+    /// Extraction of the Rényi differential privacy curves of the component.
+    ///
+    /// Each curve bounds the Rényi divergence of the mechanism at each of the `orders`.
+    /// The curves are used by the Rényi accountant in place of the privacy usages.
+    ///
+    /// # Arguments
+    /// * `self` - the protobuf object corresponding to the prost protobuf struct, containing an upper bound on privacy usage
+    /// * `privacy_definition` - the definition of privacy under which the sensitivity is to be computed
+    /// * `release_usage` - optionally, the privacy actually used by the mechanism (if it has already been released)
+    /// * `properties` - properties of the arguments to the component
+    /// * `orders` - orders of the Rényi divergence at which to evaluate each curve
+    ///
+    /// # Returns
+    /// One curve for each privacy usage, after group_size, c_stability and privacy amplification have been taken into account.
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>>;
 }
 
 /// Sensitivity component trait
@@ -257,12 +315,17 @@ impl Component for proto::Component {
 
         propagate_property!(
             // INSERT COMPONENT LIST
-            Cast, Clamp, ColumnBind, Count, Covariance, Digitize,
-            Filter, Histogram, Impute, Index, Literal, Materialize, Mean,
-            Partition, Quantile, RawMoment, Reshape, Resize, Sum, ToDataframe, Union, Variance,
+            AssignClusters, BoundContributions, Cast, ChiSquareTest, Clamp, ColumnBind,
+            ConsistentMarginals, ConstrainedInference, ContingencyTable, Count, Covariance, Digitize,
+            Filter, HierarchicalHistogram, Histogram, Impute, Index, Literal, Materialize, Mean,
+            MeanConfidenceInterval, Partition, PrincipalComponents, Quantile, RangeQuery, RawMoment, Reshape, Resize,
+            SolveNormalEquations, StandardizedMoment, Sum, ToDataframe, Union, UpdateCentroids, Variance,
 
-            ExponentialMechanism, GaussianMechanism, LaplaceMechanism,
-            SimpleGeometricMechanism, SnappingMechanism,
+            DiscreteGaussianMechanism, DiscreteLaplaceMechanism, ExponentialMechanism,
+            GaussianMechanism, LaplaceMechanism, SimpleGeometricMechanism, SnappingMechanism,
+            PermuteAndFlip, ReportNoisyMax, SparseVector, StabilityMechanism, WishartMechanism,
+
+            DpBounds, DpCountDistinct, DpLogisticRegression, DpQuantiles, MwemSynthesizer,
 
             Abs, Add, LogicalAnd, Divide, Equal, GreaterThan, LessThan, Log, Modulo, Multiply,
             Negate, Negative, LogicalOr, Power, RowMax, RowMin, Subtract, TheilSen, DpGumbelMedian
@@ -333,13 +396,17 @@ impl Expandable for proto::Component {
 
         expand_component!(
             // INSERT COMPONENT LIST
-            Clamp, Digitize, Histogram, Impute, Map, Maximum, Median, Minimum, Partition, Resize,
+            Clamp, ContingencyTable, Digitize, HierarchicalHistogram, Histogram, Impute, Map, Maximum,
+            Median, Minimum, NoiseScale, Partition, Resize,
 
-            DpCount, DpCovariance, DpHistogram, DpLinearRegression, DpMaximum, DpMean, DpMedian,
-            DpMinimum, DpQuantile, DpRawMoment, DpSum, DpVariance,
+            DpBounds, DpChiSquareTest, DpContingencyTable, DpCount, DpCountDistinct, DpCovariance, DpHierarchicalHistogram,
+            DpHistogram, DpKMeans, DpKurtosis, DpLinearRegression, DpLogisticRegression, DpMaximum, DpMean, DpMeanCi,
+            DpMedian, DpMinimum, DpPca, DpQuantile, DpQuantileCi, DpQuantiles, DpRangeQuery, DpRawMoment,
+            DpSkewness, DpSum, DpVariance, MwemSynthesizer,
 
-            ExponentialMechanism, GaussianMechanism, LaplaceMechanism,
-            SimpleGeometricMechanism, SnappingMechanism, DpGumbelMedian,
+            DiscreteGaussianMechanism, DiscreteLaplaceMechanism, ExponentialMechanism,
+            GaussianMechanism, LaplaceMechanism, SimpleGeometricMechanism, SnappingMechanism,
+            PermuteAndFlip, ReportNoisyMax, SparseVector, StabilityMechanism, WishartMechanism, DpGumbelMedian,
 
             ToBool, ToFloat, ToInt, ToString
         );
@@ -375,8 +442,46 @@ impl Mechanism for proto::Component {
 
         get_privacy_usage!(
             // INSERT COMPONENT LIST
-            ExponentialMechanism, GaussianMechanism, LaplaceMechanism,
-            SimpleGeometricMechanism, SnappingMechanism
+            DiscreteGaussianMechanism, DiscreteLaplaceMechanism, ExponentialMechanism,
+            GaussianMechanism, LaplaceMechanism, SimpleGeometricMechanism, SnappingMechanism,
+            PermuteAndFlip, ReportNoisyMax, SparseVector, StabilityMechanism, WishartMechanism,
+
+            DpBounds, DpCountDistinct, DpLogisticRegression, DpQuantiles, MwemSynthesizer
+        );
+
+        Ok(None)
+    }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let variant = self.variant.as_ref()
+            .ok_or_else(|| "variant: must be defined")?;
+
+        macro_rules! get_rdp_curves {
+            ($( $variant:ident ),*) => {
+                {
+                    $(
+                       if let proto::component::Variant::$variant(x) = variant {
+                            return x.get_rdp_curves(privacy_definition, release_usage, properties, orders)
+                                .chain_err(|| format!("node specification {:?}:", variant))
+                       }
+                    )*
+                }
+            }
+        }
+
+        get_rdp_curves!(
+            // INSERT COMPONENT LIST
+            DiscreteGaussianMechanism, DiscreteLaplaceMechanism, ExponentialMechanism,
+            GaussianMechanism, LaplaceMechanism, SimpleGeometricMechanism, SnappingMechanism,
+            PermuteAndFlip, ReportNoisyMax, SparseVector, StabilityMechanism, WishartMechanism,
+
+            DpBounds, DpCountDistinct, DpLogisticRegression, DpQuantiles, MwemSynthesizer
         );
 
         Ok(None)
@@ -409,7 +514,7 @@ impl Sensitivity for proto::component::Variant {
 
         compute_sensitivity!(
             // INSERT COMPONENT LIST
-            Count, Covariance, Histogram, Mean, Quantile, RawMoment, Sum, Union, Variance
+            ContingencyTable, Count, Covariance, HierarchicalHistogram, Histogram, Mean, Quantile, RawMoment, Sum, Union, Variance
         );
 
         Err(format!("sensitivity is not implemented for proto component {:?}", self).into())
@@ -443,9 +548,18 @@ impl Accuracy for proto::Component {
 
         accuracy_to_privacy_usage!(
              LaplaceMechanism,
+             DiscreteGaussianMechanism,
+             DiscreteLaplaceMechanism,
              GaussianMechanism,
              SimpleGeometricMechanism,
-             SnappingMechanism
+             SnappingMechanism,
+             PermuteAndFlip,
+             ReportNoisyMax,
+             SolveNormalEquations,
+             StabilityMechanism,
+             StandardizedMoment,
+             DpCountDistinct,
+             DpQuantiles
         );
 
         Ok(None)
@@ -477,9 +591,18 @@ impl Accuracy for proto::Component {
 
         privacy_usage_to_accuracy!(
             LaplaceMechanism,
+            DiscreteGaussianMechanism,
+            DiscreteLaplaceMechanism,
             GaussianMechanism,
             SimpleGeometricMechanism,
-            SnappingMechanism
+            SnappingMechanism,
+            PermuteAndFlip,
+            ReportNoisyMax,
+            SolveNormalEquations,
+            StabilityMechanism,
+            StandardizedMoment,
+            DpCountDistinct,
+            DpQuantiles
         );
 
         Ok(None)
@@ -518,8 +641,9 @@ impl Report for proto::Component {
 
         summarize!(
             // INSERT COMPONENT LIST
-            DpCount, DpCovariance, DpHistogram, DpMaximum, DpMean, DpMinimum, DpQuantile,
-            DpRawMoment, DpSum, DpVariance
+            DpBounds, DpChiSquareTest, DpContingencyTable, DpCount, DpCountDistinct, DpCovariance, DpHierarchicalHistogram,
+            DpHistogram, DpKurtosis, DpLogisticRegression, DpMaximum, DpMean, DpMeanCi, DpMinimum, DpPca, DpQuantile, DpQuantileCi,
+            DpQuantiles, DpRangeQuery, DpRawMoment, DpSkewness, DpSum, DpVariance, MwemSynthesizer
         );
 
         Ok(None)
@@ -555,7 +679,7 @@ impl Named for proto::Component {
         // TODO: transforms, covariance/cross-covariance, extended indexing, columnbind
         get_names!(
             // INSERT COMPONENT LIST
-            ToDataframe, Index, Literal, Materialize
+            ToDataframe, Index, Literal, Materialize, DpLogisticRegression
         );
 
         // default implementation
@@ -0,0 +1,323 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, Integer, proto, Warnable};
+use crate::base::{ArrayProperties, DataframeProperties, DataType, IndexKey, Jagged, Nature, NatureCategorical, NodeProperties, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism, Report};
+use crate::components::contingency_table::{check_categories, get_categories, get_layout, get_table_size, insert_categories};
+use crate::errors::*;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json};
+use crate::utilities::prepend;
+use crate::utilities::privacy::{exponential_rdp_curve, get_epsilon, privacy_usage_check, pure_rdp_curve, spread_privacy_usage};
+
+/// Largest number of cells in the full table.
+///
+/// The synthesizer keeps a weight for every cell of the full table.
+pub const MAX_DOMAIN_SIZE: usize = 1 << 24;
+
+impl Component for proto::MwemSynthesizer {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into())
+        }
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated().map_err(prepend("data:"))?;
+
+        if data_property.data_type == DataType::Float || data_property.data_type == DataType::Unknown {
+            return Err("data: atomic type must be integer, string or boolean".into())
+        }
+        let num_records = data_property.num_records
+            .ok_or_else(|| Error::from("data: number of records must be known, for example by resizing"))?;
+
+        check_categories(&public_arguments, &properties, &data_property.data_type)?;
+        if !properties.contains_key::<IndexKey>(&"marginals".into()) {
+            return Err("marginals: missing".into())
+        }
+        let (shape, marginals) = get_layout(&properties)?;
+        if shape.len() as i64 != data_property.num_columns()? {
+            return Err("categories: must be defined for every column of the data".into())
+        }
+        if marginals.is_empty() {
+            return Err("marginals: the workload must contain at least one marginal".into())
+        }
+        if get_table_size(&shape, &(0..shape.len()).collect::<Vec<usize>>())? > MAX_DOMAIN_SIZE {
+            return Err(format!("categories: the full table may contain at most {} cells", MAX_DOMAIN_SIZE).into())
+        }
+
+        if self.num_rounds == 0 {
+            return Err("num_rounds: must be greater than zero".into())
+        }
+        if self.privacy_usage.len() != 1 {
+            return Err("privacy_usage: must be of length one".into())
+        }
+
+        let warnings = privacy_usage_check(
+            &self.privacy_usage[0],
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        let categories = get_categories(&properties)?;
+
+        Ok(Warnable(ValueProperties::Dataframe(DataframeProperties {
+            children: (0..shape.len())
+                .map(|column| Ok((IndexKey::from(column as Integer), ValueProperties::Array(ArrayProperties {
+                    num_records: Some(num_records),
+                    num_columns: Some(1),
+                    nullity: false,
+                    releasable: true,
+                    c_stability: 1,
+                    aggregator: None,
+                    nature: Some(Nature::Categorical(NatureCategorical {
+                        categories: get_column_categories(&categories, column)?
+                    })),
+                    data_type: data_property.data_type.clone(),
+                    dataset_id: Some(node_id as i64),
+                    node_id: node_id as i64,
+                    is_not_empty: num_records > 0,
+                    dimensionality: Some(1),
+                    group_id: data_property.group_id.clone(),
+                    naturally_ordered: true,
+                    sample_proportion: None,
+                }))))
+                .collect::<Result<IndexMap<IndexKey, ValueProperties>>>()?
+        }), warnings))
+    }
+}
+
+impl Expandable for proto::MwemSynthesizer {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy definition must be defined")?;
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // update the privacy usage
+        let mut updated_component = component.clone();
+        if let Some(proto::component::Variant::MwemSynthesizer(variant)) = &mut updated_component.variant {
+            variant.privacy_usage = self.privacy_usage.iter()
+                .map(|usage| usage.actual_to_effective(
+                    data_property.sample_proportion.unwrap_or(1.),
+                    data_property.c_stability,
+                    privacy_definition))
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+        } else { return Err(Error::from("Variant must be defined")) }
+
+        if !properties.contains_key::<IndexKey>(&"categories".into()) {
+            let id_categories = insert_categories(&mut expansion, properties, component.submission, maximum_id)?;
+            updated_component.insert_argument(&"categories".into(), id_categories);
+        }
+
+        expansion.computation_graph.insert(component_id, updated_component);
+
+        Ok(expansion)
+    }
+}
+
+impl Mechanism for proto::MwemSynthesizer {
+    /// The usage of each mechanism of each round, so that rounds are accounted for like the mechanisms they consist of.
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        release_usage.unwrap_or(&self.privacy_usage).iter()
+            .map(|usage| get_round_usages(usage, self.num_rounds))
+            .collect::<Result<Vec<Vec<proto::PrivacyUsage>>>>()?
+            .into_iter().flatten()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>().map(Some)
+    }
+
+    /// Each round composes an exponential mechanism and a discrete epsilon-DP mechanism,
+    /// which are tighter under Rényi composition than the epsilon of the whole release.
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        let sample_proportion = data_property.sample_proportion.unwrap_or(1.);
+        let group_size = (data_property.c_stability * privacy_definition.group_size) as f64;
+
+        Ok(Some(release_usage.unwrap_or(&self.privacy_usage).iter()
+            .map(|usage| Ok(if sample_proportion == 1. {
+                get_round_rdp_curves(get_epsilon(usage)? * group_size, self.num_rounds, orders)?
+            } else {
+                get_round_usages(usage, self.num_rounds)?.iter()
+                    .map(|usage| Ok(pure_rdp_curve(get_epsilon(&usage.effective_to_actual(
+                        sample_proportion,
+                        data_property.c_stability,
+                        privacy_definition)?)?, orders)))
+                    .collect::<Result<Vec<Vec<f64>>>>()?
+            }))
+            .collect::<Result<Vec<Vec<Vec<f64>>>>>()?
+            .into_iter().flatten().collect()))
+    }
+}
+
+impl Report for proto::MwemSynthesizer {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let num_records = release.ref_dataframe()?.values().next()
+            .map(|column| column.ref_array()?.num_records()).transpose()?
+            .unwrap_or(0);
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "MWEMSynthesizer".to_string(),
+            variables: serde_json::json!(variable_names.cloned()
+                .unwrap_or_else(Vec::new).iter()
+                .map(|v| v.to_string()).collect::<Vec<String>>()),
+            // the synthetic records themselves are not summarized
+            release_info: serde_json::json!({
+                "num_records": num_records
+            }),
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "MWEM".to_string(),
+                cite: "Hardt, Ligett and McSherry (2012)".to_string(),
+                mechanism: "Exponential, Geometric".to_string(),
+                argument: serde_json::json!({
+                    "num_rounds": self.num_rounds
+                }),
+            },
+        }]))
+    }
+}
+
+fn get_column_categories(categories: &Jagged, column: usize) -> Result<Jagged> {
+    let column_error = || Error::from("categories: must be defined for every column of the data");
+    Ok(match categories {
+        Jagged::Int(categories) => Jagged::Int(vec![categories.get(column).ok_or_else(column_error)?.clone()]),
+        Jagged::Str(categories) => Jagged::Str(vec![categories.get(column).ok_or_else(column_error)?.clone()]),
+        Jagged::Bool(categories) => Jagged::Bool(vec![categories.get(column).ok_or_else(column_error)?.clone()]),
+        Jagged::Float(_) => return Err("categories: may not be float".into())
+    })
+}
+
+/// Epsilon spent by each mechanism in each round.
+///
+/// The budget is split evenly among the rounds,
+/// and within each round evenly between the selection and the measurement.
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter of the whole release.
+/// * `num_rounds` - Number of rounds.
+pub fn get_round_epsilon(epsilon: f64, num_rounds: u32) -> Result<f64> {
+    if num_rounds == 0 {
+        return Err("num_rounds: must be greater than zero".into())
+    }
+    Ok(epsilon / (2. * num_rounds as f64))
+}
+
+/// Privacy usage of each mechanism in each round, alternating between the selection and the measurement.
+///
+/// # Arguments
+/// * `usage` - Privacy usage of the whole release.
+/// * `num_rounds` - Number of rounds.
+pub fn get_round_usages(usage: &proto::PrivacyUsage, num_rounds: u32) -> Result<Vec<proto::PrivacyUsage>> {
+    if num_rounds == 0 {
+        return Err("num_rounds: must be greater than zero".into())
+    }
+    spread_privacy_usage(std::slice::from_ref(usage), 2 * num_rounds as usize)
+}
+
+/// Rényi curves of each mechanism in each round, alternating between the exponential mechanism and the measurement.
+///
+/// The measurement is a discrete laplace or simple geometric mechanism, which is accounted for as epsilon-DP.
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter of the whole release.
+/// * `num_rounds` - Number of rounds.
+/// * `orders` - Orders of the Rényi divergence at which to evaluate the curves.
+pub fn get_round_rdp_curves(epsilon: f64, num_rounds: u32, orders: &[f64]) -> Result<Vec<Vec<f64>>> {
+    let round_epsilon = get_round_epsilon(epsilon, num_rounds)?;
+    Ok((0..num_rounds)
+        .flat_map(|_| vec![
+            exponential_rdp_curve(round_epsilon, orders),
+            pure_rdp_curve(round_epsilon, orders)])
+        .collect())
+}
+
+#[cfg(test)]
+mod test_mwem_synthesizer {
+    use crate::components::mwem_synthesizer::{get_round_epsilon, get_round_rdp_curves, get_round_usages};
+    use crate::proto;
+    use crate::utilities::privacy::{get_epsilon, pure_rdp_curve};
+
+    #[test]
+    fn test_rounds_rdp_curve() {
+        assert_eq!(get_round_epsilon(1., 5).unwrap(), 0.1);
+        assert!(get_round_epsilon(1., 0).is_err());
+
+        // composing the rounds is never worse than the epsilon of the whole release
+        let orders = [1.5, 2., 4., 8., 16., 32., 64.];
+        let curves = get_round_rdp_curves(1., 10, &orders).unwrap();
+        assert_eq!(curves.len(), 20);
+        (0..orders.len())
+            .map(|i| curves.iter().map(|curve| curve[i]).sum::<f64>())
+            .zip(pure_rdp_curve(1., &orders))
+            .for_each(|(rounds, pure)| assert!(rounds <= pure + 1e-12));
+    }
+
+    #[test]
+    fn test_round_usages() {
+        let usage = proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon: 1., delta: 0.
+            }))
+        };
+        let usages = get_round_usages(&usage, 5).unwrap();
+        assert_eq!(usages.len(), 10);
+        assert!(usages.iter().all(|usage| (get_epsilon(usage).unwrap() - 0.1).abs() < 1e-12));
+        assert!(get_round_usages(&usage, 0).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto};
+use crate::base::{ArrayProperties, IndexKey, SensitivitySpace, Value};
+use crate::components::Expandable;
+use crate::components::discrete_gaussian_mechanism::get_discrete_gaussian_sigma;
+use crate::components::gaussian_mechanism::get_gaussian_sigma;
+use crate::errors::*;
+use crate::utilities::{get_effective_usages, get_literal, get_mechanism_sensitivity, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::privacy::get_epsilon;
+
+impl Expandable for proto::NoiseScale {
+    /// Replace the node with a literal, as the scale only depends on the properties of the aggregate.
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        _maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy definition must be defined")?;
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        let mechanism = self.mechanism.to_lowercase();
+        let sensitivity_space = match mechanism.as_str() {
+            "laplace" | "snapping" | "discretelaplace" | "simplegeometric" => SensitivitySpace::KNorm(1),
+            "gaussian" | "analyticgaussian" | "discretegaussian" => SensitivitySpace::KNorm(2),
+            _ => bail!("mechanism: noise scales are only defined for the laplace, snapping and gaussian mechanisms, and their discrete counterparts")
+        };
+
+        let usages = get_effective_usages(privacy_definition, &self.privacy_usage, &data_property)?;
+        let mut scales = get_mechanism_sensitivity(&sensitivity_space, privacy_definition, &data_property)?
+            .array()?.cast_float()?;
+
+        scales.gencolumns_mut().into_iter().zip(usages.iter())
+            .try_for_each(|(mut column, usage)| column.iter_mut()
+                .try_for_each(|v| get_noise_scale(&mechanism, usage, *v).map(|scale| *v = scale)))?;
+
+        let mut expansion = base::ComponentExpansion::default();
+        let (patch_node, release) = get_literal(scales.into(), component.submission)?;
+        expansion.computation_graph.insert(component_id, patch_node);
+        expansion.properties.insert(component_id, infer_property(&release.value, None, component_id)?);
+        expansion.releases.insert(component_id, release);
+
+        Ok(expansion)
+    }
+}
+
+/// Scale of the noise a mechanism adds to a statistic.
+///
+/// # Arguments
+/// * `mechanism` - One of `laplace`, `gaussian`, `analyticgaussian`, `snapping`, `discretelaplace`, `simplegeometric` or `discretegaussian`.
+/// * `usage` - Effective privacy usage of the mechanism on the statistic.
+/// * `sensitivity` - Sensitivity of the statistic, in the norm the mechanism is calibrated to.
+///
+/// # Returns
+/// The scale of the laplace distribution, or the standard deviation of the gaussian distribution.
+/// The discrete laplace and simple geometric mechanisms share the scale of the laplace mechanism,
+/// and the scale of the discrete gaussian mechanism is its sigma parameter.
+/// The snapping mechanism adds laplace noise of the same scale,
+/// but then rounds to a multiple of a power of two at least the scale, so the scale only approximates its noise.
+pub fn get_noise_scale(mechanism: &str, usage: &proto::PrivacyUsage, sensitivity: f64) -> Result<f64> {
+    Ok(match mechanism {
+        "laplace" | "snapping" | "discretelaplace" | "simplegeometric" => sensitivity / get_epsilon(usage)?,
+        "gaussian" => get_gaussian_sigma(usage, sensitivity, false)?,
+        "analyticgaussian" => get_gaussian_sigma(usage, sensitivity, true)?,
+        "discretegaussian" => get_discrete_gaussian_sigma(usage, sensitivity)?,
+        _ => bail!("noise scales are only defined for the laplace, snapping and gaussian mechanisms, and their discrete counterparts")
+    })
+}
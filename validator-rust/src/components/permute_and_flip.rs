@@ -0,0 +1,112 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Accuracy, Component, Expandable, Mechanism};
+use crate::components::exponential_mechanism::{expand_selection_mechanism, get_selection_accuracy_arguments, propagate_selection_property};
+use crate::errors::*;
+use crate::utilities::prepend;
+use crate::utilities::privacy::{get_epsilon, get_pure_rdp_curves, pure_rdp_curve, spread_privacy_usage};
+
+impl Component for proto::PermuteAndFlip {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        propagate_selection_property(&self.privacy_usage, privacy_definition, properties, node_id)
+    }
+}
+
+impl Expandable for proto::PermuteAndFlip {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_selection_mechanism(
+            &self.privacy_usage, privacy_definition, component,
+            properties, component_id, maximum_id)
+    }
+}
+
+impl Mechanism for proto::PermuteAndFlip {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"utilities".into())
+            .ok_or("utilities: missing")?.array()
+            .map_err(prepend("utilities:"))?;
+
+        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"utilities".into())
+            .ok_or("utilities: missing")?.array()
+            .map_err(prepend("utilities:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
+}
+
+impl Accuracy for proto::PermuteAndFlip {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let (sensitivity, num_candidates) = get_selection_accuracy_arguments(&public_arguments)?;
+
+        Ok(Some(accuracies.values.iter()
+            .map(|accuracy| proto::PrivacyUsage {
+                distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                    epsilon: 2. * sensitivity * (num_candidates / accuracy.alpha).ln() / accuracy.value,
+                    delta: 0.,
+                }))
+            })
+            .collect()))
+    }
+
+    /// With probability 1 - alpha, the utility of the selected candidate is within the accuracy of the largest utility.
+    ///
+    /// Permute-and-flip is equivalent to report noisy max with exponential noise of scale 2 sensitivity / epsilon.
+    /// Ding, Kifer, Wang, Zhang, Zhu (2021), The Permute-and-Flip Mechanism is Identical to Report-Noisy-Max with Exponential Noise
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        let (sensitivity, num_candidates) = get_selection_accuracy_arguments(&public_arguments)?;
+
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+
+        Ok(Some(vec![proto::Accuracy {
+            value: 2. * sensitivity * (num_candidates / alpha).ln() / epsilon,
+            alpha,
+        }]))
+    }
+}
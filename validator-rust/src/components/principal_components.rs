@@ -0,0 +1,178 @@
+use indexmap::map::IndexMap;
+use ndarray::ArrayD;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Value, ValueProperties};
+use crate::components::Component;
+use crate::errors::*;
+use crate::utilities::array::{get_symmetric_size, to_symmetric_matrix};
+use crate::utilities::prepend;
+
+impl Component for proto::PrincipalComponents {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // the decomposition is post-processing of a released matrix
+        if !data_property.releasable {
+            return Err("data: must be public".into())
+        }
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+
+        let num_columns = get_symmetric_size(data_property.num_records()?, data_property.num_columns()?)
+            .map_err(prepend("data:"))?;
+        if self.num_components == 0 || self.num_components as i64 > num_columns {
+            return Err("num_components: must be between one and the number of columns".into())
+        }
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_records: Some(self.num_components as i64),
+            num_columns: Some(num_columns),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type: DataType::Float,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(2),
+            group_id: data_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+        }).into())
+    }
+}
+
+/// Eigendecomposition of a symmetric matrix by the cyclic Jacobi method.
+///
+/// # Returns
+/// The eigenvalues, and the corresponding unit eigenvectors.
+pub fn symmetric_eigen(matrix: &[Vec<f64>]) -> Result<(Vec<f64>, Vec<Vec<f64>>)> {
+    const MAX_SWEEPS: usize = 100;
+
+    let size = matrix.len();
+    if matrix.iter().any(|row| row.len() != size) {
+        return Err("matrix must be square".into())
+    }
+
+    let mut values = matrix.to_vec();
+    let mut vectors = (0..size)
+        .map(|i| (0..size).map(|j| if i == j { 1. } else { 0. }).collect())
+        .collect::<Vec<Vec<f64>>>();
+
+    let norm = values.iter().flatten().map(|v| v.powi(2)).sum::<f64>().sqrt();
+
+    for _ in 0..MAX_SWEEPS {
+        let off_diagonal = (0..size)
+            .flat_map(|p| (p + 1..size).map(move |q| (p, q)))
+            .map(|(p, q)| values[p][q].powi(2))
+            .sum::<f64>().sqrt();
+        if off_diagonal <= f64::EPSILON * norm {
+            break
+        }
+
+        for p in 0..size {
+            for q in p + 1..size {
+                if values[p][q].abs() <= f64::MIN_POSITIVE {
+                    continue
+                }
+                // rotation that annihilates the (p, q) entry
+                let theta = (values[q][q] - values[p][p]) / (2. * values[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta.powi(2) + 1.).sqrt());
+                let c = 1. / (t.powi(2) + 1.).sqrt();
+                let s = t * c;
+
+                let rotate = |left: f64, right: f64| (c * left - s * right, s * left + c * right);
+                for row in values.iter_mut().chain(vectors.iter_mut()) {
+                    let (left, right) = rotate(row[p], row[q]);
+                    row[p] = left;
+                    row[q] = right;
+                }
+                // p < q, so row p is in the head and row q starts the tail
+                let (head, tail) = values.split_at_mut(q);
+                for (value_p, value_q) in head[p].iter_mut().zip(tail[0].iter_mut()) {
+                    let (left, right) = rotate(*value_p, *value_q);
+                    *value_p = left;
+                    *value_q = right;
+                }
+            }
+        }
+    }
+
+    // eigenvectors are the columns of the accumulated rotations
+    Ok((
+        (0..size).map(|i| values[i][i]).collect(),
+        (0..size).map(|j| vectors.iter().map(|row| row[j]).collect()).collect()
+    ))
+}
+
+/// Eigenvectors of a symmetric matrix with the largest eigenvalues, in descending order of eigenvalue.
+///
+/// The matrix may be square, or a single row holding its upper triangle.
+/// Each eigenvector is signed so that its largest-magnitude entry is positive.
+pub fn principal_components(matrix: ArrayD<f64>, num_components: usize) -> Result<Vec<Vec<f64>>> {
+    let matrix = to_symmetric_matrix(&matrix)?;
+
+    if num_components > matrix.len() {
+        return Err("num_components may not exceed the number of columns".into())
+    }
+
+    let (values, vectors) = symmetric_eigen(&matrix)?;
+
+    let mut order = (0..values.len()).collect::<Vec<usize>>();
+    order.sort_by(|l, r| values[*r].partial_cmp(&values[*l]).unwrap_or(std::cmp::Ordering::Equal));
+
+    Ok(order.into_iter().take(num_components)
+        .map(|index| {
+            let vector = &vectors[index];
+            let pivot = vector.iter().cloned().fold(0., |pivot: f64, v| if v.abs() > pivot.abs() { v } else { pivot });
+            let sign = if pivot < 0. { -1. } else { 1. };
+            vector.iter().map(|v| sign * v).collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test_principal_components {
+    use ndarray::arr2;
+
+    use crate::components::principal_components::{principal_components, symmetric_eigen};
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let matrix = vec![vec![4., 1., 2.], vec![1., 3., 0.5], vec![2., 0.5, 5.]];
+        let (values, vectors) = symmetric_eigen(&matrix).unwrap();
+
+        values.iter().zip(vectors.iter()).for_each(|(value, vector)| {
+            let error = matrix.iter()
+                .map(|row| row.iter().zip(vector.iter()).map(|(m, v)| m * v).sum::<f64>())
+                .zip(vector.iter())
+                .map(|(product, v)| (product - value * v).abs())
+                .sum::<f64>();
+            assert!(error < 1e-10);
+        });
+    }
+
+    #[test]
+    fn test_principal_components() {
+        let matrix = arr2(&[[1., 0.], [0., 4.]]).into_dyn();
+        let components = principal_components(matrix, 1).unwrap();
+        assert_eq!(components.len(), 1);
+        assert!((components[0][0]).abs() < 1e-12 && (components[0][1] - 1.).abs() < 1e-12);
+
+        // the upper triangle of the same matrix
+        let components = principal_components(arr2(&[[1., 0., 4.]]).into_dyn(), 1).unwrap();
+        assert!((components[0][0]).abs() < 1e-12 && (components[0][1] - 1.).abs() < 1e-12);
+    }
+}
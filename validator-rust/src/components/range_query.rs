@@ -0,0 +1,132 @@
+use indexmap::map::IndexMap;
+use ndarray::{ArrayD, Ix2};
+
+use crate::{base, Integer, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Value, ValueProperties};
+use crate::components::Component;
+use crate::errors::*;
+use crate::utilities::prepend;
+
+impl Component for proto::RangeQuery {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // range queries are post-processing of released counts
+        if !data_property.releasable {
+            return Err("data: must be public".into())
+        }
+        if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be numeric".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+        let num_leaves = data_property.num_records()?;
+
+        let num_records = match public_arguments.get::<IndexKey>(&"ranges".into()) {
+            Some(ranges) => {
+                let ranges = get_ranges(ranges.ref_array()?.clone().int()?)?;
+                if ranges.iter().any(|(_, end)| *end as i64 >= num_leaves) {
+                    return Err("ranges: may not extend past the last bin".into())
+                }
+                ranges.len() as i64
+            },
+            None => if properties.contains_key::<IndexKey>(&"ranges".into()) {
+                return Err("ranges: must be public".into())
+            } else { num_leaves }
+        };
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_records: Some(num_records),
+            num_columns: Some(1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type: DataType::Float,
+            dataset_id: Some(node_id as i64),
+            node_id: node_id as i64,
+            is_not_empty: num_records > 0,
+            dimensionality: Some(1),
+            group_id: data_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+        }).into())
+    }
+}
+
+/// Read inclusive (start, end) bin indices from each row of a two-column array.
+pub fn get_ranges(ranges: ArrayD<Integer>) -> Result<Vec<(usize, usize)>> {
+    let ranges = ranges.into_dimensionality::<Ix2>()
+        .map_err(|_| Error::from("ranges must be a two-column array"))?;
+    if ranges.shape()[1] != 2 {
+        return Err("ranges must be a two-column array".into())
+    }
+
+    ranges.genrows().into_iter()
+        .map(|row| {
+            if row[0] < 0 || row[0] > row[1] {
+                return Err("each range must start at a non-negative bin, and may not end before it starts".into())
+            }
+            Ok((row[0] as usize, row[1] as usize))
+        })
+        .collect()
+}
+
+/// Sum the counts within each inclusive range of bins.
+pub fn range_query(counts: &[f64], ranges: &[(usize, usize)]) -> Result<Vec<f64>> {
+    ranges.iter()
+        .map(|(start, end)| counts.get(*start..=*end)
+            .map(|counts| counts.iter().sum())
+            .ok_or_else(|| Error::from("ranges may not extend past the last bin")))
+        .collect()
+}
+
+/// Cumulative distribution function over the bins, from possibly negative noisy counts.
+///
+/// Negative counts are treated as empty, so that the estimate is non-decreasing.
+/// If every count is empty, the distribution is uniform over the bins.
+pub fn cumulative_distribution(counts: &[f64]) -> Vec<f64> {
+    let total = counts.iter().map(|v| v.max(0.)).sum::<f64>();
+    if total <= 0. {
+        return (1..=counts.len())
+            .map(|index| index as f64 / counts.len() as f64)
+            .collect()
+    }
+
+    let mut cumulative = 0.;
+    counts.iter()
+        .map(|v| {
+            cumulative += v.max(0.);
+            (cumulative / total).min(1.)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_range_query {
+    use crate::components::range_query::{cumulative_distribution, range_query};
+
+    #[test]
+    fn test_range_query() {
+        let counts = [1., 2., 3., 4.];
+        let sums = range_query(&counts, &[(0, 0), (1, 3), (0, 3)]).unwrap();
+        assert!(sums.iter().zip([1., 9., 10.].iter()).all(|(l, r)| (l - r).abs() < 1e-12));
+        assert!(range_query(&counts, &[(2, 4)]).is_err());
+    }
+
+    #[test]
+    fn test_cumulative_distribution() {
+        let cdf = cumulative_distribution(&[1., -2., 3.]);
+        assert!(cdf.iter().zip([0.25, 0.25, 1.].iter()).all(|(l, r)| (l - r).abs() < 1e-12));
+    }
+}
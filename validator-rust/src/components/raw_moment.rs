@@ -53,16 +53,19 @@ impl Sensitivity for proto::RawMoment {
 
         match sensitivity_type {
             SensitivitySpace::KNorm(k) => {
-                let k = i32::try_from(*k)?;
                 let lower = data_property.lower_float()?;
                 let upper = data_property.upper_float()?;
                 let num_records = data_property.num_records()?;
+                let order = i32::try_from(self.order)?;
 
-                let row_sensitivity = lower.iter()
-                    .zip(upper.iter())
-                    .map(|(min, max)|
-                        ((max - min).powi(self.order as i32) / (num_records as Float)).powi(k))
-                    .collect::<Vec<Float>>();
+                // AddRemove vs. Substitute share the same bounds
+                let row_sensitivity = match k {
+                    1 | 2 => lower.iter()
+                        .zip(upper.iter())
+                        .map(|(min, max)| get_power_range(*min, *max, order) / (num_records as Float))
+                        .collect::<Vec<Float>>(),
+                    _ => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
+                };
 
                 let mut array_sensitivity = Array::from(row_sensitivity).into_dyn();
                 array_sensitivity.insert_axis_inplace(Axis(0));
@@ -72,4 +75,30 @@ impl Sensitivity for proto::RawMoment {
             _ => Err("RawMoment is only implemented for KNorm sensitivity spaces".into())
         }
     }
-}
\ No newline at end of file
+}
+
+/// Width of the range of x^order, when x is within [lower, upper].
+///
+/// # Arguments
+/// * `lower` - Lower bound of the data.
+/// * `upper` - Upper bound of the data.
+/// * `order` - Power the data is raised to.
+pub fn get_power_range(lower: Float, upper: Float, order: i32) -> Float {
+    let (lower_power, upper_power) = (lower.powi(order), upper.powi(order));
+    // even powers are smallest at zero
+    let minimum = if order % 2 == 0 && lower < 0. && upper > 0. { 0. } else { lower_power.min(upper_power) };
+    lower_power.max(upper_power) - minimum
+}
+
+#[cfg(test)]
+mod test_raw_moment {
+    use crate::components::raw_moment::get_power_range;
+
+    #[test]
+    fn test_power_range() {
+        assert_eq!(get_power_range(0., 1., 3), 1.);
+        assert_eq!(get_power_range(1., 2., 2), 3.);
+        assert_eq!(get_power_range(-1., 2., 2), 4.);
+        assert_eq!(get_power_range(-2., 1., 3), 9.);
+    }
+}
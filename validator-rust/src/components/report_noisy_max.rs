@@ -0,0 +1,125 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Accuracy, Component, Expandable, Mechanism};
+use crate::components::exponential_mechanism::{expand_selection_mechanism, get_selection_accuracy_arguments, propagate_selection_property};
+use crate::errors::*;
+use crate::utilities::prepend;
+use crate::utilities::privacy::{get_epsilon, get_pure_rdp_curves, pure_rdp_curve, spread_privacy_usage};
+
+impl Component for proto::ReportNoisyMax {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        get_noise_scale_factor(&self.noise)?;
+        propagate_selection_property(&self.privacy_usage, privacy_definition, properties, node_id)
+    }
+}
+
+impl Expandable for proto::ReportNoisyMax {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_selection_mechanism(
+            &self.privacy_usage, privacy_definition, component,
+            properties, component_id, maximum_id)
+    }
+}
+
+impl Mechanism for proto::ReportNoisyMax {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"utilities".into())
+            .ok_or("utilities: missing")?.array()
+            .map_err(prepend("utilities:"))?;
+
+        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"utilities".into())
+            .ok_or("utilities: missing")?.array()
+            .map_err(prepend("utilities:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
+}
+
+impl Accuracy for proto::ReportNoisyMax {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let (sensitivity, num_candidates) = get_selection_accuracy_arguments(&public_arguments)?;
+        let factor = get_noise_scale_factor(&self.noise)?;
+
+        Ok(Some(accuracies.values.iter()
+            .map(|accuracy| proto::PrivacyUsage {
+                distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                    epsilon: factor * sensitivity * (num_candidates / accuracy.alpha).ln() / accuracy.value,
+                    delta: 0.,
+                }))
+            })
+            .collect()))
+    }
+
+    /// With probability 1 - alpha, the utility of the selected candidate is within the accuracy of the largest utility.
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        let (sensitivity, num_candidates) = get_selection_accuracy_arguments(&public_arguments)?;
+        let factor = get_noise_scale_factor(&self.noise)?;
+
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+
+        Ok(Some(vec![proto::Accuracy {
+            value: factor * sensitivity * (num_candidates / alpha).ln() / epsilon,
+            alpha,
+        }]))
+    }
+}
+
+/// Multiple of sensitivity / epsilon that bounds the utility gap to the best candidate, at confidence 1 - alpha, per ln(num_candidates / alpha).
+///
+/// Each noise is scaled by 2 sensitivity / epsilon.
+/// The selection is worse than the best candidate by at least t only if some noise exceeds t (exponential noise),
+///     or only if some noise exceeds t / 2 in magnitude (laplace noise).
+fn get_noise_scale_factor(noise: &str) -> Result<f64> {
+    Ok(match noise.to_lowercase().as_str() {
+        "exponential" => 2.,
+        "laplace" => 4.,
+        _ => bail!("noise: must be one of [Laplace, Exponential]")
+    })
+}
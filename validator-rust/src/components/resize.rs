@@ -93,6 +93,7 @@ impl Component for proto::Resize {
                 })),
                 _ => None
             };
+            propagate_sample_proportion(&mut data_property, &public_arguments, &properties)?;
             return Ok(ValueProperties::Array(data_property).into())
         }
 
@@ -231,29 +232,47 @@ impl Component for proto::Resize {
             _ => return Err("data in continuous imputation must be numeric".into())
         }
 
-        let sample_proportion: Option<Float> = public_arguments.get(&IndexKey::from("sample_proportion"))
-            .and_then(|v| v.ref_array().ok()?.first_float().ok());
-        if let Some(sample_proportion) = sample_proportion {
-            if sample_proportion <= 0. {
-                return Err("sample_proportion must be positive".into())
-            }
-        }
-        data_property.c_stability = data_property.c_stability * sample_proportion.unwrap_or(1.).ceil() as u32;
-        data_property.sample_proportion = match (data_property.sample_proportion, sample_proportion) {
-            (Some(_), Some(_)) => return Err(Error::from("multiple samplings is not currently supported")),
-            (Some(prior_prop), None) => Some(prior_prop),
-            (None, Some(new_prop)) => Some(new_prop / new_prop.ceil()),
-            (None, None) => None
-        };
-
-        if data_property.sample_proportion.is_some() {
-            data_property.naturally_ordered = false;
-        }
+        propagate_sample_proportion(&mut data_property, &public_arguments, &properties)?;
 
         Ok(ValueProperties::Array(data_property).into())
     }
 }
 
+/// Update the c-stability and sample proportion of data that is sampled by a resize.
+fn propagate_sample_proportion(
+    data_property: &mut ArrayProperties,
+    public_arguments: &IndexMap<base::IndexKey, &Value>,
+    properties: &base::NodeProperties,
+) -> Result<()> {
+    let sample_proportion: Option<Float> = public_arguments.get(&IndexKey::from("sample_proportion"))
+        .and_then(|v| v.ref_array().ok()?.first_float().ok());
+    if let Some(sample_proportion) = sample_proportion {
+        if sample_proportion <= 0. {
+            return Err("sample_proportion must be positive".into())
+        }
+        // the runtime only samples when resizing to a known number of rows
+        if !public_arguments.contains_key::<IndexKey>(&"number_rows".into()) {
+            return Err("sample_proportion requires number_rows to be set".into())
+        }
+    } else if properties.contains_key::<IndexKey>(&"sample_proportion".into())
+        && !public_arguments.contains_key::<IndexKey>(&"sample_proportion".into()) {
+        // privacy amplification is only valid if the sampling is known to have been performed by this component
+        return Err("sample_proportion must be public".into())
+    }
+    data_property.c_stability = data_property.c_stability * sample_proportion.unwrap_or(1.).ceil() as u32;
+    data_property.sample_proportion = match (data_property.sample_proportion, sample_proportion) {
+        (Some(_), Some(_)) => return Err(Error::from("multiple samplings is not currently supported")),
+        (Some(prior_prop), None) => Some(prior_prop),
+        (None, Some(new_prop)) => Some(new_prop / new_prop.ceil()),
+        (None, None) => None
+    };
+
+    if data_property.sample_proportion.is_some() {
+        data_property.naturally_ordered = false;
+    }
+    Ok(())
+}
+
 impl Expandable for proto::Resize {
     fn expand_component(
         &self,
@@ -513,4 +532,33 @@ pub mod test_resize {
         array1d_bool_0; 10.into(),
         array1d_bool_10_uniform; 10.into(),
     );
+
+    #[test]
+    fn test_sample_proportion() {
+        use crate::base::Value;
+        use crate::components::impute::test_impute;
+
+        let build = |number_rows: Option<Value>| {
+            let (mut analysis, imputed) = test_impute::utilities::analysis_f64_cont(
+                test_data::array1d_f64_10_uniform(), None, None);
+            let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+            let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+            let sample_proportion = analysis.literal().value(0.5.into()).value_public(true).build();
+
+            let number_rows = number_rows
+                .map(|number_rows| analysis.literal().value(number_rows).value_public(true).build());
+
+            let mut resized = analysis.resize(imputed)
+                .sample_proportion(sample_proportion).upper(upper).lower(lower);
+            if let Some(number_rows) = number_rows {
+                resized = resized.number_rows(number_rows);
+            }
+            let resized = resized.build();
+            analysis.properties(resized)
+        };
+
+        assert!(build(Some(5.into())).is_ok());
+        // without number_rows the runtime does not sample, so the usage must not be amplified
+        assert!(build(None).is_err());
+    }
 }
@@ -6,7 +6,7 @@ use crate::{proto, base, Warnable};
 use crate::components::{Component, Expandable};
 use crate::base::{Value, SensitivitySpace, ValueProperties, DataType, NodeProperties, IndexKey};
 use crate::utilities::{prepend, expand_mechanism, get_literal};
-use crate::utilities::privacy::{spread_privacy_usage, get_epsilon, privacy_usage_check};
+use crate::utilities::privacy::{spread_privacy_usage, get_epsilon, privacy_usage_check, get_pure_rdp_curves, pure_rdp_curve};
 use itertools::Itertools;
 use indexmap::map::IndexMap;
 use crate::utilities::inference::infer_property;
@@ -140,9 +140,25 @@ impl Mechanism for proto::SimpleGeometricMechanism {
             .map(|usage| usage.effective_to_actual(
                 data_property.sample_proportion.unwrap_or(1.),
                 data_property.c_stability,
-                privacy_definition.group_size))
+                privacy_definition))
             .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
     }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
 }
 
 
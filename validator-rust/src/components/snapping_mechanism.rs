@@ -12,7 +12,7 @@ use crate::components::{Component, Expandable};
 use crate::errors::*;
 use crate::utilities::{expand_mechanism, get_literal, prepend, standardize_numeric_argument};
 use crate::utilities::inference::infer_property;
-use crate::utilities::privacy::{get_epsilon, privacy_usage_check, spread_privacy_usage};
+use crate::utilities::privacy::{get_epsilon, get_pure_rdp_curves, privacy_usage_check, pure_rdp_curve, spread_privacy_usage};
 
 impl Component for proto::SnappingMechanism {
     fn propagate_property(
@@ -157,9 +157,25 @@ impl Mechanism for proto::SnappingMechanism {
             .map(|usage| usage.effective_to_actual(
                 data_property.sample_proportion.unwrap_or(1.),
                 data_property.c_stability,
-                privacy_definition.group_size))
+                privacy_definition))
             .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
     }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
 }
 
 
@@ -0,0 +1,372 @@
+use indexmap::map::IndexMap;
+use ndarray::{ArrayD, Ix1, Ix2};
+use statrs::function::erf;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Value, ValueProperties};
+use crate::components::{Accuracy, Component};
+use crate::errors::*;
+use crate::utilities::array::{get_symmetric_size, to_symmetric_matrix};
+use crate::utilities::prepend;
+
+impl Component for proto::SolveNormalEquations {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let covariance_property = properties.get::<IndexKey>(&"covariance".into())
+            .ok_or("covariance: missing")?.array()
+            .map_err(prepend("covariance:"))?.clone();
+
+        let means_property = properties.get::<IndexKey>(&"means".into())
+            .ok_or("means: missing")?.array()
+            .map_err(prepend("means:"))?.clone();
+
+        // the solve is post-processing of released statistics
+        if !covariance_property.releasable || !means_property.releasable {
+            return Err("covariance and means: must be public".into())
+        }
+
+        if covariance_property.data_type != DataType::Float || means_property.data_type != DataType::Float {
+            return Err("covariance and means: atomic type must be float".into())
+        }
+
+        let num_columns = get_symmetric_size(covariance_property.num_records()?, covariance_property.num_columns()?)
+            .map_err(prepend("covariance:"))?;
+        if num_columns < 2 {
+            return Err("covariance: must contain at least one predictor and the target".into())
+        }
+        if means_property.num_columns()? != num_columns {
+            return Err("means: must have one column for each column of the covariance".into())
+        }
+        if !["Laplace", "Gaussian"].contains(&self.noise.as_str()) {
+            return Err("noise: must be one of [Laplace, Gaussian]".into())
+        }
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_records: Some(1),
+            num_columns: Some(num_columns),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type: DataType::Float,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(2),
+            group_id: covariance_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+        }).into())
+    }
+}
+
+impl Accuracy for proto::SolveNormalEquations {
+    fn accuracy_to_privacy_usage(
+        &self,
+        _accuracies: &proto::Accuracies,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        // the solve does not consume privacy usage
+        Ok(None)
+    }
+
+    /// First-order bound on the error of each coefficient.
+    ///
+    /// The noise on each released statistic is bounded with confidence 1 - alpha / m, for m independent noise draws,
+    /// so that every bound holds simultaneously with confidence 1 - alpha.
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        let get_argument = |name: &str| public_arguments.get::<IndexKey>(&name.into())
+            .map(|v| v.ref_array()?.clone().cast_float());
+
+        let (covariance, means, covariance_scale, means_scale) = match (
+            get_argument("covariance"), get_argument("means"),
+            get_argument("covariance_scale"), get_argument("means_scale")) {
+            (Some(c), Some(m), Some(c_scale), Some(m_scale)) => (c?, m?, c_scale?, m_scale?),
+            // accuracy depends on the released statistics
+            _ => return Ok(None)
+        };
+
+        let covariance = to_symmetric_matrix(&covariance)?;
+        let means = to_vector(means)?;
+
+        // the covariance is symmetric, so only the upper triangle is noised independently
+        let num_draws = means.len() * (means.len() + 1) / 2 + means.len();
+        let alpha_draw = alpha / num_draws as f64;
+        let noise = self.noise.as_str();
+        let get_accuracy = |scale: f64| -> Result<f64> {
+            Ok(match noise {
+                "Laplace" => scale * (1. / alpha_draw).ln(),
+                "Gaussian" => scale * 2.0_f64.sqrt() * erf::erf_inv(1. - alpha_draw),
+                _ => bail!("noise: must be one of [Laplace, Gaussian]")
+            })
+        };
+        let covariance_accuracy = to_symmetric_matrix(&covariance_scale)?.into_iter()
+            .map(|row| row.into_iter().map(get_accuracy).collect::<Result<Vec<f64>>>())
+            .collect::<Result<Vec<Vec<f64>>>>()?;
+        let means_accuracy = to_vector(means_scale)?.into_iter()
+            .map(get_accuracy).collect::<Result<Vec<f64>>>()?;
+
+        let num_predictors = means.len() - 1;
+        if covariance.len() != means.len() {
+            return Err("means must have one entry for each column of the covariance".into())
+        }
+        if covariance_accuracy.len() != means.len() || means_accuracy.len() != means.len() {
+            return Err("noise scales must share the same shape as the statistics".into())
+        }
+
+        let (predictors, target) = split_normal_equations(&covariance)?;
+        let factor = regularized_cholesky(&predictors)?;
+        let coefficients = cholesky_solve(&factor, &target);
+
+        // error in the right-hand side, and error in the matrix scaled by the coefficients
+        let residual_errors = (0..num_predictors)
+            .map(|j| covariance_accuracy[j][num_predictors] + (0..num_predictors)
+                .map(|l| covariance_accuracy[j][l] * coefficients[l].abs())
+                .sum::<f64>())
+            .collect::<Vec<f64>>();
+
+        // |inverse| * residual_errors, where each column of the inverse is solved for independently
+        let mut coefficient_errors = vec![0.; num_predictors];
+        (0..num_predictors).for_each(|j| {
+            let mut unit = vec![0.; num_predictors];
+            unit[j] = 1.;
+            cholesky_solve(&factor, &unit).into_iter().enumerate()
+                .for_each(|(k, v)| coefficient_errors[k] += v.abs() * residual_errors[j]);
+        });
+
+        let intercept_error = means_accuracy[num_predictors] + (0..num_predictors)
+            .map(|j| coefficients[j].abs() * means_accuracy[j] + means[j].abs() * coefficient_errors[j])
+            .sum::<f64>();
+
+        Ok(Some(coefficient_errors.into_iter()
+            .chain(std::iter::once(intercept_error))
+            .map(|value| proto::Accuracy { value, alpha })
+            .collect()))
+    }
+}
+
+fn to_vector(array: ArrayD<f64>) -> Result<Vec<f64>> {
+    Ok(match array.ndim() {
+        2 => array.into_dimensionality::<Ix2>()?.iter().cloned().collect(),
+        _ => array.into_dimensionality::<Ix1>()?.to_vec()
+    })
+}
+
+/// Split a covariance matrix, with the target in the last row and column, into the predictor covariance and the predictor-target covariance.
+fn split_normal_equations(covariance: &[Vec<f64>]) -> Result<(Vec<Vec<f64>>, Vec<f64>)> {
+    let num_predictors = covariance.len().checked_sub(1)
+        .ok_or_else(|| Error::from("covariance may not be empty"))?;
+    if covariance.iter().any(|row| row.len() != covariance.len()) {
+        return Err("covariance must be a square matrix".into())
+    }
+
+    Ok((
+        covariance[..num_predictors].iter().map(|row| row[..num_predictors].to_vec()).collect(),
+        covariance[..num_predictors].iter().map(|row| row[num_predictors]).collect()
+    ))
+}
+
+/// Lower triangular Cholesky factor of a symmetric matrix, if it is positive-definite.
+fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let size = matrix.len();
+    let mut factor = vec![vec![0.; size]; size];
+    for i in 0..size {
+        for j in 0..=i {
+            let sum = matrix[i][j] - (0..j).map(|k| factor[i][k] * factor[j][k]).sum::<f64>();
+            if i == j {
+                if sum <= 0. || !sum.is_finite() {
+                    return None
+                }
+                factor[i][i] = sum.sqrt();
+            } else {
+                factor[i][j] = sum / factor[j][j];
+            }
+        }
+    }
+    Some(factor)
+}
+
+/// Cholesky factor of a symmetric matrix, adding a ridge penalty to the diagonal if the matrix is not positive-definite.
+///
+/// The ridge starts at a negligible fraction of the largest diagonal entry, and grows tenfold until the matrix is positive-definite.
+/// The ridge is then doubled, so that the smallest eigenvalue of the regularized matrix is at least the ridge.
+pub fn regularized_cholesky(matrix: &[Vec<f64>]) -> Result<Vec<Vec<f64>>> {
+    if let Some(factor) = cholesky(matrix) {
+        return Ok(factor)
+    }
+
+    let add_ridge = |ridge: f64| matrix.iter().enumerate()
+        .map(|(i, row)| row.iter().enumerate()
+            .map(|(j, v)| if i == j { v + ridge } else { *v })
+            .collect())
+        .collect::<Vec<Vec<f64>>>();
+
+    let scale = matrix.iter().enumerate()
+        .map(|(i, row)| row[i].abs())
+        .fold(0., f64::max);
+    let mut ridge = (scale * 1e-10).max(f64::EPSILON);
+
+    while ridge.is_finite() {
+        if cholesky(&add_ridge(ridge)).is_some() {
+            return cholesky(&add_ridge(2. * ridge))
+                .ok_or_else(|| "covariance could not be regularized to be positive-definite".into())
+        }
+        ridge *= 10.;
+    }
+    Err("covariance could not be regularized to be positive-definite".into())
+}
+
+/// Solve L L' x = b, given the lower triangular Cholesky factor L.
+pub fn cholesky_solve(factor: &[Vec<f64>], target: &[f64]) -> Vec<f64> {
+    let size = factor.len();
+
+    // forward substitution: L z = b
+    let mut z = vec![0.; size];
+    for i in 0..size {
+        z[i] = (target[i] - (0..i).map(|k| factor[i][k] * z[k]).sum::<f64>()) / factor[i][i];
+    }
+
+    // back substitution: L' x = z
+    let mut x = vec![0.; size];
+    for i in (0..size).rev() {
+        x[i] = (z[i] - (i + 1..size).map(|k| factor[k][i] * x[k]).sum::<f64>()) / factor[i][i];
+    }
+    x
+}
+
+/// Solve the normal equations of ordinary least squares from a covariance matrix and means.
+///
+/// # Arguments
+/// * `covariance` - Covariance matrix of the predictors, with the target as the last row and column. May also be the upper triangle, flattened into a single row.
+/// * `means` - Means of the predictors, with the mean of the target last.
+///
+/// # Returns
+/// The coefficient of each predictor, followed by the intercept.
+pub fn solve_normal_equations(covariance: ArrayD<f64>, means: ArrayD<f64>) -> Result<Vec<f64>> {
+    let covariance = to_symmetric_matrix(&covariance)?;
+    let means = to_vector(means)?;
+    if means.len() != covariance.len() {
+        return Err("means must have one entry for each column of the covariance".into())
+    }
+
+    let (predictors, target) = split_normal_equations(&covariance)?;
+    let coefficients = cholesky_solve(&regularized_cholesky(&predictors)?, &target);
+
+    let num_predictors = coefficients.len();
+    let intercept = means[num_predictors] - coefficients.iter().zip(means.iter())
+        .map(|(coefficient, mean)| coefficient * mean)
+        .sum::<f64>();
+
+    Ok(coefficients.into_iter().chain(std::iter::once(intercept)).collect())
+}
+
+#[cfg(test)]
+mod test_solve_normal_equations {
+    use indexmap::map::IndexMap;
+    use ndarray::{arr1, arr2, Array};
+
+    use crate::base::{IndexKey, Value};
+    use crate::bindings::Analysis;
+    use crate::components::Accuracy;
+    use crate::components::solve_normal_equations::{regularized_cholesky, solve_normal_equations};
+    use crate::proto;
+    use crate::utilities::propagate_properties;
+
+    #[test]
+    fn test_solve_normal_equations() {
+        // y = 2 x_1 - x_2 + 3, with uncorrelated predictors of variance 1 and 4
+        let covariance = arr2(&[[1., 0., 2.], [0., 4., -4.], [2., -4., 8.]]).into_dyn();
+        let means = arr2(&[[1., 2., 3.]]).into_dyn();
+
+        let solution = solve_normal_equations(covariance, means).unwrap();
+        let expected = [2., -1., 3.];
+        assert!(solution.iter().zip(expected.iter()).all(|(l, r)| (l - r).abs() < 1e-10));
+    }
+
+    #[test]
+    fn test_upper_triangle() {
+        let covariance = arr2(&[[1., 0., 2., 4., -4., 8.]]).into_dyn();
+        let means = arr2(&[[1., 2., 3.]]).into_dyn();
+
+        let solution = solve_normal_equations(covariance, means).unwrap();
+        let expected = [2., -1., 3.];
+        assert!(solution.iter().zip(expected.iter()).all(|(l, r)| (l - r).abs() < 1e-10));
+
+        assert!(solve_normal_equations(arr2(&[[1., 0., 2., 4., -4.]]).into_dyn(), arr2(&[[1., 2.]]).into_dyn()).is_err());
+    }
+
+    /// expand a dp linear regression, and return the solve and the public values of its arguments
+    fn expand_regression(protect_floating_point: bool) -> (proto::Component, IndexMap<IndexKey, Value>) {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = protect_floating_point;
+
+        let mut bounded = |column: usize| {
+            let data = analysis.literal()
+                .value(Array::from_shape_fn((100, 1), |(i, _)| ((i * (column + 1)) % 10) as f64).into_dyn().into())
+                .value_public(true).build();
+            let data = analysis.to_float(data).build();
+            let lower = analysis.literal().value(arr1(&[0.]).into_dyn().into()).value_public(true).build();
+            let upper = analysis.literal().value(arr1(&[10.]).into_dyn().into()).value_public(true).build();
+            let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+            analysis.impute(clamped).lower(lower).upper(upper).build()
+        };
+        let data_x = bounded(0);
+        let data_y = bounded(1);
+
+        let regression = analysis.dp_linear_regression(data_x, data_y, vec![proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon: 1., delta: 0.
+            }))
+        }]).implementation("sufficient-statistics".to_string()).build();
+
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, false).unwrap();
+
+        let solve = computation_graph[&regression].clone();
+        let public_arguments = solve.arguments().into_iter()
+            .filter_map(|(name, id)| Some((name, release.get(&id)?.value.clone())))
+            .collect();
+        (solve, public_arguments)
+    }
+
+    #[test]
+    fn test_accuracy() {
+        let (solve, mut public_arguments) = expand_regression(false);
+        assert!(public_arguments.contains_key(&IndexKey::from("covariance_scale")));
+        assert!(public_arguments.contains_key(&IndexKey::from("means_scale")));
+
+        // accuracy is only defined once the statistics are released
+        let get_accuracy = |public_arguments: &IndexMap<IndexKey, Value>| solve.privacy_usage_to_accuracy(
+            public_arguments.iter().map(|(k, v)| (k.clone(), v)).collect(), 0.05).unwrap();
+        assert!(get_accuracy(&public_arguments).is_none());
+
+        public_arguments.insert("covariance".into(), arr2(&[[8., 4., 8.]]).into_dyn().into());
+        public_arguments.insert("means".into(), arr2(&[[4., 5.]]).into_dyn().into());
+        let accuracies = get_accuracy(&public_arguments).unwrap();
+        assert_eq!(accuracies.len(), 2);
+        assert!(accuracies.iter().all(|accuracy| accuracy.value > 0. && accuracy.alpha == 0.05));
+
+        // the snapping mechanism does not have a known noise scale
+        let (_, public_arguments) = expand_regression(true);
+        assert!(!public_arguments.contains_key(&IndexKey::from("covariance_scale")));
+    }
+
+    #[test]
+    fn test_regularized_cholesky() {
+        // indefinite matrices are regularized
+        assert!(regularized_cholesky(&[vec![1., 2.], vec![2., 1.]]).is_ok());
+    }
+}
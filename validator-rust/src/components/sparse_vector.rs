@@ -0,0 +1,214 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism, Sensitivity};
+use crate::errors::*;
+use crate::utilities::{expand_mechanism, prepend};
+use crate::utilities::privacy::{get_pure_rdp_curves, privacy_usage_check, pure_rdp_curve};
+
+impl Component for proto::SparseVector {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        if privacy_definition.protect_floating_point {
+            return Err("Floating-point protections are enabled. The sparse vector technique is susceptible to floating-point attacks.".into())
+        }
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into());
+        }
+
+        if self.cutoff == 0 {
+            return Err("cutoff: must be greater than zero".into());
+        }
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be numeric".into());
+        }
+
+        if data_property.num_columns()? != 1 {
+            return Err("data: the sparse vector technique only works with one column of queries at a time".into());
+        }
+
+        let threshold = public_arguments.get::<IndexKey>(&"threshold".into())
+            .ok_or_else(|| Error::from("threshold: must be public"))?.ref_array()?;
+
+        if threshold.num_records()? != 1 || threshold.num_columns()? != 1 {
+            return Err("threshold: must be a single value".into());
+        }
+
+        let aggregator = data_property.aggregator.clone()
+            .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+        // sensitivity must be computable
+        aggregator.component.compute_sensitivity(
+            privacy_definition,
+            &aggregator.properties,
+            &SensitivitySpace::KNorm(1))?;
+
+        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
+            .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        let output_property = if self.release_values {
+            ArrayProperties {
+                nullity: true,
+                releasable: true,
+                c_stability: 1,
+                aggregator: None,
+                nature: None,
+                data_type: DataType::Float,
+                dataset_id: None,
+                node_id: node_id as i64,
+                sample_proportion: None,
+                ..data_property
+            }
+        } else {
+            ArrayProperties {
+                num_records: None,
+                num_columns: Some(1),
+                nullity: false,
+                releasable: true,
+                c_stability: 1,
+                aggregator: None,
+                nature: None,
+                data_type: DataType::Int,
+                dataset_id: None,
+                node_id: node_id as i64,
+                is_not_empty: false,
+                dimensionality: Some(1),
+                group_id: data_property.group_id,
+                naturally_ordered: true,
+                sample_proportion: None
+            }
+        };
+
+        Ok(Warnable(output_property.into(), warnings))
+    }
+}
+
+impl Expandable for proto::SparseVector {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_mechanism(
+            &SensitivitySpace::KNorm(1),
+            privacy_definition,
+            self.privacy_usage.as_ref(),
+            component,
+            properties,
+            component_id,
+            maximum_id,
+        )
+    }
+}
+
+impl Mechanism for proto::SparseVector {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or_else(|| &self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
+}
+
+/// Split the epsilon of a sparse vector release between its noise sources.
+///
+/// The threshold is perturbed with Laplace(sensitivity / epsilon_threshold) noise,
+/// each query with Laplace(2 * cutoff * sensitivity / epsilon_queries) noise,
+/// and each reported query is measured with Laplace(cutoff * sensitivity / epsilon_values) noise.
+/// The threshold and query budgets are split in the ratio 1 : (2 * cutoff)^(2/3), which minimizes the noise in the comparisons.
+/// Lyu, Su and Li (2017), Understanding the Sparse Vector Technique for Differential Privacy
+///
+/// # Arguments
+/// * `epsilon` - Total privacy usage of the release.
+/// * `cutoff` - Maximum number of above-threshold queries reported.
+/// * `release_values` - Whether noisy answers to the reported queries are released.
+///
+/// # Returns
+/// Epsilons spent on the threshold, the queries and the released values.
+pub fn get_sparse_vector_epsilons(epsilon: f64, cutoff: u32, release_values: bool) -> Result<(f64, f64, f64)> {
+    if epsilon <= 0. {
+        return Err("epsilon: must be positive".into())
+    }
+    if cutoff == 0 {
+        return Err("cutoff: must be greater than zero".into())
+    }
+
+    let (epsilon_comparisons, epsilon_values) = if release_values {
+        (epsilon / 2., epsilon / 2.)
+    } else {
+        (epsilon, 0.)
+    };
+
+    let epsilon_threshold = epsilon_comparisons / (1. + (2. * cutoff as f64).powf(2. / 3.));
+    Ok((epsilon_threshold, epsilon_comparisons - epsilon_threshold, epsilon_values))
+}
+
+#[cfg(test)]
+mod test_sparse_vector {
+    use crate::components::sparse_vector::get_sparse_vector_epsilons;
+
+    #[test]
+    fn test_sparse_vector_epsilons() {
+        let (threshold, queries, values) = get_sparse_vector_epsilons(1., 4, false).unwrap();
+        assert!((threshold + queries - 1.).abs() < 1e-12);
+        assert!((queries / threshold - 4f64).abs() < 1e-12);
+        assert!(values.abs() < 1e-12);
+
+        let (threshold, queries, values) = get_sparse_vector_epsilons(1., 4, true).unwrap();
+        assert!((threshold + queries + values - 1.).abs() < 1e-12);
+
+        assert!(get_sparse_vector_epsilons(1., 0, false).is_err());
+    }
+}
@@ -0,0 +1,314 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataframeProperties, DataType, IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Accuracy, Component, Expandable, Mechanism, Report};
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::privacy::{get_delta, get_epsilon, privacy_usage_check, spread_privacy_usage};
+
+impl Component for proto::StabilityMechanism {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into())
+        }
+
+        if privacy_definition.protect_floating_point {
+            return Err("Floating-point protections are enabled. The stability mechanism is susceptible to floating-point attacks.".into())
+        }
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated().map_err(prepend("data:"))?;
+
+        if data_property.data_type == DataType::Float || data_property.data_type == DataType::Unknown {
+            return Err("data: atomic type must be integer, string or boolean".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must have exactly one column".into())
+        }
+
+        if let Some(sensitivity) = public_arguments.get::<IndexKey>(&"sensitivity".into()) {
+            let sensitivity = sensitivity.ref_array()?.first_float().map_err(prepend("sensitivity:"))?;
+            if sensitivity < get_sensitivity(privacy_definition)? {
+                return Err("sensitivity: may not be less than the sensitivity of the histogram".into())
+            }
+        } else if properties.contains_key::<IndexKey>(&"sensitivity".into()) {
+            return Err("sensitivity: must be public".into())
+        }
+
+        if self.privacy_usage.len() != 1 {
+            return Err("privacy_usage: must be of length one".into())
+        }
+        let delta = get_delta(&self.privacy_usage[0])?;
+        if delta <= 0. || delta >= 1. {
+            return Err("privacy_usage: delta must be within (0, 1)".into())
+        }
+
+        let warnings = privacy_usage_check(
+            &self.privacy_usage[0],
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        // the number of released categories depends on the data
+        let column_property = |data_type: DataType| ValueProperties::Array(ArrayProperties {
+            num_records: None,
+            num_columns: Some(1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: false,
+            dimensionality: Some(1),
+            group_id: data_property.group_id.clone(),
+            naturally_ordered: true,
+            sample_proportion: None,
+        });
+
+        Ok(Warnable(ValueProperties::Dataframe(DataframeProperties {
+            children: indexmap![
+                "categories".into() => column_property(data_property.data_type.clone()),
+                "counts".into() => column_property(DataType::Float)
+            ]
+        }), warnings))
+    }
+}
+
+impl Expandable for proto::StabilityMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy definition must be defined")?;
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // update the privacy usage
+        let mut updated_component = component.clone();
+        if let Some(proto::component::Variant::StabilityMechanism(variant)) = &mut updated_component.variant {
+            variant.privacy_usage = self.privacy_usage.iter()
+                .map(|usage| usage.actual_to_effective(
+                    data_property.sample_proportion.unwrap_or(1.),
+                    data_property.c_stability,
+                    privacy_definition))
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+        } else { return Err(Error::from("Variant must be defined")) }
+
+        if !properties.contains_key::<IndexKey>(&"sensitivity".into()) {
+            maximum_id += 1;
+            let id_sensitivity = maximum_id;
+            let (patch_node, release) = get_literal(
+                get_sensitivity(privacy_definition)?.into(), component.submission)?;
+            expansion.computation_graph.insert(id_sensitivity, patch_node);
+            expansion.properties.insert(id_sensitivity, infer_property(&release.value, None, id_sensitivity)?);
+            expansion.releases.insert(id_sensitivity, release);
+            updated_component.insert_argument(&"sensitivity".into(), id_sensitivity);
+        }
+
+        expansion.computation_graph.insert(component_id, updated_component);
+
+        Ok(expansion)
+    }
+}
+
+impl Mechanism for proto::StabilityMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn get_rdp_curves(
+        &self,
+        _privacy_definition: &proto::PrivacyDefinition,
+        _release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        _properties: &NodeProperties,
+        _orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        // the probability of releasing a category that only one individual contributed to is not bounded in Rényi divergence
+        Err("the stability mechanism satisfies (epsilon, delta)-DP, and may not be used with the Rényi accountant".into())
+    }
+}
+
+impl Accuracy for proto::StabilityMechanism {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let sensitivity = get_sensitivity_argument(&public_arguments)?;
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let delta = get_delta(&usages[0])?;
+
+        let epsilon = accuracies.values.iter()
+            .map(|accuracy| (1. / accuracy.alpha).ln() * (sensitivity / accuracy.value))
+            .fold(0., f64::max);
+
+        Ok(Some(vec![proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon,
+                delta,
+            }))
+        }]))
+    }
+
+    /// With probability 1 - alpha, the noisy count of each released category is within the accuracy.
+    ///
+    /// Categories whose counts exceed the threshold by more than the accuracy are released with probability 1 - alpha.
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        let sensitivity = get_sensitivity_argument(&public_arguments)?;
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+
+        Ok(Some(vec![proto::Accuracy {
+            value: (1. / alpha).ln() * (sensitivity / epsilon),
+            alpha,
+        }]))
+    }
+}
+
+impl Report for proto::StabilityMechanism {
+    fn summarize(
+        &self,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let release = release.ref_dataframe()?;
+        let get_column = |name: &str| release.get::<IndexKey>(&name.into())
+            .ok_or_else(|| Error::from(format!("{}: missing from release", name)))
+            .and_then(value_to_json);
+
+        let variables = match variable_names {
+            Some(names) => names.iter().map(|name| name.to_string()).collect(),
+            None => vec!["[Unknown]".to_string()]
+        };
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPHistogram".to_string(),
+            variables: serde_json::json!(variables),
+            release_info: serde_json::json!({
+                "categories": get_column("categories")?,
+                "counts": get_column("counts")?
+            }),
+            privacy_loss: serde_json::json!(self.privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+            accuracy: None,
+            submission: component.submission,
+            node_id: Some(node_id),
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "Korolova et al. (2009)".to_string(),
+                mechanism: "Stability".to_string(),
+                argument: serde_json::json!({}),
+            },
+        }]))
+    }
+}
+
+fn get_sensitivity_argument(public_arguments: &IndexMap<base::IndexKey, &Value>) -> Result<f64> {
+    public_arguments.get::<IndexKey>(&"sensitivity".into())
+        .ok_or_else(|| Error::from("sensitivity: missing"))?
+        .ref_array()?.first_float()
+}
+
+/// L1 sensitivity of the histogram over the categories present in the data.
+///
+/// Each record is counted in one category.
+/// Adding or removing a record changes one count by one,
+/// and substituting a record changes two counts by one.
+///
+/// # Arguments
+/// * `privacy_definition` - Definition of privacy, including the neighboring relation.
+pub fn get_sensitivity(privacy_definition: &proto::PrivacyDefinition) -> Result<f64> {
+    use proto::privacy_definition::Neighboring;
+    let neighboring_type = Neighboring::from_i32(privacy_definition.neighboring)
+        .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))?;
+
+    Ok(match neighboring_type {
+        Neighboring::AddRemove => 1.,
+        Neighboring::Substitute => 2.
+    })
+}
+
+/// Smallest noisy count of a released category.
+///
+/// A category present in only one of two neighboring datasets has a count of one,
+/// and its noisy count exceeds the threshold with probability at most delta.
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `delta` - Additive privacy loss parameter, the probability of releasing a category of a single record.
+/// * `sensitivity` - L1 sensitivity of the histogram.
+pub fn get_threshold(epsilon: f64, delta: f64, sensitivity: f64) -> Result<f64> {
+    if epsilon <= 0. {
+        return Err("epsilon: must be positive".into())
+    }
+    if delta <= 0. || delta >= 1. {
+        return Err("delta: must be within (0, 1)".into())
+    }
+    Ok(1. + sensitivity / epsilon * (1. / (2. * delta)).ln())
+}
+
+#[cfg(test)]
+mod test_stability_mechanism {
+    use crate::components::stability_mechanism::get_threshold;
+
+    #[test]
+    fn test_threshold() {
+        // probability of a single record crossing the threshold under laplace noise is delta
+        let (epsilon, delta) = (1., 1e-6);
+        let threshold = get_threshold(epsilon, delta, 1.).unwrap();
+        assert!((0.5 * (-(threshold - 1.) * epsilon).exp() - delta).abs() < 1e-12);
+
+        assert!(get_threshold(epsilon, delta, 2.).unwrap() > threshold);
+        assert!(get_threshold(epsilon, 0., 1.).is_err());
+    }
+}
@@ -0,0 +1,608 @@
+use indexmap::map::IndexMap;
+use statrs::function::erf;
+
+use crate::{base, proto, Warnable};
+use crate::base::{IndexKey, NodeProperties, Value, ValueProperties, DataType};
+use crate::components::{Accuracy, Component};
+use crate::components::noise_scale::get_noise_scale;
+use crate::errors::*;
+use crate::utilities::{get_literal, get_mechanism, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{self, AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::privacy::spread_privacy_usage;
+
+/// Names of the arguments holding the raw moments, from the first order.
+pub const MOMENT_NAMES: [&str; 4] = ["first", "second", "third", "fourth"];
+
+/// Names of the arguments holding the noise scale of each raw moment, from the first order.
+pub const SCALE_NAMES: [&str; 4] = ["first_scale", "second_scale", "third_scale", "fourth_scale"];
+
+/// Probability that the error of a released standardized moment exceeds the accuracy in its report.
+pub const REPORT_ALPHA: f64 = 0.05;
+
+impl Component for proto::StandardizedMoment {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        check_order(self.order)?;
+        if !["laplace", "gaussian"].contains(&self.noise.to_lowercase().as_str()) {
+            return Err(format!("noise: unexpected invalid token {:?}", self.noise).into())
+        }
+
+        // the statistic is post-processing of the released moments
+        let moment_properties = MOMENT_NAMES[..self.order as usize].iter()
+            .map(|name| {
+                let property = properties.get::<IndexKey>(&(*name).into())
+                    .ok_or_else(|| Error::from(format!("{}: missing", name)))?.array()
+                    .map_err(prepend(&format!("{}:", name)))?;
+                if !property.releasable {
+                    return Err(format!("{}: must be public", name).into())
+                }
+                if property.data_type != DataType::Float {
+                    return Err(format!("{}: atomic type must be float", name).into())
+                }
+                Ok(property)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let num_columns = moment_properties[0].num_columns()?;
+        for property in &moment_properties {
+            if property.num_columns()? != num_columns {
+                return Err("moments must have the same number of columns".into())
+            }
+        }
+
+        // the noise scales are optional, as they are only used to estimate accuracy
+        for name in &SCALE_NAMES[..self.order as usize] {
+            if let Some(property) = properties.get::<IndexKey>(&(*name).into()) {
+                let property = property.array().map_err(prepend(&format!("{}:", name)))?;
+                if !property.releasable {
+                    return Err(format!("{}: must be public", name).into())
+                }
+                if property.num_columns()? != num_columns {
+                    return Err(format!("{}: must have a scale for every column", name).into())
+                }
+            }
+        }
+
+        let mut output_property = moment_properties[0].clone();
+        output_property.nature = None;
+        output_property.aggregator = None;
+        output_property.dataset_id = Some(node_id as i64);
+        output_property.node_id = node_id as i64;
+        Ok(ValueProperties::Array(output_property).into())
+    }
+}
+
+impl Accuracy for proto::StandardizedMoment {
+    /// The noise scales are fixed by the mechanisms that released the moments.
+    fn accuracy_to_privacy_usage(
+        &self,
+        _accuracies: &proto::Accuracies,
+        _public_arguments: IndexMap<base::IndexKey, &Value>
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        Ok(None)
+    }
+
+    /// With probability 1 - alpha, the error of the statistic due to the noise is within the accuracy, to first order.
+    ///
+    /// The gradient is evaluated at the released moments when they are public,
+    /// and otherwise at a uniform distribution over the bounds of the data.
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        check_order(self.order)?;
+        let get_columns = |names: &[&str]| names.iter()
+            .map(|name| public_arguments.get::<IndexKey>(&(*name).into())
+                .map(|value| Ok(value.ref_array()?.ref_float()?.iter().cloned().collect::<Vec<f64>>()))
+                .transpose())
+            .collect::<Result<Option<Vec<Vec<f64>>>>>();
+
+        let noise_scales = match get_columns(&SCALE_NAMES[..self.order as usize])? {
+            Some(noise_scales) => noise_scales,
+            None => return Ok(None)
+        };
+        let moments = get_columns(&MOMENT_NAMES[..self.order as usize])?;
+
+        (0..noise_scales[0].len())
+            .map(|column| {
+                let column_moments = match &moments {
+                    Some(moments) => moments.iter()
+                        .map(|moment| moment.get(column).cloned()
+                            .ok_or_else(|| Error::from("moments must have the same number of columns")))
+                        .collect::<Result<Vec<f64>>>()?,
+                    None => get_reference_moments(self.order)
+                };
+                let column_scales = noise_scales.iter()
+                    .map(|scales| scales.get(column).cloned()
+                        .ok_or_else(|| Error::from("noise scales must have the same number of columns")))
+                    .collect::<Result<Vec<f64>>>()?;
+                Ok(proto::Accuracy {
+                    value: get_accuracy(self.order, &self.noise, &column_moments, &column_scales, alpha)?,
+                    alpha,
+                })
+            })
+            .collect::<Result<Vec<proto::Accuracy>>>()
+            .map(Some)
+    }
+}
+
+fn check_order(order: u32) -> Result<()> {
+    if order != 3 && order != 4 {
+        return Err("order: must be 3 for skewness or 4 for kurtosis".into())
+    }
+    Ok(())
+}
+
+fn binomial_coefficient(n: u32, k: u32) -> f64 {
+    (0..k).fold(1., |coefficient, i| coefficient * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// Central moment from the raw moments, `moments[j - 1]` being the raw moment of order j.
+fn get_central_moment(order: u32, moments: &[f64]) -> f64 {
+    let mean = moments[0];
+    (0..=order)
+        .map(|j| {
+            let raw = if j == 0 { 1. } else { moments[j as usize - 1] };
+            binomial_coefficient(order, j) * raw * (-mean).powi((order - j) as i32)
+        })
+        .sum()
+}
+
+/// Variance from the raw moments, floored so that the statistic is always finite.
+fn get_variance(moments: &[f64]) -> f64 {
+    get_central_moment(2, moments).max(f64::EPSILON)
+}
+
+/// Standardized moment of data within `[0, 1]` from its raw moments.
+///
+/// The raw moments are clamped to `[0, 1]`, the range of the raw moments of data within `[0, 1]`,
+/// and the variance is floored at machine epsilon.
+///
+/// # Arguments
+/// * `order` - Order of the standardized moment, 3 for skewness or 4 for kurtosis.
+/// * `moments` - Raw moments of orders one through the order.
+pub fn standardized_moment(order: u32, moments: &[f64]) -> Result<f64> {
+    check_order(order)?;
+    if moments.len() < order as usize {
+        return Err(format!("moments: must contain every raw moment up to order {}", order).into())
+    }
+    let moments = moments.iter().map(|moment| moment.clamp(0., 1.)).collect::<Vec<f64>>();
+    Ok(get_central_moment(order, &moments) / get_variance(&moments).powf(order as f64 / 2.))
+}
+
+/// Gradient of the standardized moment with respect to each raw moment.
+///
+/// The derivative of a central moment of order k with respect to the mean is
+/// `-k` times the central moment of order k - 1, plus the derivative of the term of the mean itself.
+///
+/// # Arguments
+/// * `order` - Order of the standardized moment, 3 for skewness or 4 for kurtosis.
+/// * `moments` - Raw moments of orders one through the order.
+pub fn get_gradient(order: u32, moments: &[f64]) -> Vec<f64> {
+    let mean = moments[0];
+    let variance = get_variance(moments);
+    let central = get_central_moment(order, moments);
+    let half_order = order as f64 / 2.;
+
+    (1..=order)
+        .map(|j| {
+            let central_gradient = if j == 1 {
+                -(order as f64) * get_central_moment(order - 1, moments)
+                    + order as f64 * (-mean).powi(order as i32 - 1)
+            } else {
+                binomial_coefficient(order, j) * (-mean).powi((order - j) as i32)
+            };
+            let variance_gradient = match j {
+                1 => -2. * mean,
+                2 => 1.,
+                _ => 0.
+            };
+            central_gradient / variance.powf(half_order)
+                - half_order * central * variance_gradient / variance.powf(half_order + 1.)
+        })
+        .collect()
+}
+
+/// Raw moments of the uniform distribution over `[0, 1]`, from the first through the order.
+pub fn get_reference_moments(order: u32) -> Vec<f64> {
+    (1..=order).map(|j| 1. / (j + 1) as f64).collect()
+}
+
+/// Heuristic fraction of the budget spent on each raw moment.
+///
+/// Every raw moment of data within `[0, 1]` has the same sensitivity,
+/// so the first-order variance of the statistic is the sum of the squared gradients times the noise variances.
+/// When the noise variance of each moment is inversely proportional to its fraction to the power p,
+/// the sum is minimized when each fraction is proportional to its gradient to the power 2 / (p + 1).
+///
+/// The laplace variance, which the snapping mechanism shares up to rounding, is inversely proportional to the square of epsilon, so p is 2.
+/// The gaussian variance is inversely proportional to rho under a zero-concentrated usage, so p is 1.
+/// Under an approximate usage it is inversely proportional to the square of epsilon,
+/// up to a logarithmic factor in delta that is neglected, so p is 2.
+///
+/// The split is only a heuristic, and is not optimal for the data:
+/// it is fixed before any moment is released,
+/// so the gradient is evaluated at a uniform distribution over the bounds instead of at the data.
+///
+/// # Arguments
+/// * `order` - Order of the standardized moment, 3 for skewness or 4 for kurtosis.
+/// * `noise` - Distribution of the noise, either `laplace` or `gaussian`.
+/// * `privacy_usage` - Privacy usage of the whole release.
+pub fn get_heuristic_budget_fractions(order: u32, noise: &str, privacy_usage: &[proto::PrivacyUsage]) -> Result<Vec<f64>> {
+    use proto::privacy_usage::Distance;
+
+    check_order(order)?;
+    let concentrated = privacy_usage.iter()
+        .all(|usage| matches!(usage.distance, Some(Distance::Concentrated(_))));
+    let power = match noise.to_lowercase().as_str() {
+        "laplace" => 2.,
+        "gaussian" => if concentrated { 1. } else { 2. },
+        _ => bail!("noise: unexpected invalid token {:?}", noise)
+    };
+
+    let weights = get_gradient(order, &get_reference_moments(order)).into_iter()
+        .map(|gradient| gradient.abs().powf(2. / (power + 1.)))
+        .collect::<Vec<f64>>();
+    let total = weights.iter().sum::<f64>();
+    Ok(weights.into_iter().map(|weight| weight / total).collect())
+}
+
+/// First-order accuracy of the standardized moment.
+///
+/// The error of the statistic is linearized in the noise of each raw moment, at the given moments.
+/// A sum of independent gaussians is gaussian,
+/// and a sum of laplaces is bounded by splitting alpha evenly among them.
+///
+/// # Arguments
+/// * `order` - Order of the standardized moment, 3 for skewness or 4 for kurtosis.
+/// * `noise` - Distribution of the noise, either `laplace` or `gaussian`.
+/// * `moments` - Raw moments of orders one through the order, at which the gradient is evaluated.
+/// * `noise_scales` - Scale of the noise added to each raw moment.
+/// * `alpha` - Probability that the error exceeds the accuracy.
+pub fn get_accuracy(order: u32, noise: &str, moments: &[f64], noise_scales: &[f64], alpha: f64) -> Result<f64> {
+    check_order(order)?;
+    if moments.len() != order as usize {
+        return Err(format!("moments: must contain every raw moment up to order {}", order).into())
+    }
+    if noise_scales.len() != order as usize {
+        return Err("noise_scales: must contain the noise scale of every raw moment".into())
+    }
+    if alpha <= 0. || alpha >= 1. {
+        return Err("alpha: must be within (0, 1)".into())
+    }
+    let moments = moments.iter().map(|moment| moment.clamp(0., 1.)).collect::<Vec<f64>>();
+    let gradient = get_gradient(order, &moments);
+
+    Ok(match noise.to_lowercase().as_str() {
+        "laplace" => (order as f64 / alpha).ln() * gradient.iter().zip(noise_scales.iter())
+            .map(|(gradient, scale)| gradient.abs() * scale)
+            .sum::<f64>(),
+        "gaussian" => gradient.iter().zip(noise_scales.iter())
+            .map(|(gradient, scale)| (gradient * scale).powi(2))
+            .sum::<f64>().sqrt() * 2.0_f64.sqrt() * erf::erf_inv(1. - alpha),
+        _ => bail!("noise: unexpected invalid token {:?}", noise)
+    })
+}
+
+/// Distribution of the noise a mechanism adds to each raw moment.
+///
+/// # Arguments
+/// * `mechanism` - One of `laplace`, `snapping`, `gaussian` or `analyticgaussian`.
+fn get_noise(mechanism: &str) -> Result<&'static str> {
+    Ok(match mechanism {
+        "laplace" | "snapping" => "Laplace",
+        "gaussian" | "analyticgaussian" => "Gaussian",
+        _ => bail!("Unexpected invalid token {:?}", mechanism),
+    })
+}
+
+/// Expand a differentially private standardized moment into noisy raw moments of the rescaled data.
+///
+/// # Arguments
+/// * `privacy_definition` - Definition of privacy, used to resolve the `Automatic` mechanism.
+/// * `order` - Order of the standardized moment, 3 for skewness or 4 for kurtosis.
+/// * `mechanism` - Privatizing mechanism used to release each raw moment.
+/// * `privacy_usage` - Privacy usage of the whole release.
+/// * `component` - Component being expanded.
+/// * `properties` - Properties of the arguments of the component.
+/// * `component_id` - Identifier of the component being expanded.
+/// * `maximum_id` - Largest identifier in the graph.
+#[allow(clippy::too_many_arguments)]
+pub fn expand_standardized_moment(
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    order: u32,
+    mechanism: &str,
+    privacy_usage: &[proto::PrivacyUsage],
+    component: &proto::Component,
+    properties: &base::NodeProperties,
+    component_id: u32,
+    mut maximum_id: u32,
+) -> Result<base::ComponentExpansion> {
+    let mut expansion = base::ComponentExpansion::default();
+
+    let privacy_definition = privacy_definition.as_ref()
+        .ok_or("privacy_definition must be defined")?;
+
+    let argument_ids = component.arguments();
+    let data_id = *argument_ids.get::<IndexKey>(&"data".into())
+        .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+    let data_property = properties.get::<IndexKey>(&"data".into())
+        .ok_or("data: missing")?.array()
+        .map_err(prepend("data:"))?;
+
+    if data_property.num_columns()? != 1 {
+        return Err("data: must contain one column".into())
+    }
+    let lower = data_property.lower_float()?[0];
+    let width = data_property.upper_float()?[0] - lower;
+    if width <= 0. {
+        return Err("data: upper bound must be greater than lower bound".into())
+    }
+
+    let mechanism = get_mechanism(&DataType::Float, mechanism, privacy_definition)?;
+    let noise = get_noise(&mechanism)?;
+
+    // rescale the data to [0, 1], which does not change the standardized moment
+    let mut id_scaled = data_id;
+    for (value, variant) in [
+        (lower, proto::component::Variant::Subtract(proto::Subtract {})),
+        (width, proto::component::Variant::Divide(proto::Divide {}))
+    ] {
+        maximum_id += 1;
+        let id_literal = maximum_id;
+        let (patch_node, release) = get_literal(value.into(), component.submission)?;
+        expansion.computation_graph.insert(id_literal, patch_node);
+        expansion.properties.insert(id_literal, infer_property(&release.value, None, id_literal)?);
+        expansion.releases.insert(id_literal, release);
+
+        maximum_id += 1;
+        expansion.computation_graph.insert(maximum_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_scaled, "right".into() => id_literal])),
+            variant: Some(variant),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(maximum_id);
+        id_scaled = maximum_id;
+    }
+
+    let mut moment_arguments = IndexMap::new();
+    for (order_moment, fraction) in (1..=order).zip(get_heuristic_budget_fractions(order, noise, privacy_usage)?) {
+        let moment_usage = privacy_usage.iter().cloned()
+            .map(|v| v * fraction)
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+        // raw moment
+        maximum_id += 1;
+        let id_moment = maximum_id;
+        expansion.computation_graph.insert(id_moment, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_scaled])),
+            variant: Some(proto::component::Variant::RawMoment(proto::RawMoment {
+                order: order_moment
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_moment);
+
+        // noisy raw moment
+        maximum_id += 1;
+        let id_noise = maximum_id;
+        let variant = match mechanism.as_str() {
+            "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
+                privacy_usage: moment_usage.clone()
+            }),
+            "snapping" => proto::component::Variant::SnappingMechanism(proto::SnappingMechanism {
+                privacy_usage: moment_usage.clone()
+            }),
+            _ => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                privacy_usage: moment_usage.clone(),
+                analytic: mechanism == "analyticgaussian",
+            })
+        };
+        expansion.computation_graph.insert(id_noise, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_moment])),
+            variant: Some(variant),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_noise);
+        moment_arguments.insert(MOMENT_NAMES[order_moment as usize - 1].into(), id_noise);
+
+        // scale of the noise added to the raw moment
+        maximum_id += 1;
+        let id_scale = maximum_id;
+        expansion.computation_graph.insert(id_scale, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_moment])),
+            variant: Some(proto::component::Variant::NoiseScale(proto::NoiseScale {
+                mechanism: mechanism.clone(),
+                privacy_usage: moment_usage,
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_scale);
+        moment_arguments.insert(SCALE_NAMES[order_moment as usize - 1].into(), id_scale);
+    }
+
+    // standardized moment
+    expansion.computation_graph.insert(component_id, proto::Component {
+        arguments: Some(proto::ArgumentNodeIds::new(moment_arguments)),
+        variant: Some(proto::component::Variant::StandardizedMoment(proto::StandardizedMoment {
+            order,
+            noise: noise.to_string(),
+        })),
+        omit: component.omit,
+        submission: component.submission,
+    });
+    expansion.traversal.push(component_id);
+
+    Ok(expansion)
+}
+
+/// Summarize a differentially private standardized moment.
+///
+/// The accuracy is a heuristic: the released moments are omitted,
+/// so the gradient is evaluated at a uniform distribution over the bounds,
+/// and the noise scales are derived from the actual privacy usage of each moment.
+///
+/// # Arguments
+/// * `statistic` - Name of the statistic in the report.
+/// * `order` - Order of the standardized moment, 3 for skewness or 4 for kurtosis.
+/// * `mechanism` - Privatizing mechanism used to release each raw moment.
+/// * `privacy_usage` - Privacy usage of the whole release.
+/// * `node_id` - Identifier of the released node.
+/// * `component` - Component being summarized.
+/// * `properties` - Properties of the arguments of the component.
+/// * `release` - Released standardized moment.
+/// * `variable_names` - Names of the columns of the data.
+#[allow(clippy::too_many_arguments)]
+pub fn summarize_standardized_moment(
+    statistic: &str,
+    order: u32,
+    mechanism: &str,
+    privacy_usage: &[proto::PrivacyUsage],
+    node_id: u32,
+    component: &proto::Component,
+    properties: NodeProperties,
+    release: &Value,
+    variable_names: Option<&Vec<base::IndexKey>>,
+) -> Result<Option<Vec<JSONRelease>>> {
+    let data_property = properties.get::<IndexKey>(&"data".into())
+        .ok_or("data: missing")?.array()
+        .map_err(prepend("data:"))?;
+
+    // every raw moment of data within [0, 1] has sensitivity 1 / n
+    let sensitivity = 1. / data_property.num_records()? as f64;
+    // the privacy definition is not available to the report,
+    // but the automatic mechanism is either laplace or snapping, which add noise of the same scale
+    let mechanism_name = match mechanism.to_lowercase().as_str() {
+        "automatic" => "laplace".to_string(),
+        mechanism => mechanism.to_string()
+    };
+    let noise = get_noise(&mechanism_name)?;
+    let noise_scales = get_heuristic_budget_fractions(order, noise, privacy_usage)?.into_iter()
+        .map(|fraction| {
+            let moment_usage = privacy_usage.iter().cloned()
+                .map(|v| v * fraction)
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+            get_noise_scale(
+                &mechanism_name,
+                &spread_privacy_usage(&moment_usage, 1)?[0],
+                sensitivity)
+        })
+        .collect::<Result<Vec<f64>>>()?;
+
+    Ok(Some(vec![JSONRelease {
+        description: "DP release information".to_string(),
+        statistic: statistic.to_string(),
+        variables: serde_json::json!(variable_names.cloned()
+            .unwrap_or_else(Vec::new).iter()
+            .map(|v| v.to_string()).collect::<Vec<String>>()),
+        release_info: value_to_json(release)?,
+        privacy_loss: serde_json::json!(privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
+        accuracy: Some(json::Accuracy {
+            accuracy_value: get_accuracy(
+                order, noise, &get_reference_moments(order), &noise_scales, REPORT_ALPHA)?,
+            alpha: REPORT_ALPHA,
+        }),
+        submission: component.submission,
+        node_id: Some(node_id),
+        postprocess: false,
+        algorithm_info: AlgorithmInfo {
+            name: "Standardized raw moments".to_string(),
+            cite: "Casella and Berger (2002)".to_string(),
+            mechanism: mechanism.to_string(),
+            argument: serde_json::json!({
+                "n": data_property.num_records,
+                "constraint": {
+                    "lowerbound": data_property.lower_float().ok().map(|v| v[0]),
+                    "upperbound": data_property.upper_float().ok().map(|v| v[0])
+                }
+            }),
+        },
+    }]))
+}
+
+#[cfg(test)]
+mod test_standardized_moment {
+    use crate::proto;
+    use crate::components::standardized_moment::{get_accuracy, get_gradient, get_heuristic_budget_fractions, get_reference_moments, standardized_moment};
+
+    #[test]
+    fn test_standardized_moment() {
+        // the uniform distribution is symmetric, with kurtosis 9 / 5
+        assert!(standardized_moment(3, &get_reference_moments(3)).unwrap().abs() < 1e-12);
+        assert!((standardized_moment(4, &get_reference_moments(4)).unwrap() - 1.8).abs() < 1e-12);
+
+        // a bernoulli(1/4) has skewness 2 / sqrt(3) and kurtosis 7 / 3
+        assert!((standardized_moment(3, &[0.25; 3]).unwrap() - 2. / 3_f64.sqrt()).abs() < 1e-12);
+        assert!((standardized_moment(4, &[0.25; 4]).unwrap() - 7. / 3.).abs() < 1e-12);
+
+        assert!(standardized_moment(2, &[0.5, 0.25]).is_err());
+    }
+
+    #[test]
+    fn test_gradient() {
+        // compare to central differences
+        let moments = [0.3, 0.15, 0.09, 0.06];
+        for order in 3..=4 {
+            let gradient = get_gradient(order, &moments);
+            for j in 0..order as usize {
+                let step = 1e-6;
+                let mut upper = moments.to_vec();
+                upper[j] += step;
+                let mut lower = moments.to_vec();
+                lower[j] -= step;
+                let numeric = (standardized_moment(order, &upper).unwrap()
+                    - standardized_moment(order, &lower).unwrap()) / (2. * step);
+                assert!((gradient[j] - numeric).abs() < 1e-4 * numeric.abs().max(1.));
+            }
+        }
+    }
+
+    #[test]
+    fn test_budget() {
+        use crate::proto::privacy_usage::{Distance, DistanceApproximate, DistanceConcentrated};
+        let approximate = vec![proto::PrivacyUsage {
+            distance: Some(Distance::Approximate(DistanceApproximate { epsilon: 1., delta: 1e-6 }))
+        }];
+        let concentrated = vec![proto::PrivacyUsage {
+            distance: Some(Distance::Concentrated(DistanceConcentrated { rho: 0.5 }))
+        }];
+
+        for order in 3..=4 {
+            for (noise, usage) in &[("Laplace", &approximate), ("Gaussian", &approximate), ("Gaussian", &concentrated)] {
+                let fractions = get_heuristic_budget_fractions(order, noise, usage).unwrap();
+                assert_eq!(fractions.len(), order as usize);
+                assert!((fractions.iter().sum::<f64>() - 1.).abs() < 1e-12);
+                assert!(fractions.iter().all(|fraction| *fraction > 0.));
+            }
+
+            // the gaussian variance is inversely proportional to rho, so rho is proportional to the gradient
+            let gradient = get_gradient(order, &get_reference_moments(order));
+            let fractions = get_heuristic_budget_fractions(order, "Gaussian", &concentrated).unwrap();
+            for j in 1..order as usize {
+                assert!((fractions[j] / fractions[0] - gradient[j].abs() / gradient[0].abs()).abs() < 1e-12);
+            }
+        }
+
+        // accuracy scales with the noise
+        let moments = get_reference_moments(3);
+        let accuracy = get_accuracy(3, "Laplace", &moments, &[0.01; 3], 0.05).unwrap();
+        assert!((get_accuracy(3, "Laplace", &moments, &[0.02; 3], 0.05).unwrap() - 2. * accuracy).abs() < 1e-12);
+        assert!(get_accuracy(3, "Laplace", &moments, &[0.01; 2], 0.05).is_err());
+
+        // accuracy depends on the moments it is evaluated at
+        assert!((get_accuracy(3, "Laplace", &[0.25; 3], &[0.01; 3], 0.05).unwrap() - accuracy).abs() > 1e-6);
+    }
+}
@@ -0,0 +1,67 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Value, ValueProperties};
+use crate::components::Component;
+use crate::errors::*;
+use crate::utilities::prepend;
+
+impl Component for proto::UpdateCentroids {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let centroids_property = properties.get::<IndexKey>(&"centroids".into())
+            .ok_or("centroids: missing")?.array()
+            .map_err(prepend("centroids:"))?.clone();
+
+        // the update is post-processing of released statistics
+        if properties.values().any(|property| !property.is_public()) {
+            return Err("arguments: must all be public".into())
+        }
+
+        let num_clusters = centroids_property.num_records()?;
+        let num_columns = centroids_property.num_columns()?;
+
+        for name in &["lower", "upper"] {
+            let bound_property = properties.get::<IndexKey>(&(*name).into())
+                .ok_or_else(|| Error::from(format!("{}: missing", name)))?.array()
+                .map_err(prepend(&format!("{}:", name)))?;
+            if bound_property.num_columns()? != num_columns {
+                return Err(format!("{}: must have one column for each column of the centroids", name).into())
+            }
+        }
+
+        for cluster in 0..num_clusters {
+            let sum_property = properties.get::<IndexKey>(&format!("sum_{}", cluster).into())
+                .ok_or_else(|| Error::from(format!("sum_{}: missing", cluster)))?.array()
+                .map_err(prepend(&format!("sum_{}:", cluster)))?;
+            if sum_property.num_columns()? != num_columns {
+                return Err(format!("sum_{}: must have one column for each column of the centroids", cluster).into())
+            }
+            properties.get::<IndexKey>(&format!("count_{}", cluster).into())
+                .ok_or_else(|| Error::from(format!("count_{}: missing", cluster)))?;
+        }
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_records: Some(num_clusters),
+            num_columns: Some(num_columns),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type: DataType::Float,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: num_clusters > 0,
+            dimensionality: Some(2),
+            group_id: centroids_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+        }).into())
+    }
+}
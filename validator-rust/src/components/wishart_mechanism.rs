@@ -0,0 +1,298 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism};
+use crate::errors::*;
+use crate::utilities::{get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::privacy::{get_pure_rdp_curves, privacy_usage_check, pure_rdp_curve};
+
+impl Component for proto::WishartMechanism {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy_definition must be defined")?;
+
+        if privacy_definition.protect_floating_point {
+            return Err("Floating-point protections are enabled. The wishart mechanism is susceptible to floating-point attacks.".into())
+        }
+
+        if privacy_definition.group_size == 0 {
+            return Err("group size must be greater than zero".into())
+        }
+
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        data_property.assert_is_not_aggregated().map_err(prepend("data:"))?;
+        data_property.assert_is_not_empty().map_err(prepend("data:"))?;
+
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        if data_property.nullity {
+            return Err("data: may not contain nullity. Impute first.".into())
+        }
+
+        let num_columns = data_property.num_columns()?;
+        let data_lower = data_property.lower_float().map_err(prepend("data:"))?;
+        let data_upper = data_property.upper_float().map_err(prepend("data:"))?;
+
+        // user-provided bounds must contain the bounds of the data
+        if let Some(lower) = public_arguments.get::<IndexKey>(&"lower".into()) {
+            let lower = lower.ref_array()?.clone().vec_float(Some(num_columns))?;
+            if lower.iter().zip(data_lower.iter()).any(|(l, d)| l > d) {
+                return Err("lower: may not be greater than the lower bound of the data".into())
+            }
+        } else if properties.contains_key::<IndexKey>(&"lower".into()) {
+            return Err("lower: must be public".into())
+        }
+        if let Some(upper) = public_arguments.get::<IndexKey>(&"upper".into()) {
+            let upper = upper.ref_array()?.clone().vec_float(Some(num_columns))?;
+            if upper.iter().zip(data_upper.iter()).any(|(u, d)| u < d) {
+                return Err("upper: may not be less than the upper bound of the data".into())
+            }
+        } else if properties.contains_key::<IndexKey>(&"upper".into()) {
+            return Err("upper: must be public".into())
+        }
+
+        // the moment is normalized by a public number of records, as the number of records in the data may be private
+        if let Some(num_records) = public_arguments.get::<IndexKey>(&"num_records".into()) {
+            if num_records.ref_array()?.first_int()? <= 0 {
+                return Err("num_records: must be positive".into())
+            }
+        } else if properties.contains_key::<IndexKey>(&"num_records".into()) {
+            return Err("num_records: must be public".into())
+        } else {
+            data_property.num_records().map_err(prepend("data:"))?;
+        }
+
+        if self.privacy_usage.len() != 1 {
+            return Err("privacy_usage: must be of length one".into())
+        }
+
+        let warnings = privacy_usage_check(
+            &self.privacy_usage[0],
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks)?;
+
+        Ok(Warnable(ArrayProperties {
+            num_records: Some(num_columns),
+            num_columns: Some(num_columns),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type: DataType::Float,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(2),
+            group_id: data_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+        }.into(), warnings))
+    }
+}
+
+impl Expandable for proto::WishartMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy definition must be defined")?;
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // update the privacy usage
+        let mut updated_component = component.clone();
+        if let Some(proto::component::Variant::WishartMechanism(variant)) = &mut updated_component.variant {
+            variant.privacy_usage = self.privacy_usage.iter()
+                .map(|usage| usage.actual_to_effective(
+                    data_property.sample_proportion.unwrap_or(1.),
+                    data_property.c_stability,
+                    privacy_definition))
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+        } else { return Err(Error::from("Variant must be defined")) }
+
+        if !properties.contains_key::<IndexKey>(&"lower".into()) {
+            maximum_id += 1;
+            let id_lower = maximum_id;
+            let (patch_node, release) = get_literal(Value::Array(data_property.lower()?), component.submission)?;
+            expansion.computation_graph.insert(id_lower, patch_node);
+            expansion.properties.insert(id_lower, infer_property(&release.value, None, id_lower)?);
+            expansion.releases.insert(id_lower, release);
+            updated_component.insert_argument(&"lower".into(), id_lower);
+        }
+
+        if !properties.contains_key::<IndexKey>(&"upper".into()) {
+            maximum_id += 1;
+            let id_upper = maximum_id;
+            let (patch_node, release) = get_literal(Value::Array(data_property.upper()?), component.submission)?;
+            expansion.computation_graph.insert(id_upper, patch_node);
+            expansion.properties.insert(id_upper, infer_property(&release.value, None, id_upper)?);
+            expansion.releases.insert(id_upper, release);
+            updated_component.insert_argument(&"upper".into(), id_upper);
+        }
+
+        if !properties.contains_key::<IndexKey>(&"num_records".into()) {
+            maximum_id += 1;
+            let id_num_records = maximum_id;
+            let (patch_node, release) = get_literal(data_property.num_records()?.into(), component.submission)?;
+            expansion.computation_graph.insert(id_num_records, patch_node);
+            expansion.properties.insert(id_num_records, infer_property(&release.value, None, id_num_records)?);
+            expansion.releases.insert(id_num_records, release);
+            updated_component.insert_argument(&"num_records".into(), id_num_records);
+        }
+
+        expansion.computation_graph.insert(component_id, updated_component);
+
+        Ok(expansion)
+    }
+}
+
+impl Mechanism for proto::WishartMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Some(release_usage.unwrap_or(&self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn get_rdp_curves(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties,
+        orders: &[f64],
+    ) -> Result<Option<Vec<Vec<f64>>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        get_pure_rdp_curves(
+            release_usage.unwrap_or(&self.privacy_usage),
+            privacy_definition, data_property, orders, pure_rdp_curve).map(Some)
+    }
+}
+
+/// Scale matrix of the Wishart noise, as a multiple of the identity.
+///
+/// Each row is centered at the midpoint of the bounds, so its L2 norm is at most the radius of the bounding box.
+/// Noise drawn from W_d(d + 1, 3 r^2 / (2 epsilon) I) added to the unnormalized second moment matrix satisfies epsilon-differential privacy.
+/// Jiang, Xie and Zhang (2016), Wishart Mechanism for Differentially Private Principal Components Analysis, Theorem 1
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `lower` - Lower bound on each column.
+/// * `upper` - Upper bound on each column.
+pub fn get_wishart_scale(epsilon: f64, lower: &[f64], upper: &[f64]) -> Result<f64> {
+    if epsilon <= 0. {
+        return Err("epsilon: must be positive".into())
+    }
+    if lower.len() != upper.len() {
+        return Err("lower and upper must share the same length".into())
+    }
+    if lower.iter().zip(upper.iter()).any(|(l, u)| l > u) {
+        return Err("lower may not be greater than upper".into())
+    }
+
+    let squared_radius = lower.iter().zip(upper.iter())
+        .map(|(l, u)| ((u - l) / 2.).powi(2))
+        .sum::<f64>();
+    Ok(3. * squared_radius / (2. * epsilon))
+}
+
+#[cfg(test)]
+mod test_wishart_mechanism {
+    use ndarray::{arr1, Array};
+
+    use crate::base::IndexKey;
+    use crate::bindings::Analysis;
+    use crate::components::wishart_mechanism::get_wishart_scale;
+    use crate::proto;
+    use crate::utilities::propagate_properties;
+
+    #[test]
+    fn test_wishart_scale() {
+        // the bounding box [0, 2] x [-1, 1] has radius sqrt(2)
+        let scale = get_wishart_scale(1., &[0., -1.], &[2., 1.]).unwrap();
+        assert!((scale - 3.).abs() < 1e-12);
+
+        assert!(get_wishart_scale(0., &[0.], &[1.]).is_err());
+    }
+
+    #[test]
+    fn test_public_num_records() {
+        let expand = |private_num_records: bool, protect_floating_point: bool| {
+            let mut analysis = Analysis::new();
+            analysis.privacy_definition.protect_floating_point = protect_floating_point;
+            let data = analysis.literal()
+                .value(Array::from_shape_fn((10, 2), |(i, j)| (i + j) as f64).into_dyn().into())
+                .value_public(true).build();
+            let data = analysis.to_float(data).build();
+            let lower = analysis.literal().value(arr1(&[0., 0.]).into_dyn().into()).value_public(true).build();
+            let upper = analysis.literal().value(arr1(&[10., 10.]).into_dyn().into()).value_public(true).build();
+            let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+            let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+            let num_records = if private_num_records {
+                Some(analysis.count(imputed).build())
+            } else { None };
+            let mut wishart = analysis.wishart_mechanism(imputed, vec![proto::PrivacyUsage {
+                distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                    epsilon: 1., delta: 0.
+                }))
+            }]);
+            if let Some(num_records) = num_records {
+                wishart = wishart.num_records(num_records);
+            }
+            let wishart = wishart.build();
+
+            let mut computation_graph = analysis.components.clone();
+            let mut release = analysis.release.clone();
+            propagate_properties(
+                &Some(analysis.privacy_definition.clone()),
+                &mut computation_graph, &mut release, None, false)?;
+
+            let id_num_records = *computation_graph[&wishart].arguments()
+                .get::<IndexKey>(&"num_records".into()).unwrap();
+            release[&id_num_records].value.ref_array()?.first_int()
+        };
+
+        // the number of records is known, and is passed publicly
+        assert_eq!(expand(false, false).unwrap(), 10);
+        assert!(expand(true, false).is_err());
+        // the wishart mechanism samples continuous noise
+        assert!(expand(false, true).is_err());
+    }
+}
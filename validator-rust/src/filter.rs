@@ -0,0 +1,416 @@
+//! Privacy filter and odometer for interactive sessions.
+//!
+//! In an interactive session, each submission may be chosen adaptively based on prior releases,
+//!     and the privacy usage of each submission may itself be chosen adaptively.
+//! The filter admits or rejects each submission so that the session as a whole never exceeds a global (epsilon, delta) cap.
+//! The odometer is a running bound on the privacy usage of the admitted submissions.
+//!
+//! The filter is enabled by setting the `filter_cap` of the privacy definition.
+//! It is replayed over the submissions of the analysis on every release, see [filter_analysis](fn.filter_analysis.html).
+
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::base::Release;
+use crate::errors::*;
+use crate::proto;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json};
+use crate::utilities::privacy::{compute_graph_privacy_usage, get_delta, get_epsilon};
+use crate::utilities::propagate_properties;
+
+/// number of caps in the grid of filters the odometer is stitched from
+const ODOMETER_GRID_SIZE: usize = 20;
+
+/// Admits submissions while their privacy usages compose within a global cap.
+///
+/// Under basic composition, submissions are admitted while the sums of epsilons and deltas are within the cap.
+/// Rogers, Roth, Ullman and Vadhan (2016), Privacy Odometers and Filters: Pay-as-you-Go Composition
+///
+/// Under advanced composition, submissions are admitted by the advanced composition filter.
+/// Half of the `slack_delta` of the privacy definition is spent by the filter,
+///     and the other half is spent by the odometer.
+/// The deltas of the submissions must sum to at most the delta of the cap less the `slack_delta`.
+/// Whitehouse, Ramdas, Rogers and Wu (2022), Fully Adaptive Composition in Differential Privacy, Theorem 3.4
+///
+/// There is no filter for optimal or Rényi composition, so these compositions are rejected.
+pub struct PrivacyFilter {
+    privacy_definition: proto::PrivacyDefinition,
+    /// global epsilon cap
+    epsilon: f64,
+    /// global delta cap
+    delta: f64,
+    /// submission ids and privacy usages of admitted submissions, in order of admission
+    admitted: Vec<(u32, proto::PrivacyUsage)>,
+}
+
+impl PrivacyFilter {
+    pub fn new(privacy_definition: proto::PrivacyDefinition, cap: &proto::PrivacyUsage) -> Result<Self> {
+        let epsilon = get_epsilon(cap).chain_err(|| "cap:")?;
+        let delta = get_delta(cap).chain_err(|| "cap:")?;
+
+        use proto::privacy_definition::Composition;
+        match Composition::from_i32(privacy_definition.composition) {
+            Some(Composition::Basic) | Some(Composition::Advanced) => (),
+            _ => bail!("the privacy filter is only defined under basic or advanced composition")
+        }
+
+        if epsilon <= 0. {
+            bail!("cap: epsilon must be positive")
+        }
+        if !(0. ..1.).contains(&delta) {
+            bail!("cap: delta must be within [0, 1)")
+        }
+
+        let filter = PrivacyFilter {
+            privacy_definition, epsilon, delta,
+            admitted: Vec::new(),
+        };
+        if filter.is_advanced() {
+            let slack_delta = filter.privacy_definition.slack_delta;
+            if slack_delta <= 0. || slack_delta >= delta {
+                bail!("the advanced composition filter requires a slack_delta within (0, delta of the cap)")
+            }
+        }
+        Ok(filter)
+    }
+
+    fn is_advanced(&self) -> bool {
+        self.privacy_definition.composition == proto::privacy_definition::Composition::Advanced as i32
+    }
+
+    /// Privacy usages of the admitted submissions, in order of admission.
+    pub fn get_admitted(&self) -> &[(u32, proto::PrivacyUsage)] {
+        &self.admitted
+    }
+
+    /// Admit a submission if its privacy usage, composed with the usages of all prior admitted submissions, is within the cap.
+    ///
+    /// Rejected submissions are not recorded, and must not be released.
+    pub fn admit(&mut self, submission: u32, usage: proto::PrivacyUsage) -> Result<()> {
+        if self.admitted.iter().any(|(id, _)| *id == submission) {
+            bail!("submission {} has already been admitted", submission)
+        }
+
+        let mut epsilons = self.admitted.iter()
+            .map(|(_, usage)| get_epsilon(usage))
+            .collect::<Result<Vec<f64>>>()?;
+        let mut deltas = self.admitted.iter()
+            .map(|(_, usage)| get_delta(usage))
+            .collect::<Result<Vec<f64>>>()?;
+        epsilons.push(get_epsilon(&usage)
+            .chain_err(|| "the privacy filter requires approximate privacy usages")?);
+        deltas.push(get_delta(&usage)?);
+
+        let delta_sum: f64 = deltas.iter().sum();
+
+        if self.is_advanced() {
+            let slack_delta = self.privacy_definition.slack_delta;
+            if delta_sum > self.delta - slack_delta {
+                bail!("submission {} rejected: deltas would sum to {}, exceeding the cap's delta {} less the slack_delta {}",
+                      submission, delta_sum, self.delta, slack_delta)
+            }
+            if !advanced_filter_admits(&epsilons, self.epsilon, slack_delta / 2.) {
+                bail!("submission {} rejected: the advanced composition filter would exceed the cap's epsilon {}",
+                      submission, self.epsilon)
+            }
+        } else {
+            let epsilon_sum: f64 = epsilons.iter().sum();
+            if epsilon_sum > self.epsilon || delta_sum > self.delta {
+                bail!("submission {} rejected: privacy usage would sum to ({}, {}), exceeding the cap ({}, {})",
+                      submission, epsilon_sum, delta_sum, self.epsilon, self.delta)
+            }
+        }
+
+        self.admitted.push((submission, usage));
+        Ok(())
+    }
+
+    /// Compute the privacy usage of a submission in the analysis, and admit it if it is within the cap.
+    ///
+    /// The computation graph and release should contain all prior submissions, so that properties may be propagated,
+    ///     but only the nodes of the given submission are counted.
+    pub fn admit_analysis(
+        &mut self,
+        mut computation_graph: HashMap<u32, proto::Component>,
+        mut release: Release,
+        submission: u32,
+    ) -> Result<proto::PrivacyUsage> {
+        let mut privacy_definition = self.privacy_definition.clone();
+        privacy_definition.approximate_total = true;
+
+        let properties = propagate_properties(
+            &Some(privacy_definition.clone()),
+            &mut computation_graph,
+            &mut release, None, false)?.0;
+
+        let submission_graph = computation_graph.into_iter()
+            .filter(|(_, component)| component.submission == submission)
+            .collect::<HashMap<u32, proto::Component>>();
+
+        let (usage, _) = compute_graph_privacy_usage(
+            &submission_graph, &privacy_definition, &properties, &release)?;
+
+        self.admit(submission, usage.clone())?;
+        Ok(usage)
+    }
+
+    /// Running bound on the privacy usage of all admitted submissions.
+    ///
+    /// Under basic composition, the odometer is the sum of the privacy usages.
+    /// Otherwise, the odometer is stitched from a grid of advanced composition filters with geometrically decreasing caps,
+    ///     and is the smallest cap in the grid whose filter would have admitted every submission.
+    /// Whitehouse, Ramdas, Rogers and Wu (2022), Fully Adaptive Composition in Differential Privacy, Section 4
+    pub fn odometer(&self) -> Result<proto::PrivacyUsage> {
+        let epsilons = self.admitted.iter()
+            .map(|(_, usage)| get_epsilon(usage))
+            .collect::<Result<Vec<f64>>>()?;
+        let delta_sum: f64 = self.admitted.iter()
+            .map(|(_, usage)| get_delta(usage))
+            .sum::<Result<f64>>()?;
+
+        let (epsilon, delta) = if epsilons.is_empty() {
+            (0., 0.)
+        } else if self.is_advanced() {
+            let slack_delta = self.privacy_definition.slack_delta;
+            // the first cap in the grid is the cap of the filter itself.
+            //    If even the first filter of the grid would reject, the filter itself still bounds the usage
+            let epsilon = (0..ODOMETER_GRID_SIZE)
+                .map(|j| self.epsilon * 2f64.powf(-(j as f64) / 2.))
+                .take_while(|cap| advanced_filter_admits(
+                    &epsilons, *cap, slack_delta / 2. / ODOMETER_GRID_SIZE as f64))
+                .last()
+                .unwrap_or(self.epsilon);
+            (epsilon, slack_delta + delta_sum)
+        } else {
+            (epsilons.iter().sum(), delta_sum)
+        };
+
+        Ok(proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon, delta
+            }))
+        })
+    }
+
+    /// Summarize the odometer as an entry in a report.
+    ///
+    /// # Arguments
+    /// * `rejection` - Submission the filter rejected, and the reason, if any.
+    pub fn odometer_to_json(&self, rejection: Option<&(u32, String)>) -> Result<JSONRelease> {
+        Ok(JSONRelease {
+            description: "Privacy odometer of the admitted submissions".to_string(),
+            variables: serde_json::json!([]),
+            statistic: "Odometer".to_string(),
+            release_info: serde_json::json!({
+                "submissions": self.admitted.iter().map(|(id, _)| *id).collect::<Vec<u32>>(),
+                "rejected": rejection.map(|(submission, reason)| serde_json::json!({
+                    "submission": submission, "reason": reason
+                }))
+            }),
+            privacy_loss: privacy_usage_to_json(&self.odometer()?),
+            accuracy: None,
+            submission: self.admitted.iter().map(|(id, _)| *id).max().unwrap_or(0),
+            node_id: None,
+            postprocess: true,
+            algorithm_info: AlgorithmInfo {
+                mechanism: "".to_string(),
+                name: if self.is_advanced() { "AdvancedFilter" } else { "BasicFilter" }.to_string(),
+                cite: if self.is_advanced() {
+                    "Whitehouse, J., Ramdas, A., Rogers, R., & Wu, Z. S. (2022). Fully adaptive composition in differential privacy."
+                } else {
+                    "Rogers, R. M., Roth, A., Ullman, J., & Vadhan, S. (2016). Privacy odometers and filters: Pay-as-you-go composition."
+                }.to_string(),
+                argument: serde_json::json!({
+                    "cap": {"epsilon": self.epsilon, "delta": self.delta},
+                    "slack_delta": self.privacy_definition.slack_delta
+                }),
+            },
+        })
+    }
+}
+
+/// Replay the privacy filter over the submissions of an analysis, in order of submission id.
+///
+/// Every submission in the graph must be admitted, so a submission the filter rejects may not be released.
+/// Submissions that were already released are admitted with the privacy usages recorded in the release.
+///
+/// # Returns
+/// The filter with every submission admitted, or None if the privacy definition does not set a `filter_cap`.
+pub fn filter_analysis(
+    privacy_definition: &proto::PrivacyDefinition,
+    computation_graph: &HashMap<u32, proto::Component>,
+    release: &Release,
+) -> Result<Option<PrivacyFilter>> {
+    replay_filter(privacy_definition, computation_graph, release)?
+        .map(|(filter, rejection)| match rejection {
+            Some((_, reason)) => Err(reason.into()),
+            None => Ok(filter)
+        })
+        .transpose()
+}
+
+/// Replay the privacy filter over the submissions of an analysis, in order of submission id, up to the first rejection.
+///
+/// # Returns
+/// The filter with the submissions before the first rejection admitted, and the rejected submission with the reason,
+///     or None if the privacy definition does not set a `filter_cap`.
+pub fn replay_filter(
+    privacy_definition: &proto::PrivacyDefinition,
+    computation_graph: &HashMap<u32, proto::Component>,
+    release: &Release,
+) -> Result<Option<(PrivacyFilter, Option<(u32, String)>)>> {
+    let cap = match &privacy_definition.filter_cap {
+        Some(cap) => cap,
+        None => return Ok(None)
+    };
+    let mut filter = PrivacyFilter::new(privacy_definition.clone(), cap)?;
+
+    let rejection = computation_graph.values()
+        .map(|component| component.submission)
+        .unique().sorted()
+        .find_map(|submission| filter
+            .admit_analysis(computation_graph.clone(), release.clone(), submission)
+            .err().map(|err| (submission, err.to_string())));
+
+    Ok(Some((filter, rejection)))
+}
+
+/// Check the condition of the advanced composition filter.
+///
+/// sqrt(2 ln(1/slack_delta) sum epsilon_i^2) + sum epsilon_i^2 / 2 <= cap
+/// Whitehouse, Ramdas, Rogers and Wu (2022), Fully Adaptive Composition in Differential Privacy, Theorem 3.4
+pub fn advanced_filter_admits(epsilons: &[f64], cap: f64, slack_delta: f64) -> bool {
+    let sum_squares: f64 = epsilons.iter().map(|epsilon| epsilon.powi(2)).sum();
+    (2. * (1. / slack_delta).ln() * sum_squares).sqrt() + sum_squares / 2. <= cap
+}
+
+
+#[cfg(test)]
+mod test_filter {
+    use ndarray::arr1;
+
+    use crate::base::ReleaseNode;
+    use crate::bindings::Analysis;
+    use crate::filter::{advanced_filter_admits, filter_analysis, PrivacyFilter};
+    use crate::proto;
+    use crate::utilities::privacy::{get_delta, get_epsilon};
+
+    fn usage(epsilon: f64, delta: f64) -> proto::PrivacyUsage {
+        proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon, delta
+            }))
+        }
+    }
+
+    #[test]
+    fn test_basic_filter() {
+        let mut filter = PrivacyFilter::new(proto::PrivacyDefinition::default(), &usage(1., 1e-6)).unwrap();
+        filter.admit(0, usage(0.5, 0.)).unwrap();
+        assert!(filter.admit(0, usage(0.1, 0.)).is_err());
+        assert!(filter.admit(1, usage(0.6, 0.)).is_err());
+        filter.admit(2, usage(0.4, 1e-6)).unwrap();
+        assert!(filter.admit(3, usage(0.2, 0.)).is_err());
+        assert_eq!(filter.get_admitted().len(), 2);
+
+        let odometer = filter.odometer().unwrap();
+        assert!((get_epsilon(&odometer).unwrap() - 0.9).abs() < 1e-12);
+        assert!((get_delta(&odometer).unwrap() - 1e-6).abs() < 1e-18);
+    }
+
+    #[test]
+    fn test_advanced_filter() {
+        let privacy_definition = proto::PrivacyDefinition {
+            composition: proto::privacy_definition::Composition::Advanced as i32,
+            slack_delta: 1e-6,
+            ..Default::default()
+        };
+        let mut filter = PrivacyFilter::new(privacy_definition, &usage(1., 2e-6)).unwrap();
+
+        // many small submissions are admitted beyond what basic composition would allow
+        let admitted = (0..1000)
+            .take_while(|submission| filter.admit(*submission, usage(0.02, 0.)).is_ok())
+            .count();
+        assert!(admitted > 50);
+        assert!(advanced_filter_admits(&vec![0.02; admitted], 1., 5e-7));
+        assert!(!advanced_filter_admits(&vec![0.02; admitted + 1], 1., 5e-7));
+
+        let odometer = filter.odometer().unwrap();
+        assert!(get_epsilon(&odometer).unwrap() <= 1.);
+        assert!((get_delta(&odometer).unwrap() - 1e-6).abs() < 1e-18);
+    }
+
+    #[test]
+    fn test_filter_analysis() {
+        let mut analysis = Analysis::new();
+        let literal = analysis.literal().value(arr1(&[1., 2., 3.]).into_dyn().into()).value_public(true).build();
+        let data = analysis.to_float(literal).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+        let first = analysis.dp_sum(imputed, vec![usage(0.6, 0.)]).build();
+        analysis.submission_count += 1;
+        let second = analysis.dp_sum(imputed, vec![usage(0.6, 0.)]).build();
+
+        // without a cap, there is no filter
+        assert!(filter_analysis(&analysis.privacy_definition, &analysis.components, &analysis.release)
+            .unwrap().is_none());
+
+        // the second submission is rejected
+        analysis.privacy_definition.filter_cap = Some(usage(1., 0.));
+        assert!(filter_analysis(&analysis.privacy_definition, &analysis.components, &analysis.release).is_err());
+
+        analysis.privacy_definition.filter_cap = Some(usage(2., 0.));
+        let filter = filter_analysis(&analysis.privacy_definition, &analysis.components, &analysis.release)
+            .unwrap().unwrap();
+        assert_eq!(filter.get_admitted().iter().map(|(id, _)| *id).collect::<Vec<u32>>(), vec![0, 1]);
+        assert!((get_epsilon(&filter.odometer().unwrap()).unwrap() - 1.2).abs() < 1e-12);
+
+        // the report includes the odometer of the released submissions
+        let mut release = analysis.release.clone();
+        release.insert(first, ReleaseNode {
+            value: 1.0.into(), privacy_usages: Some(vec![usage(0.6, 0.)]), public: true,
+        });
+        let report: serde_json::Value = serde_json::from_str(&crate::generate_report(
+            analysis.privacy_definition.clone(), analysis.components.clone(), release.clone()).unwrap()).unwrap();
+        let odometer = report.as_array().unwrap().iter()
+            .find(|entry| entry["statistic"] == "Odometer").unwrap();
+        assert_eq!(odometer["releaseInfo"]["submissions"], serde_json::json!([0]));
+        assert!((odometer["privacyLoss"]["epsilon"].as_f64().unwrap() - 0.6).abs() < 1e-12);
+
+        assert!(odometer["releaseInfo"]["rejected"].is_null());
+
+        // summaries of the whole analysis do not belong to a node
+        let composition = report.as_array().unwrap().iter()
+            .find(|entry| entry["statistic"] == "Composition").unwrap();
+        assert!(composition.get("nodeID").is_none());
+        assert!(odometer.get("nodeID").is_none());
+
+        // a rejected submission is reported instead of failing the report
+        analysis.privacy_definition.filter_cap = Some(usage(1., 0.));
+        release.insert(second, ReleaseNode {
+            value: 1.0.into(), privacy_usages: Some(vec![usage(0.6, 0.)]), public: true,
+        });
+        let report: serde_json::Value = serde_json::from_str(&crate::generate_report(
+            analysis.privacy_definition.clone(), analysis.components.clone(), release).unwrap()).unwrap();
+        let odometer = report.as_array().unwrap().iter()
+            .find(|entry| entry["statistic"] == "Odometer").unwrap();
+        assert_eq!(odometer["releaseInfo"]["submissions"], serde_json::json!([0]));
+        assert_eq!(odometer["releaseInfo"]["rejected"]["submission"], serde_json::json!(1));
+    }
+
+    #[test]
+    fn test_unsupported_composition() {
+        // there is no filter for optimal or Rényi composition, so they do not fall back to another filter
+        for composition in &[proto::privacy_definition::Composition::Optimal, proto::privacy_definition::Composition::Renyi] {
+            let privacy_definition = proto::PrivacyDefinition {
+                composition: *composition as i32,
+                slack_delta: 1e-6,
+                ..Default::default()
+            };
+            assert!(PrivacyFilter::new(privacy_definition, &usage(1., 2e-6)).is_err());
+        }
+    }
+}
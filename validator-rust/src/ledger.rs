@@ -0,0 +1,502 @@
+//! File-backed ledger of the privacy budgets of datasets.
+//!
+//! The ledger contains an account for each dataset id.
+//! Each account holds the total budget of the dataset, and a debit for every release made against it.
+//!
+//! Writers hold an exclusive operating system lock on a file beside the ledger while they read, check and rewrite it,
+//! and the ledger is replaced atomically, so concurrent writers never lose or overdraw a debit.
+//! The lock is released by the operating system when the writer exits, so a crashed writer never blocks later analyses.
+//!
+//! The ledger is configured by the data owner, through the environment of the runtime,
+//! and never by the analysis, so that an analyst may not choose the account that is debited.
+
+use std::collections::{BTreeMap, HashMap};
+use std::collections::btree_map::Entry;
+use std::{env, fs, io};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use fs2::FileExt;
+use itertools::Itertools;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::base::Release;
+use crate::errors::*;
+use crate::proto;
+use crate::utilities::json::{json_to_privacy_usage, privacy_usage_to_json};
+
+/// environment variable holding the path to the ledger file
+pub const LEDGER_PATH_VARIABLE: &str = "SMARTNOISE_LEDGER_PATH";
+/// environment variable holding the key of the dataset's account in the ledger
+pub const LEDGER_DATASET_VARIABLE: &str = "SMARTNOISE_LEDGER_DATASET_ID";
+/// environment variable holding the total budget of the dataset, as json, used to open the account if it does not exist
+pub const LEDGER_BUDGET_VARIABLE: &str = "SMARTNOISE_LEDGER_BUDGET";
+
+/// maximum time to wait for another writer to release the ledger
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+/// time to wait between attempts to lock the ledger
+const LOCK_RETRY: Duration = Duration::from_millis(10);
+
+/// The budget of a dataset, and the debits made against it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Account {
+    /// total privacy budget of the dataset
+    pub budget: serde_json::Value,
+    /// one debit for every release made against the dataset
+    pub debits: Vec<Debit>,
+}
+
+/// The privacy usage of a single release.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Debit {
+    /// hash of the computation graph that was released
+    pub graph_hash: String,
+    /// privacy usage of the release
+    pub privacy_usage: serde_json::Value,
+    /// seconds since the unix epoch
+    pub timestamp: u64,
+}
+
+impl Account {
+    pub fn new(budget: &proto::PrivacyUsage) -> Self {
+        Account {
+            budget: privacy_usage_to_json(budget),
+            debits: Vec::new(),
+        }
+    }
+
+    pub fn budget(&self) -> Result<proto::PrivacyUsage> {
+        json_to_privacy_usage(&self.budget).chain_err(|| "budget:")
+    }
+
+    /// Sum of the privacy usages of all debits.
+    ///
+    /// Each release is an independent analysis of the dataset, so debits are composed with basic composition.
+    pub fn spent(&self) -> Result<proto::PrivacyUsage> {
+        self.debits.iter()
+            .map(|debit| json_to_privacy_usage(&debit.privacy_usage))
+            .try_fold(zero_usage(&self.budget()?), |total, usage| total + usage?)
+    }
+
+    /// Error if the budget is exhausted, or if `usage` would exceed the remaining budget.
+    pub fn check(&self, usage: Option<&proto::PrivacyUsage>) -> Result<()> {
+        use proto::privacy_usage::Distance::{Approximate, Concentrated};
+
+        let budget = self.budget()?;
+        let spent = self.spent()?;
+
+        match (spent.distance.as_ref(), budget.distance.as_ref()) {
+            (Some(Approximate(spent)), Some(Approximate(budget))) =>
+                if spent.epsilon >= budget.epsilon || spent.delta > budget.delta {
+                    bail!("privacy budget is exhausted: spent epsilon {} of {}, delta {} of {}",
+                          spent.epsilon, budget.epsilon, spent.delta, budget.delta)
+                },
+            (Some(Concentrated(spent)), Some(Concentrated(budget))) => if spent.rho >= budget.rho {
+                bail!("privacy budget is exhausted: spent rho {} of {}", spent.rho, budget.rho)
+            },
+            _ => bail!("debits must be of the same type as the budget")
+        }
+
+        let usage = match usage {
+            Some(usage) => usage.clone(),
+            None => return Ok(())
+        };
+
+        let total = (spent + usage)
+            .chain_err(|| "privacy usage must be of the same type as the budget")?;
+
+        match (total.distance.as_ref(), budget.distance.as_ref()) {
+            (Some(Approximate(total)), Some(Approximate(budget))) =>
+                if total.epsilon > budget.epsilon || total.delta > budget.delta {
+                    bail!("privacy usage would exceed the remaining budget: epsilon {} of {}, delta {} of {}",
+                          total.epsilon, budget.epsilon, total.delta, budget.delta)
+                },
+            (Some(Concentrated(total)), Some(Concentrated(budget))) =>
+                if total.rho > budget.rho {
+                    bail!("privacy usage would exceed the remaining budget: rho {} of {}", total.rho, budget.rho)
+                },
+            _ => bail!("privacy usage must be of the same type as the budget")
+        }
+        Ok(())
+    }
+}
+
+/// A file-backed ledger of privacy budgets, keyed by dataset id.
+pub struct Ledger {
+    path: PathBuf
+}
+
+impl Ledger {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Ledger { path: path.as_ref().to_path_buf() }
+    }
+
+    /// Retrieve the account of a dataset, if the dataset has been opened in the ledger.
+    pub fn get_account(&self, dataset_id: &str) -> Result<Option<Account>> {
+        Ok(self.read()?.remove(dataset_id))
+    }
+
+    /// Error if the budget of the dataset is exhausted, or if `usage` would exceed the remaining budget.
+    ///
+    /// # Arguments
+    /// * `dataset_id` - key of the dataset's account
+    /// * `budget` - total budget of the dataset, if the dataset does not have an account yet
+    /// * `usage` - privacy usage about to be spent, if known
+    pub fn check(
+        &self, dataset_id: &str, budget: Option<&proto::PrivacyUsage>, usage: Option<&proto::PrivacyUsage>,
+    ) -> Result<()> {
+        match self.get_account(dataset_id)? {
+            Some(account) => account.check(usage),
+            None => Account::new(budget.ok_or_else(|| Error::from(format!(
+                "the ledger has no account for dataset {}, and no budget was provided to open one", dataset_id)))?)
+                .check(usage)
+        }
+    }
+
+    /// Record a debit against the account of a dataset.
+    ///
+    /// The ledger is locked for the duration of the debit.
+    /// If the debit would exceed the remaining budget, it is not recorded, and an error is returned.
+    ///
+    /// # Arguments
+    /// * `dataset_id` - key of the dataset's account
+    /// * `budget` - total budget of the dataset, used to open the account if it does not exist
+    /// * `usage` - privacy usage of the release
+    /// * `graph_hash` - hash of the computation graph that was released
+    ///
+    /// # Returns
+    /// The account after the debit
+    pub fn debit(
+        &self, dataset_id: &str, budget: Option<&proto::PrivacyUsage>,
+        usage: &proto::PrivacyUsage, graph_hash: String,
+    ) -> Result<Account> {
+        let _lock = LedgerLock::acquire(&self.path)?;
+
+        let mut accounts = self.read()?;
+        let account = match accounts.entry(dataset_id.to_string()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Account::new(budget.ok_or_else(|| Error::from(format!(
+                "the ledger has no account for dataset {}, and no budget was provided to open one", dataset_id)))?))
+        };
+
+        account.check(Some(usage))?;
+        account.debits.push(Debit {
+            graph_hash,
+            privacy_usage: privacy_usage_to_json(usage),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs()).unwrap_or(0),
+        });
+        let account = account.clone();
+
+        self.write(&accounts)?;
+        Ok(account)
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, Account>> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .chain_err(|| format!("unable to parse ledger {}", self.path.display())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(err) => Err(format!("unable to read ledger {}: {}", self.path.display(), err).into())
+        }
+    }
+
+    /// Write to a temporary file, and then move it over the ledger, so that readers never observe a partial write.
+    fn write(&self, accounts: &BTreeMap<String, Account>) -> Result<()> {
+        let temporary_path = sibling_path(&self.path, "tmp");
+        let contents = serde_json::to_string_pretty(accounts)
+            .chain_err(|| "unable to serialize ledger")?;
+        fs::write(&temporary_path, contents)
+            .chain_err(|| format!("unable to write ledger {}", temporary_path.display()))?;
+        fs::rename(&temporary_path, &self.path)
+            .chain_err(|| format!("unable to write ledger {}", self.path.display()))
+    }
+}
+
+/// Exclusive lock on a ledger, held on a lock file beside the ledger for as long as the lock is open.
+///
+/// The lock file itself is never removed. Only the operating system lock on it is meaningful,
+///     and the operating system releases it when the file is closed, even if the process is killed.
+struct LedgerLock {
+    _file: fs::File
+}
+
+impl LedgerLock {
+    fn acquire(ledger_path: &Path) -> Result<Self> {
+        let path = sibling_path(ledger_path, "lock");
+        let file = fs::OpenOptions::new().write(true).create(true).truncate(false).open(&path)
+            .chain_err(|| format!("unable to open ledger lock {}", path.display()))?;
+        let start = Instant::now();
+        loop {
+            match try_lock(&file) {
+                Ok(true) => return Ok(LedgerLock { _file: file }),
+                Ok(false) => {
+                    if start.elapsed() > LOCK_TIMEOUT {
+                        bail!("timed out waiting for another release to unlock the ledger {}", path.display())
+                    }
+                    sleep(LOCK_RETRY)
+                }
+                Err(err) => bail!("unable to lock ledger {}: {}", path.display(), err)
+            }
+        }
+    }
+}
+
+/// Attempt to take an exclusive lock on the file without blocking. Returns false if the lock is held elsewhere.
+fn try_lock(file: &fs::File) -> io::Result<bool> {
+    match FileExt::try_lock_exclusive(file) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == fs2::lock_contended_error().kind() => Ok(false),
+        Err(err) => Err(err)
+    }
+}
+
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut sibling = path.as_os_str().to_owned();
+    sibling.push(format!(".{}", extension));
+    PathBuf::from(sibling)
+}
+
+fn zero_usage(usage: &proto::PrivacyUsage) -> proto::PrivacyUsage {
+    use proto::privacy_usage::{Distance, DistanceApproximate, DistanceConcentrated};
+    proto::PrivacyUsage {
+        distance: Some(match usage.distance {
+            Some(Distance::Concentrated(_)) => Distance::Concentrated(DistanceConcentrated { rho: 0. }),
+            _ => Distance::Approximate(DistanceApproximate { epsilon: 0., delta: 0. })
+        })
+    }
+}
+
+/// Hash of a computation graph that is stable across processes.
+///
+/// Components are encoded in order of node id, and hashed with 64-bit FNV-1a.
+pub fn hash_graph(computation_graph: &HashMap<u32, proto::Component>) -> Result<String> {
+    let mut buffer = Vec::new();
+    for node_id in computation_graph.keys().sorted() {
+        buffer.extend_from_slice(&node_id.to_le_bytes());
+        computation_graph[node_id].encode(&mut buffer)
+            .chain_err(|| format!("unable to encode node {}", node_id))?;
+    }
+
+    let hash = buffer.into_iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte|
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3));
+    Ok(format!("{:016x}", hash))
+}
+
+/// Ledger configured by the data owner in the environment of the runtime.
+///
+/// Returns None if no ledger path is configured.
+/// If a path is configured, then the dataset id must be configured too.
+pub fn configured_ledger() -> Result<Option<proto::Ledger>> {
+    let path = match env::var(LEDGER_PATH_VARIABLE) {
+        Ok(path) => path,
+        Err(env::VarError::NotPresent) => return Ok(None),
+        Err(err) => bail!("{}: {}", LEDGER_PATH_VARIABLE, err)
+    };
+    let dataset_id = env::var(LEDGER_DATASET_VARIABLE)
+        .chain_err(|| format!("{} must be set when {} is set", LEDGER_DATASET_VARIABLE, LEDGER_PATH_VARIABLE))?;
+    let budget = match env::var(LEDGER_BUDGET_VARIABLE) {
+        Ok(budget) => Some(json_to_privacy_usage(&serde_json::from_str(&budget)
+            .chain_err(|| format!("{} must be json", LEDGER_BUDGET_VARIABLE))?)
+            .chain_err(|| LEDGER_BUDGET_VARIABLE)?),
+        Err(env::VarError::NotPresent) => None,
+        Err(err) => bail!("{}: {}", LEDGER_BUDGET_VARIABLE, err)
+    };
+    Ok(Some(proto::Ledger { path, dataset_id, budget }))
+}
+
+/// Replace the public nodes of a prior release with literals, so that their privacy usages are not debited again.
+fn exclude_released(
+    mut computation_graph: HashMap<u32, proto::Component>, release: &Release,
+) -> HashMap<u32, proto::Component> {
+    release.iter()
+        .filter(|(_, release_node)| release_node.public)
+        .for_each(|(node_id, _)| if let Some(component) = computation_graph.get_mut(node_id) {
+            component.arguments = None;
+            component.variant = Some(proto::component::Variant::Literal(proto::Literal {}));
+        });
+    computation_graph
+}
+
+/// Privacy usage of an analysis, in the same terms as the budget of the dataset.
+fn compute_ledger_usage(
+    privacy_definition: &proto::PrivacyDefinition,
+    ledger: &proto::Ledger,
+    computation_graph: HashMap<u32, proto::Component>,
+    release: Release,
+) -> Result<proto::PrivacyUsage> {
+    let budget = match Ledger::new(&ledger.path).get_account(&ledger.dataset_id)? {
+        Some(account) => Some(account.budget()?),
+        None => ledger.budget.clone()
+    };
+
+    let mut privacy_definition = privacy_definition.clone();
+    // zero-concentrated usages are only reported as such if the budget is zero-concentrated
+    privacy_definition.approximate_total = !matches!(
+        budget.and_then(|v| v.distance), Some(proto::privacy_usage::Distance::Concentrated(_)));
+
+    Ok(crate::compute_privacy_usage(privacy_definition, computation_graph, release)
+        .chain_err(|| "unable to determine the privacy usage of the analysis")?.0)
+}
+
+/// Error if the budget of the dataset is exhausted, or would be exceeded by the analysis.
+///
+/// Nodes that were already released are not charged again.
+pub fn check_analysis(
+    privacy_definition: &proto::PrivacyDefinition,
+    ledger: &proto::Ledger,
+    computation_graph: HashMap<u32, proto::Component>,
+    release: Release,
+) -> Result<()> {
+    let computation_graph = exclude_released(computation_graph, &release);
+    let usage = compute_ledger_usage(privacy_definition, ledger, computation_graph, release)?;
+
+    Ledger::new(&ledger.path)
+        .check(&ledger.dataset_id, ledger.budget.as_ref(), Some(&usage))
+        .chain_err(|| format!("dataset {}", ledger.dataset_id))
+}
+
+/// Debit the privacy usage of a released analysis from the budget of the dataset.
+///
+/// The actual privacy usages of mechanisms in the release are debited.
+/// Nodes that were not released, for example because they failed to evaluate,
+///     and nodes that were already present in the `prior_release`, are not debited.
+pub fn debit_analysis(
+    privacy_definition: &proto::PrivacyDefinition,
+    ledger: &proto::Ledger,
+    mut computation_graph: HashMap<u32, proto::Component>,
+    prior_release: &Release,
+    release: Release,
+) -> Result<Account> {
+    let graph_hash = hash_graph(&computation_graph)?;
+    computation_graph.retain(|node_id, _| release.contains_key(node_id));
+    let computation_graph = exclude_released(computation_graph, prior_release);
+    let usage = compute_ledger_usage(privacy_definition, ledger, computation_graph, release)?;
+
+    Ledger::new(&ledger.path)
+        .debit(&ledger.dataset_id, ledger.budget.as_ref(), &usage, graph_hash)
+        .chain_err(|| format!("dataset {}", ledger.dataset_id))
+}
+
+
+#[cfg(test)]
+mod test_ledger {
+    use std::sync::Arc;
+    use std::thread;
+
+    use ndarray::arr1;
+
+    use crate::base::ReleaseNode;
+    use crate::bindings::Analysis;
+    use crate::ledger::{check_analysis, debit_analysis, Ledger};
+    use crate::proto;
+
+    fn usage(epsilon: f64, delta: f64) -> proto::PrivacyUsage {
+        proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon, delta
+            }))
+        }
+    }
+
+    fn temporary_ledger(name: &str) -> Ledger {
+        let path = std::env::temp_dir().join(format!("smartnoise_ledger_{}_{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Ledger::new(path)
+    }
+
+    #[test]
+    fn test_exhaustion() {
+        let ledger = temporary_ledger("exhaustion");
+        let budget = usage(1., 1e-6);
+
+        ledger.check("data", Some(&budget), Some(&usage(0.5, 0.))).unwrap();
+        ledger.debit("data", Some(&budget), &usage(0.5, 0.), "a".to_string()).unwrap();
+        // the budget recorded in the ledger takes priority
+        assert!(ledger.check("data", Some(&usage(10., 1e-6)), Some(&usage(0.6, 0.))).is_err());
+        assert!(ledger.debit("data", None, &usage(0.5, 1e-5), "b".to_string()).is_err());
+
+        let account = ledger.debit("data", None, &usage(0.5, 1e-6), "c".to_string()).unwrap();
+        assert_eq!(account.debits.iter().map(|debit| debit.graph_hash.as_str()).collect::<Vec<_>>(), vec!["a", "c"]);
+        assert!(ledger.check("data", None, None).is_err());
+
+        // unknown datasets require a budget
+        assert!(ledger.check("other", None, None).is_err());
+    }
+
+    #[test]
+    fn test_concurrent_debits() {
+        let ledger = Arc::new(temporary_ledger("concurrent"));
+
+        let successes = (0..20)
+            .map(|_| {
+                let ledger = ledger.clone();
+                thread::spawn(move || ledger.debit(
+                    "data", Some(&usage(1., 0.)), &usage(0.1, 0.), "graph".to_string()).is_ok())
+            })
+            .collect::<Vec<_>>().into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(|success| *success)
+            .count();
+
+        assert_eq!(successes, 10);
+        assert_eq!(ledger.get_account("data").unwrap().unwrap().debits.len(), 10);
+    }
+
+    #[test]
+    fn test_analysis() {
+        let ledger = temporary_ledger("analysis");
+        let config = proto::Ledger {
+            path: ledger.path.to_string_lossy().to_string(),
+            dataset_id: "data".to_string(),
+            budget: Some(usage(0.6, 0.)),
+        };
+
+        let mut analysis = Analysis::new();
+        let literal = analysis.literal().value(arr1(&[1., 2., 3.]).into_dyn().into()).value_public(true).build();
+        let data = analysis.to_float(literal).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+        let first = analysis.dp_sum(imputed, vec![usage(0.5, 0.)]).build();
+        let second = analysis.dp_sum(imputed, vec![usage(0.5, 0.)]).build();
+
+        // both sums together exceed the budget
+        let error = check_analysis(
+            &analysis.privacy_definition, &config,
+            analysis.components.clone(), analysis.release.clone()).unwrap_err();
+        assert!(error.iter().any(|err| err.to_string().starts_with("privacy usage would exceed")));
+
+        // the first sum has already been released, so only the second is charged
+        let mut prior_release = analysis.release.clone();
+        prior_release.insert(first, ReleaseNode {
+            value: 1.0.into(), privacy_usages: Some(vec![usage(0.5, 0.)]), public: true,
+        });
+        check_analysis(
+            &analysis.privacy_definition, &config,
+            analysis.components.clone(), prior_release.clone()).unwrap();
+
+        // the runtime releases every evaluated node, including private intermediate nodes
+        let mut release = prior_release.clone();
+        for node_id in [data, clamped, imputed] {
+            release.insert(node_id, ReleaseNode::new(arr1(&[1., 2., 3.]).into_dyn().into()));
+        }
+        release.insert(second, ReleaseNode {
+            value: 2.0.into(), privacy_usages: Some(vec![usage(0.5, 0.)]), public: true,
+        });
+        let account = debit_analysis(
+            &analysis.privacy_definition, &config,
+            analysis.components.clone(), &prior_release, release).unwrap();
+        assert_eq!(account.debits.len(), 1);
+        assert_eq!(account.spent().unwrap(), usage(0.5, 0.));
+
+        // analyses whose privacy usage cannot be determined are refused
+        let mut invalid = analysis.components.clone();
+        invalid.remove(&imputed);
+        assert!(check_analysis(&analysis.privacy_definition, &config, invalid, analysis.release.clone()).is_err());
+    }
+}
@@ -59,6 +59,8 @@ pub mod base;
 pub mod bindings;
 pub mod utilities;
 pub mod components;
+pub mod filter;
+pub mod ledger;
 pub mod docs;
 
 // include protobuf-generated traits
@@ -108,25 +110,28 @@ pub fn validate_analysis(
 
 /// Compute overall privacy usage of an analysis.
 ///
-/// The privacy usage is sum of the privacy usages for each node.
+/// The privacy usages of nodes released in the same batch are combined by the composition theorem in the privacy definition,
+///     and the privacy usages of batches are summed.
 /// The Release's actual privacy usage, if defined, takes priority over the maximum allowable privacy usage defined in the Analysis.
+///
+/// The composition theorems that were applied are returned alongside the privacy usage.
 pub fn compute_privacy_usage(
     privacy_definition: proto::PrivacyDefinition,
     mut computation_graph: HashMap<u32, proto::Component>,
     mut release: base::Release
-) -> Result<proto::PrivacyUsage> {
+) -> Result<(proto::PrivacyUsage, Vec<proto::CompositionTheorem>)> {
 
     let properties = utilities::propagate_properties(
         &Some(privacy_definition.clone()),
         &mut computation_graph,
         &mut release, None, false)?.0;
 
-    let privacy_usage = compute_graph_privacy_usage(
+    let (privacy_usage, theorems) = compute_graph_privacy_usage(
         &computation_graph, &privacy_definition, &properties, &release)?;
 
     utilities::privacy::privacy_usage_check(&privacy_usage, None, false)?;
 
-    Ok(privacy_usage)
+    Ok((privacy_usage, theorems.into_iter().collect()))
 }
 
 
@@ -137,9 +142,24 @@ pub fn generate_report(
     mut release: base::Release
 ) -> Result<String> {
 
+    // the odometer covers every submission with a released value
+    let released_submissions = computation_graph.iter()
+        .filter(|(node_id, _)| release.contains_key(node_id))
+        .map(|(_, component)| component.submission)
+        .collect::<HashSet<u32>>();
+    // a submission the filter rejects is reported, instead of failing the report
+    let filter = filter::replay_filter(
+        &privacy_definition,
+        &computation_graph.iter()
+            .filter(|(_, component)| released_submissions.contains(&component.submission))
+            .map(|(node_id, component)| (*node_id, component.clone()))
+            .collect(),
+        &release)?;
+
+    let mut expanded_graph = computation_graph.clone();
     let graph_properties = utilities::propagate_properties(
-        &Some(privacy_definition),
-        &mut computation_graph.clone(),
+        &Some(privacy_definition.clone()),
+        &mut expanded_graph,
         &mut release, None, false)?.0;
 
     // variable names
@@ -174,7 +194,7 @@ pub fn generate_report(
     });
 
     // generate summaries for any component that has a release, and has summarize implemented on it
-    let release_schemas = computation_graph.iter()
+    let mut release_schemas = computation_graph.iter()
         .map(|(node_id, component)| {
             let public_arguments = utilities::get_public_arguments(&component, &release)?;
             let input_properties = utilities::get_input_properties(&component, &graph_properties)?;
@@ -197,6 +217,31 @@ pub fn generate_report(
         .filter_map(|v| v).flat_map(|v| v)
         .collect::<Vec<utilities::json::JSONRelease>>();
 
+    // summarize the overall privacy usage, and the composition theorems used to compute it
+    if !release_schemas.is_empty() {
+        let (privacy_usage, theorems) = compute_graph_privacy_usage(
+            &expanded_graph, &privacy_definition, &graph_properties, &release)?;
+
+        release_schemas.push(utilities::json::JSONRelease {
+            description: "Total privacy usage of the analysis".to_string(),
+            variables: serde_json::json!([]),
+            statistic: "Composition".to_string(),
+            release_info: serde_json::Value::Null,
+            privacy_loss: utilities::json::privacy_usage_to_json(&privacy_usage),
+            accuracy: None,
+            submission: computation_graph.values()
+                .map(|component| component.submission).max().unwrap_or(0),
+            node_id: None,
+            postprocess: true,
+            algorithm_info: utilities::json::composition_to_algorithm_info(
+                &theorems, &privacy_definition),
+        });
+    }
+
+    if let Some((filter, rejection)) = filter {
+        release_schemas.push(filter.odometer_to_json(rejection.as_ref())?);
+    }
+
     match serde_json::to_string(&release_schemas) {
         Ok(serialized) => Ok(serialized),
         Err(_) => Err("unable to parse report into json".into())
@@ -272,9 +317,8 @@ pub fn privacy_usage_to_accuracy(
         .filter_map(|(name, idx)| Some((*idx, public_arguments.remove(name)?)))
         .collect();
 
-    let mut computation_graph = hashmap![
-        component.arguments().values().max().cloned().unwrap_or(0) + 1 => component
-    ];
+    let component_id = component.arguments().values().max().cloned().unwrap_or(0) + 1;
+    let mut computation_graph = hashmap![component_id => component];
 
     utilities::propagate_properties(
         &Some(privacy_definition.clone()),
@@ -284,7 +328,7 @@ pub fn privacy_usage_to_accuracy(
         false,
     )?;
 
-    let accuracies = computation_graph.iter().map(|(idx, component)| {
+    let mut accuracies = computation_graph.iter().map(|(idx, component)| {
         Ok(component.privacy_usage_to_accuracy(
             get_public_arguments(&component, &release)?,
             alpha,
@@ -295,6 +339,11 @@ pub fn privacy_usage_to_accuracy(
         .into_iter().filter_map(|v| v)
         .collect::<HashMap<u32, Vec<proto::Accuracy>>>();
 
+    // components that expand into post-processing report the accuracy of the whole release
+    if let Some(values) = accuracies.remove(&component_id) {
+        return Ok(proto::Accuracies { values })
+    }
+
     Ok(proto::Accuracies {
         values: accuracies.into_iter().map(|(_, v)| v).collect::<Vec<Vec<proto::Accuracy>>>()
             // TODO: propagate/combine accuracies, don't just take the first
@@ -108,4 +108,44 @@ pub fn slow_select<A, D>(data: &Array<A, D>, axis: Axis, indices: &[Ix]) -> Arra
     } else {
         slow_stack(axis, &subs).unwrap()
     }
-}
\ No newline at end of file
+}
+
+/// Number of rows of a symmetric matrix, given as either a square matrix or a single row holding its upper triangle.
+///
+/// The upper triangle is the layout of the covariance aggregator before it is reshaped.
+pub fn get_symmetric_size(num_records: i64, num_columns: i64) -> Result<i64> {
+    if num_records == num_columns {
+        return Ok(num_columns)
+    }
+    // solve size (size + 1) / 2 = num_columns
+    let size = (((8 * num_columns + 1) as f64).sqrt() as i64 - 1) / 2;
+    if num_records != 1 || size * (size + 1) / 2 != num_columns {
+        bail!("must be a square matrix, or a single row containing the upper triangle of a square matrix")
+    }
+    Ok(size)
+}
+
+/// Rebuild a symmetric matrix from either a square matrix, or a single row holding its upper triangle in row-major order.
+pub fn to_symmetric_matrix<T: Clone>(array: &ArrayD<T>) -> Result<Vec<Vec<T>>> {
+    let (num_records, num_columns) = match array.shape() {
+        [num_records, num_columns] => (*num_records, *num_columns),
+        _ => bail!("must be 2-dimensional")
+    };
+    let size = get_symmetric_size(num_records as i64, num_columns as i64)? as usize;
+    let values = array.iter().cloned().collect::<Vec<T>>();
+    if num_records == num_columns {
+        return Ok(values.chunks(size).map(|row| row.to_vec()).collect())
+    }
+
+    // offset of the first element of each row of the upper triangle
+    let offsets = (0..size).scan(0, |offset, i| {
+        let start = *offset;
+        *offset += size - i;
+        Some(start)
+    }).collect::<Vec<usize>>();
+    Ok((0..size)
+        .map(|i| (0..size)
+            .map(|j| if i <= j { values[offsets[i] + j - i].clone() } else { values[offsets[j] + i - j].clone() })
+            .collect())
+        .collect())
+}
@@ -6,8 +6,11 @@ use serde::{Deserialize, Serialize};
 use crate::proto;
 use crate::base;
 
+use std::collections::BTreeSet;
+
 use serde_json::Value;
 use ndarray::prelude::*;
+use itertools::Itertools;
 
 
 /// JSONRelease represents JSON objects in the differential privacy release schema.
@@ -29,9 +32,10 @@ pub struct JSONRelease {
     pub accuracy: Option<Accuracy>,
     /// which release the implemented statistic is originating from. This provides a tool to keep track of overall privacyLoss.
     pub submission: u32,
-    /// For advanced users. Corresponds to the node of the graph this release originated from
-    #[serde(rename(serialize = "nodeID", deserialize = "nodeID"))]
-    pub node_id: u32,
+    /// For advanced users. Corresponds to the node of the graph this release originated from.
+    /// Summaries of the whole analysis, like the composition and the odometer, have no node.
+    #[serde(rename(serialize = "nodeID", deserialize = "nodeID"), default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<u32>,
     /// true when the released value is derived from public/released data
     pub postprocess: bool,
     /// the name of the algorithm which is implemented for computation of the given statistic and the arguments of the algorithm such as n(number of observations),  range (upper and lower bound, etc.)
@@ -94,6 +98,66 @@ pub fn arraynd_to_json<T: Serialize + Clone>(array: &ArrayD<T>) -> Result<serde_
 pub fn privacy_usage_to_json(privacy_usage: &proto::PrivacyUsage) -> serde_json::Value {
     match privacy_usage.distance.clone().unwrap() {
         proto::privacy_usage::Distance::Approximate(distance) =>
-            serde_json::json!({"name": "approximate", "epsilon": distance.epsilon, "delta": distance.delta})
+            serde_json::json!({"name": "approximate", "epsilon": distance.epsilon, "delta": distance.delta}),
+        proto::privacy_usage::Distance::Concentrated(distance) =>
+            serde_json::json!({"name": "concentrated", "rho": distance.rho})
+    }
+}
+
+/// Inverse of `privacy_usage_to_json`.
+pub fn json_to_privacy_usage(value: &serde_json::Value) -> Result<proto::PrivacyUsage> {
+    use proto::privacy_usage::{Distance, DistanceApproximate, DistanceConcentrated};
+
+    let get_f64 = |name: &str| value.get(name).and_then(Value::as_f64)
+        .ok_or_else(|| Error::from(format!("privacy usage: {} must be a number", name)));
+
+    Ok(proto::PrivacyUsage {
+        distance: Some(match value.get("name").and_then(Value::as_str) {
+            Some("approximate") => Distance::Approximate(DistanceApproximate {
+                epsilon: get_f64("epsilon")?,
+                delta: get_f64("delta")?,
+            }),
+            Some("concentrated") => Distance::Concentrated(DistanceConcentrated {
+                rho: get_f64("rho")?
+            }),
+            _ => bail!("privacy usage: name must be either \"approximate\" or \"concentrated\"")
+        })
+    })
+}
+
+
+/// Summarizes the composition theorems applied to the privacy usages of an analysis.
+pub fn composition_to_algorithm_info(
+    theorems: &BTreeSet<proto::CompositionTheorem>,
+    privacy_definition: &proto::PrivacyDefinition,
+) -> AlgorithmInfo {
+    use proto::CompositionTheorem as Theorem;
+
+    let (names, citations): (Vec<&str>, Vec<&str>) = theorems.iter()
+        .map(|theorem| match theorem {
+            Theorem::BasicComposition => ("Basic", ""),
+            Theorem::AdvancedComposition => ("Advanced",
+                "Dwork, C., Rothblum, G. N., & Vadhan, S. (2010). Boosting and differential privacy."),
+            Theorem::OptimalHomogeneousComposition => ("OptimalHomogeneous",
+                "Kairouz, P., Oh, S., & Viswanath, P. (2015). The composition theorem for differential privacy."),
+            Theorem::OptimalHeterogeneousComposition => ("OptimalHeterogeneous",
+                "Murtagh, J., & Vadhan, S. (2016). The complexity of computing the optimal composition of differential privacy."),
+            Theorem::ConcentratedComposition => ("Concentrated",
+                "Bun, M., & Steinke, T. (2016). Concentrated differential privacy: Simplifications, extensions, and lower bounds."),
+            Theorem::ConcentratedConversion => ("ConcentratedConversion",
+                "Bun, M., & Steinke, T. (2016). Concentrated differential privacy: Simplifications, extensions, and lower bounds."),
+            Theorem::RenyiComposition => ("Renyi",
+                "Mironov, I. (2017). Rényi differential privacy."),
+        })
+        .unzip();
+
+    AlgorithmInfo {
+        mechanism: "".to_string(),
+        name: names.join(", "),
+        cite: citations.into_iter().filter(|cite| !cite.is_empty()).unique().collect::<Vec<&str>>().join(" "),
+        argument: serde_json::json!({
+            "theorems": names,
+            "slack_delta": privacy_definition.slack_delta
+        }),
     }
 }
@@ -7,8 +7,8 @@ use itertools::Itertools;
 use ndarray::prelude::*;
 use noisy_float::prelude::n64;
 
-use crate::{base, Float, proto, Warnable};
-use crate::base::{IndexKey, NodeProperties, Release, SensitivitySpace, Value, ValueProperties, ArrayProperties, Array};
+use crate::{base, Float, Integer, proto, Warnable};
+use crate::base::{IndexKey, NodeProperties, Release, SensitivitySpace, Value, ValueProperties, ArrayProperties, Array, DataType};
 // import all trait implementations
 use crate::components::*;
 use crate::errors::*;
@@ -506,8 +506,35 @@ pub fn prepend(text: &str) -> impl Fn(Error) -> Error + '_ {
     move |e| format!("{} {}", text, e).into()
 }
 
+/// Lowercase name of the mechanism a component privatizes its aggregate with, resolving `Automatic`.
+///
+/// Integer aggregates use the discrete laplace mechanism when floating-point protections are enabled,
+///     unless elapsed time is protected, as the discrete laplace sampler does not run in constant time.
+///     Otherwise they use the simple geometric mechanism.
+/// Float aggregates use the snapping mechanism when floating-point protections are enabled, and otherwise the laplace mechanism.
+///
+/// # Arguments
+/// * `data_type` - Atomic type of the aggregate.
+/// * `mechanism` - Mechanism chosen by the analyst, which is returned as-is unless it is `Automatic`.
+/// * `privacy_definition` - Definition of privacy, with the protections the mechanism must satisfy.
+pub fn get_mechanism(
+    data_type: &DataType, mechanism: &str, privacy_definition: &proto::PrivacyDefinition,
+) -> Result<String> {
+    let mechanism = mechanism.to_lowercase();
+    if mechanism != "automatic" {
+        return Ok(mechanism)
+    }
+
+    let protect_floating_point = privacy_definition.protect_floating_point;
+    Ok(match data_type {
+        DataType::Int => if protect_floating_point && !privacy_definition.protect_elapsed_time
+            { "discretelaplace" } else { "simplegeometric" },
+        DataType::Float => if protect_floating_point { "snapping" } else { "laplace" },
+        _ => return Err("mechanisms are only defined for numeric aggregates".into())
+    }.to_string())
+}
+
 /// Utility function for building component expansions for dp mechanisms
-#[allow(clippy::float_cmp)]
 pub fn expand_mechanism(
     sensitivity_type: &SensitivitySpace,
     privacy_definition: &Option<proto::PrivacyDefinition>,
@@ -527,19 +554,7 @@ pub fn expand_mechanism(
         .ok_or("data: missing")?.array()
         .map_err(prepend("data:"))?.clone();
 
-    // spread privacy usage over each column
-    let spread_usages = spread_privacy_usage(
-        // spread usage over each column
-        privacy_usage, data_property.num_columns()? as usize)?;
-
-    // convert to effective usage
-    let effective_usages = spread_usages.into_iter()
-        // reduce epsilon allowed to algorithm based on c-stability and group size
-        .map(|usage| usage.actual_to_effective(
-            data_property.sample_proportion.unwrap_or(1.),
-            data_property.c_stability,
-            privacy_definition.group_size))
-        .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+    let effective_usages = get_effective_usages(privacy_definition, privacy_usage, &data_property)?;
 
     // insert sensitivity and usage
     let mut noise_component = component.clone();
@@ -553,7 +568,7 @@ pub fn expand_mechanism(
             }
         }
     }
-    assign_usage!(LaplaceMechanism, GaussianMechanism, SimpleGeometricMechanism, SnappingMechanism);
+    assign_usage!(LaplaceMechanism, GaussianMechanism, DiscreteGaussianMechanism, DiscreteLaplaceMechanism, SimpleGeometricMechanism, SnappingMechanism, SparseVector);
 
     if let Some(sensitivity_property) = properties.get(&IndexKey::from("sensitivity")) {
         if privacy_definition.protect_sensitivity {
@@ -561,32 +576,8 @@ pub fn expand_mechanism(
         }
         check_sensitivity_properties(sensitivity_property.array()?, &data_property)?;
     } else {
-        let aggregator = data_property.aggregator.as_ref()
-            .ok_or_else(|| Error::from("aggregator: missing"))?;
-
-        // sensitivity scaling
-        let mut sensitivity_value = aggregator.component.compute_sensitivity(
-            privacy_definition,
-            &aggregator.properties,
-            &sensitivity_type)?;
-
-        match aggregator.lipschitz_constants.clone().array()? {
-            Array::Float(lipschitz) => {
-                if lipschitz.iter().any(|v| v != &1.) {
-                    let mut sensitivity = sensitivity_value.array()?.float()?;
-                    sensitivity.mul_assign(&lipschitz);
-                    sensitivity_value = sensitivity.into();
-                }
-            },
-            Array::Int(lipschitz) => {
-                if lipschitz.iter().any(|v| v != &1) {
-                    let mut sensitivity = sensitivity_value.array()?.int()?;
-                    sensitivity.mul_assign(&lipschitz);
-                    sensitivity_value = sensitivity.into();
-                }
-            },
-            _ => return Err(Error::from("lipschitz constants must be numeric"))
-        };
+        let sensitivity_value = get_mechanism_sensitivity(
+            sensitivity_type, privacy_definition, &data_property)?;
 
         maximum_id += 1;
         let id_sensitivity = maximum_id;
@@ -602,6 +593,163 @@ pub fn expand_mechanism(
     Ok(expansion)
 }
 
+/// Privacy usage of each column of a mechanism, after accounting for c-stability and sampling
+pub fn get_effective_usages(
+    privacy_definition: &proto::PrivacyDefinition,
+    privacy_usage: &[proto::PrivacyUsage],
+    data_property: &ArrayProperties,
+) -> Result<Vec<proto::PrivacyUsage>> {
+    // spread usage over each column
+    spread_privacy_usage(privacy_usage, data_property.num_columns()? as usize)?.into_iter()
+        // reduce epsilon allowed to algorithm based on c-stability and group size
+        .map(|usage| usage.actual_to_effective(
+            data_property.sample_proportion.unwrap_or(1.),
+            data_property.c_stability,
+            privacy_definition))
+        .collect()
+}
+
+/// Sensitivity of the aggregate a mechanism privatizes, scaled by the lipschitz constants of any transforms after aggregation
+#[allow(clippy::float_cmp)]
+pub fn get_mechanism_sensitivity(
+    sensitivity_type: &SensitivitySpace,
+    privacy_definition: &proto::PrivacyDefinition,
+    data_property: &ArrayProperties,
+) -> Result<Value> {
+    let aggregator = data_property.aggregator.as_ref()
+        .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+    let mut sensitivity_value = aggregator.component.compute_sensitivity(
+        privacy_definition,
+        &aggregator.properties,
+        sensitivity_type)?;
+
+    match aggregator.lipschitz_constants.clone().array()? {
+        Array::Float(lipschitz) => {
+            if lipschitz.iter().any(|v| v != &1.) {
+                let mut sensitivity = sensitivity_value.array()?.float()?;
+                sensitivity.mul_assign(&lipschitz);
+                sensitivity_value = sensitivity.into();
+            }
+        },
+        Array::Int(lipschitz) => {
+            if lipschitz.iter().any(|v| v != &1) {
+                let mut sensitivity = sensitivity_value.array()?.int()?;
+                sensitivity.mul_assign(&lipschitz);
+                sensitivity_value = sensitivity.into();
+            }
+        },
+        _ => return Err(Error::from("lipschitz constants must be numeric"))
+    };
+
+    Ok(sensitivity_value)
+}
+
+/// Noise counts with the same variance on every entry, then post-process the noisy counts, such as into a consistent release.
+///
+/// The post-processing component takes the place of the component being expanded,
+/// with the noisy counts as its "data" argument, followed by `arguments`.
+///
+/// # Arguments
+/// * `expansion` - Expansion to insert the noise and post-processing into.
+/// * `privacy_definition` - Definition of privacy, used to resolve the `Automatic` mechanism.
+/// * `component` - Component being expanded.
+/// * `component_id` - Id of the component in the graph.
+/// * `maximum_id` - Largest id in the graph.
+/// * `id_counts` - Id of the node holding the counts.
+/// * `num_records` - Number of records that were counted, if known, which bounds the counts under the simple geometric mechanism.
+/// * `mechanism` - One of `Automatic`, `SimpleGeometric`, `DiscreteLaplace`, `DiscreteGaussian`, `Laplace`, `Gaussian` or `AnalyticGaussian`.
+/// * `privacy_usage` - Privacy usage of the noise.
+/// * `consistency` - Variant of the post-processing component.
+/// * `arguments` - Arguments of the post-processing component, besides the noisy counts.
+#[allow(clippy::too_many_arguments)]
+pub fn expand_consistent_counts(
+    expansion: &mut base::ComponentExpansion,
+    privacy_definition: &proto::PrivacyDefinition,
+    component: &proto::Component,
+    component_id: u32,
+    mut maximum_id: u32,
+    id_counts: u32,
+    num_records: Option<i64>,
+    mechanism: &str,
+    privacy_usage: &[proto::PrivacyUsage],
+    consistency: proto::component::Variant,
+    arguments: IndexMap<IndexKey, u32>,
+) -> Result<u32> {
+    let mut insert_literal = |value: Value, maximum_id: &mut u32| -> Result<u32> {
+        *maximum_id += 1;
+        let id_literal = *maximum_id;
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_literal, patch_node);
+        expansion.properties.insert(id_literal, infer_property(&release.value, None, id_literal)?);
+        expansion.releases.insert(id_literal, release);
+        Ok(id_literal)
+    };
+
+    // noise, with the same variance on every entry
+    let mut noise_arguments = indexmap!["data".into() => id_counts];
+    let variant = match get_mechanism(&DataType::Int, mechanism, privacy_definition)?.as_str() {
+        "simplegeometric" => {
+            // counts are bounded by the number of records
+            let count_max = match num_records {
+                Some(num_records) => num_records as Integer,
+                None => if privacy_definition.protect_elapsed_time {
+                    return Err("the number of records must be known to bound the counts when protecting elapsed time".into())
+                } else {
+                    Integer::MAX
+                }
+            };
+            noise_arguments.insert("lower".into(), insert_literal(0.into(), &mut maximum_id)?);
+            noise_arguments.insert("upper".into(), insert_literal(count_max.into(), &mut maximum_id)?);
+            proto::component::Variant::SimpleGeometricMechanism(proto::SimpleGeometricMechanism {
+                privacy_usage: privacy_usage.to_vec()
+            })
+        },
+        "discretelaplace" => proto::component::Variant::DiscreteLaplaceMechanism(proto::DiscreteLaplaceMechanism {
+            privacy_usage: privacy_usage.to_vec()
+        }),
+        "discretegaussian" => proto::component::Variant::DiscreteGaussianMechanism(proto::DiscreteGaussianMechanism {
+            privacy_usage: privacy_usage.to_vec()
+        }),
+        "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
+            privacy_usage: privacy_usage.to_vec()
+        }),
+        "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+            privacy_usage: privacy_usage.to_vec(),
+            analytic: false,
+        }),
+        "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+            privacy_usage: privacy_usage.to_vec(),
+            analytic: true,
+        }),
+        _ => bail!("Unexpected invalid token {:?}", mechanism),
+    };
+
+    maximum_id += 1;
+    let id_noise = maximum_id;
+    expansion.computation_graph.insert(id_noise, proto::Component {
+        arguments: Some(proto::ArgumentNodeIds::new(noise_arguments)),
+        variant: Some(variant),
+        omit: true,
+        submission: component.submission,
+    });
+    expansion.traversal.push(id_noise);
+
+    // consistent counts
+    let mut consistent_arguments = indexmap!["data".into() => id_noise];
+    consistent_arguments.extend(arguments);
+
+    expansion.computation_graph.insert(component_id, proto::Component {
+        arguments: Some(proto::ArgumentNodeIds::new(consistent_arguments)),
+        variant: Some(consistency),
+        omit: component.omit,
+        submission: component.submission,
+    });
+    expansion.traversal.push(component_id);
+
+    Ok(maximum_id)
+}
+
 pub fn check_sensitivity_properties(
     sensitivity_property: &ArrayProperties, data_property: &ArrayProperties
 ) -> Result<()> {
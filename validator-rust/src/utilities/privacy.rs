@@ -1,10 +1,10 @@
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use itertools::Itertools;
 
 use crate::proto;
-use crate::base::{GroupId, IndexKey, Release, ValueProperties};
+use crate::base::{ArrayProperties, GroupId, IndexKey, Release, ValueProperties};
 use crate::components::Mechanism;
 use crate::errors::*;
 use crate::utilities::{get_common_value, get_dependents, get_input_properties};
@@ -12,19 +12,504 @@ use crate::utilities::{get_common_value, get_dependents, get_input_properties};
 type BatchIdentifier = (u32, u32);
 type PartitionIds = Vec<u32>;
 
+/// Orders of the Rényi divergence tracked by the Rényi accountant
+pub const RENYI_ORDERS: [f64; 23] = [
+    1.25, 1.5, 1.75, 2., 2.5, 3., 4., 5., 6., 8., 10., 12.,
+    16., 20., 24., 32., 48., 64., 96., 128., 192., 256., 512.];
+
+/// Privacy cost of a single release, as tracked by the accountant
+#[derive(Clone, Debug)]
+enum ReleaseCost {
+    Usage(proto::PrivacyUsage),
+    /// Rényi divergence bounds at each of the RENYI_ORDERS
+    Curve(Vec<f64>),
+}
+
+/// Privacy usage of a portion of the graph, along with the composition theorems applied to compute it
+#[derive(Clone, Debug, Default)]
+struct ComposedUsage {
+    /// total epsilon of approximate privacy usages
+    epsilon: f64,
+    /// total delta of approximate privacy usages
+    delta: f64,
+    /// total rho of zero-concentrated privacy usages
+    rho: f64,
+    /// total Rényi curve. Empty if no curves have been composed
+    curve: Vec<f64>,
+    theorems: BTreeSet<proto::CompositionTheorem>,
+}
+
+impl ComposedUsage {
+    /// sequential composition
+    fn sum(mut self, other: ComposedUsage) -> Self {
+        self.epsilon += other.epsilon;
+        self.delta += other.delta;
+        self.rho += other.rho;
+        self.curve = combine_curves(self.curve, other.curve, |l, r| l + r);
+        self.theorems.extend(other.theorems);
+        self
+    }
+
+    /// parallel composition
+    fn max(mut self, other: ComposedUsage) -> Self {
+        self.epsilon = self.epsilon.max(other.epsilon);
+        self.delta = self.delta.max(other.delta);
+        self.rho = self.rho.max(other.rho);
+        self.curve = combine_curves(self.curve, other.curve, f64::max);
+        self.theorems.extend(other.theorems);
+        self
+    }
+}
+
+/// Elementwise combination of two Rényi curves, where an empty curve is zero at every order
+fn combine_curves(left: Vec<f64>, right: Vec<f64>, operator: fn(f64, f64) -> f64) -> Vec<f64> {
+    if left.is_empty() { return right }
+    if right.is_empty() { return left }
+    left.into_iter().zip(right.into_iter())
+        .map(|(l, r)| operator(l, r))
+        .collect()
+}
+
+/// Compose the privacy usages within a batch, using the composition theorem in the privacy definition.
+///
+/// Approximate privacy usages are composed with the theorem in the privacy definition,
+///     and zero-concentrated privacy usages are summed.
 fn compute_batch_privacy_usage(
-    privacy_usages: Vec<&proto::PrivacyUsage>
-) -> Result<proto::PrivacyUsage> {
-    // TODO: insert advanced composition here
-    //    This is just linear composition
-    privacy_usages.into_iter().cloned().map(Ok)
-        .fold1(|l, r| l? + r?)
-        .unwrap_or_else(|| Ok(proto::PrivacyUsage {
-            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
-                epsilon: 0.,
-                delta: 0.,
-            }))
+    costs: Vec<&ReleaseCost>,
+    privacy_definition: &proto::PrivacyDefinition,
+) -> Result<ComposedUsage> {
+    use proto::privacy_usage::Distance;
+
+    let mut epsilons = Vec::new();
+    let mut deltas = Vec::new();
+    let mut composed = ComposedUsage::default();
+
+    costs.into_iter().try_for_each(|cost| {
+        let usage = match cost {
+            ReleaseCost::Usage(usage) => usage,
+            ReleaseCost::Curve(curve) => {
+                composed.curve = combine_curves(
+                    std::mem::replace(&mut composed.curve, Vec::new()), curve.clone(), |l, r| l + r);
+                return Ok(())
+            }
+        };
+        match usage.distance.as_ref().ok_or_else(|| "distance must be defined on a privacy usage")? {
+            Distance::Approximate(approximate) => {
+                epsilons.push(approximate.epsilon);
+                deltas.push(approximate.delta);
+            }
+            Distance::Concentrated(concentrated) => composed.rho += concentrated.rho
+        };
+        Ok::<_, Error>(())
+    })?;
+
+    if composed.rho > 0. {
+        composed.theorems.insert(proto::CompositionTheorem::ConcentratedComposition);
+    }
+
+    if !composed.curve.is_empty() {
+        composed.theorems.insert(proto::CompositionTheorem::RenyiComposition);
+    }
+
+    if !epsilons.is_empty() {
+        let (epsilon, delta, theorem) = compose_approximate(&epsilons, &deltas, privacy_definition)?;
+        composed.epsilon = epsilon;
+        composed.delta = delta;
+        composed.theorems.insert(theorem);
+    }
+
+    Ok(composed)
+}
+
+/// Compose a batch of (epsilon, delta) privacy usages.
+///
+/// Advanced and optimal composition spend an additional `slack_delta` in exchange for a smaller epsilon.
+/// These theorems are only applied when the resulting epsilon is smaller than under basic composition.
+/// Batches are composed with each other by basic composition,
+///     so every batch that applies one of these theorems spends its own `slack_delta`.
+#[allow(clippy::float_cmp)]
+fn compose_approximate(
+    epsilons: &[f64], deltas: &[f64],
+    privacy_definition: &proto::PrivacyDefinition,
+) -> Result<(f64, f64, proto::CompositionTheorem)> {
+    use proto::privacy_definition::Composition;
+    use proto::CompositionTheorem as Theorem;
+
+    let basic_epsilon = epsilons.iter().sum::<f64>();
+    let basic_delta = deltas.iter().sum::<f64>();
+
+    let composition = Composition::from_i32(privacy_definition.composition)
+        .ok_or_else(|| Error::from("composition must be one of \"Basic\", \"Advanced\", \"Optimal\" or \"Renyi\""))?;
+
+    let slack_delta = privacy_definition.slack_delta;
+    if !(0.0..1.0).contains(&slack_delta) {
+        bail!("slack_delta: must be within [0, 1)")
+    }
+
+    // advanced composition theorems require a positive slack delta, and only help when composing multiple usages
+    //    the Rényi accountant composes curves instead of usages
+    if composition == Composition::Basic || composition == Composition::Renyi
+        || slack_delta == 0. || epsilons.len() < 2 {
+        return Ok((basic_epsilon, basic_delta, Theorem::BasicComposition));
+    }
+
+    let (epsilon, delta, theorem) = match composition {
+        Composition::Basic | Composition::Renyi => unreachable!(),
+        Composition::Advanced => (
+            advanced_composition_epsilon(epsilons, slack_delta),
+            basic_delta + slack_delta,
+            Theorem::AdvancedComposition
+        ),
+        Composition::Optimal => {
+            let delta = 1. - deltas.iter().map(|delta| 1. - delta).product::<f64>() * (1. - slack_delta);
+            if epsilons.iter().all(|epsilon| epsilon == &epsilons[0]) {
+                (optimal_homogeneous_composition_epsilon(epsilons[0], epsilons.len(), slack_delta),
+                 delta, Theorem::OptimalHomogeneousComposition)
+            } else {
+                (optimal_heterogeneous_composition_epsilon(epsilons, slack_delta),
+                 delta, Theorem::OptimalHeterogeneousComposition)
+            }
+        }
+    };
+
+    if epsilon >= basic_epsilon {
+        return Ok((basic_epsilon, basic_delta, Theorem::BasicComposition));
+    }
+
+    Ok((epsilon, delta, theorem))
+}
+
+/// Rényi curves of mechanisms that satisfy pure differential privacy.
+///
+/// When the data is not subsampled, `curve` bounds the mechanism at its epsilon with respect to groups of c_stability * group_size individuals.
+/// Otherwise the amplified epsilon is converted with the bound that holds for any pure differentially private mechanism.
+///
+/// # Arguments
+/// * `effective_usages` - privacy usages the mechanism was calibrated to
+/// * `privacy_definition` - definition of privacy the mechanism was evaluated under
+/// * `data_property` - properties of the data the mechanism was applied to
+/// * `orders` - orders of the Rényi divergence at which to evaluate each curve
+/// * `curve` - Rényi curve of the mechanism at a given epsilon
+///
+/// # Return
+/// One curve for each privacy usage
+#[allow(clippy::float_cmp)]
+pub fn get_pure_rdp_curves(
+    effective_usages: &[proto::PrivacyUsage],
+    privacy_definition: &proto::PrivacyDefinition,
+    data_property: &ArrayProperties,
+    orders: &[f64],
+    curve: fn(f64, &[f64]) -> Vec<f64>,
+) -> Result<Vec<Vec<f64>>> {
+    let sample_proportion = data_property.sample_proportion.unwrap_or(1.);
+    let group_size = (data_property.c_stability * privacy_definition.group_size) as f64;
+
+    effective_usages.iter()
+        .map(|usage| Ok(if sample_proportion == 1. {
+            curve(get_epsilon(usage)? * group_size, orders)
+        } else {
+            pure_rdp_curve(get_epsilon(&usage.effective_to_actual(
+                sample_proportion,
+                data_property.c_stability,
+                privacy_definition)?)?, orders)
         }))
+        .collect()
+}
+
+/// Rényi curve of an epsilon-DP mechanism.
+///
+/// Any epsilon-DP mechanism is (epsilon^2 / 2)-zCDP, and its Rényi divergence is at most epsilon.
+/// Bun and Steinke (2016), Proposition 3.3
+pub fn pure_rdp_curve(epsilon: f64, orders: &[f64]) -> Vec<f64> {
+    orders.iter()
+        .map(|alpha| epsilon.min(alpha * epsilon.powi(2) / 2.))
+        .collect()
+}
+
+/// Rényi curve of the laplace mechanism with noise scale sensitivity / epsilon.
+///
+/// Mironov (2017), Proposition 6
+pub fn laplace_rdp_curve(epsilon: f64, orders: &[f64]) -> Vec<f64> {
+    orders.iter()
+        .map(|alpha| {
+            let ratio = (alpha - 1.) / (2. * alpha - 1.);
+            let divergence = epsilon + ((1. - ratio) + ratio * (-(2. * alpha - 1.) * epsilon).exp()).ln() / (alpha - 1.);
+            divergence.min(epsilon)
+        })
+        .collect()
+}
+
+/// Rényi curve of the exponential mechanism.
+///
+/// The exponential mechanism is epsilon-bounded range, and therefore (epsilon^2 / 8)-zCDP.
+/// Cesar and Rogers (2021), Lemma 3.2
+pub fn exponential_rdp_curve(epsilon: f64, orders: &[f64]) -> Vec<f64> {
+    orders.iter()
+        .map(|alpha| epsilon.min(alpha * epsilon.powi(2) / 8.))
+        .collect()
+}
+
+/// Rényi curve of the gaussian mechanism, where `sigma` is the noise scale relative to the sensitivity.
+///
+/// Mironov (2017), Proposition 7
+pub fn gaussian_rdp_curve(sigma: f64, orders: &[f64]) -> Vec<f64> {
+    orders.iter()
+        .map(|alpha| alpha / (2. * sigma.powi(2)))
+        .collect()
+}
+
+/// Rényi curve of the gaussian mechanism applied to data subsampled with probability `q`, via Poisson sampling.
+///
+/// The bound only holds at integral orders. The curve is infinite at fractional orders.
+/// Without subsampling, when `q` is one, the curve is that of the gaussian mechanism.
+/// Mironov, Talwar and Zhang (2019), Section 3.3
+#[allow(clippy::float_cmp)]
+pub fn subsampled_gaussian_rdp_curve(sigma: f64, q: f64, orders: &[f64]) -> Result<Vec<f64>> {
+    if q <= 0. || q > 1. {
+        bail!("sample proportion: must be within (0, 1]")
+    }
+    if q == 1. {
+        return Ok(gaussian_rdp_curve(sigma, orders))
+    }
+
+    Ok(orders.iter()
+        .map(|alpha| {
+            if alpha.fract() != 0. {
+                return f64::INFINITY
+            }
+            let alpha_int = *alpha as u64;
+
+            // log of each term in the binomial expansion of A_alpha
+            let mut log_binomial = 0.;
+            let log_terms = (0..=alpha_int)
+                .map(|j| {
+                    let j_float = j as f64;
+                    let log_term = log_binomial
+                        + (alpha - j_float) * (-q).ln_1p() + j_float * q.ln()
+                        + (j_float.powi(2) - j_float) / (2. * sigma.powi(2));
+                    if j < alpha_int {
+                        log_binomial += ((alpha_int - j) as f64).ln() - ((j + 1) as f64).ln();
+                    }
+                    log_term
+                })
+                .collect::<Vec<f64>>();
+
+            let max_term = log_terms.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            let log_a = max_term + log_terms.iter().map(|term| (term - max_term).exp()).sum::<f64>().ln();
+
+            // subsampling never increases the divergence
+            (log_a / (alpha - 1.)).min(alpha / (2. * sigma.powi(2)))
+        })
+        .collect())
+}
+
+/// Convert a Rényi curve to the epsilon of an approximate privacy usage.
+///
+/// Canonne, Kamath and Steinke (2020), Proposition 12
+///
+/// # Arguments
+/// * `curve` - Rényi divergence bounds at each of the `orders`
+/// * `orders` - orders of the Rényi divergence
+/// * `delta` - delta of the resulting approximate privacy usage
+///
+/// # Return
+/// Smallest epsilon over all orders
+pub fn rdp_to_epsilon(curve: &[f64], orders: &[f64], delta: f64) -> Result<f64> {
+    if delta <= 0. || delta >= 1. {
+        bail!("delta: must be within (0, 1) to convert a Rényi curve to an approximate privacy usage")
+    }
+    Ok(curve.iter().zip(orders.iter())
+        .map(|(divergence, alpha)| divergence
+            + ((alpha - 1.) / alpha).ln()
+            - (delta.ln() + alpha.ln()) / (alpha - 1.))
+        .fold(f64::INFINITY, f64::min)
+        .max(0.))
+}
+
+/// Convert a zero-concentrated privacy usage to an approximate privacy usage.
+///
+/// A rho-zCDP mechanism is (rho + 2 sqrt(rho ln(1 / delta)), delta)-DP for any delta > 0.
+/// Bun and Steinke (2016), Proposition 1.3
+///
+/// # Arguments
+/// * `rho` - privacy usage under zero-concentrated differential privacy
+/// * `delta` - delta of the resulting approximate privacy usage
+///
+/// # Return
+/// Epsilon of the resulting approximate privacy usage
+pub fn concentrated_to_approximate_epsilon(rho: f64, delta: f64) -> Result<f64> {
+    if delta <= 0. || delta >= 1. {
+        bail!("delta: must be within (0, 1) to convert a zero-concentrated privacy usage to an approximate privacy usage")
+    }
+    Ok(rho + 2. * (rho * (1. / delta).ln()).sqrt())
+}
+
+/// Epsilon of the heterogeneous advanced composition theorem.
+///
+/// The composition of (epsilon_i, delta_i)-DP mechanisms is (epsilon, sum(delta_i) + slack_delta)-DP.
+/// Dwork, Rothblum and Vadhan (2010), Theorem III.3
+///
+/// # Arguments
+/// * `epsilons` - epsilon consumed by each of the composed mechanisms
+/// * `slack_delta` - additional delta spent on the composition
+///
+/// # Return
+/// Epsilon of the composed mechanisms
+pub fn advanced_composition_epsilon(epsilons: &[f64], slack_delta: f64) -> f64 {
+    (2. * (1. / slack_delta).ln() * epsilons.iter().map(|epsilon| epsilon.powi(2)).sum::<f64>()).sqrt()
+        + epsilons.iter().map(|epsilon| epsilon * epsilon.exp_m1()).sum::<f64>()
+}
+
+/// Epsilon of the optimal composition theorem for k mechanisms with equal epsilon.
+///
+/// The k-fold composition of (epsilon, delta_i)-DP mechanisms is
+///     (epsilon', 1 - prod(1 - delta_i) * (1 - slack_delta))-DP.
+/// Kairouz, Oh and Viswanath (2015), Theorem 3.3
+///
+/// # Arguments
+/// * `epsilon` - epsilon consumed by each of the composed mechanisms
+/// * `k` - number of composed mechanisms
+/// * `slack_delta` - additional delta spent on the composition
+///
+/// # Return
+/// Smallest epsilon' that satisfies the composed privacy guarantee
+pub fn optimal_homogeneous_composition_epsilon(epsilon: f64, k: usize, slack_delta: f64) -> f64 {
+    let log_normalizer = k as f64 * log_one_plus_exp(epsilon);
+
+    // privacy loss of the composed randomized response mechanisms, where l of the k mechanisms disagree
+    let mut log_binomial = 0.;
+    let loss_distribution = (0..=k)
+        .map(|l| {
+            let loss = (k as f64 - 2. * l as f64) * epsilon;
+            let probability = (log_binomial + (k - l) as f64 * epsilon - log_normalizer).exp();
+            if l < k {
+                log_binomial += ((k - l) as f64).ln() - ((l + 1) as f64).ln();
+            }
+            (loss, probability)
+        })
+        .collect::<Vec<(f64, f64)>>();
+
+    loss_distribution_to_epsilon(&loss_distribution, slack_delta)
+}
+
+/// Epsilon of the optimal composition theorem for mechanisms with differing epsilons.
+///
+/// Computing the optimal composition exactly is #P-complete,
+///     so each epsilon is rounded up onto a grid before composing.
+/// Since an epsilon-DP mechanism is also DP for any larger epsilon, the result is a valid upper bound.
+/// The result is then rounded up onto the grid as well.
+/// The delta is the same as in the homogeneous case.
+/// Murtagh and Vadhan (2016), Theorem 1.5
+///
+/// Each of the k epsilons grows by less than one grid step, and the composed epsilon grows by at most
+///     the total growth of the epsilons, so the result exceeds the optimal composition by less than
+///     `get_optimal_composition_grid_error(epsilons)`, k + 1 grid steps.
+///
+/// # Arguments
+/// * `epsilons` - epsilon consumed by each of the composed mechanisms
+/// * `slack_delta` - additional delta spent on the composition
+///
+/// # Return
+/// Smallest epsilon' on the grid that satisfies the composed privacy guarantee
+pub fn optimal_heterogeneous_composition_epsilon(epsilons: &[f64], slack_delta: f64) -> f64 {
+    let grid_size = get_optimal_composition_grid_size(epsilons);
+    if grid_size == 0. {
+        return 0.;
+    }
+
+    let steps = epsilons.iter()
+        .map(|epsilon| (epsilon / grid_size).ceil() as usize)
+        .collect::<Vec<usize>>();
+    let offset = steps.iter().sum::<usize>();
+
+    // distribution of the privacy loss of the composed randomized response mechanisms, in grid steps
+    let mut probabilities = vec![0.; 2 * offset + 1];
+    probabilities[offset] = 1.;
+    steps.iter().for_each(|step| {
+        let epsilon = *step as f64 * grid_size;
+        let probability_agree = 1. / (1. + (-epsilon).exp());
+        let mut updated = vec![0.; 2 * offset + 1];
+        probabilities.iter().enumerate()
+            .filter(|(_, probability)| **probability > 0.)
+            .for_each(|(idx, probability)| {
+                updated[idx + step] += probability * probability_agree;
+                updated[idx - step] += probability * (1. - probability_agree);
+            });
+        probabilities = updated;
+    });
+
+    let loss_distribution = probabilities.into_iter().enumerate()
+        .filter(|(_, probability)| *probability > 0.)
+        .map(|(idx, probability)| ((idx as f64 - offset as f64) * grid_size, probability))
+        .collect::<Vec<(f64, f64)>>();
+
+    (loss_distribution_to_epsilon(&loss_distribution, slack_delta) / grid_size).ceil() * grid_size
+}
+
+/// Step of the grid the epsilons are rounded up onto in `optimal_heterogeneous_composition_epsilon`.
+///
+/// The step is 1% of the smallest epsilon, but at least 1 / 20000 of the total to bound the number of grid cells,
+///     so the rounding of an epsilon may exceed 1% when the epsilons differ by orders of magnitude.
+fn get_optimal_composition_grid_size(epsilons: &[f64]) -> f64 {
+    let total = epsilons.iter().sum::<f64>();
+    let smallest = epsilons.iter().cloned().fold(f64::INFINITY, f64::min);
+    (smallest / 100.).max(total / 20_000.)
+}
+
+/// Bound on how much `optimal_heterogeneous_composition_epsilon` exceeds the optimal composition.
+///
+/// Every epsilon is rounded up by less than one grid step, and the result by less than one more.
+pub fn get_optimal_composition_grid_error(epsilons: &[f64]) -> f64 {
+    (epsilons.len() + 1) as f64 * get_optimal_composition_grid_size(epsilons)
+}
+
+/// Find the smallest epsilon such that a privacy loss distribution is (epsilon, slack_delta)-DP.
+///
+/// The delta at a given epsilon is E[max(0, 1 - exp(epsilon - loss))].
+fn loss_distribution_to_epsilon(loss_distribution: &[(f64, f64)], slack_delta: f64) -> f64 {
+    let get_delta = |epsilon: f64| loss_distribution.iter()
+        .filter(|(loss, _)| *loss > epsilon)
+        .map(|(loss, probability)| -probability * (epsilon - loss).exp_m1())
+        .sum::<f64>();
+
+    let mut lower = 0.;
+    let mut upper = loss_distribution.iter()
+        .map(|(loss, _)| *loss).fold(0., f64::max);
+
+    if get_delta(lower) <= slack_delta {
+        return lower;
+    }
+
+    // delta is decreasing in epsilon. Upper always satisfies the bound
+    for _ in 0..100 {
+        let middle = (lower + upper) / 2.;
+        if get_delta(middle) <= slack_delta {
+            upper = middle;
+        } else {
+            lower = middle;
+        }
+    }
+    upper
+}
+
+/// Numerically stable ln(1 + exp(x))
+fn log_one_plus_exp(x: f64) -> f64 {
+    if x > 0. {
+        x + (-x).exp().ln_1p()
+    } else {
+        x.exp().ln_1p()
+    }
+}
+
+/// Compose the privacy usages of each batch, and sum across batches
+fn compute_batches_privacy_usage(
+    batches: HashMap<BatchIdentifier, Vec<&ReleaseCost>>,
+    privacy_definition: &proto::PrivacyDefinition,
+) -> Result<ComposedUsage> {
+    batches.into_iter()
+        .map(|(_, batch)| compute_batch_privacy_usage(batch, privacy_definition))
+        .fold1(|l, r| Ok(l?.sum(r?)))
+        .unwrap_or_else(|| Ok(ComposedUsage::default()))
 }
 
 /// Use a computation graph to partition privacy usages into batches.
@@ -34,8 +519,8 @@ fn compute_batch_privacy_usage(
 ///     The partition ids are returned as a second argument.
 fn batch_partition<'a>(
     graph: &HashMap<u32, proto::Component>,
-    privacy_usages: &'a HashMap<u32, Vec<proto::PrivacyUsage>>,
-) -> Result<(HashMap<BatchIdentifier, Vec<&'a proto::PrivacyUsage>>, PartitionIds)> {
+    privacy_usages: &'a HashMap<u32, Vec<ReleaseCost>>,
+) -> Result<(HashMap<BatchIdentifier, Vec<&'a ReleaseCost>>, PartitionIds)> {
 
     // contains the subgraph for each submission id
     let mut submissions = HashMap::<u32, HashMap<u32, proto::Component>>::new();
@@ -51,7 +536,7 @@ fn batch_partition<'a>(
 
     // each batch is identified by the (submission_id, dependency_id),
     //    where the dependency_id is the maximum number of releases prior to a node id in the batch
-    let mut batches = HashMap::<BatchIdentifier, Vec<&proto::PrivacyUsage>>::new();
+    let mut batches = HashMap::<BatchIdentifier, Vec<&ReleaseCost>>::new();
 
     // node ids of partitions - these will require special treatment, and are not yet counted
     let mut partition_ids = Vec::new();
@@ -163,7 +648,7 @@ fn batch_partition<'a>(
                 batches.insert((submission_id, dependency_id), batch_values.iter()
                     .map(|node_id| privacy_usages.get(node_id))
                     .flatten().flatten()
-                    .collect::<Vec<&'a proto::PrivacyUsage>>());
+                    .collect::<Vec<&'a ReleaseCost>>());
             });
             Ok::<_, Error>(())
         })?;
@@ -174,25 +659,79 @@ fn batch_partition<'a>(
 /// Compute the privacy usage of a graph,
 ///     based on the privacy definition
 ///     and actual usages reported by any computed values.
+///
+/// The composition theorems applied to the batches in the graph are returned alongside the privacy usage.
+/// If every privacy usage is zero-concentrated, the total is zero-concentrated,
+///     unless an approximate total is requested in the privacy definition.
+#[allow(clippy::float_cmp)]
 pub fn compute_graph_privacy_usage(
     graph: &HashMap<u32, proto::Component>,
     privacy_definition: &proto::PrivacyDefinition,
     properties: &HashMap<u32, ValueProperties>,
     release: &Release,
-) -> Result<proto::PrivacyUsage> {
+) -> Result<(proto::PrivacyUsage, BTreeSet<proto::CompositionTheorem>)> {
+    use proto::privacy_usage::{Distance, DistanceApproximate, DistanceConcentrated};
+
+    let ComposedUsage {
+        mut epsilon, mut delta, rho, curve, mut theorems
+    } = compute_graph_composed_usage(graph, privacy_definition, properties, release)?;
+
+    if !curve.is_empty() {
+        epsilon += rdp_to_epsilon(&curve, &RENYI_ORDERS, privacy_definition.slack_delta)
+            .chain_err(|| "slack_delta is used to convert Rényi curves")?;
+        delta += privacy_definition.slack_delta;
+    }
+
+    if rho > 0. && epsilon == 0. && delta == 0. && !privacy_definition.approximate_total {
+        return Ok((proto::PrivacyUsage {
+            distance: Some(Distance::Concentrated(DistanceConcentrated { rho }))
+        }, theorems));
+    }
+
+    if rho > 0. {
+        epsilon += concentrated_to_approximate_epsilon(rho, privacy_definition.slack_delta)
+            .chain_err(|| "slack_delta is used to convert zero-concentrated privacy usages")?;
+        delta += privacy_definition.slack_delta;
+        theorems.insert(proto::CompositionTheorem::ConcentratedConversion);
+    }
+
+    Ok((proto::PrivacyUsage {
+        distance: Some(Distance::Approximate(DistanceApproximate { epsilon, delta }))
+    }, theorems))
+}
+
+fn compute_graph_composed_usage(
+    graph: &HashMap<u32, proto::Component>,
+    privacy_definition: &proto::PrivacyDefinition,
+    properties: &HashMap<u32, ValueProperties>,
+    release: &Release,
+) -> Result<ComposedUsage> {
+
+    let renyi = privacy_definition.composition == proto::privacy_definition::Composition::Renyi as i32;
 
     // compute the privacy usage for every node in the graph
     //    include updated privacy usages for nodes that have already been released and may have actually consumed a different amount
+    //    the Rényi accountant tracks the Rényi curves of each mechanism instead
     let release_privacy_usages = graph.iter()
-        .map(|(node_id, component)| Ok((*node_id, component.get_privacy_usage(
-            &privacy_definition,
-            release.get(node_id)
-                .and_then(|v| v.privacy_usages.as_ref()),
-            &get_input_properties(component, &properties)?)?))
-        )
-        .collect::<Result<Vec<(u32, Option<Vec<proto::PrivacyUsage>>)>>>()?
-        .into_iter().filter_map(|(node_id, usages)| Some((node_id, usages?)))
-        .collect::<HashMap<u32, Vec<proto::PrivacyUsage>>>();
+        .map(|(node_id, component)| {
+            let release_usage = release.get(node_id)
+                .and_then(|v| v.privacy_usages.as_ref());
+            let input_properties = get_input_properties(component, &properties)?;
+
+            Ok((*node_id, if renyi {
+                let curves = component.get_rdp_curves(&privacy_definition, release_usage, &input_properties, &RENYI_ORDERS)?;
+                if curves.is_none() && component.get_privacy_usage(&privacy_definition, release_usage, &input_properties)?.is_some() {
+                    bail!("Rényi curves are not available for node {}", node_id)
+                }
+                curves.map(|curves| curves.into_iter().map(ReleaseCost::Curve).collect())
+            } else {
+                component.get_privacy_usage(&privacy_definition, release_usage, &input_properties)?
+                    .map(|usages| usages.into_iter().map(ReleaseCost::Usage).collect())
+            }))
+        })
+        .collect::<Result<Vec<(u32, Option<Vec<ReleaseCost>>)>>>()?
+        .into_iter().filter_map(|(node_id, costs)| Some((node_id, costs?)))
+        .collect::<HashMap<u32, Vec<ReleaseCost>>>();
 
     // for any node id in the submission, list all nodes that use it
     let dependent_edges = get_dependents(graph);
@@ -202,13 +741,6 @@ pub fn compute_graph_privacy_usage(
     //     also return the node ids of partitions, as parallel composition needs to be applied to its dependents
     let (batches, partition_ids) = batch_partition(graph, &release_privacy_usages)?;
 
-    let zero_usage = || proto::PrivacyUsage {
-        distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
-            epsilon: 0.0,
-            delta: 0.0,
-        }))
-    };
-
     // get all node ids that are indexed by a specific category
     let get_category_indexes = |
         category: IndexKey, partition_id: u32,
@@ -285,44 +817,21 @@ pub fn compute_graph_privacy_usage(
             .collect::<HashMap<u32, proto::Component>>())
     };
 
-    // return the max of the left and right privacy usages
-    let max_usage = |l: Result<proto::PrivacyUsage>, r: Result<proto::PrivacyUsage>| -> Result<proto::PrivacyUsage> {
-        let proto::privacy_usage::DistanceApproximate {
-            epsilon: eps_l, delta: del_l
-        } = match l?.distance {
-            Some(proto::privacy_usage::Distance::Approximate(x)) => Ok(x),
-            _ => Err("expected approximate privacy")
-        }?;
-        let proto::privacy_usage::DistanceApproximate {
-            epsilon: eps_r, delta: del_r
-        } = match r?.distance {
-            Some(proto::privacy_usage::Distance::Approximate(x)) => Ok(x),
-            _ => Err("expected approximate privacy")
-        }?;
-
-        Ok(proto::PrivacyUsage {
-            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
-                epsilon: eps_l.max(eps_r),
-                delta: del_l.max(del_r),
-            }))
-        })
-    };
-
     // compute privacy usage of a subset of the graph,
     //     where the subset is indicated by a collection of node ids
     let compute_all_partitions_usage = |
         partition_ids: Vec<u32>
-    | -> Result<proto::PrivacyUsage> {
+    | -> Result<ComposedUsage> {
         partition_ids.iter()
-            .map(|partition_id| compute_graph_privacy_usage(
+            .map(|partition_id| compute_graph_composed_usage(
                 &get_downstream_graph(None, *partition_id)?,
                 privacy_definition, properties, release))
-            .fold1(max_usage)
-            .unwrap_or_else(|| Ok(zero_usage()))
+            .fold1(|l, r| Ok(l?.max(r?)))
+            .unwrap_or_else(|| Ok(ComposedUsage::default()))
     };
 
     // compute the overall privacy usage
-    let partitions_usage: proto::PrivacyUsage = partition_ids.into_iter()
+    let partitions_usage: ComposedUsage = partition_ids.into_iter()
         // for each partition component...
         .map(|partition_node_id| {
             let partition_properties = properties.get(&partition_node_id)
@@ -330,7 +839,7 @@ pub fn compute_graph_privacy_usage(
 
             partition_properties.partitions()?.children.keys()
                 // for each category/part in the partition...
-                .map(|category| {
+                .map(|category| -> Result<ComposedUsage> {
                     let unioned_downstream_graph = get_category_indexes(category.clone(), partition_node_id)?.iter()
                         // for each index into the category...
                         .map(|index_id| get_downstream_graph(Some(category.clone()), *index_id))
@@ -339,25 +848,16 @@ pub fn compute_graph_privacy_usage(
 
                     let (batches, partition_ids) = batch_partition(
                         &unioned_downstream_graph, &release_privacy_usages)?;
-                    let batch_usages = batches.into_iter()
-                        .map(|(_, batch)| compute_batch_privacy_usage(batch))
-                        .fold1(|l, r| l? + r?)
-                        .unwrap_or_else(|| Ok(zero_usage()))?;
-
-                    batch_usages + compute_all_partitions_usage(partition_ids)?
+                    Ok(compute_batches_privacy_usage(batches, privacy_definition)?
+                        .sum(compute_all_partitions_usage(partition_ids)?))
                 })
-                .fold1(max_usage)
-                .unwrap_or_else(|| Ok(zero_usage()))
+                .fold1(|l, r| Ok(l?.max(r?)))
+                .unwrap_or_else(|| Ok(ComposedUsage::default()))
         })
-        .fold1(|l, r| l? + r?)
-        .unwrap_or_else(|| Ok(zero_usage()))?;
-
-    let batch_usages = batches.into_iter()
-        .map(|(_, batch)| compute_batch_privacy_usage(batch))
-        .fold1(|l, r| l? + r?)
-        .unwrap_or_else(|| Ok(zero_usage()))?;
+        .fold1(|l, r| Ok(l?.sum(r?)))
+        .unwrap_or_else(|| Ok(ComposedUsage::default()))?;
 
-    batch_usages + partitions_usage
+    Ok(compute_batches_privacy_usage(batches, privacy_definition)?.sum(partitions_usage))
 }
 
 // pub fn privacy_usage_reducer(
@@ -421,6 +921,15 @@ pub fn privacy_usage_check(
                 }
             }
         }
+        proto::privacy_usage::Distance::Concentrated(usage) => {
+            if usage.rho <= 0.0 {
+                return Err("rho: privacy parameter rho must be greater than 0".into());
+            }
+
+            if strict_parameter_check && usage.rho > 0.5 {
+                warnings.push(format!("Warning: A large privacy parameter of rho = {} is in use", usage.rho.to_string()).into())
+            }
+        }
     };
 
     Ok(warnings)
@@ -430,7 +939,7 @@ pub fn get_epsilon(usage: &proto::PrivacyUsage) -> Result<f64> {
     match usage.distance.clone()
         .ok_or_else(|| Error::from("distance must be defined on a PrivacyUsage"))? {
         proto::privacy_usage::Distance::Approximate(distance) => Ok(distance.epsilon),
-//        _ => Err("epsilon is not defined".into())
+        _ => Err("epsilon is not defined on a zero-concentrated privacy usage".into())
     }
 }
 
@@ -438,7 +947,15 @@ pub fn get_delta(usage: &proto::PrivacyUsage) -> Result<f64> {
     match usage.distance.clone()
         .ok_or_else(|| Error::from("distance must be defined on a PrivacyUsage"))? {
         proto::privacy_usage::Distance::Approximate(distance) => Ok(distance.delta),
-        // _ => Err("delta is not defined".into())
+        _ => Err("delta is not defined on a zero-concentrated privacy usage".into())
+    }
+}
+
+pub fn get_rho(usage: &proto::PrivacyUsage) -> Result<f64> {
+    match usage.distance.clone()
+        .ok_or_else(|| Error::from("distance must be defined on a PrivacyUsage"))? {
+        proto::privacy_usage::Distance::Concentrated(distance) => Ok(distance.rho),
+        _ => Err("rho is not defined on an approximate privacy usage".into())
     }
 }
 
@@ -462,6 +979,12 @@ pub fn spread_privacy_usage(usages: &[proto::PrivacyUsage], length: usize) -> Re
                     epsilon: approx.epsilon / (length as f64),
                     delta: approx.delta / (length as f64),
                 }))
+            }).collect(),
+        proto::privacy_usage::Distance::Concentrated(concentrated) => (0..length)
+            .map(|_| proto::PrivacyUsage {
+                distance: Some(proto::privacy_usage::Distance::Concentrated(proto::privacy_usage::DistanceConcentrated {
+                    rho: concentrated.rho / (length as f64),
+                }))
             }).collect()
     })
 }
@@ -519,4 +1042,76 @@ pub fn get_c_stability_multiplier(arguments: Vec<Vec<GroupId>>) -> Result<u32> {
         *counts.entry(group_id.index).or_insert(0) += 1);
 
     Ok(*counts.values().max().unwrap())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test_composition {
+    use super::*;
+
+    #[test]
+    fn test_advanced_composition() {
+        let epsilons = vec![0.1; 100];
+        let epsilon = advanced_composition_epsilon(&epsilons, 1e-6);
+        let expected = (2. * 100. * 1e6_f64.ln()).sqrt() * 0.1 + 100. * 0.1 * 0.1_f64.exp_m1();
+        assert!((epsilon - expected).abs() < 1e-10);
+        assert!(epsilon < 10.);
+    }
+
+    #[test]
+    fn test_optimal_homogeneous_composition() {
+        let optimal = optimal_homogeneous_composition_epsilon(0.1, 100, 1e-6);
+        let advanced = advanced_composition_epsilon(&vec![0.1; 100], 1e-6);
+        assert!(optimal < advanced);
+
+        // a single mechanism may only trade the slack delta for a negligible amount of epsilon
+        let single = optimal_homogeneous_composition_epsilon(1., 1, 1e-6);
+        assert!(single <= 1. && single > 1. - 1e-4);
+    }
+
+    #[test]
+    fn test_optimal_heterogeneous_composition() {
+        let homogeneous = optimal_homogeneous_composition_epsilon(0.1, 50, 1e-6);
+        let heterogeneous = optimal_heterogeneous_composition_epsilon(&vec![0.1; 50], 1e-6);
+        // rounding onto the grid may only make the bound looser, by at most the grid error
+        assert!(heterogeneous >= homogeneous - 1e-6);
+        assert!(heterogeneous <= homogeneous + get_optimal_composition_grid_error(&vec![0.1; 50]));
+
+        let mut epsilons = vec![0.1; 50];
+        epsilons.extend(vec![0.05; 50]);
+        let optimal = optimal_heterogeneous_composition_epsilon(&epsilons, 1e-6);
+        assert!(optimal < advanced_composition_epsilon(&epsilons, 1e-6));
+    }
+
+    #[test]
+    fn test_concentrated_conversion() {
+        let epsilon = concentrated_to_approximate_epsilon(0.5, 1e-6).unwrap();
+        assert!((epsilon - (0.5 + 2. * (0.5 * 1e6_f64.ln()).sqrt())).abs() < 1e-10);
+        assert!(concentrated_to_approximate_epsilon(0.5, 0.).is_err());
+    }
+
+    #[test]
+    fn test_renyi_curves() {
+        let laplace = laplace_rdp_curve(0.1, &RENYI_ORDERS);
+        let pure = pure_rdp_curve(0.1, &RENYI_ORDERS);
+        assert!(laplace.iter().zip(pure.iter()).all(|(l, p)| l <= p));
+
+        // without subsampling, the subsampled gaussian is the gaussian
+        let gaussian = gaussian_rdp_curve(2., &RENYI_ORDERS);
+        assert_eq!(subsampled_gaussian_rdp_curve(2., 1., &RENYI_ORDERS).unwrap(), gaussian);
+
+        // subsampling never loosens the bound at integral orders, and tightens it at small orders
+        let subsampled = subsampled_gaussian_rdp_curve(2., 0.5, &RENYI_ORDERS).unwrap();
+        RENYI_ORDERS.iter().zip(gaussian.iter().zip(subsampled.iter()))
+            .filter(|(alpha, _)| alpha.fract() == 0.)
+            .for_each(|(_, (g, s))| assert!(s.is_finite() && s <= g));
+        assert!(subsampled[3] < gaussian[3] / 2.);
+        assert!(subsampled_gaussian_rdp_curve(2., 0., &RENYI_ORDERS).is_err());
+        assert!(subsampled_gaussian_rdp_curve(2., 1.5, &RENYI_ORDERS).is_err());
+
+        // composing many laplace mechanisms is tighter than summing epsilons
+        let composed = laplace.iter().map(|divergence| divergence * 100.).collect::<Vec<f64>>();
+        let epsilon = rdp_to_epsilon(&composed, &RENYI_ORDERS, 1e-6).unwrap();
+        assert!(epsilon < 10.);
+        assert!(rdp_to_epsilon(&composed, &RENYI_ORDERS, 0.).is_err());
+    }
+}